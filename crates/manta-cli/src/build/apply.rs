@@ -16,6 +16,7 @@
 //! - `apply sat-file` uses `output_flag_long_only` because `-o` is
 //!   taken by `--overwrite-configuration`.
 
+use chrono::{NaiveDateTime, TimeDelta};
 use clap::{ArgAction, ArgGroup, Command, ValueHint, arg, value_parser};
 use std::path::PathBuf;
 
@@ -30,7 +31,7 @@ fn add_apply_hw_group_args(cmd: Command) -> Command {
   cmd
     .arg_required_else_help(true)
     .arg(
-      arg!(-P -- pattern <PATTERN> "Hardware pattern: <component>:<qty>[:<component>:<qty>...].\neg: 'a100:12:epyc:5'")
+      arg!(-P -- pattern <PATTERN> "Hardware pattern: comma-separated clauses such as 'a100>=8', 'mem>=512GiB', 'node.a100>=4', '!mi250'.\nLegacy <component>:<qty>[:<component>:<qty>...] still works, eg: 'a100:12:epyc:5'")
         .required(true),
     )
     .arg(
@@ -53,9 +54,71 @@ fn add_apply_hw_group_args(cmd: Command) -> Command {
         .visible_alias("delete-empty-parent-hsm-group"),
     )
     .arg(arg!(-u --"unpin-nodes" "Allow any available nodes to be selected"))
+    .arg(
+      arg!(--locality <LEVEL> "Prefer keeping whole blades, chassis or cabinets together when picking nodes")
+        .value_parser(["blade", "chassis", "cabinet"]),
+    )
+    .arg(
+      arg!(--solver <SOLVER> "Node selection for a pin: 'greedy' (default) or 'exact', which searches for the plan moving the fewest nodes and falls back to greedy when it runs out of time")
+        .value_parser(["greedy", "exact"])
+        .conflicts_with("unpin-nodes"),
+    )
+    .arg(arg!(--explain "Show why each node was added, kept or released: component counts, per-component score contributions, scarcity weights and the pattern terms it satisfies"))
+    .arg(
+      arg!(--until <DATETIME> "Reserve the pinned nodes until this local time (YYYY-MM-DDTHH:MM[:SS]); the server then returns them to the parent group")
+        .value_parser(parse_reservation_until)
+        .conflicts_with("unpin-nodes"),
+    )
+    .arg(
+      arg!(--for <DURATION> "Reserve the pinned nodes for this long, eg '72h', '3d', '1w2d', '90m'; the server then returns them to the parent group")
+        .value_parser(parse_duration)
+        .conflicts_with_all(["unpin-nodes", "until"]),
+    )
     .arg(output_flag())
 }
 
+/// `--until` value parser: a local wall-clock deadline, with or
+/// without seconds. The dispatcher converts it to UTC for the wire.
+fn parse_reservation_until(raw: &str) -> Result<NaiveDateTime, String> {
+  ["%Y-%m-%dT%H:%M:%S", "%Y-%m-%dT%H:%M"]
+    .iter()
+    .find_map(|fmt| NaiveDateTime::parse_from_str(raw, fmt).ok())
+    .ok_or_else(|| {
+      format!("invalid time '{raw}': expected YYYY-MM-DDTHH:MM[:SS]")
+    })
+}
+
+/// `--for` / `--ttl` value parser: one or more `<number><unit>` terms
+/// with units `m`, `h`, `d` and `w`, summed (`1d12h` is 36 hours).
+fn parse_duration(raw: &str) -> Result<TimeDelta, String> {
+  let invalid = || {
+    format!("invalid duration '{raw}': expected eg '72h', '3d', '1w2d', '90m'")
+  };
+  let mut total = TimeDelta::zero();
+  let mut digits = String::new();
+  for c in raw.trim().chars() {
+    if c.is_ascii_digit() {
+      digits.push(c);
+      continue;
+    }
+    let n: i64 = digits.parse().map_err(|_| invalid())?;
+    digits.clear();
+    let term = match c {
+      'm' => TimeDelta::try_minutes(n),
+      'h' => TimeDelta::try_hours(n),
+      'd' => TimeDelta::try_days(n),
+      'w' => TimeDelta::try_weeks(n),
+      _ => None,
+    }
+    .ok_or_else(invalid)?;
+    total = total.checked_add(&term).ok_or_else(invalid)?;
+  }
+  if !digits.is_empty() || total <= TimeDelta::zero() {
+    return Err(invalid());
+  }
+  Ok(total)
+}
+
 /// `manta apply hardware group` — pattern-driven group rescale.
 /// Handler: `crate::dispatch::apply::hw_configuration`.
 pub fn subcommand_apply_hw_configuration() -> Command {
@@ -68,8 +131,15 @@ pub fn subcommand_apply_hw_configuration() -> Command {
         .long_about(
           "[experimental] Upscale or downscale a group by specifying a hardware component pattern.\n\n\
           If the group does not exist it will be created; otherwise its node assignment is updated.\n\n\
-          Pattern format: <component>:<quantity>[:<component>:<quantity>...]\n\
-          eg: 'a100:12:epyc:5'  — assign nodes with 12 A100 GPUs and 5 EPYC CPUs total",
+          Pattern format: comma-separated clauses, case-insensitive\n  \
+          <component>>=<qty>        total across the group, eg 'a100>=8' ('=' and ':' mean the same)\n  \
+          mem>=<size>               total memory in KiB/MiB/GiB/TiB, eg 'mem>=512GiB'\n  \
+          node.<component><op><qty> every node must satisfy it, op is >=, <= or =, eg 'node.a100>=4'\n  \
+          !<component>              no node may carry it, eg '!mi250'\n\n\
+          The legacy form <component>:<quantity>[:<component>:<quantity>...] is still accepted.\n\
+          eg: 'a100>=12,epyc>=5,!mi250'  — assign nodes with 12 A100 GPUs and 5 EPYC CPUs total, none with MI250s\n\n\
+          With --until or --for the pin becomes a reservation: manta-server returns the nodes it added to the \
+          parent group when it expires. List active reservations with 'manta get reservations'.",
         ),
     )
 }
@@ -109,9 +179,16 @@ pub fn subcommand_apply_ephemeral_environment() -> Command {
     .about("Launch an ephemeral SSH environment from an image")
     .long_about(
       "Launch an ephemeral SSH environment from an image.\n\n\
-      Returns an SSH hostname once the environment is ready (usually within a few seconds).",
+      Returns an SSH hostname once the environment is ready (usually within a few seconds).\n\n\
+      The environment is deleted automatically once its time to live runs out; \
+      list yours with 'manta get ephemeral-environments' and delete one early with \
+      'manta delete ephemeral-environment'.",
     )
     .arg(arg!(-i --"image-id" <IMAGE_ID> "Image ID to use").required(true))
+    .arg(
+      arg!(--ttl <DURATION> "Delete the environment after this long, eg '8h', '2d', '90m'. Defaults to the server's configured time to live")
+        .value_parser(parse_duration),
+    )
     .arg(dry_run_flag())
     .arg(output_flag())
 }
@@ -133,7 +210,22 @@ pub fn subcommand_apply_sat_file() -> Command {
       - `session_templates`: session templates to create\n\
       \n\
       Use --image-only to process only configurations and images.\n\
-      Use --sessiontemplate-only to process only configurations and session templates.",
+      Use --sessiontemplate-only to process only configurations and session templates.\n\
+      \n\
+      The rendered file is checked against the SAT schema before anything is sent (see\n\
+      `manta validate sat-file`); --skip-validation disables the check.\n\
+      \n\
+      Every completed element is recorded in a checkpoint file. If the run fails, re-run with\n\
+      --resume <checkpoint> to skip the elements that completed; anything that depends on a\n\
+      changed or failed element is rebuilt.\n\
+      \n\
+      Images that do not build on one another run concurrently with --parallel <N>; an image\n\
+      waits for the image its `base.image_ref` names. A failed image only stops the images\n\
+      that build on it.\n\
+      \n\
+      The directory holding the template is the SAT bundle root: `{% import %}` and\n\
+      `{% include %}` resolve paths against it. Repeat --values-file to layer values files\n\
+      (e.g. -f base.yaml -f prod.yaml); later files win on conflicting keys.",
     )
     .arg(
       arg!(-t --"sat-template-file" <FILE> "SAT file path (may be a jinja2 template)")
@@ -142,8 +234,9 @@ pub fn subcommand_apply_sat_file() -> Command {
         .value_hint(ValueHint::FilePath),
     )
     .arg(
-      arg!(-f --"values-file" <FILE> "Values file to expand jinja2 variables in the SAT file")
+      arg!(-f --"values-file" <FILE> "Values file to expand jinja2 variables in the SAT file; repeat to layer files, later ones win")
         .value_parser(value_parser!(PathBuf))
+        .action(ArgAction::Append)
         .value_hint(ValueHint::FilePath),
     )
     .arg(arg!(-V --"values" <VALUE> ... "Inline values to expand jinja2 variables (overrides --values-file)"))
@@ -177,6 +270,25 @@ pub fn subcommand_apply_sat_file() -> Command {
     .arg(arg!(-p --"pre-hook" <SCRIPT> "Command to run before processing.\neg: --pre-hook \"echo hello\""))
     .arg(arg!(-a --"post-hook" <SCRIPT> "Command to run after successful processing.\neg: --post-hook \"echo hello\""))
     .arg(arg!(-y --"assume-yes" "Skip confirmation prompts").action(ArgAction::SetTrue))
+    .arg(
+      arg!(--checkpoint <FILE> "File recording each completed element and the ids it produced (default: <cache dir>/sat-checkpoints/<template>.checkpoint.json)")
+        .value_parser(value_parser!(PathBuf))
+        .value_hint(ValueHint::FilePath),
+    )
+    .arg(
+      arg!(--resume <CHECKPOINT> "Resume from a checkpoint written by an earlier run, skipping elements that already completed")
+        .value_parser(value_parser!(PathBuf))
+        .value_hint(ValueHint::FilePath),
+    )
+    .arg(
+      arg!(--"skip-validation" "Do not check the rendered SAT file against the SAT schema before applying it")
+        .action(ArgAction::SetTrue),
+    )
+    .arg(
+      arg!(--parallel <N> "Maximum number of images built at the same time")
+        .value_parser(value_parser!(u16).range(1..))
+        .default_value("1"),
+    )
     .arg(dry_run_flag())
     .arg(output_flag_long_only())
 }
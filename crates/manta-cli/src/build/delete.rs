@@ -14,6 +14,9 @@
 //!   `--configuration-name`.
 //! - `delete kernel-parameters` uses `ArgGroup("cluster_or_nodes")`
 //!   to require exactly one of `--nodes` / `--group`.
+//! - `delete sessions` uses `ArgGroup("sessions_filter")` to require
+//!   `--status` and/or `--older-than`, so it never selects every
+//!   session.
 
 use clap::{ArgAction, ArgGroup, Command, arg};
 
@@ -32,9 +35,11 @@ pub fn subcommand_delete() -> Command {
     .subcommand(subcommand_delete_boot_parameter())
     .subcommand(subcommand_delete_configuration())
     .subcommand(subcommand_delete_session())
+    .subcommand(subcommand_delete_sessions())
     .subcommand(subcommand_delete_image())
     .subcommand(subcommand_delete_hw_component())
     .subcommand(subcommand_delete_redfish_endpoint())
+    .subcommand(subcommand_delete_ephemeral_environment())
 }
 
 /// `manta delete nodes` — remove nodes from a group's membership.
@@ -198,6 +203,48 @@ pub fn subcommand_delete_session() -> Command {
     .arg(output_flag())
 }
 
+/// `manta delete sessions` — delete every configuration session
+/// matching the filters, after one confirmation. Handler:
+/// `crate::dispatch::delete::sessions`.
+pub fn subcommand_delete_sessions() -> Command {
+  Command::new("sessions")
+    .arg_required_else_help(true)
+    .about("Delete all configuration sessions matching filters")
+    .long_about(
+      "Delete all configuration sessions matching filters.\n\n\
+      Shows what the sessions together leave behind or change (images \
+      built, images kept because nodes boot them, components whose \
+      error count is maxed out) and asks once before deleting them. \
+      Sessions are deleted a few at a time; a failure is reported per \
+      session and does not stop the others.",
+    )
+    .arg(
+      arg!(-s --status <VALUE> "Delete only sessions with this status")
+        .value_parser(["pending", "running", "complete"]),
+    )
+    .arg(
+      arg!(-a --"older-than" <VALUE> "Delete only sessions older than this age (eg: '30d', '6h')")
+        .visible_alias("min-age"),
+    )
+    .arg(
+      arg!(-t --type <VALUE> "Delete only sessions of this type")
+        .value_parser(["image", "runtime"]),
+    )
+    .arg(
+      arg!(-H --group <GROUP_NAME> "Delete only sessions targeting this group")
+        .visible_alias("hsm-group"),
+    )
+    .arg(arg!(-y --"assume-yes" "Skip confirmation prompts").action(ArgAction::SetTrue))
+    .arg(dry_run_flag())
+    .arg(output_flag())
+    .group(
+      ArgGroup::new("sessions_filter")
+        .args(["status", "older-than"])
+        .multiple(true)
+        .required(true),
+    )
+}
+
 /// `manta delete kernel-parameters` — remove named kernel parameters
 /// (by parameter name, ignoring values) from a node set or group.
 /// Handler: `crate::dispatch::delete::kernel_parameters`.
@@ -248,3 +295,20 @@ pub fn subcommand_delete_redfish_endpoint() -> Command {
     .arg(dry_run_flag())
     .arg(output_flag())
 }
+
+/// `manta delete ephemeral-environment` — delete one of the caller's
+/// ephemeral environments before its time to live runs out. Handler:
+/// `crate::dispatch::delete::ephemeral_environment`.
+pub fn subcommand_delete_ephemeral_environment() -> Command {
+  Command::new("ephemeral-environment")
+    .visible_alias("ephemeral-environments")
+    .arg_required_else_help(true)
+    .about("Delete an ephemeral SSH environment")
+    .arg(arg!(-y --"assume-yes" "Skip confirmation prompts").action(ArgAction::SetTrue))
+    .arg(dry_run_flag())
+    .arg(
+      arg!(<ID> "ID of the ephemeral environment, as listed by 'manta get ephemeral-environments'")
+        .required(true),
+    )
+    .arg(output_flag())
+}
@@ -0,0 +1,40 @@
+//! Clap definitions for `manta diff *` subcommands.
+//!
+//! Builds `manta diff hardware`, which compares two hardware snapshots
+//! (or a snapshot and the live inventory) and reports the nodes whose
+//! hardware changed. Dispatched in `crate::dispatch::diff`.
+
+use clap::{Command, arg};
+
+use super::output_flag;
+
+/// `manta diff hardware` — compare hardware snapshots. Handler:
+/// `crate::dispatch::diff::hardware`.
+pub fn subcommand_diff_hardware() -> Command {
+  Command::new("hardware")
+    .arg_required_else_help(true)
+    .about("Report nodes whose hardware changed between two snapshots")
+    .long_about(
+      "Report nodes whose hardware changed between two snapshots.\n\n\
+      Each snapshot is a path to a file written by 'manta snapshot \
+      hardware --local' or the id of a snapshot stored on the server. \
+      The second snapshot defaults to 'live': the current inventory of \
+      the first snapshot's group.\n\
+      eg: 'manta diff hardware 3f2a9c1e-...' or \
+      'manta diff hardware before.json after.json'",
+    )
+    .arg(arg!(<FROM> "Earlier snapshot: file path or server snapshot id"))
+    .arg(
+      arg!([TO] "Later snapshot: file path, server snapshot id, or 'live'")
+        .default_value("live"),
+    )
+    .arg(output_flag())
+}
+
+/// Top-level `manta diff` verb.
+pub fn subcommand_diff() -> Command {
+  Command::new("diff")
+    .arg_required_else_help(true)
+    .about("Compare hardware inventory snapshots")
+    .subcommand(subcommand_diff_hardware())
+}
@@ -17,7 +17,7 @@
 use chrono::{NaiveDate, NaiveDateTime, NaiveTime};
 use clap::{ArgAction, ArgGroup, Command, arg, value_parser};
 
-use super::HOSTLIST_HELP;
+use super::{HOSTLIST_HELP, output_flag};
 
 /// `manta get groups` — list/look-up node groups. Handler:
 /// `crate::dispatch::get::group`.
@@ -32,9 +32,19 @@ pub fn subcommand_get_group() -> Command {
     )
     .arg(
       arg!(-o --output <FORMAT> "Output format")
-        .value_parser(["json", "table"])
+        .value_parser(["json", "table", "ansible-inventory", "prometheus-sd"])
         .default_value("table"),
     )
+    .arg(
+      arg!(--"inventory-format" <FORMAT> "Ansible inventory syntax, with -o ansible-inventory")
+        .value_parser(["ini", "yaml"])
+        .default_value("ini"),
+    )
+    .arg(
+      arg!(--"sd-port" <PORT> "Port appended to each target, with -o prometheus-sd")
+        .value_parser(value_parser!(u16))
+        .default_value("9100"),
+    )
 }
 
 /// Subcommand `manta get hardware nodes`. Extracted so unit tests in
@@ -53,8 +63,8 @@ pub fn subcommand_get_hardware_nodes() -> Command {
     )
 }
 
-/// `manta get hardware` — parent of `nodes` and `group` hardware
-/// inspection subcommands. Handler:
+/// `manta get hardware` — parent of the `nodes`, `group` and
+/// `snapshots` hardware inspection subcommands. Handler:
 /// `crate::dispatch::get::hardware`.
 pub fn subcommand_get_hardware() -> Command {
   Command::new("hardware")
@@ -62,6 +72,7 @@ pub fn subcommand_get_hardware() -> Command {
     .about("Inspect hardware components")
     .subcommand(subcommand_get_hardware_nodes())
     .subcommand(subcommand_get_hardware_group())
+    .subcommand(subcommand_get_hardware_snapshots())
 }
 
 /// `manta get hardware group` — hardware inventory for every node in
@@ -72,10 +83,29 @@ pub fn subcommand_get_hardware_group() -> Command {
     .about("Show hardware inventory for a group")
     .arg(arg!(<GROUP_NAME> "Group name").required(true))
     .arg(
-      arg!(-o --output <FORMAT> "Output format")
-        .value_parser(["json", "summary", "details", "pattern"])
+      arg!(-o --output <FORMAT> "Output format; 'slurm' prints slurm.conf NodeName lines and gres.conf lines")
+        .value_parser(["json", "summary", "details", "pattern", "slurm"])
         .default_value("summary"),
     )
+    .arg(
+      arg!(--"threads-per-core" <N> "With '-o slurm': hardware threads per core, multiplied into CPUs=")
+        .value_parser(value_parser!(u8).range(1..))
+        .default_value("1"),
+    )
+    .arg(
+      arg!(--"memory-reserve" <PERCENT> "With '-o slurm': share of the DIMM total left out of RealMemory= for the OS")
+        .value_parser(value_parser!(u8).range(0..100))
+        .default_value("3"),
+    )
+}
+
+/// `manta get hardware snapshots` — list hardware snapshots stored on
+/// the server. Handler: `crate::dispatch::get::hardware_snapshots`.
+pub fn subcommand_get_hardware_snapshots() -> Command {
+  Command::new("snapshots")
+    .about("List hardware snapshots stored on the server")
+    .arg(arg!(-g --group <GROUP_NAME> "Only list snapshots of this group"))
+    .arg(output_flag())
 }
 
 /// `manta get configurations` — list CFS configurations. Handler:
@@ -131,6 +161,10 @@ pub fn subcommand_get_cfs_session() -> Command {
         .value_parser(value_parser!(u8).range(1..)),
     )
     .arg(arg!(-o --output <FORMAT> "Output format").value_parser(["json"]))
+    .arg(
+      arg!(--failures "Summarise the failed sessions instead: which hosts failed which task in which layer, and the error")
+        .action(ArgAction::SetTrue),
+    )
     .arg(arg!(-x --xnames <NODES> "Xnames, NIDs, or hostlist expression. Returns sessions targeting these nodes or their groups"))
     .arg(arg!(-H --group <GROUP_NAME> "Node group name. Returns sessions targeting this group or its members").visible_alias("hsm-group"))
     .group(ArgGroup::new("hsm-group_or_xnames_or_name").args([
@@ -363,6 +397,24 @@ pub fn subcommand_get_redfish_endpoints() -> Command {
     )
 }
 
+/// `manta get reservations` — list active time-limited hardware pins.
+/// Handler: `crate::dispatch::get::reservations`.
+pub fn subcommand_get_reservations() -> Command {
+  Command::new("reservations")
+    .about("List active hardware reservations (pins made with 'apply hardware group --until/--for') with their owners and expiry")
+    .arg(output_flag())
+}
+
+/// `manta get ephemeral-environments` — list the caller's ephemeral
+/// SSH environments. Handler:
+/// `crate::dispatch::get::ephemeral_environments`.
+pub fn subcommand_get_ephemeral_environments() -> Command {
+  Command::new("ephemeral-environments")
+    .visible_alias("ephemeral-environment")
+    .about("List your ephemeral SSH environments with their status, hostname and expiry")
+    .arg(output_flag())
+}
+
 /// Top-level `manta get` verb — wires every `get <noun>` subcommand
 /// together. Invoked from `build_cli` in `super::mod`.
 pub fn subcommand_get() -> Command {
@@ -380,4 +432,6 @@ pub fn subcommand_get() -> Command {
     .subcommand(subcommand_get_boot_parameters())
     .subcommand(subcommand_get_kernel_parameters())
     .subcommand(subcommand_get_redfish_endpoints())
+    .subcommand(subcommand_get_reservations())
+    .subcommand(subcommand_get_ephemeral_environments())
 }
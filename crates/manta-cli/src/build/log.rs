@@ -3,9 +3,10 @@
 //! Single-verb tail of CFS session logs. The positional argument is
 //! polymorphic — a session name, group name, xname, or NID — and the
 //! handler resolves the most recent session targeting that entity.
+//! `--running` tails every running session of the target at once.
 //! Handler: `crate::dispatch::log`.
 
-use clap::{ArgAction, Command, arg};
+use clap::{ArgAction, ArgGroup, Command, arg};
 
 /// Build `manta log` / `manta logs`.
 pub fn subcommand_log() -> Command {
@@ -13,9 +14,35 @@ pub fn subcommand_log() -> Command {
     .alias("logs")
     .about("Stream configuration session logs to stdout (accepts session, node, group, or NID)")
     .arg(arg!(-t --timestamps "Show log timestamps").action(ArgAction::SetTrue))
+    .arg(
+      arg!(--archived "Print the log archived by the server for session VALUE, available after the pod is gone")
+        .action(ArgAction::SetTrue)
+        .conflicts_with_all(["group", "running"]),
+    )
+    .arg(
+      arg!(--summary "Summarise the Ansible output instead of printing it: PLAY RECAP totals per layer and the hosts that failed each task")
+        .action(ArgAction::SetTrue)
+        .conflicts_with_all(["running", "layer", "grep"]),
+    )
+    .arg(
+      arg!(-H --group <GROUP_NAME> "Follow the most recent session targeting this node group (with --running, all its running sessions)")
+        .visible_alias("hsm-group"),
+    )
+    .arg(
+      arg!(--running "Follow every running session of the target at once, each line prefixed with its session name")
+        .action(ArgAction::SetTrue),
+    )
+    .arg(arg!(--layer <LAYER> "Show only this configuration layer: its index (from 0), or its layer, repository or playbook name"))
+    .arg(arg!(--grep <REGEX> "Show only the lines matching this regular expression (filtered on the server)"))
+    .arg(super::output_flag().requires("summary"))
     // ID preserved as "VALUE" for handler compatibility
     .arg(
       arg!([VALUE] "Session name, node group, xname, or NID.\neg: x1003c1s7b0n0, nid001313, zinal, batcher-64d35a81-d0e1-496d-9eda-0010e502f2a3")
         .value_name("TARGET"),
     )
+    .group(
+      ArgGroup::new("log_target")
+        .args(["VALUE", "group"])
+        .required(true),
+    )
 }
@@ -2,9 +2,9 @@
 //!
 //! Top-level `build_cli()` lives here. Each top-level verb has its own
 //! sibling module (`add`, `apply`, `backup`, `config`, `console`,
-//! `delete`, `gen_autocomplete`, `gen_man`, `get`, `log`, `migrate`,
-//! `power`, `restore`, `run`, `upgrade`) so each file covers one
-//! slice of the user-facing tree.
+//! `delete`, `diff`, `gen_autocomplete`, `gen_man`, `get`, `log`,
+//! `migrate`, `power`, `restore`, `run`, `snapshot`, `upgrade`,
+//! `validate`) so each file covers one slice of the user-facing tree.
 
 use clap::{Command, arg};
 
@@ -14,6 +14,7 @@ mod backup;
 mod config;
 mod console;
 mod delete;
+mod diff;
 mod gen_autocomplete;
 mod gen_man;
 pub(crate) mod get;
@@ -23,14 +24,19 @@ mod migrate;
 mod power;
 mod restore;
 mod run;
+mod snapshot;
 mod upgrade;
+mod validate;
 
 const CLI_TERM_WIDTH: usize = 100;
 
-/// Shared help text for arguments that accept xnames, NIDs, or hostlist expressions.
-pub(super) const HOSTLIST_HELP: &str = "Xnames, NIDs, or a hostlist expression.\n\
+/// Shared help text for arguments that accept xnames, NIDs, hostlist
+/// expressions, or set expressions over them.
+pub(super) const HOSTLIST_HELP: &str = "Xnames, NIDs, a hostlist expression, or a set expression.\n\
   eg: 'x1003c1s7b0n0,x1003c1s7b0n1', 'nid001313,nid001314',\n\
-  'x1003c1s7b0n[0-1],x1003c1s7b1n0', 'nid00131[0-9]'";
+  'x1003c1s7b0n[0-1],x1003c1s7b1n0', 'nid00131[0-9]',\n\
+  'group:compute & !group:gpu', 'group:a | x1000c3s0b0n[0-1]',\n\
+  'nid00131[0-9] & power=off' (filters: power=, cfs=, arch=, role=)";
 
 /// Standard `-o/--output {table,json}` flag with `table` as default.
 /// Mutating commands consume the result via
@@ -88,8 +94,17 @@ pub fn build_cli() -> Command {
         .required(false)
         .global(true),
     )
+    .arg(
+      // Also global: node lists are compressed into hostlist notation
+      // (`x1000c[0-3]s[0-7]b0n[0-1]`) in tables and confirmation
+      // prompts unless this is set anywhere on the command line.
+      arg!(--expand "Print node lists in full instead of hostlist notation")
+        .global(true),
+    )
     .subcommand(config::subcommand_config())
     .subcommand(get::subcommand_get())
+    .subcommand(snapshot::subcommand_snapshot())
+    .subcommand(diff::subcommand_diff())
     .subcommand(add::subcommand_add())
     .subcommand(apply::subcommand_apply())
     .subcommand(delete::subcommand_delete())
@@ -97,6 +112,7 @@ pub fn build_cli() -> Command {
     .subcommand(backup::subcommand_backup())
     .subcommand(restore::subcommand_restore())
     .subcommand(run::subcommand_run())
+    .subcommand(validate::subcommand_validate())
     .subcommand(power::subcommand_power())
     .subcommand(log::subcommand_log())
     .subcommand(console::subcommand_console())
@@ -177,4 +193,13 @@ mod tests {
     let site = matches.get_one::<String>("site");
     assert_eq!(site.map(String::as_str), Some("alpsb"));
   }
+
+  #[test]
+  fn expand_flag_accepted_after_nested_subcommand() {
+    let matches =
+      build_cli().get_matches_from(["manta", "get", "groups", "--expand"]);
+    assert!(matches.get_flag("expand"));
+    let matches = build_cli().get_matches_from(["manta", "get", "groups"]);
+    assert!(!matches.get_flag("expand"));
+  }
 }
@@ -15,17 +15,41 @@ use super::{dry_run_flag, output_flag};
 fn add_run_session_args(cmd: Command) -> Command {
   cmd
     .arg_required_else_help(true)
-    .arg(arg!(-n --name <VALUE> "Session name").required(true))
+    .arg(
+      arg!(-n --name <VALUE> "Session name")
+        .required_unless_present("rerun"),
+    )
+    .arg(
+      arg!(--rerun <SESSION_NAME> "Re-run this session with its configuration, targets and Ansible settings; -l, -v and -n override them")
+        .conflicts_with_all([
+          "repo-path",
+          "repo-url",
+          "playbook-name",
+          "ansible-passthrough",
+          "group",
+        ]),
+    )
     .arg(
       arg!(-p --"playbook-name" <VALUE> "Ansible playbook filename")
         .default_value("site.yml"),
     )
     .arg(
       arg!(-r --"repo-path" <REPO_PATH> ... "Path to the local git repo containing the Ansible playbook")
-        .required(true)
+        .required_unless_present_any(["rerun", "repo-url"])
         .value_parser(value_parser!(PathBuf))
         .value_hint(ValueHint::DirPath),
     )
+    .arg(
+      arg!(-u --"repo-url" <URL> ... "Repo in the system's VCS to take a layer from, instead of a local repo (repeatable, paired in order with --ref)")
+        .conflicts_with("repo-path")
+        .requires("ref")
+        .value_hint(ValueHint::Url),
+    )
+    .arg(
+      arg!(--"ref" <REF> ... "Branch, tag or commit of the matching --repo-url")
+        .requires("repo-url")
+        .conflicts_with("repo-path"),
+    )
     .arg(arg!(-w --"watch-logs" "Stream session logs to stdout").action(ArgAction::SetTrue))
     .arg(arg!(-t --timestamps "Show log timestamps").action(ArgAction::SetTrue))
     .arg(
@@ -43,7 +67,7 @@ fn add_run_session_args(cmd: Command) -> Command {
     .arg(
       arg!(-l --"ansible-limit" <VALUE>
         "Limit the session to specific nodes (must be a subset of --group if both are provided)")
-        .required(true),
+        .required_unless_present("rerun"),
     )
     .arg(
       arg!(-H --group <GROUP_NAME> "Run the session against every node in this group")
@@ -51,8 +75,7 @@ fn add_run_session_args(cmd: Command) -> Command {
     )
     .group(
       ArgGroup::new("hsm-group_or_ansible-limit")
-        .args(["group", "ansible-limit"])
-        .required(true),
+        .args(["group", "ansible-limit"]),
     )
     .arg(dry_run_flag())
     .arg(output_flag())
@@ -69,7 +92,9 @@ pub fn subcommand_run() -> Command {
         .long_about(
           "Create and run a configuration session from a local git repo.\n\n\
           The repo must already exist in the system's VCS. The session runs \
-          the specified Ansible playbook against the target nodes or group.",
+          the specified Ansible playbook against the target nodes or group.\n\n\
+          With --repo-url and --ref the layers are taken from the VCS \
+          instead, without a local checkout.",
         ),
     )
 }
@@ -0,0 +1,35 @@
+//! Clap definitions for `manta snapshot *` subcommands.
+//!
+//! Builds `manta snapshot hardware`, which records a group's per-node
+//! hardware inventory on the server (or in a local JSON file) so
+//! `manta diff hardware` can later compare against it. Dispatched in
+//! `crate::dispatch::snapshot`.
+
+use clap::{Command, arg};
+
+use super::output_flag;
+
+/// `manta snapshot hardware` — snapshot a group's hardware inventory.
+/// Handler: `crate::dispatch::snapshot::hardware`.
+pub fn subcommand_snapshot_hardware() -> Command {
+  Command::new("hardware")
+    .arg_required_else_help(true)
+    .about("Snapshot the per-node hardware inventory of a group")
+    .long_about(
+      "Snapshot the per-node hardware inventory of a group.\n\n\
+      The snapshot is stored on the server and its id printed; compare it \
+      later with 'manta diff hardware <ID>'. With --local the snapshot is \
+      written to FILE instead and nothing is stored server-side.",
+    )
+    .arg(arg!(-g --group <GROUP_NAME> "Group to inventory").required(true))
+    .arg(arg!(--local <FILE> "Write the snapshot to FILE as JSON instead of storing it on the server"))
+    .arg(output_flag())
+}
+
+/// Top-level `manta snapshot` verb.
+pub fn subcommand_snapshot() -> Command {
+  Command::new("snapshot")
+    .arg_required_else_help(true)
+    .about("Record hardware inventory snapshots")
+    .subcommand(subcommand_snapshot_hardware())
+}
@@ -0,0 +1,47 @@
+//! Clap definitions for `manta validate *` subcommands.
+//!
+//! Offline checks of input files: nothing under `manta validate`
+//! contacts the manta server or needs a token. Execution is dispatched
+//! in `crate::dispatch::validate`.
+
+use clap::{ArgAction, Command, ValueHint, arg, value_parser};
+use std::path::PathBuf;
+
+/// Build the `manta validate` subcommand tree.
+pub fn subcommand_validate() -> Command {
+  Command::new("validate")
+    .arg_required_else_help(true)
+    .about("Check input files offline, without contacting the server")
+    .subcommand(subcommand_validate_sat_file())
+}
+
+/// `manta validate sat-file` — render a SAT template and check it
+/// against the SAT schema and its own cross-references. Handler:
+/// `crate::dispatch::validate::sat_file`.
+pub fn subcommand_validate_sat_file() -> Command {
+  Command::new("sat-file")
+    .arg_required_else_help(true)
+    .about("Render a SAT file and check it against the SAT schema")
+    .long_about(
+      "Render a SAT file (with the same Jinja2 values as `apply sat-file`) and check it:\n\
+      \n\
+      - unknown, missing, or mistyped fields and invalid enum values\n\
+      - entries that must set exactly one of several fields (eg. a layer's `git` or `product`)\n\
+      - `image_ref` references to images not declared in the file, and image cycles\n\
+      \n\
+      Errors point at the line and column of the rendered file. Nothing is sent to the server.",
+    )
+    .arg(
+      arg!(-t --"sat-template-file" <FILE> "SAT file path (may be a jinja2 template)")
+        .value_parser(value_parser!(PathBuf))
+        .required(true)
+        .value_hint(ValueHint::FilePath),
+    )
+    .arg(
+      arg!(-f --"values-file" <FILE> "Values file to expand jinja2 variables in the SAT file; repeat to layer files, later ones win")
+        .value_parser(value_parser!(PathBuf))
+        .action(ArgAction::Append)
+        .value_hint(ValueHint::FilePath),
+    )
+    .arg(arg!(-V --"values" <VALUE> ... "Inline values to expand jinja2 variables (overrides --values-file)"))
+}
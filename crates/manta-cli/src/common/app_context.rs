@@ -87,6 +87,9 @@ pub struct AppContext<'a> {
   /// backend-mutating verbs before any HTTP request leaves the
   /// process. `false` by default.
   pub read_only: bool,
+  /// Global `--expand` flag: print node lists in full rather than
+  /// compressed with [`manta_shared::common::hostlist::compress`].
+  pub expand_hostlists: bool,
   /// Raw loaded `cli.toml` settings; held alongside the parsed
   /// `CliConfiguration` so handlers can read fields (e.g. `log`)
   /// that don't live on the typed struct.
@@ -147,6 +150,7 @@ mod tests {
       sat_file_poll_budget_secs: None,
       sat_file_not_visible_budget_secs: None,
       read_only: false,
+      expand_hostlists: false,
       settings,
       token: None,
       session: None,
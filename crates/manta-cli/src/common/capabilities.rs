@@ -0,0 +1,421 @@
+//! Per-site capability cache: which commands the active site can serve.
+//!
+//! The server reports what a site supports at
+//! `GET /sites/{name}/capabilities` (backend kind, Vault and k8s
+//! configuration). The report is cached per server and site at
+//! `<cache_dir>/<site>@<server>_capabilities.json` and refreshed by
+//! `crate::dispatch::process::process_cli` once it is older than
+//! [`MAX_AGE`], right after the token cascade.
+//!
+//! Two consumers:
+//!
+//! - [`warn_if_unsupported`] prints a stderr warning before running a
+//!   command the site can't serve. The command still runs; the server
+//!   stays the authority and answers 501 if the report was right.
+//! - [`hide_unsupported`] marks such commands hidden so
+//!   `manta gen-autocomplete` leaves them out of the completion script.
+//!
+//! Every failure here (no cache, unreadable cache, older server without
+//! the endpoint) degrades to "no report": nothing is hidden or warned.
+
+use std::fs;
+use std::path::PathBuf;
+use std::time::Duration;
+
+use anyhow::Result;
+use clap::parser::ValueSource;
+use clap::{ArgMatches, Command};
+use manta_shared::common::config::get_default_cache_path;
+use manta_shared::types::api::site::{SiteCapabilities, feature};
+
+use crate::http_client::{MantaClient, OpenApiResultExt};
+
+/// Suffix appended to the site and server to form the capability
+/// cache filename.
+const CACHE_FILE_SUFFIX: &str = "_capabilities.json";
+
+/// Age after which the cached report is fetched again.
+pub const MAX_AGE: Duration = Duration::from_secs(24 * 60 * 60);
+
+/// Subcommand paths (below `manta`), the flag that changes what they
+/// need (if any), and the site feature they need. A path covers every
+/// subcommand under it; a flagged entry applies only when its flag is
+/// given on the command at exactly that path, and comes before the
+/// path's flagless entry.
+const COMMAND_FEATURES: &[(&[&str], Option<&str>, &str)] = &[
+  (&["get", "configurations"], None, feature::CFS),
+  (&["get", "sessions"], None, feature::CFS),
+  (&["delete", "configurations"], None, feature::CFS),
+  (&["delete", "session"], None, feature::CFS),
+  (&["delete", "sessions"], None, feature::CFS),
+  (&["run", "session"], Some("rerun"), feature::SESSION_RERUN),
+  (&["run", "session"], None, feature::SESSION_CREATE),
+  (&["log"], Some("archived"), feature::SESSION_LOG_ARCHIVE),
+  (&["log"], None, feature::SESSION_LOGS),
+  (&["console"], None, feature::CONSOLE),
+  (&["apply", "sat-file"], None, feature::SAT_FILE),
+  (&["get", "images"], None, feature::IMAGES),
+  (&["delete", "images"], None, feature::IMAGES),
+  (&["get", "templates"], None, feature::TEMPLATES),
+  (&["apply", "template"], None, feature::TEMPLATES),
+  (&["backup", "vcluster"], None, feature::MIGRATE),
+  (&["restore", "vcluster"], None, feature::MIGRATE),
+  (&["apply", "hardware"], None, feature::HARDWARE_PIN),
+  (
+    &["apply", "ephemeral-environment"],
+    None,
+    feature::EPHEMERAL_ENV,
+  ),
+  (
+    &["get", "ephemeral-environments"],
+    None,
+    feature::EPHEMERAL_ENV,
+  ),
+  (
+    &["delete", "ephemeral-environment"],
+    None,
+    feature::EPHEMERAL_ENV,
+  ),
+];
+
+/// Cache filename for `site_name` on the server at `server_url`: the
+/// URL without its scheme, with anything but ASCII letters, digits,
+/// `.` and `-` replaced by `_`, so two servers serving a site of the
+/// same name don't share a report.
+fn cache_file_name(server_url: &str, site_name: &str) -> String {
+  let server = server_url
+    .split_once("://")
+    .map_or(server_url, |(_, rest)| rest)
+    .trim_end_matches('/');
+  let server: String = server
+    .chars()
+    .map(|c| {
+      if c.is_ascii_alphanumeric() || c == '.' || c == '-' {
+        c
+      } else {
+        '_'
+      }
+    })
+    .collect();
+  format!("{site_name}@{server}{CACHE_FILE_SUFFIX}")
+}
+
+fn cache_path(server_url: &str, site_name: &str) -> Result<PathBuf> {
+  let mut path = get_default_cache_path()?;
+  path.push(cache_file_name(server_url, site_name));
+  Ok(path)
+}
+
+/// Read the cached report for `site_name` on the server at
+/// `server_url`, whatever its age. `None` when there is no cache or it
+/// can't be parsed.
+pub fn load_cached(
+  server_url: &str,
+  site_name: &str,
+) -> Option<SiteCapabilities> {
+  let path = cache_path(server_url, site_name).ok()?;
+  let bytes = fs::read(&path).ok()?;
+  serde_json::from_slice(&bytes)
+    .inspect_err(|e| {
+      tracing::debug!(path = %path.display(), "bad capability cache: {e}");
+    })
+    .ok()
+}
+
+fn is_fresh(server_url: &str, site_name: &str) -> bool {
+  cache_path(server_url, site_name)
+    .and_then(|p| Ok(fs::metadata(p)?.modified()?.elapsed()?))
+    .is_ok_and(|age| age < MAX_AGE)
+}
+
+/// Return the report for `site_name` on the server at `server_url`
+/// (the one `client` talks to), fetching and caching it first when the
+/// cache is missing or older than [`MAX_AGE`]. A failed fetch falls
+/// back to whatever is cached.
+pub async fn load_or_refresh(
+  client: &MantaClient,
+  server_url: &str,
+  site_name: &str,
+) -> Option<SiteCapabilities> {
+  if !is_fresh(server_url, site_name)
+    && let Err(e) = refresh(client, server_url, site_name).await
+  {
+    tracing::debug!(site = site_name, "capability refresh failed: {e:#}");
+  }
+  load_cached(server_url, site_name)
+}
+
+async fn refresh(
+  client: &MantaClient,
+  server_url: &str,
+  site_name: &str,
+) -> Result<()> {
+  let caps = client
+    .openapi
+    .get_site_capabilities(site_name)
+    .await
+    .into_anyhow()
+    .await?;
+  let path = cache_path(server_url, site_name)?;
+  if let Some(dir) = path.parent() {
+    fs::create_dir_all(dir)?;
+  }
+  fs::write(&path, serde_json::to_vec(&caps)?)?;
+  tracing::debug!(path = %path.display(), "capability report cached");
+  Ok(())
+}
+
+/// Why `caps` says `feature_name` is unavailable, or `None` when it is
+/// (or the report doesn't mention it).
+fn feature_reason<'a>(
+  caps: &'a SiteCapabilities,
+  feature_name: &str,
+) -> Option<&'a str> {
+  let f = caps.features.iter().find(|f| f.name == feature_name)?;
+  (!f.supported).then(|| f.reason.as_deref().unwrap_or("unsupported"))
+}
+
+/// Why `caps` says the command at `path`, run with the flags for which
+/// `given` is true, can't run, or `None` when it can (or the report
+/// doesn't mention its feature).
+fn unsupported_reason<'a>(
+  caps: &'a SiteCapabilities,
+  path: &[&str],
+  given: impl Fn(&str) -> bool,
+) -> Option<(&'static str, &'a str)> {
+  let (_, _, name) =
+    COMMAND_FEATURES
+      .iter()
+      .find(|(prefix, flag, _)| match flag {
+        Some(flag) => path == *prefix && given(flag),
+        None => path.starts_with(prefix),
+      })?;
+  Some((*name, feature_reason(caps, name)?))
+}
+
+/// Whether argument `id` was given on the command line (or through its
+/// environment variable) rather than left at its default.
+fn is_given(matches: &ArgMatches, id: &str) -> bool {
+  matches.ids().any(|i| i.as_str() == id)
+    && matches
+      .value_source(id)
+      .is_some_and(|source| source != ValueSource::DefaultValue)
+}
+
+/// Print a warning to stderr when the parsed command needs a feature
+/// `caps` reports as unsupported. Never stops the command.
+pub fn warn_if_unsupported(cli_root: &ArgMatches, caps: &SiteCapabilities) {
+  let mut path = Vec::new();
+  let mut current = cli_root;
+  while let Some((name, child)) = current.subcommand() {
+    path.push(name);
+    current = child;
+  }
+  if let Some((name, reason)) =
+    unsupported_reason(caps, &path, |id| is_given(current, id))
+  {
+    eprintln!(
+      "Warning: site '{}' does not support '{}' ({reason}); \
+       the server will likely refuse this command",
+      caps.site, name
+    );
+  }
+}
+
+/// Hide every subcommand `caps` reports as unsupported, so help and
+/// generated completions leave it out. A subcommand with a flagged
+/// entry is hidden only when none of its entries is supported;
+/// otherwise just its unsupported flags are. Hidden subcommands and
+/// flags still parse.
+pub fn hide_unsupported(mut cli: Command, caps: &SiteCapabilities) -> Command {
+  for (path, flag, name) in COMMAND_FEATURES {
+    if feature_reason(caps, name).is_none() {
+      continue;
+    }
+    match flag {
+      Some(flag) => cli = hide_path(cli, path, Some(flag)),
+      None => {
+        let all_unsupported = COMMAND_FEATURES
+          .iter()
+          .filter(|(other, _, _)| other == path)
+          .all(|(_, _, name)| feature_reason(caps, name).is_some());
+        if all_unsupported {
+          cli = hide_path(cli, path, None);
+        }
+      }
+    }
+  }
+  cli
+}
+
+/// Hide the subcommand at `path`, or only its argument `flag`.
+fn hide_path(cli: Command, path: &[&str], flag: Option<&str>) -> Command {
+  match path {
+    [] => match flag {
+      Some(flag) => cli.mut_arg(flag, |arg| arg.hide(true)),
+      None => cli.hide(true),
+    },
+    [first, rest @ ..] => {
+      if cli.find_subcommand(first).is_none() {
+        return cli;
+      }
+      cli.mut_subcommand(first, |sub| hide_path(sub, rest, flag))
+    }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use manta_shared::types::api::site::SiteFeature;
+
+  fn caps(unsupported: &[&str]) -> SiteCapabilities {
+    let f = |name: &str| SiteFeature {
+      name: name.to_string(),
+      supported: !unsupported.contains(&name),
+      reason: unsupported
+        .contains(&name)
+        .then(|| "not implemented for the ochami backend".to_string()),
+    };
+    SiteCapabilities {
+      site: "alps".to_string(),
+      backend: "ochami".to_string(),
+      vault: false,
+      k8s: false,
+      features: [
+        feature::CFS,
+        feature::IMAGES,
+        feature::CONSOLE,
+        feature::SESSION_CREATE,
+        feature::SESSION_RERUN,
+        feature::SESSION_LOGS,
+        feature::SESSION_LOG_ARCHIVE,
+      ]
+      .into_iter()
+      .map(f)
+      .collect(),
+    }
+  }
+
+  #[test]
+  fn unsupported_reason_matches_path_prefixes() {
+    let caps = caps(&[feature::CFS, feature::CONSOLE]);
+    let reason = |path: &[&str]| unsupported_reason(&caps, path, |_| false);
+    assert_eq!(
+      reason(&["get", "sessions"]).map(|r| r.0),
+      Some(feature::CFS)
+    );
+    assert_eq!(
+      reason(&["console", "node"]).map(|r| r.0),
+      Some(feature::CONSOLE)
+    );
+    assert!(reason(&["get", "images"]).is_none());
+    assert!(reason(&["get", "groups"]).is_none());
+    // Features the report doesn't mention are assumed supported.
+    assert!(reason(&["get", "templates"]).is_none());
+  }
+
+  #[test]
+  fn flags_pick_the_feature_they_need() {
+    // A site without Vault: no session creation or live logs, but
+    // re-runs and archived logs work.
+    let no_vault = caps(&[feature::SESSION_CREATE, feature::SESSION_LOGS]);
+    let reason = |path: &[&str], flag: &str| {
+      unsupported_reason(&no_vault, path, |id| id == flag).map(|r| r.0)
+    };
+    assert_eq!(
+      reason(&["run", "session"], ""),
+      Some(feature::SESSION_CREATE)
+    );
+    assert!(reason(&["run", "session"], "rerun").is_none());
+    assert_eq!(reason(&["log"], ""), Some(feature::SESSION_LOGS));
+    assert!(reason(&["log"], "archived").is_none());
+
+    let no_archive = caps(&[feature::SESSION_LOG_ARCHIVE]);
+    assert_eq!(
+      unsupported_reason(&no_archive, &["log"], |id| id == "archived")
+        .map(|r| r.0),
+      Some(feature::SESSION_LOG_ARCHIVE)
+    );
+  }
+
+  #[test]
+  fn warnings_read_flags_from_the_parsed_command() {
+    let cli = crate::build::build_cli();
+    let matches = cli
+      .clone()
+      .try_get_matches_from(["manta", "log", "--archived", "batcher-1"])
+      .unwrap();
+    let (_, log) = matches.subcommand().unwrap();
+    assert!(is_given(log, "archived"));
+    let matches = cli
+      .try_get_matches_from(["manta", "log", "batcher-1"])
+      .unwrap();
+    let (_, log) = matches.subcommand().unwrap();
+    assert!(!is_given(log, "archived"));
+    assert!(!is_given(log, "rerun"));
+  }
+
+  #[test]
+  fn cache_files_are_keyed_on_server_and_site() {
+    assert_eq!(
+      cache_file_name("https://manta.example.com:8443/", "alps"),
+      "alps@manta.example.com_8443_capabilities.json"
+    );
+    assert_ne!(
+      cache_file_name("https://a.example.com", "alps"),
+      cache_file_name("https://b.example.com", "alps")
+    );
+  }
+
+  #[test]
+  fn every_mapped_command_path_exists() {
+    let cli = crate::build::build_cli();
+    for (path, flag, _) in COMMAND_FEATURES {
+      let mut cmd = &cli;
+      for name in *path {
+        cmd = cmd
+          .find_subcommand(name)
+          .unwrap_or_else(|| panic!("no subcommand {path:?}"));
+      }
+      if let Some(flag) = flag {
+        assert!(
+          cmd.get_arguments().any(|a| a.get_id() == *flag),
+          "no --{flag} on {path:?}"
+        );
+      }
+    }
+  }
+
+  #[test]
+  fn hide_unsupported_hides_only_unsupported_commands() {
+    let cli =
+      hide_unsupported(crate::build::build_cli(), &caps(&[feature::CFS]));
+    let get = cli.find_subcommand("get").unwrap();
+    assert!(get.find_subcommand("sessions").unwrap().is_hide_set());
+    assert!(get.find_subcommand("configurations").unwrap().is_hide_set());
+    assert!(!get.find_subcommand("images").unwrap().is_hide_set());
+    assert!(!get.is_hide_set());
+
+    // `log` stays for --archived; only live streaming is gone.
+    let cli = hide_unsupported(
+      crate::build::build_cli(),
+      &caps(&[feature::SESSION_LOGS, feature::SESSION_LOG_ARCHIVE]),
+    );
+    assert!(cli.find_subcommand("log").unwrap().is_hide_set());
+    let cli = hide_unsupported(
+      crate::build::build_cli(),
+      &caps(&[feature::SESSION_RERUN]),
+    );
+    let session = cli
+      .find_subcommand("run")
+      .and_then(|run| run.find_subcommand("session"))
+      .unwrap();
+    assert!(!session.is_hide_set());
+    let rerun = session
+      .get_arguments()
+      .find(|a| a.get_id() == "rerun")
+      .unwrap();
+    assert!(rerun.is_hide_set());
+  }
+}
@@ -4,6 +4,8 @@
 //!   global flags) threaded through every dispatch handler.
 //! - [`authentication`] — token bootstrap, refresh, and keyring
 //!   persistence; talks to `manta-server`'s `/auth` endpoints.
+//! - [`capabilities`] — cached per-site capability report; hides and
+//!   warns on commands the active site can't serve.
 //! - [`clap_ext`] — `ArgMatches` extension trait with type-safe
 //!   accessors (`req_str`, `opt_str`, …) used by every handler.
 //! - [`config`] — typed schema for `cli.toml`
@@ -19,6 +21,7 @@
 
 pub mod app_context;
 pub mod authentication;
+pub mod capabilities;
 pub mod clap_ext;
 pub mod config;
 pub mod confirm;
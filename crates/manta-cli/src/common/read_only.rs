@@ -36,10 +36,13 @@ pub const MUTATING_VERBS: &[&str] = &[
 #[cfg(test)]
 const READ_ONLY_VERBS: &[&str] = &[
   "get",
+  "snapshot",
+  "diff",
   "console",
   "log",
   "config",
   "upgrade",
+  "validate",
   "gen-autocomplete",
   "gen-man",
 ];
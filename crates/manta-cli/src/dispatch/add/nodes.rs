@@ -14,7 +14,7 @@ use crate::common;
 use crate::common::app_context::AppContext;
 use crate::http_client::{MantaClient, OpenApiResultExt};
 use crate::openapi_client::types::AddNodesToGroupRequest;
-use crate::output::action_result;
+use crate::output::{self, action_result};
 
 /// Add/assign a list of xnames to an HSM group.
 ///
@@ -34,7 +34,8 @@ pub async fn exec(
 ) -> Result<(), Error> {
   if !common::confirm::confirm(
     &format!(
-      "Nodes matching '{hosts_expression}' will be added to HSM group '{target_hsm_name}'. Do you want to proceed?"
+      "Nodes matching '{}' will be added to HSM group '{target_hsm_name}'. Do you want to proceed?",
+      output::hosts_expression(hosts_expression, ctx.expand_hostlists)
     ),
     assume_yes,
   ) {
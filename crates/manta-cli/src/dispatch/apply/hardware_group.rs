@@ -6,24 +6,65 @@
 //! request's `dry_run` flag, `create_target_hsm_group`, and
 //! `delete_empty_parent_hsm_group` flags honoured server-side. See
 //! [`super::super::add::hardware`] for the variant that *adds*
-//! components without flipping the pin mode.
+//! components without flipping the pin mode. `--explain` asks the
+//! server for the per-node reasoning and renders it as tables (or
+//! leaves it in the JSON with `-o json`). `--until` / `--for` turn the
+//! pin into a reservation the server releases on expiry. `--solver
+//! exact` asks for the plan moving the fewest nodes.
 
 use anyhow::{Context, Error};
+use chrono::{
+  DateTime, Local, NaiveDateTime, SecondsFormat, TimeDelta, TimeZone, Utc,
+};
 use clap::ArgMatches;
 
 use crate::common::app_context::AppContext;
 use crate::common::clap_ext::ArgMatchesExt;
 use crate::http_client::{MantaClient, OpenApiResultExt};
 use crate::openapi_client::types::{
-  ApplyHwConfigurationRequest, HwClusterMode,
+  ApplyHwConfigurationRequest, HwClusterMode, HwLocality, HwSolver,
 };
-use crate::output::action_result;
+use crate::output::{action_result, hardware};
+
+/// Reservation expiry requested with `--until` (read in `tz`) or
+/// `--for` (counted from `now`), as the RFC 3339 UTC string the server
+/// expects.
+///
+/// # Errors
+///
+/// Returns an error when `--until` names a wall-clock time that does
+/// not exist in `tz` (skipped by a daylight-saving change).
+fn reservation_expires_at<Tz: TimeZone>(
+  cli_args: &ArgMatches,
+  now: DateTime<Utc>,
+  tz: &Tz,
+) -> Result<Option<String>, Error> {
+  let until = cli_args
+    .get_one::<NaiveDateTime>("until")
+    .map(|naive| {
+      tz.from_local_datetime(naive)
+        .earliest()
+        .map(|at| at.with_timezone(&Utc))
+        .with_context(|| format!("--until {naive} does not exist locally"))
+    })
+    .transpose()?;
+  Ok(
+    until
+      .or_else(|| {
+        cli_args
+          .get_one::<TimeDelta>("for")
+          .map(|duration| now + *duration)
+      })
+      .map(|at| at.to_rfc3339_opts(SecondsFormat::Secs, true)),
+  )
+}
 
 /// Apply a hardware cluster configuration (pin or unpin).
 ///
 /// # Errors
 ///
-/// Returns an error when `--pattern` is missing, when neither the CLI
+/// Returns an error when `--pattern` is missing, when `--until` does
+/// not exist in the local timezone, when neither the CLI
 /// nor `cli.toml` supplies a target or parent group, when the HTTP
 /// client cannot be built, or when the `apply_hw_configuration` call
 /// fails.
@@ -54,6 +95,19 @@ pub async fn exec(
     HwClusterMode::Pin
   };
   let pattern = cli_apply_hw_group.req_str("pattern")?;
+  let explain = cli_apply_hw_group.get_flag("explain");
+  let expires_at =
+    reservation_expires_at(cli_apply_hw_group, Utc::now(), &Local)?;
+  let locality = match cli_apply_hw_group.opt_str("locality") {
+    Some("blade") => Some(HwLocality::Blade),
+    Some("chassis") => Some(HwLocality::Chassis),
+    Some("cabinet") => Some(HwLocality::Cabinet),
+    _ => None,
+  };
+  let solver = match cli_apply_hw_group.opt_str("solver") {
+    Some("exact") => HwSolver::Exact,
+    _ => HwSolver::Greedy,
+  };
 
   let target = target_hsm_group_name_arg_opt
     .or(settings_hsm_group_name_opt)
@@ -74,17 +128,124 @@ pub async fn exec(
         dry_run: Some(dryrun),
         create_target_hsm_group: Some(create_target_hsm_group),
         delete_empty_parent_hsm_group: Some(delete_empty_parent_hsm_group),
+        locality,
+        solver: Some(solver),
+        explain: Some(explain),
+        expires_at,
       },
     )
     .await
     .into_anyhow()
     .await?;
+  if solver == HwSolver::Exact
+    && result.get("solver").and_then(|s| s.as_str()) == Some("greedy")
+  {
+    eprintln!(
+      "The exact solver ran out of time; this plan comes from the greedy solver."
+    );
+  }
   let output_opt = cli_apply_hw_group.opt_str("output");
   let message = if dryrun {
     "Dry run enabled, not modifying the HSM groups on the system."
   } else {
     "Hardware configuration applied."
   };
-  action_result::print_with_data(message, &result, output_opt)?;
+  if explain && output_opt != Some("json") {
+    let mut memberships = result.clone();
+    if let Some(obj) = memberships.as_object_mut() {
+      obj.remove("explanation");
+    }
+    action_result::print_with_data(message, &memberships, output_opt)?;
+    hardware::print_plan_explanation(&result)?;
+  } else {
+    action_result::print_with_data(message, &result, output_opt)?;
+  }
   Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn matches(extra: &[&str]) -> Result<ArgMatches, clap::Error> {
+    let mut args = vec![
+      "manta", "apply", "hardware", "group", "-P", "a100>=8", "-t", "tenant-a",
+      "-p", "free",
+    ];
+    args.extend_from_slice(extra);
+    crate::build::build_cli()
+      .try_get_matches_from(args)
+      .map(|m| {
+        m.subcommand_matches("apply")
+          .and_then(|m| m.subcommand_matches("hardware"))
+          .and_then(|m| m.subcommand_matches("group"))
+          .unwrap()
+          .clone()
+      })
+  }
+
+  fn now() -> DateTime<Utc> {
+    DateTime::parse_from_rfc3339("2026-10-19T08:00:00Z")
+      .unwrap()
+      .with_timezone(&Utc)
+  }
+
+  fn expires_at(m: &ArgMatches) -> Option<String> {
+    reservation_expires_at(
+      m,
+      now(),
+      &chrono::FixedOffset::east_opt(3600).unwrap(),
+    )
+    .unwrap()
+  }
+
+  #[test]
+  fn no_reservation_without_until_or_for() {
+    assert_eq!(expires_at(&matches(&[]).unwrap()), None);
+  }
+
+  #[test]
+  fn for_is_added_to_now() {
+    let m = matches(&["--for", "72h"]).unwrap();
+    assert_eq!(expires_at(&m).as_deref(), Some("2026-10-22T08:00:00Z"));
+    let m = matches(&["--for", "1w1d30m"]).unwrap();
+    assert_eq!(expires_at(&m).as_deref(), Some("2026-10-27T08:30:00Z"));
+  }
+
+  #[test]
+  fn until_is_read_in_the_local_timezone() {
+    // The helper pins the "local" zone to UTC+1.
+    let m = matches(&["--until", "2026-11-01T09:00"]).unwrap();
+    assert_eq!(expires_at(&m).as_deref(), Some("2026-11-01T08:00:00Z"));
+    let m = matches(&["--until", "2026-11-01T09:00:30"]).unwrap();
+    assert_eq!(expires_at(&m).as_deref(), Some("2026-11-01T08:00:30Z"));
+  }
+
+  #[test]
+  fn malformed_reservation_values_are_rejected() {
+    for bad in [
+      ["--for", "72"],
+      ["--for", "3y"],
+      ["--for", "0h"],
+      ["--for", "h"],
+      ["--until", "tomorrow"],
+      ["--until", "2026-11-01T09:00:00+01:00"],
+    ] {
+      assert!(matches(&bad).is_err(), "{bad:?} should be rejected");
+    }
+  }
+
+  #[test]
+  fn reservation_conflicts_with_unpin_and_each_other() {
+    assert!(matches(&["--for", "1d", "-u"]).is_err());
+    assert!(matches(&["--until", "2026-11-01T08:00", "-u"]).is_err());
+    assert!(matches(&["--for", "1d", "--until", "2026-11-01T08:00"]).is_err());
+  }
+
+  #[test]
+  fn solver_is_pin_only_and_validated() {
+    assert!(matches(&["--solver", "exact"]).is_ok());
+    assert!(matches(&["--solver", "exact", "-u"]).is_err());
+    assert!(matches(&["--solver", "optimal"]).is_err());
+  }
+}
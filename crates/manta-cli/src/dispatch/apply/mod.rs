@@ -24,7 +24,8 @@
 //!   existing record.
 //! - `ephemeral-environment` (handled inline below) — `POST
 //!   /v2/ephemeral-env` to provision an ephemeral environment for
-//!   an image; refuses to run if stdout is not a TTY.
+//!   an image, optionally with a `--ttl`; refuses to run if stdout is
+//!   not a TTY.
 //!
 //! Most leaves accept `--dry-run`; whether that flows to the server or
 //! short-circuits client-side depends on the endpoint and is
@@ -76,42 +77,7 @@ pub async fn handle_apply(
     },
 
     Some(("sat-file", m)) => {
-      let timestamp = chrono::Utc::now().format("%Y%m%d%H%M%S").to_string();
-
-      let cli_value_vec_opt: Option<Vec<String>> =
-        m.get_many("values").map(|value_vec| {
-          value_vec
-            .map(|value: &String| value.replace("__DATE__", &timestamp))
-            .collect()
-        });
-
-      let cli_values_file_content_opt: Option<String> =
-        if let Some(values_file_path) =
-          m.get_one::<std::path::PathBuf>("values-file")
-        {
-          let content = std::fs::read_to_string(values_file_path)
-            .with_context(|| {
-              format!(
-                "Failed to read values file '{}'",
-                values_file_path.display()
-              )
-            })?;
-          Some(content.replace("__DATE__", &timestamp))
-        } else {
-          None
-        };
-
-      let sat_template_file = m
-        .get_one::<std::path::PathBuf>("sat-template-file")
-        .context("SAT template file argument not provided")?;
-
-      let sat_file_content: String = std::fs::read_to_string(sat_template_file)
-        .with_context(|| {
-          format!(
-            "Could not read SAT file template '{}'",
-            sat_template_file.display()
-          )
-        })?;
+      let inputs = sat_file::render::SatTemplateInputs::read(m)?;
 
       let ansible_passthrough_env: Option<String> =
         ctx.settings.get("ansible-passthrough").ok();
@@ -136,14 +102,28 @@ pub async fn handle_apply(
       let assume_yes: bool = m.get_flag("assume-yes");
       let dry_run: bool = m.get_flag("dry-run");
       let output_opt = m.opt_str("output");
+      let parallel = m.get_one::<u16>("parallel").copied().unwrap_or(1);
+
+      // `--resume` keeps recording into the checkpoint it resumed from
+      // unless `--checkpoint` points somewhere else.
+      let resume_opt = m.get_one::<std::path::PathBuf>("resume");
+      let checkpoint_path: std::path::PathBuf =
+        match m.get_one::<std::path::PathBuf>("checkpoint").or(resume_opt) {
+          Some(path) => path.clone(),
+          None => sat_file::checkpoint::default_checkpoint_path(
+            &inputs.sat_template_file,
+            &inputs.sat_file_content,
+          )?,
+        };
 
       sat_file::exec::exec(
         ctx,
         &token,
         &sat_file::exec::SatApplyOptions {
-          sat_file_content: sat_file_content.as_str(),
-          values_file_content_opt: cli_values_file_content_opt.as_deref(),
-          values_cli_opt: cli_value_vec_opt.as_deref(),
+          sat_file_content: inputs.sat_file_content.as_str(),
+          values_file_contents: &inputs.values_file_contents,
+          values_cli_opt: inputs.values_cli_opt.as_deref(),
+          bundle_dir_opt: Some(inputs.bundle_dir()),
           ansible_verbosity_opt: ansible_verbosity,
           ansible_passthrough_opt: ansible_passthrough.as_deref(),
           create_bos_session,
@@ -157,6 +137,10 @@ pub async fn handle_apply(
           dry_run,
           assume_yes,
           output_opt,
+          checkpoint_path: &checkpoint_path,
+          resume_opt: resume_opt.map(std::path::PathBuf::as_path),
+          skip_validation: m.get_flag("skip-validation"),
+          parallel: usize::from(parallel),
         },
       )
       .await?;
@@ -189,8 +173,12 @@ pub async fn handle_apply(
     Some(("ephemeral-environment", m)) => {
       let image_id = m.req_str("image-id")?;
       let dry_run = m.get_flag("dry-run");
+      let ttl_secs = m
+        .get_one::<chrono::TimeDelta>("ttl")
+        .map(chrono::TimeDelta::num_seconds);
       let req = crate::openapi_client::types::CreateEphemeralEnvRequest {
         image_id: image_id.to_string(),
+        ttl_secs,
       };
 
       if dry_run {
@@ -214,6 +202,13 @@ pub async fn handle_apply(
         .into_anyhow()
         .await?;
       println!("{}", response.hostname);
+      match &response.expires_at {
+        Some(expires_at) => eprintln!(
+          "Ephemeral environment '{}' expires at {expires_at}",
+          response.id
+        ),
+        None => eprintln!("Ephemeral environment '{}' created", response.id),
+      }
     }
 
     Some(("boot-parameters", m)) => {
@@ -424,6 +419,56 @@ mod tests {
     );
   }
 
+  /// `--resume` and `--checkpoint` both take a path and compose.
+  #[test]
+  fn sat_file_accepts_resume_and_checkpoint() {
+    let m = crate::build::build_cli()
+      .try_get_matches_from([
+        "manta",
+        "apply",
+        "sat-file",
+        "-t",
+        "sat.yaml",
+        "--resume",
+        "old.checkpoint.json",
+        "--checkpoint",
+        "new.checkpoint.json",
+      ])
+      .expect("`--resume` + `--checkpoint` should parse");
+    let (_, sat) = m.subcommand().unwrap().1.subcommand().unwrap();
+    assert_eq!(
+      sat.get_one::<std::path::PathBuf>("resume"),
+      Some(&std::path::PathBuf::from("old.checkpoint.json"))
+    );
+    assert_eq!(
+      sat.get_one::<std::path::PathBuf>("checkpoint"),
+      Some(&std::path::PathBuf::from("new.checkpoint.json"))
+    );
+  }
+
+  #[test]
+  fn sat_file_parallel_defaults_to_one() {
+    let m = crate::build::build_cli()
+      .try_get_matches_from(["manta", "apply", "sat-file", "-t", "sat.yaml"])
+      .expect("sat-file without --parallel should parse");
+    let (_, sat) = m.subcommand().unwrap().1.subcommand().unwrap();
+    assert_eq!(sat.get_one::<u16>("parallel"), Some(&1));
+  }
+
+  #[test]
+  fn sat_file_parallel_rejects_zero() {
+    let res = crate::build::build_cli().try_get_matches_from([
+      "manta",
+      "apply",
+      "sat-file",
+      "-t",
+      "sat.yaml",
+      "--parallel",
+      "0",
+    ]);
+    assert!(res.is_err(), "`--parallel 0` must be rejected");
+  }
+
   // ------------------------------------------------------------------
   // `apply runtime-configuration nodes` / `group` — happy paths
   // ------------------------------------------------------------------
@@ -0,0 +1,400 @@
+//! Checkpoint file for resuming a partially applied SAT file.
+//!
+//! `dispatch_plan` records every element it completes — configuration,
+//! image, session_template — into a JSON file, together with the
+//! rendered SAT entry that produced it and the id the backend handed back
+//! (CFS configuration name, IMS image id from the stamp step, BOS
+//! session template name). The file is rewritten after every element
+//! so a failure mid-way (image 4 of 7, say) leaves a record of
+//! everything that finished.
+//!
+//! `manta apply sat-file --resume <checkpoint>` loads that record and
+//! skips any element whose rendered entry is identical to the
+//! recorded one, feeding the recorded ids into `ref_lookup` so
+//! dependents resolve exactly as if the element had just been built.
+//! Reuse is conservative: an element is only skipped when everything
+//! it depends on (its `configuration`, its `base.image_ref` /
+//! `image.image_ref` image) was itself skipped. Anything downstream
+//! of a rebuilt or failed element is rebuilt too, so a dependent can
+//! never be stamped on top of a stale or missing parent.
+//!
+//! Dry-run never writes the checkpoint; it may still read one, which
+//! previews what a resumed run would skip.
+
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, bail};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+/// On-disk format version. Bumped on any incompatible change to
+/// [`Checkpoint`]; older files are rejected rather than misread.
+pub const CHECKPOINT_VERSION: u32 = 1;
+
+/// Directory (under the manta cache dir) holding checkpoints written
+/// when `--checkpoint` is not given.
+const DEFAULT_CHECKPOINT_DIR: &str = "sat-checkpoints";
+
+/// SAT section a recorded element came from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ElementKind {
+  Configuration,
+  Image,
+  SessionTemplate,
+}
+
+/// One element that finished successfully.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CompletedElement {
+  pub kind: ElementKind,
+  /// Key the element is looked up by: `name` for configurations and
+  /// session_templates, `ref_name.or(name)` for images (the same label
+  /// downstream `image_ref`s resolve against).
+  pub label: String,
+  /// The rendered SAT entry exactly as it was applied. A resumed run
+  /// reuses the record only when the current entry is identical.
+  pub element: Value,
+  /// What the element produced: the CFS configuration name, the IMS
+  /// image id, or the BOS session template name.
+  pub produced_id: String,
+  /// Server response for the element, replayed into the final summary
+  /// so a resumed run prints the same four lists as an uninterrupted
+  /// one.
+  pub output: Value,
+}
+
+/// Progress record for one `manta apply sat-file` run.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Checkpoint {
+  pub version: u32,
+  pub completed: Vec<CompletedElement>,
+  /// File the checkpoint is persisted to after every
+  /// [`Checkpoint::record`]. `None` keeps it in memory only (dry-run).
+  #[serde(skip)]
+  path: Option<PathBuf>,
+  /// Labels reused from a previous run during this one, per kind.
+  /// Drives the dependency check in [`Checkpoint::reusable`].
+  #[serde(skip)]
+  reused: HashSet<(ElementKind, String)>,
+}
+
+impl Checkpoint {
+  /// Start an empty checkpoint persisted to `path` (or nowhere).
+  pub fn new(path: Option<PathBuf>) -> Self {
+    Self {
+      version: CHECKPOINT_VERSION,
+      completed: Vec::new(),
+      path,
+      reused: HashSet::new(),
+    }
+  }
+
+  /// Load a checkpoint written by a previous run. Subsequent
+  /// [`Checkpoint::record`] calls go to `persist_to`, which is the
+  /// same file for `--resume` and `None` for dry-run.
+  ///
+  /// # Errors
+  ///
+  /// Returns an error when the file cannot be read, is not a valid
+  /// checkpoint, or was written by an incompatible version.
+  pub fn load(
+    path: &Path,
+    persist_to: Option<PathBuf>,
+  ) -> anyhow::Result<Self> {
+    let content = std::fs::read_to_string(path).with_context(|| {
+      format!("Failed to read SAT checkpoint '{}'", path.display())
+    })?;
+    let mut checkpoint: Self =
+      serde_json::from_str(&content).with_context(|| {
+        format!("'{}' is not a valid SAT checkpoint", path.display())
+      })?;
+    if checkpoint.version != CHECKPOINT_VERSION {
+      bail!(
+        "SAT checkpoint '{}' has version {}, expected {}",
+        path.display(),
+        checkpoint.version,
+        CHECKPOINT_VERSION
+      );
+    }
+    checkpoint.path = persist_to;
+    Ok(checkpoint)
+  }
+
+  /// File progress is written to, if any.
+  pub fn path(&self) -> Option<&Path> {
+    self.path.as_deref()
+  }
+
+  /// Return the recorded completion for `element` if it can be
+  /// skipped: a record with the same kind and label exists, its entry
+  /// is identical to `element`, and every `(kind, label)` in
+  /// `depends_on` was itself reused in this run. A hit is remembered
+  /// so the element's own dependents may be reused in turn.
+  pub fn reusable(
+    &mut self,
+    kind: ElementKind,
+    label: &str,
+    element: &Value,
+    depends_on: &[(ElementKind, String)],
+  ) -> Option<CompletedElement> {
+    if !depends_on.iter().all(|dep| self.reused.contains(dep)) {
+      return None;
+    }
+    let hit = self
+      .completed
+      .iter()
+      .find(|c| c.kind == kind && c.label == label && &c.element == element)
+      .cloned()?;
+    self.reused.insert((kind, label.to_string()));
+    Some(hit)
+  }
+
+  /// Record a completed element, replacing any earlier record with the
+  /// same kind and label, and persist the checkpoint.
+  ///
+  /// # Errors
+  ///
+  /// Returns an error when the checkpoint file cannot be written.
+  pub fn record(&mut self, done: CompletedElement) -> anyhow::Result<()> {
+    self
+      .completed
+      .retain(|c| !(c.kind == done.kind && c.label == done.label));
+    self.completed.push(done);
+    self.save()
+  }
+
+  /// Write the checkpoint to a sibling temp file and rename it into
+  /// place, so a crash mid-write never leaves a truncated checkpoint.
+  fn save(&self) -> anyhow::Result<()> {
+    let Some(path) = &self.path else {
+      return Ok(());
+    };
+    if let Some(parent) = path.parent()
+      && !parent.as_os_str().is_empty()
+    {
+      std::fs::create_dir_all(parent).with_context(|| {
+        format!(
+          "Failed to create checkpoint directory '{}'",
+          parent.display()
+        )
+      })?;
+    }
+    let body = serde_json::to_vec_pretty(self)
+      .context("Failed to serialize SAT checkpoint")?;
+    let tmp = path.with_extension("json.tmp");
+    std::fs::write(&tmp, body).with_context(|| {
+      format!("Failed to write SAT checkpoint '{}'", tmp.display())
+    })?;
+    std::fs::rename(&tmp, path).with_context(|| {
+      format!("Failed to write SAT checkpoint '{}'", path.display())
+    })?;
+    Ok(())
+  }
+}
+
+/// Default checkpoint location for a SAT template when `--checkpoint`
+/// is not given:
+/// `<cache_dir>/sat-checkpoints/<template file name>-<hash>.checkpoint.json`.
+///
+/// `<hash>` covers the template's canonical path and its content, so
+/// two `sat.yaml` files in different directories (or an edited file)
+/// never share a checkpoint. `--resume` names its checkpoint
+/// explicitly, so resuming after an edit still finds the old record.
+///
+/// # Errors
+///
+/// Returns an error when the manta cache directory cannot be resolved
+/// (typically because `$HOME` is unset).
+pub fn default_checkpoint_path(
+  sat_template_file: &Path,
+  sat_file_content: &str,
+) -> anyhow::Result<PathBuf> {
+  let file_name = sat_template_file
+    .file_name()
+    .map_or_else(|| "sat-file".into(), |n| n.to_string_lossy().into_owned());
+  let canonical = std::fs::canonicalize(sat_template_file)
+    .unwrap_or_else(|_| sat_template_file.to_path_buf());
+  let hash = fnv1a(&[
+    canonical.to_string_lossy().as_bytes(),
+    sat_file_content.as_bytes(),
+  ]);
+  let mut path = manta_shared::common::config::get_default_cache_path()
+    .context("Could not resolve the manta cache directory")?;
+  path.push(DEFAULT_CHECKPOINT_DIR);
+  path.push(format!("{file_name}-{hash:016x}.checkpoint.json"));
+  Ok(path)
+}
+
+/// 64-bit FNV-1a over `parts`, each followed by a NUL separator.
+/// Stable across toolchains, unlike `DefaultHasher`, so a checkpoint
+/// path survives a manta upgrade.
+fn fnv1a(parts: &[&[u8]]) -> u64 {
+  let mut hash: u64 = 0xcbf2_9ce4_8422_2325;
+  for byte in parts.iter().flat_map(|p| p.iter().chain(&[0])) {
+    hash ^= u64::from(*byte);
+    hash = hash.wrapping_mul(0x0100_0000_01b3);
+  }
+  hash
+}
+
+#[cfg(test)]
+mod tests {
+  use super::{
+    Checkpoint, CompletedElement, ElementKind, default_checkpoint_path,
+  };
+  use serde_json::json;
+
+  fn done(
+    kind: ElementKind,
+    label: &str,
+    element: serde_json::Value,
+  ) -> CompletedElement {
+    CompletedElement {
+      kind,
+      label: label.to_string(),
+      element,
+      produced_id: format!("id-{label}"),
+      output: json!({ "name": label }),
+    }
+  }
+
+  #[test]
+  fn reusable_requires_identical_element() {
+    let mut cp = Checkpoint::new(None);
+    cp.record(done(
+      ElementKind::Configuration,
+      "cfg",
+      json!({ "name": "cfg", "layers": [] }),
+    ))
+    .unwrap();
+    assert!(
+      cp.reusable(
+        ElementKind::Configuration,
+        "cfg",
+        &json!({ "name": "cfg", "layers": [1] }),
+        &[]
+      )
+      .is_none(),
+      "edited entry must not be reused"
+    );
+    assert!(
+      cp.reusable(
+        ElementKind::Configuration,
+        "cfg",
+        &json!({ "name": "cfg", "layers": [] }),
+        &[]
+      )
+      .is_some()
+    );
+  }
+
+  #[test]
+  fn reusable_blocks_dependents_of_rebuilt_parent() {
+    let mut cp = Checkpoint::new(None);
+    let base = json!({ "name": "base" });
+    let child = json!({ "name": "child", "base": { "image_ref": "base" } });
+    cp.record(done(ElementKind::Image, "base", base)).unwrap();
+    cp.record(done(ElementKind::Image, "child", child.clone()))
+      .unwrap();
+
+    // `base` changed, so it is not reused — `child` must be rebuilt
+    // even though its own entry is unchanged.
+    assert!(
+      cp.reusable(
+        ElementKind::Image,
+        "base",
+        &json!({ "name": "base", "v": 2 }),
+        &[]
+      )
+      .is_none()
+    );
+    let dep = [(ElementKind::Image, "base".to_string())];
+    assert!(
+      cp.reusable(ElementKind::Image, "child", &child, &dep)
+        .is_none()
+    );
+  }
+
+  #[test]
+  fn reusable_chains_through_reused_parent() {
+    let mut cp = Checkpoint::new(None);
+    let base = json!({ "name": "base" });
+    let child = json!({ "name": "child", "base": { "image_ref": "base" } });
+    cp.record(done(ElementKind::Image, "base", base.clone()))
+      .unwrap();
+    cp.record(done(ElementKind::Image, "child", child.clone()))
+      .unwrap();
+
+    assert!(
+      cp.reusable(ElementKind::Image, "base", &base, &[])
+        .is_some()
+    );
+    let dep = [(ElementKind::Image, "base".to_string())];
+    let hit = cp
+      .reusable(ElementKind::Image, "child", &child, &dep)
+      .unwrap();
+    assert_eq!(hit.produced_id, "id-child");
+  }
+
+  #[test]
+  fn record_replaces_same_label() {
+    let mut cp = Checkpoint::new(None);
+    cp.record(done(ElementKind::Image, "img", json!({ "v": 1 })))
+      .unwrap();
+    cp.record(done(ElementKind::Image, "img", json!({ "v": 2 })))
+      .unwrap();
+    assert_eq!(cp.completed.len(), 1);
+    assert_eq!(cp.completed[0].element, json!({ "v": 2 }));
+  }
+
+  #[test]
+  fn save_then_load_round_trips() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("nested").join("cp.json");
+    let mut cp = Checkpoint::new(Some(path.clone()));
+    cp.record(done(
+      ElementKind::SessionTemplate,
+      "st",
+      json!({ "name": "st" }),
+    ))
+    .unwrap();
+
+    let loaded = Checkpoint::load(&path, None).unwrap();
+    assert_eq!(loaded.completed, cp.completed);
+    assert!(loaded.path().is_none());
+  }
+
+  #[test]
+  fn load_rejects_unknown_version() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("cp.json");
+    std::fs::write(&path, r#"{ "version": 99, "completed": [] }"#).unwrap();
+    let err = Checkpoint::load(&path, None).unwrap_err().to_string();
+    assert!(err.contains("version 99"), "got: {err}");
+  }
+
+  #[test]
+  fn default_path_depends_on_location_and_content() {
+    let dir = std::env::temp_dir().join("manta-checkpoint-path-test");
+    let (a, b) = (dir.join("a"), dir.join("b"));
+    std::fs::create_dir_all(&a).unwrap();
+    std::fs::create_dir_all(&b).unwrap();
+    let path = |d: &std::path::Path, content: &str| {
+      default_checkpoint_path(&d.join("sat.yaml"), content).unwrap()
+    };
+    let first = path(&a, "configurations: []");
+    assert_eq!(first, path(&a, "configurations: []"));
+    assert_ne!(first, path(&b, "configurations: []"));
+    assert_ne!(first, path(&a, "images: []"));
+    assert!(
+      first
+        .file_name()
+        .unwrap()
+        .to_string_lossy()
+        .starts_with("sat.yaml-")
+    );
+    std::fs::remove_dir_all(&dir).ok();
+  }
+}
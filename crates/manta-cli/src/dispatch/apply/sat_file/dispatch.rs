@@ -13,14 +13,26 @@
 //! on one long server call. `dispatch_plan` only sees the final
 //! `Image` value and records its `id` into `ref_lookup` for any
 //! downstream images / session_templates that reference it.
+//! Independent images (no `base.image_ref` chain between them) build
+//! concurrently, up to `--parallel` at a time.
+//!
+//! Every completed element is recorded into the run's
+//! [`Checkpoint`]; elements the checkpoint already holds (from a
+//! `--resume`d run) are skipped and their recorded outputs replayed.
+//! See [`super::checkpoint`] for the reuse rules.
 
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 
-use anyhow::Context as _;
+use anyhow::bail;
+use futures::StreamExt as _;
+use futures::stream::FuturesUnordered;
 use serde_json::Value;
 
 use super::{
-  exec::SatApplyOptions, image_pipeline::run_image_pipeline, plan::SatElement,
+  checkpoint::{Checkpoint, CompletedElement, ElementKind},
+  exec::SatApplyOptions,
+  image_pipeline::run_image_pipeline,
+  plan::SatElement,
 };
 use crate::http_client::{MantaClient, OpenApiResultExt};
 use crate::openapi_client::types::{
@@ -33,6 +45,13 @@ use crate::openapi_client::types::{
 /// under `image.ref_name.or(image.name)` so subsequent images and
 /// session_templates can resolve their `image_ref` references.
 ///
+/// Each element is first looked up in `checkpoint`: a reusable record
+/// (see [`Checkpoint::reusable`]) is replayed instead of POSTed, and
+/// every element that does run is recorded on success. Images are
+/// built by [`dispatch_images`]; a failed image blocks only the images
+/// that build on it, and no session_template is applied unless every
+/// image succeeded.
+///
 /// Returns a `Value` with the four-list shape
 /// (`{ configurations, images, session_templates, bos_sessions }`) the
 /// existing `print_with_data` helper expects — the same shape the
@@ -42,16 +61,19 @@ use crate::openapi_client::types::{
 /// # Errors
 ///
 /// Returns the first per-element failure encountered: a configuration
-/// or session_template POST that the server rejects, or a failure
-/// inside [`run_image_pipeline`] for an `Image` element.
+/// or session_template POST that the server rejects, or the failure
+/// or failures inside [`run_image_pipeline`] for `Image` elements
+/// (reported together once the image DAG drains). Also fails when the
+/// checkpoint file cannot be written.
 pub async fn dispatch_plan(
   ctx: &crate::common::app_context::AppContext<'_>,
   client: &MantaClient,
   plan: Vec<SatElement>,
   opts: &SatApplyOptions<'_>,
+  checkpoint: &mut Checkpoint,
 ) -> anyhow::Result<Value> {
   let mut ref_lookup: HashMap<String, String> = HashMap::new();
-  let mut images: Vec<Value> = Vec::new();
+  let mut configurations: Vec<Value> = Vec::new();
   let mut session_templates: Vec<Value> = Vec::new();
   let mut bos_sessions: Vec<Value> = Vec::new();
 
@@ -69,9 +91,33 @@ pub async fn dispatch_plan(
     }
   }
 
+  // Configurations declared in this file. An image or template that
+  // names a configuration living only in CFS has nothing to wait for.
+  let file_configs: HashSet<String> =
+    config_bodies.iter().filter_map(entry_name).collect();
+
+  // Replay configurations the checkpoint already holds; the rest are
+  // fanned out concurrently below.
+  config_bodies.retain(|body| {
+    let Some(name) = entry_name(body) else {
+      return true;
+    };
+    match checkpoint.reusable(ElementKind::Configuration, &name, body, &[]) {
+      Some(done) => {
+        tracing::info!("Skipping SAT configuration '{name}' (checkpoint)");
+        configurations.push(done.output);
+        false
+      }
+      None => true,
+    }
+  });
+
   // Fan out all Configuration POSTs concurrently. Configurations have no
   // inter-dependencies (plan.rs topo-sort covers only Image entries).
   // Build request objects upfront so the &req borrows outlive the futures.
+  // `join_all` rather than `try_join_all` so every configuration that
+  // did succeed lands in the checkpoint before the first error is
+  // returned.
   let config_reqs: Vec<PostSatConfigurationRequest> = config_bodies
     .into_iter()
     .map(|body| PostSatConfigurationRequest {
@@ -80,8 +126,8 @@ pub async fn dispatch_plan(
       dry_run: Some(opts.dry_run),
     })
     .collect();
-  let configurations: Vec<Value> =
-    futures::future::try_join_all(config_reqs.iter().map(|req| async move {
+  let config_results =
+    futures::future::join_all(config_reqs.iter().map(|req| async move {
       client
         .openapi
         .post_sat_configuration(client.site_name(), req)
@@ -89,30 +135,66 @@ pub async fn dispatch_plan(
         .into_anyhow()
         .await
     }))
-    .await?;
-
-  // Images stay strictly sequential: each image's id must be recorded in
-  // ref_lookup before any downstream image or session_template that
-  // references it is dispatched.
-  for body in image_elements {
-    let label = image_label(&body);
-    let display_name = body
-      .get("name")
-      .and_then(Value::as_str)
-      .unwrap_or("<unnamed>")
-      .to_string();
-
-    let img = run_image_pipeline(ctx, client, &body, &ref_lookup, opts)
-      .await
-      .with_context(|| format!("building SAT image '{display_name}'"))?;
-
-    if let Some(lab) = label {
-      let id = resolve_image_id(&img, &lab);
-      ref_lookup.insert(lab, id);
+    .await;
+  let mut first_err: Option<anyhow::Error> = None;
+  for (req, result) in config_reqs.iter().zip(config_results) {
+    match result {
+      Ok(cfg) => {
+        if let Some(name) = entry_name(&req.configuration) {
+          checkpoint.record(CompletedElement {
+            kind: ElementKind::Configuration,
+            label: name.clone(),
+            element: req.configuration.clone(),
+            produced_id: name,
+            output: cfg.clone(),
+          })?;
+        }
+        configurations.push(cfg);
+      }
+      Err(e) => {
+        first_err.get_or_insert(e);
+      }
     }
-
-    images.push(img);
   }
+  if let Some(e) = first_err {
+    return Err(e);
+  }
+
+  // Images run as a DAG: an image starts once the image named by its
+  // `base.image_ref` is done, with at most `opts.parallel` builds in
+  // flight. Session templates wait for every image.
+  let images = dispatch_images(
+    ctx,
+    client,
+    image_elements,
+    opts,
+    checkpoint,
+    &file_configs,
+    &mut ref_lookup,
+  )
+  .await?;
+
+  // Replay session_templates the checkpoint already holds. Their BOS
+  // sessions (if any) were created by the earlier run and are not
+  // replayed.
+  st_bodies.retain(|body| {
+    let Some(name) = entry_name(body) else {
+      return true;
+    };
+    match checkpoint.reusable(
+      ElementKind::SessionTemplate,
+      &name,
+      body,
+      &session_template_dependencies(body, &file_configs),
+    ) {
+      Some(done) => {
+        tracing::info!("Skipping SAT session_template '{name}' (checkpoint)");
+        session_templates.push(done.output);
+        false
+      }
+      None => true,
+    }
+  });
 
   // Fan out all SessionTemplate POSTs concurrently. ref_lookup is fully
   // populated at this point (all images complete); each session_template
@@ -128,7 +210,7 @@ pub async fn dispatch_plan(
     })
     .collect();
   let st_results =
-    futures::future::try_join_all(st_reqs.iter().map(|req| async move {
+    futures::future::join_all(st_reqs.iter().map(|req| async move {
       client
         .openapi
         .post_sat_session_template(client.site_name(), req)
@@ -136,8 +218,25 @@ pub async fn dispatch_plan(
         .into_anyhow()
         .await
     }))
-    .await?;
-  for resp in st_results {
+    .await;
+  let mut first_err: Option<anyhow::Error> = None;
+  for (req, result) in st_reqs.iter().zip(st_results) {
+    let resp = match result {
+      Ok(resp) => resp,
+      Err(e) => {
+        first_err.get_or_insert(e);
+        continue;
+      }
+    };
+    if let Some(name) = entry_name(&req.session_template) {
+      checkpoint.record(CompletedElement {
+        kind: ElementKind::SessionTemplate,
+        label: name.clone(),
+        element: req.session_template.clone(),
+        produced_id: name,
+        output: resp.template.clone(),
+      })?;
+    }
     session_templates.push(resp.template);
     if let Some(s) = resp.session
       && matches!(s, Value::Object(_))
@@ -145,6 +244,9 @@ pub async fn dispatch_plan(
       bos_sessions.push(s);
     }
   }
+  if let Some(e) = first_err {
+    return Err(e);
+  }
 
   Ok(serde_json::json!({
     "configurations": configurations,
@@ -154,6 +256,250 @@ pub async fn dispatch_plan(
   }))
 }
 
+/// Scheduling state of one `images[]` entry in [`dispatch_images`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ImageState {
+  Pending,
+  Running,
+  Done,
+  Failed,
+  /// Never started: an image it (transitively) builds on failed.
+  Blocked,
+}
+
+/// Build every image in `image_elements` (topologically sorted by
+/// `plan::build_plan`) and return their `Image` values in plan order.
+///
+/// Images are scheduled as a DAG over `base.image_ref`: an image is
+/// started once its parent is done, independent images run
+/// concurrently up to `opts.parallel`, and a checkpointed image is
+/// reused instead of rebuilt (see [`Checkpoint::reusable`]). With
+/// `--parallel` > 1 each streamed log line is prefixed with the image
+/// name so interleaved output stays readable.
+///
+/// A failed image never stops builds that do not depend on it — those
+/// keep running and land in the checkpoint — but everything that
+/// builds on it (directly or through another image) is blocked and
+/// never started. Each completed image's id is recorded in
+/// `ref_lookup`.
+///
+/// # Errors
+///
+/// Returns an error listing every failed image (with its cause) and
+/// every image blocked behind one, once nothing else can run. Also
+/// fails when the checkpoint file cannot be written.
+async fn dispatch_images(
+  ctx: &crate::common::app_context::AppContext<'_>,
+  client: &MantaClient,
+  image_elements: Vec<Value>,
+  opts: &SatApplyOptions<'_>,
+  checkpoint: &mut Checkpoint,
+  file_configs: &HashSet<String>,
+  ref_lookup: &mut HashMap<String, String>,
+) -> anyhow::Result<Vec<Value>> {
+  let n = image_elements.len();
+  let parents = image_parents(&image_elements);
+  let labels: Vec<Option<String>> =
+    image_elements.iter().map(image_label).collect();
+  let names: Vec<String> = image_elements
+    .iter()
+    .map(|body| {
+      body
+        .get("name")
+        .and_then(Value::as_str)
+        .unwrap_or("<unnamed>")
+        .to_string()
+    })
+    .collect();
+  let limit = opts.parallel.max(1);
+
+  let mut state = vec![ImageState::Pending; n];
+  let mut results: Vec<Option<Value>> = vec![None; n];
+  let mut failures: Vec<(usize, anyhow::Error)> = Vec::new();
+  let mut in_flight = FuturesUnordered::new();
+
+  loop {
+    // One forward pass per completion. The list is topologically
+    // sorted, so a parent's state is final for this pass by the time
+    // its children are visited.
+    for i in 0..n {
+      if state[i] != ImageState::Pending {
+        continue;
+      }
+      match parents[i].map(|p| state[p]) {
+        Some(ImageState::Failed | ImageState::Blocked) => {
+          state[i] = ImageState::Blocked;
+          continue;
+        }
+        Some(ImageState::Pending | ImageState::Running) => continue,
+        None | Some(ImageState::Done) => {}
+      }
+
+      if let Some(lab) = &labels[i]
+        && let Some(done) = checkpoint.reusable(
+          ElementKind::Image,
+          lab,
+          &image_elements[i],
+          &image_dependencies(&image_elements[i], file_configs),
+        )
+      {
+        tracing::info!(
+          "Skipping SAT image '{}' (checkpoint: image id '{}')",
+          names[i],
+          done.produced_id
+        );
+        ref_lookup.insert(lab.clone(), done.produced_id);
+        results[i] = Some(done.output);
+        state[i] = ImageState::Done;
+        continue;
+      }
+
+      if in_flight.len() >= limit {
+        continue;
+      }
+      state[i] = ImageState::Running;
+      // Each build gets its own snapshot of `ref_lookup`: everything
+      // it references is already done, and the live map keeps
+      // changing while it runs.
+      let body = image_elements[i].clone();
+      let lookup = ref_lookup.clone();
+      let log_prefix = if limit > 1 {
+        format!("[{}] ", names[i])
+      } else {
+        String::new()
+      };
+      in_flight.push(async move {
+        let result =
+          run_image_pipeline(ctx, client, &body, &lookup, opts, &log_prefix)
+            .await;
+        (i, result)
+      });
+    }
+
+    let Some((i, result)) = in_flight.next().await else {
+      break;
+    };
+    match result {
+      Ok(img) => {
+        if let Some(lab) = &labels[i] {
+          let id = resolve_image_id(&img, lab);
+          checkpoint.record(CompletedElement {
+            kind: ElementKind::Image,
+            label: lab.clone(),
+            element: image_elements[i].clone(),
+            produced_id: id.clone(),
+            output: img.clone(),
+          })?;
+          ref_lookup.insert(lab.clone(), id);
+        }
+        results[i] = Some(img);
+        state[i] = ImageState::Done;
+      }
+      Err(e) => {
+        tracing::error!("SAT image '{}' failed: {e:#}", names[i]);
+        failures.push((i, e));
+        state[i] = ImageState::Failed;
+      }
+    }
+  }
+
+  if !failures.is_empty() {
+    let mut msg = format!("{} SAT image(s) failed:", failures.len());
+    failures.sort_by_key(|(i, _)| *i);
+    for (i, e) in &failures {
+      msg.push_str(&format!("\n  - building SAT image '{}': {e:#}", names[*i]));
+    }
+    let blocked: Vec<&str> = (0..n)
+      .filter(|i| state[*i] == ImageState::Blocked)
+      .map(|i| names[i].as_str())
+      .collect();
+    if !blocked.is_empty() {
+      msg.push_str(&format!(
+        "\nNot started (builds on a failed image): {}",
+        blocked.join(", ")
+      ));
+    }
+    bail!(msg);
+  }
+
+  Ok(results.into_iter().flatten().collect())
+}
+
+/// For each image, the index of the image its `base.image_ref` names
+/// (matched against `ref_name.or(name)`), or `None` for images built
+/// from an IMS image or recipe. `plan::build_plan` has already
+/// rejected dangling references and cycles.
+fn image_parents(images: &[Value]) -> Vec<Option<usize>> {
+  let labels: Vec<Option<String>> = images.iter().map(image_label).collect();
+  images
+    .iter()
+    .map(|body| {
+      let dep = body
+        .get("base")
+        .and_then(|b| b.get("image_ref"))
+        .and_then(Value::as_str)?;
+      labels.iter().position(|l| l.as_deref() == Some(dep))
+    })
+    .collect()
+}
+
+/// `name` of a configuration or session_template entry — the label
+/// those elements are checkpointed under.
+fn entry_name(body: &Value) -> Option<String> {
+  body.get("name").and_then(Value::as_str).map(str::to_string)
+}
+
+/// What an image entry must find reused before it may be reused
+/// itself: its `configuration` (when declared in this file, i.e. in
+/// `file_configs`) and, when present, the image named by
+/// `base.image_ref`.
+fn image_dependencies(
+  body: &Value,
+  file_configs: &HashSet<String>,
+) -> Vec<(ElementKind, String)> {
+  let mut deps = configuration_dependency(body, file_configs);
+  if let Some(base) = body
+    .get("base")
+    .and_then(|b| b.get("image_ref"))
+    .and_then(Value::as_str)
+  {
+    deps.push((ElementKind::Image, base.to_string()));
+  }
+  deps
+}
+
+/// Same as [`image_dependencies`] for a session_template entry: its
+/// `configuration` and the image named by `image.image_ref`.
+/// Templates pointing at a pre-built image (`image.ims`) only depend
+/// on their configuration.
+fn session_template_dependencies(
+  body: &Value,
+  file_configs: &HashSet<String>,
+) -> Vec<(ElementKind, String)> {
+  let mut deps = configuration_dependency(body, file_configs);
+  if let Some(image_ref) = body
+    .get("image")
+    .and_then(|i| i.get("image_ref"))
+    .and_then(Value::as_str)
+  {
+    deps.push((ElementKind::Image, image_ref.to_string()));
+  }
+  deps
+}
+
+/// The entry's `configuration`, if this file declares it.
+fn configuration_dependency(
+  body: &Value,
+  file_configs: &HashSet<String>,
+) -> Vec<(ElementKind, String)> {
+  body
+    .get("configuration")
+    .and_then(Value::as_str)
+    .filter(|cfg| file_configs.contains(*cfg))
+    .map(|cfg| vec![(ElementKind::Configuration, cfg.to_string())])
+    .unwrap_or_default()
+}
+
 /// The label downstream `image_ref` references resolve against —
 /// `ref_name` if the image declares one, else `name`. Matches csm-rs's
 /// `ref_name.or(name)` resolver.
@@ -178,8 +524,70 @@ fn resolve_image_id(img: &Value, label: &str) -> String {
 
 #[cfg(test)]
 mod tests {
-  use super::{image_label, resolve_image_id};
+  use super::{
+    ElementKind, image_dependencies, image_label, image_parents,
+    resolve_image_id, session_template_dependencies,
+  };
   use serde_json::json;
+  use std::collections::HashSet;
+
+  fn configs(names: &[&str]) -> HashSet<String> {
+    names.iter().map(|n| (*n).to_string()).collect()
+  }
+
+  #[test]
+  fn image_parents_resolve_image_ref_by_label() {
+    let images = vec![
+      json!({ "name": "base", "ref_name": "base-ref", "base": { "ims": {} } }),
+      json!({ "name": "other", "base": { "ims": {} } }),
+      json!({ "name": "child", "base": { "image_ref": "base-ref" } }),
+      json!({ "name": "grandchild", "base": { "image_ref": "child" } }),
+    ];
+    assert_eq!(image_parents(&images), vec![None, None, Some(0), Some(2)]);
+  }
+
+  #[test]
+  fn image_dependencies_cover_configuration_and_base_image() {
+    let body = json!({
+      "name": "child",
+      "configuration": "cfg-1",
+      "base": { "image_ref": "parent" },
+    });
+    assert_eq!(
+      image_dependencies(&body, &configs(&["cfg-1"])),
+      vec![
+        (ElementKind::Configuration, "cfg-1".to_string()),
+        (ElementKind::Image, "parent".to_string()),
+      ]
+    );
+  }
+
+  #[test]
+  fn image_dependencies_empty_for_standalone_ims_image() {
+    let body = json!({ "name": "img", "ims": { "id": "abc" } });
+    assert!(image_dependencies(&body, &configs(&[])).is_empty());
+  }
+
+  /// A configuration that lives only in CFS (not declared in this
+  /// file) is never checkpointed, so it must not block reuse.
+  #[test]
+  fn image_dependencies_skip_configuration_not_in_file() {
+    let body = json!({ "name": "img", "configuration": "cfg-in-cfs" });
+    assert!(image_dependencies(&body, &configs(&["other"])).is_empty());
+  }
+
+  #[test]
+  fn session_template_dependencies_ignore_prebuilt_ims_image() {
+    let body = json!({
+      "name": "st",
+      "configuration": "cfg-1",
+      "image": { "ims": { "id": "abc" } },
+    });
+    assert_eq!(
+      session_template_dependencies(&body, &configs(&["cfg-1"])),
+      vec![(ElementKind::Configuration, "cfg-1".to_string())]
+    );
+  }
 
   #[test]
   fn image_label_prefers_ref_name() {
@@ -16,11 +16,14 @@
 //! accumulates the `ref_name → image_id` map between calls so
 //! downstream `image_ref` references resolve.
 //!
-//! The CLI never embeds the SAT schema: filtering and plan-building
-//! navigate a small set of field names (`configurations`, `images`,
-//! `session_templates`, `hardware`, `name`, `ref_name`, `configuration`,
-//! `image`, `image_ref`, `ims`) on the `serde_json::Value`. The
-//! canonical schema lives in csm-rs (which deserialises during apply).
+//! Right after rendering, the YAML is checked against the typed SAT
+//! schema in `manta-shared` (see [`validate`]) so a misspelled field
+//! fails before the preview instead of mid-apply. Filtering and
+//! plan-building still navigate a small set of field names
+//! (`configurations`, `images`, `session_templates`, `hardware`,
+//! `name`, `ref_name`, `configuration`, `image`, `image_ref`, `ims`)
+//! on the `serde_json::Value`, which is what gets sent; the canonical
+//! schema lives in csm-rs (which deserialises during apply).
 //!
 //! On success [`dispatch::dispatch_plan`] returns the same four-list
 //! summary the legacy `POST /sat-file` endpoint used to produce
@@ -30,13 +33,16 @@
 //! sees a status message plus pretty-printed JSON (or `{ "status":
 //! "ok", "message": ..., "data": ... }` with `--output json`).
 
+use std::path::Path;
+
 use anyhow::{Context, Error, bail};
 use crossterm::style::Stylize;
 
 use super::render::render_jinja2_sat_file_yaml;
 use crate::common;
 use crate::common::app_context::AppContext;
-use crate::dispatch::apply::sat_file::{dispatch, plan};
+use crate::dispatch::apply::sat_file::checkpoint::Checkpoint;
+use crate::dispatch::apply::sat_file::{dispatch, plan, validate};
 use crate::http_client::MantaClient;
 use crate::output::action_result;
 
@@ -47,10 +53,14 @@ use crate::output::action_result;
 pub struct SatApplyOptions<'a> {
   /// Raw text of the SAT template before Jinja2 rendering.
   pub sat_file_content: &'a str,
-  /// Optional values-file text providing Jinja2 variables.
-  pub values_file_content_opt: Option<&'a str>,
+  /// Values-file texts providing Jinja2 variables, later files
+  /// winning on conflicting keys.
+  pub values_file_contents: &'a [String],
   /// Optional CLI `--var key=value` overrides (highest precedence).
   pub values_cli_opt: Option<&'a [String]>,
+  /// SAT bundle root that `{% import %}` / `{% include %}` resolve
+  /// against.
+  pub bundle_dir_opt: Option<&'a std::path::Path>,
   /// `--ansible-verbosity` (0-255) forwarded to the per-image CFS
   /// session.
   pub ansible_verbosity_opt: Option<u8>,
@@ -84,23 +94,39 @@ pub struct SatApplyOptions<'a> {
   /// prompts.
   pub assume_yes: bool,
   pub output_opt: Option<&'a str>,
+  /// File progress is recorded to after every completed element (see
+  /// [`super::checkpoint`]). Ignored on `--dry-run`.
+  pub checkpoint_path: &'a Path,
+  /// `--resume`: checkpoint from an earlier run whose completed
+  /// elements are skipped. Progress keeps being recorded to
+  /// `checkpoint_path`.
+  pub resume_opt: Option<&'a Path>,
+  /// `--skip-validation`: do not check the rendered file against the
+  /// typed SAT schema before planning.
+  pub skip_validation: bool,
+  /// `--parallel`: maximum number of images built concurrently (at
+  /// least 1). See [`super::dispatch`].
+  pub parallel: usize,
 }
 
 /// Process and apply a SAT file to the system.
 ///
-/// Top-level pipeline driver: render Jinja2 -> parse YAML -> filter +
-/// build plan -> preview + confirm -> server-side validate -> pre-hook
-/// -> dispatch -> post-hook -> print summary.
+/// Top-level pipeline driver: render Jinja2 -> schema check -> parse
+/// YAML -> filter + build plan -> preview + confirm -> server-side
+/// validate -> pre-hook -> dispatch (skipping checkpointed elements on `--resume`) ->
+/// post-hook -> print summary.
 ///
 /// # Errors
 ///
 /// Returns an error when a hook script is missing or not executable,
-/// when Jinja2 rendering fails, when the rendered SAT YAML cannot be
-/// parsed, when [`plan::build_plan`] rejects the file (filter
+/// when Jinja2 rendering fails, when the rendered SAT file fails the
+/// schema check, when the rendered SAT YAML cannot be parsed, when [`plan::build_plan`] rejects the file (filter
 /// mismatch, dangling `image_ref`, cycle), when the user declines the
 /// preview or BOS-session confirmation prompts, when the server-side
-/// validation call fails, when any plan element's POST fails, or when
-/// a hook process exits non-zero.
+/// validation call fails, when the `--resume` checkpoint cannot be
+/// read, when any plan element's POST fails (the message then names
+/// the checkpoint to resume from), or when a hook process exits
+/// non-zero.
 pub async fn exec(
   ctx: &AppContext<'_>,
   token: &str,
@@ -109,19 +135,34 @@ pub async fn exec(
   common::hooks::validate_hook(opts.prehook_opt, "Pre")?;
   common::hooks::validate_hook(opts.posthook_opt, "Post")?;
 
+  // Load the resume checkpoint up front so a bad path fails before any
+  // rendering or prompting. Dry-run never persists progress.
+  let persist_to = (!opts.dry_run).then(|| opts.checkpoint_path.to_path_buf());
+  let mut checkpoint = match opts.resume_opt {
+    Some(path) => Checkpoint::load(path, persist_to)?,
+    None => Checkpoint::new(persist_to),
+  };
+
   // 1. Render Jinja2 (text-in / text-out).
   tracing::info!("Render SAT template file");
   let rendered_yaml = render_jinja2_sat_file_yaml(
     opts.sat_file_content,
-    opts.values_file_content_opt,
+    opts.values_file_contents,
     opts.values_cli_opt,
+    opts.bundle_dir_opt,
   )
   .context("Failed to render SAT Jinja2 template")?;
 
-  // 2. Parse into a structured value. The CLI carries the SAT file as
-  //    `serde_json::Value` end-to-end — the server forwards it
-  //    verbatim, and csm-rs transcodes to its preferred shape during
-  //    apply. No SAT schema lives in the CLI.
+  // 2. Check the rendered file against the typed schema, then parse
+  //    it into a structured value. The typed form is only used for the
+  //    check: the CLI carries the SAT file as `serde_json::Value`
+  //    end-to-end — the server forwards it verbatim, and csm-rs
+  //    transcodes to its preferred shape during apply.
+  if opts.skip_validation {
+    tracing::warn!("Skipping SAT schema validation (--skip-validation)");
+  } else {
+    validate::check_schema(&rendered_yaml)?;
+  }
   let mut sat_file: serde_json::Value = serde_yaml::from_str(&rendered_yaml)
     .context("Rendered SAT template is not valid YAML")?;
 
@@ -182,7 +223,19 @@ pub async fn exec(
   //     `ref_name → image_id` across calls and builds the same
   //     four-list response the legacy endpoint used to return.
   let client = MantaClient::from_app_ctx(ctx, Some(token))?;
-  let result = dispatch::dispatch_plan(ctx, &client, plan, opts).await?;
+  let result =
+    dispatch::dispatch_plan(ctx, &client, plan, opts, &mut checkpoint)
+      .await
+      .map_err(|e| match checkpoint.path() {
+        // `main` prints only the outermost message, so fold the cause
+        // chain into the hint rather than wrapping it in `context`.
+        Some(path) => anyhow::anyhow!(
+          "{e:#}\n\nProgress saved to '{0}'. Fix the failure and re-run \
+           with `--resume {0}` to skip the elements that completed.",
+          path.display()
+        ),
+        None => e,
+      })?;
 
   crate::common::hooks::run_hook_if_present(opts.posthook_opt, "post")?;
 
@@ -36,12 +36,11 @@ use std::{
 
 use anyhow::{Context, bail};
 use serde_json::Value;
-use tokio::io::AsyncBufReadExt as _;
 
 use manta_shared::types::dto::CfsSessionGetResponse;
 
 use super::exec::SatApplyOptions;
-use crate::http_client::{MantaClient, OpenApiResultExt};
+use crate::http_client::{MantaClient, OpenApiResultExt, SessionLogFilter};
 use crate::openapi_client::types::{
   CreateImageCfsSessionRequest, StampImageFromSessionRequest,
 };
@@ -98,6 +97,10 @@ impl SatMonitorBudgets {
 /// so the caller (`dispatch_plan`) can drop it into the `images: [...]`
 /// summary list unchanged.
 ///
+/// `log_prefix` is prepended to every streamed `--watch-logs` line;
+/// `dispatch_images` passes `[<image name>] ` when several images
+/// build at once so their interleaved logs can be told apart.
+///
 /// # Errors
 ///
 /// Returns an error when the create-session POST fails or its
@@ -111,6 +114,7 @@ pub async fn run_image_pipeline(
   image: &Value,
   ref_lookup: &HashMap<String, String>,
   opts: &SatApplyOptions<'_>,
+  log_prefix: &str,
 ) -> anyhow::Result<Value> {
   // 1. Translate the SAT image entry into a CFS session and create it.
   let session_value = client
@@ -168,6 +172,7 @@ pub async fn run_image_pipeline(
       client,
       &session_name,
       opts.timestamps,
+      log_prefix,
       budgets,
     )
     .await?;
@@ -211,27 +216,25 @@ async fn stream_session_until_terminal(
   client: &MantaClient,
   session_name: &str,
   timestamps: bool,
+  log_prefix: &str,
   budgets: SatMonitorBudgets,
 ) -> anyhow::Result<()> {
   // Capture start before streaming so the fallback poll phase counts
   // wall time already consumed by the stream against the same budget.
   let start = Instant::now();
   tracing::info!("Streaming logs for CFS session '{session_name}' ...");
-  let reader = client
-    .stream_session_logs(session_name, timestamps)
+  let mut log = client
+    .follow_session_logs(session_name, timestamps, SessionLogFilter::default())
     .await
     .with_context(|| {
       format!("open SSE log stream for CFS session '{session_name}'")
     })?;
-  let mut lines = reader.lines();
-  while let Some(raw) = lines
+  while let Some(line) = log
     .next_line()
     .await
     .context("read CFS session log stream")?
   {
-    if let Some(content) = raw.strip_prefix("data: ") {
-      println!("{content}");
-    }
+    println!("{log_prefix}{line}");
   }
 
   poll_session_until_terminal(client, session_name, budgets, start).await
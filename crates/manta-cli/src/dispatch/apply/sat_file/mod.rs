@@ -5,8 +5,11 @@
 //! 1. **Render** the SAT-file template by layering the values file
 //!    and any `--var key=value` overrides through Jinja2, producing
 //!    a YAML string.
-//! 2. **Parse** the YAML into a `serde_json::Value` (the CLI keeps
-//!    SAT untyped — csm-rs owns the schema).
+//! 2. **Validate + parse**: check the YAML against the typed schema
+//!    in `manta_shared::types::sat_schema` (unknown fields, missing
+//!    fields, bad enum values, "exactly one of" rules), then parse it
+//!    into the `serde_json::Value` that is actually sent — csm-rs
+//!    still owns the canonical schema.
 //! 3. **Plan**: walk the parsed `Value` and produce an ordered
 //!    `Vec<SatElement>` (configurations → images → session
 //!    templates), honouring the `--image-only` /
@@ -20,12 +23,17 @@
 //!    dispatcher hands off to [`image_pipeline`], which drives the
 //!    multi-step CFS-session lifecycle (create → monitor → stamp)
 //!    so the operator sees progress instead of blocking on one long
-//!    server call.
+//!    server call. Every completed element is recorded in a
+//!    [`checkpoint`] file so a failed run can be `--resume`d.
 //!
 //! Module map:
 //!
 //! - [`render`] — Jinja2 rendering: layer the values file + `--var`
 //!   overrides, then render the SAT template to a YAML string.
+//! - [`validate`] — offline schema check of the rendered YAML, shared
+//!   with `manta validate sat-file`.
+//! - [`checkpoint`] — on-disk record of completed elements and the
+//!   ids they produced; drives `--resume`.
 //! - [`exec`] — entry point: render, parse, filter, build the plan,
 //!   preview + confirm, then dispatch.
 //! - [`plan`] — walks the parsed SAT `Value` and returns
@@ -41,11 +49,13 @@
 //!   observe the image build instead of blocking on one long server
 //!   call.
 
+pub mod checkpoint;
 pub mod dispatch;
 pub mod exec;
 pub mod image_pipeline;
 pub mod plan;
 pub mod render;
+pub mod validate;
 // -- TESTS --
 #[cfg(test)]
 pub mod tests;
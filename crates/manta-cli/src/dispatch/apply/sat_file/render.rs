@@ -5,24 +5,177 @@
 //! into typed structs during apply). This module only handles the
 //! pre-parse step the CLI needs:
 //!
-//! 1. Render Jinja2 templates with a values file + `--var` overrides
-//!    (the renderer takes parsed YAML as Jinja context for the values
-//!    file but produces a string for the SAT file content).
+//! 1. Render Jinja2 templates with layered values files + `--var`
+//!    overrides (the renderer takes parsed YAML as Jinja context for
+//!    the values files but produces a string for the SAT file
+//!    content). The template's directory is the bundle root: a path
+//!    loader lets templates `{% import %}` shared macros and
+//!    `{% include %}` per-cluster fragments from it.
 //! 2. After rendering, the caller parses the result into a
 //!    `serde_json::Value` itself.
 //!
+//! Secrets are never rendered here. `{{ secret("vault://<path>#<key>") }}`
+//! and `!secret vault://<path>#<key>` values render to the opaque
+//! placeholder defined in [`manta_shared::types::secret_ref`]; the
+//! server resolves them against the site's Vault when the entry is
+//! applied, so the rendered file, the dry-run preview and the CLI logs
+//! only ever show the reference.
+//!
 //! The `--image-only` / `--sessiontemplate-only` filters live inside
 //! `build_plan` in [`super::plan`], where they share the SAT-file walk
 //! that builds the execution plan.
 
+use std::path::{Path, PathBuf};
+
+use anyhow::Context as _;
+use clap::ArgMatches;
 use serde_yaml::{Mapping, Value};
 
 use manta_shared::common::error::MantaError as Error;
+use manta_shared::types::secret_ref::SecretRef;
+
+/// YAML tag marking a values-file entry as a Vault secret reference.
+const SECRET_TAG: &str = "secret";
+
+/// Raw renderer inputs read from the `-t` / `-f` / `-V` arguments
+/// that `apply sat-file` and `validate sat-file` share. `__DATE__` in
+/// the values files and `--values` overrides is replaced with the
+/// current UTC timestamp (`%Y%m%d%H%M%S`).
+pub struct SatTemplateInputs {
+  /// `-t/--sat-template-file` path.
+  pub sat_template_file: PathBuf,
+  /// Contents of the SAT template.
+  pub sat_file_content: String,
+  /// Contents of each `-f/--values-file`, in command-line order.
+  pub values_file_contents: Vec<String>,
+  /// `-V/--values` overrides, if given.
+  pub values_cli_opt: Option<Vec<String>>,
+}
+
+impl SatTemplateInputs {
+  /// Read the template and values files named in `m`.
+  ///
+  /// # Errors
+  ///
+  /// Returns an error when `--sat-template-file` is missing or any
+  /// file cannot be read.
+  pub fn read(m: &ArgMatches) -> anyhow::Result<Self> {
+    let timestamp = chrono::Utc::now().format("%Y%m%d%H%M%S").to_string();
+
+    let values_cli_opt: Option<Vec<String>> =
+      m.get_many("values").map(|value_vec| {
+        value_vec
+          .map(|value: &String| value.replace("__DATE__", &timestamp))
+          .collect()
+      });
+
+    let values_file_contents: Vec<String> = m
+      .get_many::<PathBuf>("values-file")
+      .into_iter()
+      .flatten()
+      .map(|values_file_path| {
+        std::fs::read_to_string(values_file_path)
+          .map(|content| content.replace("__DATE__", &timestamp))
+          .with_context(|| {
+            format!(
+              "Failed to read values file '{}'",
+              values_file_path.display()
+            )
+          })
+      })
+      .collect::<anyhow::Result<_>>()?;
+
+    let sat_template_file = m
+      .get_one::<PathBuf>("sat-template-file")
+      .context("SAT template file argument not provided")?
+      .clone();
+
+    let sat_file_content: String = std::fs::read_to_string(&sat_template_file)
+      .with_context(|| {
+        format!(
+          "Could not read SAT file template '{}'",
+          sat_template_file.display()
+        )
+      })?;
+
+    Ok(Self {
+      sat_template_file,
+      sat_file_content,
+      values_file_contents,
+      values_cli_opt,
+    })
+  }
+
+  /// Root of the SAT bundle: the directory holding the template.
+  /// `{% import %}` / `{% include %}` paths resolve against it.
+  pub fn bundle_dir(&self) -> &Path {
+    self
+      .sat_template_file
+      .parent()
+      .filter(|p| !p.as_os_str().is_empty())
+      .unwrap_or(Path::new("."))
+  }
+}
+
+/// `secret("vault://<path>#<key>")` — the Jinja function behind
+/// secret references. Returns the placeholder the server later
+/// resolves; the CLI never sees the value.
+fn secret_fn(uri: String) -> Result<String, minijinja::Error> {
+  SecretRef::parse(&uri)
+    .map(|r| r.placeholder())
+    .map_err(|e| {
+      minijinja::Error::new(minijinja::ErrorKind::InvalidOperation, e)
+    })
+}
+
+/// Rewrite every `!secret <uri>` in the values file text into the
+/// equivalent `"{{ secret('<uri>') }}"` call before the file is parsed
+/// and rendered. This has to happen on the text: the values file is
+/// itself rendered as a Jinja template, and the `#` line-comment
+/// syntax would otherwise cut the `#<key>` off a bare tagged scalar.
+/// The URI may be single- or double-quoted.
+fn rewrite_secret_tags(values_file_content: &str) -> Result<String, Error> {
+  let tag = format!("!{SECRET_TAG}");
+  let mut out = String::with_capacity(values_file_content.len());
+  let mut rest = values_file_content;
+  while let Some(start) = rest.find(&tag) {
+    let (before, after_tag) = rest.split_at(start);
+    let after_tag = &after_tag[tag.len()..];
+    let at_token_start = before
+      .chars()
+      .next_back()
+      .is_none_or(|c| c.is_whitespace() || c == '-' || c == ':');
+    if !at_token_start || !after_tag.starts_with([' ', '\t']) {
+      out.push_str(before);
+      out.push_str(&tag);
+      rest = after_tag;
+      continue;
+    }
+    let scalar_start = after_tag.trim_start_matches([' ', '\t']);
+    let end = scalar_start
+      .find(char::is_whitespace)
+      .unwrap_or(scalar_start.len());
+    let raw = &scalar_start[..end];
+    let uri = raw
+      .strip_prefix('"')
+      .and_then(|r| r.strip_suffix('"'))
+      .or_else(|| raw.strip_prefix('\'').and_then(|r| r.strip_suffix('\'')))
+      .unwrap_or(raw);
+    SecretRef::parse(uri).map_err(|e| {
+      Error::TemplateError(format!("Invalid '{tag}' value in values file: {e}"))
+    })?;
+    out.push_str(before);
+    out.push_str(&format!("\"{{{{ secret('{uri}') }}}}\""));
+    rest = &scalar_start[end..];
+  }
+  out.push_str(rest);
+  Ok(out)
+}
 
 /// Merges two `serde_yaml::Value`s into a single `serde_yaml::Value`.
 /// `merge` values override `base` values when keys collide; sequences
-/// concatenate. Used to layer CLI `--var` overrides on top of a values
-/// file during Jinja rendering.
+/// concatenate. Used to layer values files over each other, and CLI
+/// `--var` overrides over the result, during Jinja rendering.
 fn merge_yaml(base: Value, merge: Value) -> Option<Value> {
   match (base, merge) {
     (Value::Mapping(mut base_map), Value::Mapping(merge_map)) => {
@@ -78,22 +231,30 @@ fn dot_notation_to_yaml(dot_notation: &str) -> Result<Value, Error> {
 /// value they need (CLI parses to [`serde_json::Value`]).
 ///
 /// Precedence on variable conflicts (highest wins): CLI `--var`
-/// overrides, then the values file, then the SAT file itself. Strict
+/// overrides, then the values files (later files merged over earlier
+/// ones with [`merge_yaml`]), then the SAT file itself. Each values
+/// file is rendered against the earlier files, its own values and the
+/// CLI overrides, so it can refer to any of them. With
+/// `bundle_dir_opt` set, templates and values files can
+/// `{% import %}` macros and `{% include %}` fragments by path
+/// relative to that directory; paths cannot escape it. Strict
 /// undefined-variable behaviour is enabled — referencing an unset
 /// variable returns an error rather than rendering an empty string.
 ///
 /// # Errors
 ///
 /// Returns a [`MantaError`](manta_shared::common::error::MantaError)
-/// when the Jinja2 environment fails to configure, when the values
+/// when the Jinja2 environment fails to configure, when a values
 /// file is not valid YAML, when a CLI `--var` string is not in
 /// `key.path=value` form, when merging the CLI overrides fails, or
 /// when either render step encounters an undefined variable or
-/// malformed template.
+/// malformed template, or when a `secret()` / `!secret` reference is
+/// not of the form `vault://<path>#<key>`.
 pub fn render_jinja2_sat_file_yaml(
   sat_file_content: &str,
-  values_file_content_opt: Option<&str>,
+  values_file_contents: &[String],
   value_cli_vec_opt: Option<&[String]>,
+  bundle_dir_opt: Option<&Path>,
 ) -> Result<String, Error> {
   let mut env = minijinja::Environment::new();
   // Set/enable debug in order to force minijinja to print debug error messages which are more
@@ -113,56 +274,123 @@ pub fn render_jinja2_sat_file_yaml(
   // Set 'String' as undefined behaviour meaning, missing values won't pass the template
   // rendering
   env.set_undefined_behavior(minijinja::UndefinedBehavior::Strict);
+  env.add_function("secret", secret_fn);
+  if let Some(bundle_dir) = bundle_dir_opt {
+    env.set_loader(minijinja::path_loader(bundle_dir));
+  }
 
-  // Render session values file
-  let mut values_file_yaml: Value = if let Some(values_file_content) =
-    values_file_content_opt
-  {
-    tracing::info!(
-      "'Session vars' file provided. Going to process SAT file as a jinja template."
-    );
-    tracing::info!("Expand variables in 'session vars' file");
-    let values_file_yaml: Value = serde_yaml::from_str(values_file_content)?;
-    let values_file_rendered = env
-      .render_str(values_file_content, values_file_yaml)
-      .map_err(|e| {
-        Error::TemplateError(format!("Error parsing values file to YAML: {e}"))
-      })?;
-    serde_yaml::from_str(&values_file_rendered)?
-  } else {
-    serde_yaml::from_str(sat_file_content)?
-  };
-
-  // Convert variable values sent by cli argument from dot notation to yaml format
+  // Convert variable values sent by cli argument from dot notation to
+  // yaml format. Done first so values files can refer to them too.
   tracing::debug!(
     "Convert variable values sent by cli argument from dot notation to yaml format"
   );
-  if let Some(value_option_vec) = value_cli_vec_opt {
-    for value_option in value_option_vec {
-      let cli_var_context_yaml = dot_notation_to_yaml(value_option)?;
-
-      values_file_yaml =
-        merge_yaml(values_file_yaml.clone(), cli_var_context_yaml).ok_or_else(
-          || {
-            Error::TemplateError(
-              "Failed to merge CLI variable values into \
-             SAT file YAML"
-                .to_string(),
-            )
-          },
-        )?;
-    }
+  let mut cli_vars_yaml = Value::Mapping(Mapping::new());
+  for value_option in value_cli_vec_opt.unwrap_or_default() {
+    let cli_var_context_yaml = dot_notation_to_yaml(value_option)?;
+    cli_vars_yaml = merge_yaml(cli_vars_yaml, cli_var_context_yaml)
+      .ok_or_else(|| {
+        Error::TemplateError(
+          "Failed to merge CLI variable values into SAT file YAML".to_string(),
+        )
+      })?;
+  }
+
+  if values_file_contents.is_empty() {
+    // No values file: the SAT file is its own context.
+    tracing::info!("Expand variables in 'SAT file'");
+    let (self_yaml, parse_err) = self_context(sat_file_content);
+    return render_with_context(
+      &env,
+      sat_file_content,
+      merge_with(self_yaml, &cli_vars_yaml)?,
+      parse_err,
+      "Failed to render SAT file template",
+    );
+  }
+
+  // Render session values files, layering later files over earlier ones
+  tracing::info!(
+    "'Session vars' file provided. Going to process SAT file as a jinja template."
+  );
+  let mut values_file_yaml = Value::Mapping(Mapping::new());
+  for (idx, values_file_content) in values_file_contents.iter().enumerate() {
+    tracing::info!("Expand variables in 'session vars' file #{}", idx + 1);
+    let values_file_content = rewrite_secret_tags(values_file_content)?;
+    // Each file sees the earlier files, its own values (for values
+    // that refer to other values) and the CLI overrides, in rising
+    // precedence.
+    let (self_yaml, parse_err) = self_context(&values_file_content);
+    let context = merge_with(
+      merge_with(values_file_yaml.clone(), &self_yaml)?,
+      &cli_vars_yaml,
+    )?;
+    let values_file_rendered = render_with_context(
+      &env,
+      &values_file_content,
+      context,
+      parse_err,
+      &format!("Error parsing values file #{} to YAML", idx + 1),
+    )?;
+    let rendered: Value = serde_yaml::from_str(&values_file_rendered)?;
+    values_file_yaml =
+      merge_yaml(values_file_yaml, rendered).ok_or_else(|| {
+        Error::TemplateError(format!(
+          "Failed to merge values file #{} over the earlier ones",
+          idx + 1
+        ))
+      })?;
   }
 
   // render sat template file
   tracing::info!("Expand variables in 'SAT file'");
-  let sat_file_rendered = env
-    .render_str(sat_file_content, values_file_yaml)
-    .map_err(|e| {
-      Error::TemplateError(format!("Failed to render SAT file template: {e}"))
-    })?;
+  render_with_context(
+    &env,
+    sat_file_content,
+    merge_with(values_file_yaml, &cli_vars_yaml)?,
+    None,
+    "Failed to render SAT file template",
+  )
+}
 
-  Ok(sat_file_rendered)
+/// `content` parsed as YAML, to serve as its own render context. A
+/// template that uses `{% import %}` / `{% include %}` may only be
+/// YAML after rendering, so a parse failure yields an empty context
+/// plus the error, which [`render_with_context`] reports if rendering
+/// then fails too.
+fn self_context(content: &str) -> (Value, Option<serde_yaml::Error>) {
+  match serde_yaml::from_str::<Value>(content) {
+    Ok(Value::Mapping(m)) => (Value::Mapping(m), None),
+    Ok(_) => (Value::Mapping(Mapping::new()), None),
+    Err(e) => (Value::Mapping(Mapping::new()), Some(e)),
+  }
+}
+
+/// [`merge_yaml`] of `over` onto `base`, as a render error on failure.
+fn merge_with(base: Value, over: &Value) -> Result<Value, Error> {
+  merge_yaml(base, over.clone()).ok_or_else(|| {
+    Error::TemplateError("Failed to merge template contexts".to_string())
+  })
+}
+
+/// Render `content` against `context`. On failure the error is
+/// prefixed with `what` and, when the template was not valid YAML to
+/// begin with (`parse_err`), names that too — it is usually the real
+/// cause of the undefined variable.
+fn render_with_context(
+  env: &minijinja::Environment<'_>,
+  content: &str,
+  context: Value,
+  parse_err: Option<serde_yaml::Error>,
+  what: &str,
+) -> Result<String, Error> {
+  env.render_str(content, context).map_err(|e| {
+    Error::TemplateError(match parse_err {
+      Some(p) => {
+        format!("{what}: {e} (the template is also not valid YAML: {p})")
+      }
+      None => format!("{what}: {e}"),
+    })
+  })
 }
 
 #[cfg(test)]
@@ -313,8 +541,9 @@ mod tests {
 
     let rendered = render_jinja2_sat_file_yaml(
       sat_file_content,
-      Some(values_file_content),
+      &[values_file_content.to_string()],
       Some(&var_content),
+      None,
     )
     .unwrap();
 
@@ -366,7 +595,7 @@ mod tests {
         name: "{{ config.name }}"
         "#;
 
-    let result = render_jinja2_sat_file_yaml(sat_file_content, None, None);
+    let result = render_jinja2_sat_file_yaml(sat_file_content, &[], None, None);
 
     // Should fail because config.name is undefined
     assert!(
@@ -391,7 +620,7 @@ mod tests {
         "#;
 
     let rendered =
-      render_jinja2_sat_file_yaml(sat_file_content, None, None).unwrap();
+      render_jinja2_sat_file_yaml(sat_file_content, &[], None, None).unwrap();
     let parsed: Value = serde_yaml::from_str(&rendered).unwrap();
 
     assert_eq!(parsed.get("name").unwrap().as_str().unwrap(), "my-config");
@@ -418,7 +647,8 @@ mod tests {
 
     let rendered = render_jinja2_sat_file_yaml(
       sat_file_content,
-      Some(values_file_content),
+      &[values_file_content.to_string()],
+      None,
       None,
     )
     .unwrap();
@@ -427,4 +657,117 @@ mod tests {
     assert_eq!(parsed.get("name").unwrap().as_str().unwrap(), "my-app");
     assert_eq!(parsed.get("version").unwrap().as_str().unwrap(), "2.0");
   }
+
+  #[test]
+  fn secret_function_renders_placeholder() {
+    let sat = "pw: \"{{ secret('vault://ldap/bind#password') }}\"\n";
+    let out =
+      render_jinja2_sat_file_yaml(sat, &["x: 1\n".to_string()], None, None)
+        .unwrap();
+    assert_eq!(out, "pw: \"((vault://ldap/bind#password))\"");
+  }
+
+  #[test]
+  fn secret_tag_in_values_file_renders_placeholder() {
+    let values = "ldap:\n  pw: !secret vault://ldap/bind#password\n";
+    let sat = "kernel_parameters: \"bind={{ ldap.pw }}\"\n";
+    let out =
+      render_jinja2_sat_file_yaml(sat, &[values.to_string()], None, None)
+        .unwrap();
+    assert_eq!(
+      out,
+      "kernel_parameters: \"bind=((vault://ldap/bind#password))\""
+    );
+  }
+
+  #[test]
+  fn quoted_secret_tag_and_list_item_render_placeholders() {
+    let values = "pw: !secret 'vault://a#k'\nl:\n- !secret \"vault://b#k\"\n";
+    let sat = "x: \"{{ pw }} {{ l[0] }}\"\n";
+    let out =
+      render_jinja2_sat_file_yaml(sat, &[values.to_string()], None, None)
+        .unwrap();
+    assert_eq!(out, "x: \"((vault://a#k)) ((vault://b#k))\"");
+  }
+
+  #[test]
+  fn malformed_secret_reference_is_rejected() {
+    let sat = "pw: \"{{ secret('ldap#password') }}\"\n";
+    assert!(
+      render_jinja2_sat_file_yaml(sat, &["x: 1\n".to_string()], None, None)
+        .is_err()
+    );
+    let values = "pw: !secret vault://ldap\n";
+    assert!(
+      render_jinja2_sat_file_yaml("a: b\n", &[values.to_string()], None, None)
+        .is_err()
+    );
+  }
+
+  #[test]
+  fn later_values_files_win() {
+    let base = "cluster:\n  name: base\n  arch: x86_64\n".to_string();
+    let prod = "cluster:\n  name: prod\n".to_string();
+    let sat = "n: \"{{ cluster.name }}-{{ cluster.arch }}\"\n";
+    let out =
+      render_jinja2_sat_file_yaml(sat, &[base, prod], None, None).unwrap();
+    assert_eq!(out, "n: \"prod-x86_64\"");
+  }
+
+  #[test]
+  fn values_files_see_earlier_files_and_cli_overrides() {
+    let base = "arch: x86_64\n".to_string();
+    let prod = "image: \"cos-{{ arch }}-{{ release }}\"\n".to_string();
+    let sat = "n: \"{{ image }}\"\n";
+    let cli = ["release=3.1".to_string()];
+    let out = render_jinja2_sat_file_yaml(sat, &[base, prod], Some(&cli), None)
+      .unwrap();
+    assert_eq!(out, "n: \"cos-x86_64-3.1\"");
+  }
+
+  #[test]
+  fn yaml_error_is_reported_when_rendering_fails() {
+    let sat = "a: [unclosed\nn: \"{{ a }}\"\n";
+    let err = render_jinja2_sat_file_yaml(sat, &[], None, None)
+      .unwrap_err()
+      .to_string();
+    assert!(err.contains("not valid YAML"), "{err}");
+  }
+
+  #[test]
+  fn bundle_import_and_include_resolve_against_bundle_dir() {
+    let dir = tempfile::tempdir().unwrap();
+    std::fs::create_dir(dir.path().join("macros")).unwrap();
+    std::fs::write(
+      dir.path().join("macros/layers.j2"),
+      "{% macro layer(name) %}- name: {{ name }}\n  playbook: site.yml\n{% endmacro %}",
+    )
+    .unwrap();
+    std::fs::write(
+      dir.path().join("images.yaml"),
+      "images:\n- name: {{ cluster }}-img\n",
+    )
+    .unwrap();
+    let sat = "{% import 'macros/layers.j2' as l %}\n\
+               configurations:\n\
+               - name: cfg\n  layers:\n  {{ l.layer('cos') | indent(2) }}\n\
+               {% include 'images.yaml' %}\n";
+    let values = vec!["cluster: alps\n".to_string()];
+    let out = render_jinja2_sat_file_yaml(sat, &values, None, Some(dir.path()))
+      .unwrap();
+    let parsed: Value = serde_yaml::from_str(&out).unwrap();
+    assert_eq!(parsed["configurations"][0]["layers"][0]["name"], "cos");
+    assert_eq!(parsed["images"][0]["name"], "alps-img");
+  }
+
+  #[test]
+  fn bundle_include_cannot_escape_bundle_dir() {
+    let dir = tempfile::tempdir().unwrap();
+    let sat = "{% include '../outside.yaml' %}\n";
+    let values = vec!["x: 1\n".to_string()];
+    assert!(
+      render_jinja2_sat_file_yaml(sat, &values, None, Some(dir.path()))
+        .is_err()
+    );
+  }
 }
@@ -50,7 +50,8 @@ app:
 
   let rendered = render_jinja2_sat_file_yaml(
     sat_file_content,
-    Some(values_file_content),
+    &[values_file_content.to_string()],
+    None,
     None,
   )
   .expect("render");
@@ -0,0 +1,70 @@
+//! Offline schema check for a rendered SAT file.
+//!
+//! Wraps [`manta_shared::types::sat_schema::validate_sat_file`] and
+//! turns its located errors into one operator-facing message that
+//! quotes the offending line of the rendered YAML. Shared by
+//! `manta validate sat-file` and the pre-flight check at the start of
+//! `manta apply sat-file`; neither contacts the server.
+
+use anyhow::{Error, anyhow};
+
+use manta_shared::types::sat_schema::{
+  SatFile, SatValidationError, validate_sat_file,
+};
+
+/// Validate `rendered_yaml` against the typed SAT schema.
+///
+/// # Errors
+///
+/// Returns an error listing every schema problem found, each with its
+/// line and column in the rendered YAML and the quoted source line.
+pub fn check_schema(rendered_yaml: &str) -> Result<SatFile, Error> {
+  validate_sat_file(rendered_yaml)
+    .map_err(|errors| anyhow!(format_errors(rendered_yaml, &errors)))
+}
+
+/// Render validation errors as a numbered list, quoting the rendered
+/// line each one points at:
+///
+/// ```text
+/// SAT file failed schema validation (1 error):
+///   line 6, column 7: configurations[0].layers[0].git: unknown field `brnach`, ...
+///     |       brnach: main
+/// ```
+fn format_errors(rendered_yaml: &str, errors: &[SatValidationError]) -> String {
+  let mut out = format!(
+    "SAT file failed schema validation ({} error{}):",
+    errors.len(),
+    if errors.len() == 1 { "" } else { "s" }
+  );
+  let lines: Vec<&str> = rendered_yaml.lines().collect();
+  for err in errors {
+    out.push_str(&format!("\n  {err}"));
+    if let Some(src) = err.line.and_then(|l| lines.get(l.wrapping_sub(1))) {
+      out.push_str(&format!("\n    | {src}"));
+    }
+  }
+  out.push_str("\nLine numbers refer to the SAT file after Jinja2 rendering.");
+  out
+}
+
+#[cfg(test)]
+mod tests {
+  use super::check_schema;
+
+  #[test]
+  fn check_schema_quotes_offending_line() {
+    let yaml = "images:\n- name: img\n  bsae:\n    image_ref: x\n";
+    let msg = check_schema(yaml).unwrap_err().to_string();
+    assert!(msg.contains("(1 error)"), "{msg}");
+    assert!(msg.contains("line 3, column 3"), "{msg}");
+    assert!(msg.contains("unknown field `bsae`"), "{msg}");
+    assert!(msg.contains("    |   bsae:"), "{msg}");
+  }
+
+  #[test]
+  fn check_schema_accepts_valid_file() {
+    let yaml = "images:\n- name: img\n  base:\n    image_ref: other\n";
+    assert!(check_schema(yaml).is_ok());
+  }
+}
@@ -0,0 +1,100 @@
+//! Implements the `manta delete ephemeral-environment` command.
+//!
+//! Deletes one of the caller's ephemeral environments (the IMS
+//! customize job behind it) via `DELETE /v2/ephemeral-env/{id}`,
+//! before its time to live runs out. The endpoint has no `dry_run`
+//! flag; `--dry-run` is a client-side short-circuit that prints the
+//! request via [`crate::output::action_result::preview_request`].
+//! Sibling of `manta apply ephemeral-environment` (creation) and
+//! [`super::super::get::ephemeral_environments`] (listing).
+
+use anyhow::Error;
+
+use crate::common;
+use crate::common::app_context::AppContext;
+use crate::http_client::{MantaClient, OpenApiResultExt};
+use crate::output::action_result;
+
+/// CLI adapter for `manta delete ephemeral-environment`.
+///
+/// # Errors
+///
+/// Returns an error when the HTTP client cannot be built, when the
+/// `delete_ephemeral_env` call fails (404 for an environment that is
+/// not one of the caller's), or when the dry-run preview fails to
+/// serialise. Declining the confirmation prompt is reported as a
+/// successful no-op rather than an error.
+pub async fn exec(
+  ctx: &AppContext<'_>,
+  token: &str,
+  id: &str,
+  dry_run: bool,
+  assume_yes: bool,
+  output_opt: Option<&str>,
+) -> Result<(), Error> {
+  if dry_run {
+    return action_result::preview_request(
+      "DELETE",
+      &format!("/ephemeral-env/{id}"),
+      &id,
+      output_opt,
+    );
+  }
+
+  if !common::confirm::confirm(
+    &format!(
+      "Ephemeral environment '{id}' will be deleted:\nDo you want to continue?",
+    ),
+    assume_yes,
+  ) {
+    action_result::print("Operation cancelled by user", output_opt)?;
+    return Ok(());
+  }
+
+  let client = MantaClient::from_app_ctx(ctx, Some(token))?;
+  client
+    .openapi
+    .delete_ephemeral_env(id, client.site_name())
+    .await
+    .into_anyhow()
+    .await?;
+  action_result::print(
+    &format!("Ephemeral environment '{id}' deleted"),
+    output_opt,
+  )?;
+  Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+  #[test]
+  fn accepts_id_and_assume_yes() {
+    let matches = crate::build::build_cli()
+      .try_get_matches_from([
+        "manta",
+        "delete",
+        "ephemeral-environment",
+        "4f1c",
+        "-y",
+      ])
+      .unwrap();
+    let (_, delete) = matches.subcommand().unwrap();
+    let (_, env) = delete.subcommand().unwrap();
+    assert_eq!(
+      env.get_one::<String>("ID").map(String::as_str),
+      Some("4f1c")
+    );
+    assert!(env.get_flag("assume-yes"));
+  }
+
+  #[test]
+  fn requires_id() {
+    let result = crate::build::build_cli().try_get_matches_from([
+      "manta",
+      "delete",
+      "ephemeral-environment",
+      "--dry-run",
+    ]);
+    assert!(result.is_err());
+  }
+}
@@ -14,17 +14,22 @@
 //!   each configuration; the name reflects the cascade)
 //! - [`images`]            — `DELETE /v2/images?ids=…`
 //! - [`session`]           — `DELETE /v2/sessions/{name}` (cancel a CFS session)
+//! - [`sessions`]          — `DELETE /v2/sessions?status=…&min_age=…`
+//!   (every CFS session matching the filters)
 //! - [`redfish_endpoint`]  — `DELETE /v2/redfish-endpoints/{id}`
+//! - [`ephemeral_environment`] — `DELETE /v2/ephemeral-env/{id}`
 //!
 //! `--dry-run` is supported on every leaf. Most leaves either forward
 //! the flag to the server (when the endpoint accepts `?dry_run=true`)
 //! or short-circuit client-side via
 //! [`crate::output::action_result::preview_request`]; see each leaf's
 //! doc for the exact policy. A few destructive leaves prompt before
-//! firing — `nodes` and `session` always confirm.
+//! firing — `nodes`, `session`, `sessions` and `ephemeral-environment`
+//! always confirm.
 
 pub mod boot_parameters;
 pub mod configurations_and_derivatives;
+pub mod ephemeral_environment;
 pub mod group;
 pub mod hardware;
 pub mod images;
@@ -33,6 +38,7 @@ pub mod node;
 pub mod nodes;
 pub mod redfish_endpoint;
 pub mod session;
+pub mod sessions;
 
 use crate::common::app_context::AppContext;
 use crate::common::authentication::get_api_token;
@@ -42,7 +48,8 @@ use clap::ArgMatches;
 
 /// Dispatch `manta delete` subcommands (group, node, nodes,
 /// kernel-parameters, boot-parameters, configurations, session,
-/// images, hardware, redfish-endpoints).
+/// sessions, images, hardware, redfish-endpoints,
+/// ephemeral-environment).
 ///
 /// # Errors
 ///
@@ -124,6 +131,17 @@ pub async fn handle_delete(
       let dry_run = m.get_flag("dry-run");
       redfish_endpoint::exec(ctx, &token, id, output_opt, dry_run).await?;
     }
+    Some(("ephemeral-environment", m)) => {
+      ephemeral_environment::exec(
+        ctx,
+        &token,
+        m.req_str("ID")?,
+        m.get_flag("dry-run"),
+        m.get_flag("assume-yes"),
+        m.opt_str("output"),
+      )
+      .await?;
+    }
     Some(("kernel-parameters", m)) => {
       let hsm_group_name_arg_opt = m.opt_str("group");
       let nodes_arg = m.opt_str("nodes");
@@ -161,6 +179,25 @@ pub async fn handle_delete(
         bail!("Failed to delete session: {e}");
       }
     }
+    Some(("sessions", m)) => {
+      if let Err(e) = sessions::exec(
+        ctx,
+        &token,
+        sessions::ExecParams {
+          status: m.opt_str("status"),
+          older_than: m.opt_str("older-than"),
+          session_type: m.opt_str("type"),
+          group: m.opt_str("group"),
+          dry_run: m.get_flag("dry-run"),
+          assume_yes: m.get_flag("assume-yes"),
+          output: m.opt_str("output"),
+        },
+      )
+      .await
+      {
+        bail!("Failed to delete sessions: {e}");
+      }
+    }
     Some(("configurations", m)) => {
       let since_opt = if let Some(since) = m.get_one::<String>("since") {
         let date_time = chrono::NaiveDateTime::parse_from_str(
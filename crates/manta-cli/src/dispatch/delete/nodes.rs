@@ -12,7 +12,7 @@ use crate::common;
 use crate::common::app_context::AppContext;
 use crate::http_client::{MantaClient, OpenApiResultExt};
 use crate::openapi_client::types::DeleteGroupMembersRequest;
-use crate::output::action_result;
+use crate::output::{self, action_result};
 
 /// Remove/unassign a list of xnames from a list of HSM groups.
 ///
@@ -32,7 +32,8 @@ pub async fn exec(
 ) -> Result<(), Error> {
   if !common::confirm::confirm(
     &format!(
-      "Nodes matching '{hosts_expression}' will be removed from HSM group '{target_hsm_name}'. Do you want to proceed?"
+      "Nodes matching '{}' will be removed from HSM group '{target_hsm_name}'. Do you want to proceed?",
+      output::hosts_expression(hosts_expression, ctx.expand_hostlists)
     ),
     assume_yes,
   ) {
@@ -0,0 +1,228 @@
+//! Implements the `manta delete sessions` command.
+//!
+//! Deletes every CFS session matching `--status` / `--older-than` /
+//! `--type` / `--group` via `DELETE /v2/sessions`. The plan is fetched
+//! first (`?dry_run=true`) and shown in a single confirmation prompt;
+//! the deletion then names exactly the sessions shown, so one that
+//! starts matching the filters in between is left alone. `--dry-run`
+//! prints the plan and stops.
+
+use anyhow::{Error, bail};
+
+use crate::common;
+use crate::common::app_context::AppContext;
+use crate::http_client::{MantaClient, OpenApiResultExt};
+use crate::openapi_client::types::SessionsDeletionPlan;
+use crate::output::{self, action_result};
+
+pub struct ExecParams<'a> {
+  pub status: Option<&'a str>,
+  /// Minimum session age, e.g. `30d`; sent as `min_age`.
+  pub older_than: Option<&'a str>,
+  pub session_type: Option<&'a str>,
+  pub group: Option<&'a str>,
+  pub dry_run: bool,
+  pub assume_yes: bool,
+  pub output: Option<&'a str>,
+}
+
+/// Delete the CFS sessions matching the filters of `p`.
+///
+/// # Errors
+///
+/// Returns an error when the HTTP client cannot be built, when either
+/// `delete_sessions` call fails, or when any session could not be
+/// deleted. Declining the confirmation prompt is reported as a
+/// successful no-op rather than an error.
+pub async fn exec(
+  ctx: &AppContext<'_>,
+  token: &str,
+  p: ExecParams<'_>,
+) -> Result<(), Error> {
+  let client = MantaClient::from_app_ctx(ctx, Some(token))?;
+  let preview = client
+    .openapi
+    .delete_sessions(
+      Some(true),
+      p.group,
+      p.older_than,
+      None,
+      p.session_type,
+      p.status,
+      client.site_name(),
+    )
+    .await
+    .into_anyhow()
+    .await?;
+  let plan = preview.plan;
+
+  if plan.sessions.is_empty() {
+    action_result::print("No sessions match the filters", p.output)?;
+    return Ok(());
+  }
+
+  if p.dry_run {
+    action_result::print_with_data(
+      "Dry-run enabled. No changes persisted into the system.",
+      &plan,
+      p.output,
+    )?;
+    return Ok(());
+  }
+
+  if !common::confirm::confirm(
+    &format!(
+      "{}Do you want to continue?",
+      plan_summary(&plan, ctx.expand_hostlists)
+    ),
+    p.assume_yes,
+  ) {
+    action_result::print("Operation cancelled by user", p.output)?;
+    return Ok(());
+  }
+
+  let names = plan
+    .sessions
+    .iter()
+    .map(|session| session.name.as_str())
+    .collect::<Vec<_>>()
+    .join(",");
+  let response = client
+    .openapi
+    .delete_sessions(
+      Some(false),
+      p.group,
+      p.older_than,
+      Some(&names),
+      p.session_type,
+      p.status,
+      client.site_name(),
+    )
+    .await
+    .into_anyhow()
+    .await?;
+
+  let failed = response.results.iter().filter(|r| !r.deleted).count();
+  let total = response.results.len();
+  if p.output == Some("json") {
+    action_result::print_with_data(
+      &format!("{} of {total} sessions deleted", total - failed),
+      &response.results,
+      p.output,
+    )?;
+  } else {
+    for result in &response.results {
+      match &result.error {
+        None => println!("Session '{}' deleted", result.session_name),
+        Some(e) => {
+          println!("Session '{}' NOT deleted: {e}", result.session_name);
+        }
+      }
+    }
+  }
+  if failed > 0 {
+    bail!("{failed} of {total} sessions could not be deleted");
+  }
+  Ok(())
+}
+
+/// The confirmation text for `plan`: the sessions, then what deleting
+/// them does to images and components.
+fn plan_summary(plan: &SessionsDeletionPlan, expand: bool) -> String {
+  let mut summary =
+    format!("{} sessions will be deleted:\n", plan.sessions.len());
+  for session in &plan.sessions {
+    let details: Vec<&str> = [
+      session.target_definition.as_deref(),
+      session.status.as_deref(),
+      session.start_time.as_deref(),
+    ]
+    .into_iter()
+    .flatten()
+    .collect();
+    summary.push_str(&format!("  {} ({})\n", session.name, details.join(", ")));
+  }
+  if !plan.image_ids.is_empty() {
+    summary.push_str(&format!(
+      "Images deleted with them: {}\n",
+      plan.image_ids.join(", ")
+    ));
+  }
+  for kept in &plan.boot_images_kept {
+    summary.push_str(&format!(
+      "Image {} kept: nodes {} boot from it\n",
+      kept.image_id,
+      output::node_list(&kept.hosts, expand)
+    ));
+  }
+  if !plan.components.is_empty() {
+    summary.push_str(&format!(
+      "Components whose error count is set to the retry limit: {}\n",
+      output::node_list(&plan.components, expand)
+    ));
+  }
+  summary
+}
+
+#[cfg(test)]
+mod tests {
+  use super::plan_summary;
+  use crate::openapi_client::types::{
+    BootImageInUse, SessionDeletionItem, SessionsDeletionPlan,
+  };
+
+  #[test]
+  fn accepts_status_and_older_than() {
+    let matches = crate::build::build_cli()
+      .try_get_matches_from([
+        "manta",
+        "delete",
+        "sessions",
+        "--status",
+        "complete",
+        "--older-than",
+        "30d",
+        "-H",
+        "compute",
+      ])
+      .unwrap();
+    let (_, delete) = matches.subcommand().unwrap();
+    let (_, sessions) = delete.subcommand().unwrap();
+    assert_eq!(
+      sessions.get_one::<String>("older-than").map(String::as_str),
+      Some("30d")
+    );
+  }
+
+  /// A group alone would select every session of the group.
+  #[test]
+  fn requires_status_or_older_than() {
+    let result = crate::build::build_cli()
+      .try_get_matches_from(["manta", "delete", "sessions", "-H", "compute"]);
+    assert!(result.is_err());
+  }
+
+  #[test]
+  fn summary_lists_sessions_images_and_components() {
+    let plan = SessionsDeletionPlan {
+      sessions: vec![SessionDeletionItem {
+        name: "img-1".to_string(),
+        target_definition: Some("image".to_string()),
+        status: Some("complete".to_string()),
+        start_time: None,
+      }],
+      image_ids: vec!["ims-a".to_string()],
+      boot_images_kept: vec![BootImageInUse {
+        image_id: "ims-b".to_string(),
+        hosts: vec!["x3000c0s1b0n0".to_string()],
+      }],
+      components: vec![],
+    };
+    assert_eq!(
+      plan_summary(&plan, true),
+      "1 sessions will be deleted:\n  img-1 (image, complete)\n\
+       Images deleted with them: ims-a\n\
+       Image ims-b kept: nodes x3000c0s1b0n0 boot from it\n"
+    );
+  }
+}
@@ -0,0 +1,101 @@
+//! Implements the `manta diff hardware` command.
+//!
+//! Each side is a local file written by `manta snapshot hardware
+//! --local`, or the id of a snapshot stored on the server (fetched
+//! with `GET /hardware-snapshots/{id}`). The second side may also be
+//! `live`, the current inventory of the first snapshot's group (see
+//! [`super::super::snapshot::hardware::fetch_live_snapshot`]). The
+//! comparison itself runs locally through
+//! [`manta_shared::types::hardware_diff::diff_snapshots`], the same
+//! function the server's hardware watcher uses.
+
+use std::path::Path;
+
+use anyhow::{Context, Error, bail};
+use manta_shared::types::api::hardware::HardwareSnapshot;
+use manta_shared::types::hardware_diff::diff_snapshots;
+
+use crate::common::app_context::AppContext;
+use crate::common::clap_ext::ArgMatchesExt;
+use crate::dispatch::snapshot::hardware::fetch_live_snapshot;
+use crate::http_client::{MantaClient, OpenApiResultExt};
+use crate::output;
+
+/// Second-side keyword for the current inventory.
+const LIVE: &str = "live";
+
+/// Load the snapshot named by `arg`: an existing file path wins over a
+/// server id.
+async fn load_snapshot(
+  client: &MantaClient,
+  arg: &str,
+) -> Result<HardwareSnapshot, Error> {
+  if Path::new(arg).is_file() {
+    let bytes = std::fs::read(arg)
+      .with_context(|| format!("Failed to read snapshot file '{arg}'"))?;
+    return serde_json::from_slice(&bytes)
+      .with_context(|| format!("'{arg}' is not a hardware snapshot"));
+  }
+  let snapshot = client
+    .openapi
+    .get_hardware_snapshot(arg, client.site_name())
+    .await
+    .into_anyhow()
+    .await?;
+  serde_json::from_value(serde_json::to_value(snapshot)?)
+    .context("Failed to decode hardware snapshot")
+}
+
+/// CLI adapter for `manta diff hardware`.
+///
+/// Consumes clap matches for the `diff hardware` subcommand
+/// (positional `FROM`, optional `TO` defaulting to `live`, `--output`).
+///
+/// # Errors
+///
+/// Returns an error if `FROM` is `live`, a snapshot cannot be loaded,
+/// or the live inventory cannot be fetched.
+pub async fn exec(
+  ctx: &AppContext<'_>,
+  token: &str,
+  cli_args: &clap::ArgMatches,
+) -> Result<(), Error> {
+  let from_arg = cli_args.req_str("FROM")?;
+  let to_arg = cli_args.opt_str("TO").unwrap_or(LIVE);
+  if from_arg == LIVE {
+    bail!("The first snapshot cannot be 'live'; pass it as the second one");
+  }
+
+  let client = MantaClient::from_app_ctx(ctx, Some(token))?;
+  let from = load_snapshot(&client, from_arg).await?;
+  let to = if to_arg == LIVE {
+    if from.site != client.site_name() {
+      eprintln!(
+        "Warning: snapshot {from_arg} was taken on site '{}', comparing it \
+         with the live inventory of site '{}'",
+        from.site,
+        client.site_name()
+      );
+    }
+    let taken_by = ctx.session.as_ref().map_or("", |s| s.username.as_str());
+    fetch_live_snapshot(&client, &from.group, taken_by).await?
+  } else {
+    load_snapshot(&client, to_arg).await?
+  };
+  if from.group != to.group {
+    eprintln!(
+      "Warning: comparing snapshots of different groups ('{}' and '{}')",
+      from.group, to.group
+    );
+  }
+
+  let diff = diff_snapshots(&from, &to);
+  output::hardware_snapshot::print_diff(
+    from_arg,
+    to_arg,
+    &diff,
+    cli_args.opt_str("output"),
+    ctx.expand_hostlists,
+  )?;
+  Ok(())
+}
@@ -0,0 +1,31 @@
+//! `manta diff` subcommands — compare inventory snapshots.
+//!
+//! - [`hardware`] — `manta diff hardware`: report nodes whose
+//!   hardware changed between two snapshots, or a snapshot and the
+//!   live inventory.
+
+pub mod hardware;
+
+use crate::common::app_context::AppContext;
+use crate::common::authentication::get_api_token;
+use anyhow::{Error, bail};
+use clap::ArgMatches;
+
+/// Dispatch `manta diff` subcommands.
+///
+/// # Errors
+///
+/// Returns an error when token acquisition fails, no subcommand is
+/// provided or the name is unknown, or the leaf handler fails.
+pub async fn handle_diff(
+  cli_diff: &ArgMatches,
+  ctx: &AppContext<'_>,
+) -> Result<(), Error> {
+  let token = get_api_token(ctx).await?;
+  match cli_diff.subcommand() {
+    Some(("hardware", m)) => hardware::exec(ctx, &token, m).await?,
+    Some((other, _)) => bail!("Unknown 'diff' subcommand: {other}"),
+    None => bail!("No 'diff' subcommand provided"),
+  }
+  Ok(())
+}
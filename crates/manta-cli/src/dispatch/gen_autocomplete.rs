@@ -15,18 +15,27 @@ use clap_complete::{Shell, generate, generate_to};
 use serde_json::json;
 
 use crate::common::app_context::AppContext;
+use crate::common::capabilities;
 use crate::common::clap_ext::ArgMatchesExt;
 use crate::output::action_result;
 
 /// Dispatch `manta gen-autocomplete`.
 ///
 /// Like `manta upgrade`, this handler does NOT call `get_api_token` —
-/// generating shell-completion scripts is purely local.
+/// generating shell-completion scripts is purely local. Commands the
+/// active site's cached capability report marks unsupported are left
+/// out of the script; with no cached report everything is included.
 pub async fn handle_gen_autocomplete(
   cli_gen_autocomplete: &ArgMatches,
-  _ctx: &AppContext<'_>,
+  ctx: &AppContext<'_>,
 ) -> Result<(), Error> {
-  let cli = crate::build::build_cli();
+  let mut cli = crate::build::build_cli();
+  if let Some(caps) = ctx
+    .site_name
+    .and_then(|site| capabilities::load_cached(ctx.manta_server_url, site))
+  {
+    cli = capabilities::hide_unsupported(cli, &caps);
+  }
   exec(cli, cli_gen_autocomplete)
 }
 
@@ -0,0 +1,41 @@
+//! Implements the `manta get ephemeral-environments` command.
+//!
+//! Hits `GET /ephemeral-env` on `manta-server` to list the caller's
+//! ephemeral SSH environments (made with `manta apply
+//! ephemeral-environment`) on the current site, with their status,
+//! hostname and expiry. Output is a [`crate::output::ephemeral_env`]
+//! table or JSON.
+
+use anyhow::Error;
+
+use crate::common::app_context::AppContext;
+use crate::common::clap_ext::ArgMatchesExt;
+use crate::http_client::{MantaClient, OpenApiResultExt};
+use crate::output;
+
+/// CLI adapter for `manta get ephemeral-environments`.
+///
+/// Consumes clap matches for the `ephemeral-environments` subcommand
+/// (`--output`), issues a single `get_ephemeral_envs` call, and renders
+/// the list.
+///
+/// # Errors
+///
+/// Returns an error if the HTTP request fails or JSON serialisation
+/// fails.
+pub async fn exec(
+  ctx: &AppContext<'_>,
+  token: &str,
+  cli_args: &clap::ArgMatches,
+) -> Result<(), Error> {
+  let client = MantaClient::from_app_ctx(ctx, Some(token))?;
+  let envs = client
+    .openapi
+    .get_ephemeral_envs(client.site_name())
+    .await
+    .into_anyhow()
+    .await?;
+
+  output::ephemeral_env::print(&envs, cli_args.opt_str("output"))?;
+  Ok(())
+}
@@ -4,6 +4,11 @@
 //! groups visible to the caller. The positional `VALUE` filters to a
 //! single group; absent it lists every group. Output is either a
 //! [`crate::output::group`] table or a pretty-printed JSON document.
+//!
+//! `-o ansible-inventory` also fetches the members' details from
+//! `GET /groups/nodes` for the host variables; `-o prometheus-sd`
+//! fetches the targets from `GET /groups/prometheus-sd`, the endpoint
+//! Prometheus can poll directly.
 
 use anyhow::Error;
 
@@ -11,7 +16,8 @@ use crate::common::app_context::AppContext;
 use crate::common::clap_ext::ArgMatchesExt;
 use crate::http_client::{MantaClient, OpenApiResultExt};
 use crate::output;
-use manta_shared::types::api::group::GetGroupParams;
+use manta_shared::types::api::group::{GetGroupParams, PrometheusTargetGroup};
+use manta_shared::types::dto::NodeDetails;
 
 /// Parse CLI arguments into typed [`GetGroupParams`].
 ///
@@ -31,9 +37,8 @@ fn parse_group_params(
 /// CLI adapter for `manta get groups`.
 ///
 /// Consumes clap matches for the `groups` subcommand (optional
-/// positional `VALUE` group name; required `--output table|json`),
-/// issues a single `get_groups` call, and writes the rendered list to
-/// stdout.
+/// positional `VALUE` group name; `--output`), issues the calls the
+/// output format needs, and writes the rendered list to stdout.
 ///
 /// # Errors
 ///
@@ -48,6 +53,25 @@ pub async fn exec(
   let params = parse_group_params(cli_args, ctx.settings_group_name_opt);
 
   let client = MantaClient::from_app_ctx(ctx, Some(token))?;
+  let output_opt = cli_args.opt_str("output");
+
+  if output_opt == Some("prometheus-sd") {
+    let port = cli_args.get_one::<u16>("sd-port").copied().map(i32::from);
+    let targets = client
+      .openapi
+      .get_groups_prometheus_sd(
+        params.effective_group(),
+        port,
+        client.site_name(),
+      )
+      .await
+      .into_anyhow()
+      .await?;
+    let targets: Vec<PrometheusTargetGroup> =
+      serde_json::from_value(serde_json::to_value(targets)?)?;
+    return output::group::print_prometheus_sd(&targets);
+  }
+
   let groups = client
     .openapi
     .get_groups(params.effective_group(), client.site_name())
@@ -55,8 +79,20 @@ pub async fn exec(
     .into_anyhow()
     .await?;
 
-  let output_opt = cli_args.opt_str("output");
-  output::group::print(&groups, output_opt)?;
+  if output_opt == Some("ansible-inventory") {
+    let nodes = client
+      .openapi
+      .get_groups_nodes(params.effective_group(), None, client.site_name())
+      .await
+      .into_anyhow()
+      .await?;
+    let nodes: Vec<NodeDetails> =
+      serde_json::from_value(serde_json::to_value(nodes)?)?;
+    let format = cli_args.opt_str("inventory-format").unwrap_or("ini");
+    return output::group::print_ansible_inventory(&groups, &nodes, format);
+  }
+
+  output::group::print(&groups, output_opt, ctx.expand_hostlists)?;
 
   Ok(())
 }
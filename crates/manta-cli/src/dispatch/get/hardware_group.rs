@@ -3,8 +3,12 @@
 //! Hits `GET /groups/hardware` on `manta-server` to return the
 //! aggregated hardware inventory of an HSM group. Output defaults to
 //! the `summary` view from [`crate::output::hardware::print_cluster`];
-//! pass `--output` for an alternative view. See
-//! [`super::hardware_nodes`] for the per-node variant.
+//! pass `--output` for an alternative view. `--output slurm` also
+//! fetches the group's node list (`GET /groups/nodes`) so the Slurm
+//! node definitions use NIDs. See [`super::hardware_nodes`] for the
+//! per-node variant.
+
+use std::collections::HashMap;
 
 use anyhow::Error;
 
@@ -32,7 +36,9 @@ fn parse_hardware_cluster_params(
 /// Consumes clap matches for the `hardware group` subcommand
 /// (positional `GROUP_NAME`, optional `--output`), fetches the
 /// aggregated inventory, and hands the JSON to
-/// [`crate::output::hardware::print_cluster`].
+/// [`crate::output::hardware::print_cluster`] — or, with `slurm`, to
+/// [`crate::output::hardware::print_slurm_conf`] with an xname → NID
+/// map and the `--threads-per-core` / `--memory-reserve` settings.
 ///
 /// # Errors
 ///
@@ -51,6 +57,41 @@ pub async fn exec(
   let hsm = params.effective_group();
 
   let client = MantaClient::from_app_ctx(ctx, Some(token))?;
+  if output == "slurm" {
+    let (json, nodes) = tokio::try_join!(
+      async {
+        client
+          .openapi
+          .get_groups_hardware(hsm, client.site_name())
+          .await
+          .into_anyhow()
+          .await
+      },
+      async {
+        client
+          .openapi
+          .get_groups_nodes(hsm, None, client.site_name())
+          .await
+          .into_anyhow()
+          .await
+      },
+    )?;
+    let nids: HashMap<String, String> = nodes
+      .into_iter()
+      .filter(|node| !node.nid.is_empty())
+      .map(|node| (node.xname, node.nid))
+      .collect();
+    let setting = |name: &str, default: u8| {
+      usize::from(*cli_args.get_one::<u8>(name).unwrap_or(&default))
+    };
+    let options = output::hardware::SlurmOptions {
+      threads_per_core: setting("threads-per-core", 1),
+      memory_reserve_percent: setting("memory-reserve", 3),
+    };
+    output::hardware::print_slurm_conf(&json, &nids, &options)?;
+    return Ok(());
+  }
+
   let json = client
     .openapi
     .get_groups_hardware(hsm, client.site_name())
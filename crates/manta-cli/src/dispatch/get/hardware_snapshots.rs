@@ -0,0 +1,41 @@
+//! Implements the `manta get hardware snapshots` command.
+//!
+//! Hits `GET /hardware-snapshots` on `manta-server` to list the
+//! hardware snapshots stored on the current site, newest first,
+//! optionally limited to one group. The server only returns snapshots
+//! of groups the caller can access. Output is a
+//! [`crate::output::hardware_snapshot`] table or JSON.
+
+use anyhow::Error;
+
+use crate::common::app_context::AppContext;
+use crate::common::clap_ext::ArgMatchesExt;
+use crate::http_client::{MantaClient, OpenApiResultExt};
+use crate::output;
+
+/// CLI adapter for `manta get hardware snapshots`.
+///
+/// # Errors
+///
+/// Returns an error if the HTTP request fails (including the 501 a
+/// server without `[server.hardware_snapshots]` answers) or JSON
+/// serialisation fails.
+pub async fn exec(
+  ctx: &AppContext<'_>,
+  token: &str,
+  cli_args: &clap::ArgMatches,
+) -> Result<(), Error> {
+  let client = MantaClient::from_app_ctx(ctx, Some(token))?;
+  let snapshots = client
+    .openapi
+    .get_hardware_snapshots(cli_args.opt_str("group"), client.site_name())
+    .await
+    .into_anyhow()
+    .await?;
+
+  output::hardware_snapshot::print_list(
+    &snapshots,
+    cli_args.opt_str("output"),
+  )?;
+  Ok(())
+}
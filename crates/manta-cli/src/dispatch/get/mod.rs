@@ -8,14 +8,17 @@
 
 pub mod boot_parameters;
 pub mod configurations;
+pub mod ephemeral_environments;
 pub mod group_nodes;
 pub mod groups;
 pub mod hardware_group;
 pub mod hardware_nodes;
+pub mod hardware_snapshots;
 pub mod images;
 pub mod kernel_parameters;
 pub mod nodes;
 pub mod redfish_endpoints;
+pub mod reservations;
 pub mod sessions;
 pub mod templates;
 
@@ -28,9 +31,9 @@ use clap::ArgMatches;
 ///
 /// Routes the parsed clap matches to one of the per-subcommand `exec`
 /// handlers in this module: `groups`, `group-nodes`, `hardware nodes`,
-/// `hardware group`, `configurations`, `sessions`, `templates`,
-/// `nodes`, `images`, `boot-parameters`, `kernel-parameters`, and
-/// `redfish-endpoints`. The API token is resolved once here and passed
+/// `hardware group`, `hardware snapshots`, `configurations`, `sessions`, `templates`,
+/// `nodes`, `images`, `boot-parameters`, `kernel-parameters`,
+/// `redfish-endpoints`, `reservations`, and `ephemeral-environments`. The API token is resolved once here and passed
 /// down so each handler can issue HTTPS requests against `manta-server`.
 ///
 /// # Errors
@@ -50,6 +53,9 @@ pub async fn handle_get(
     Some(("hardware", m)) => match m.subcommand() {
       Some(("nodes", m)) => hardware_nodes::exec(ctx, &token, m).await?,
       Some(("group", m)) => hardware_group::exec(ctx, &token, m).await?,
+      Some(("snapshots", m)) => {
+        hardware_snapshots::exec(ctx, &token, m).await?;
+      }
       Some((other, _)) => bail!("Unknown 'get hardware' subcommand: {other}"),
       None => bail!("No 'get hardware' subcommand provided"),
     },
@@ -69,6 +75,10 @@ pub async fn handle_get(
     Some(("redfish-endpoints", m)) => {
       redfish_endpoints::exec(ctx, &token, m).await?;
     }
+    Some(("reservations", m)) => reservations::exec(ctx, &token, m).await?,
+    Some(("ephemeral-environments", m)) => {
+      ephemeral_environments::exec(ctx, &token, m).await?;
+    }
     Some((other, _)) => bail!("Unknown 'get' subcommand: {other}"),
     None => bail!("No 'get' subcommand provided"),
   }
@@ -0,0 +1,47 @@
+//! Implements the `manta get reservations` command.
+//!
+//! Hits `GET /reservations` on `manta-server` to list the active
+//! time-limited hardware pins (made with `manta apply hardware group
+//! --until/--for`) on the current site, with their owners and expiry.
+//! The server only returns reservations whose target group the caller
+//! can access. Output is a [`crate::output::reservation`] table or
+//! JSON.
+
+use anyhow::Error;
+
+use crate::common::app_context::AppContext;
+use crate::common::clap_ext::ArgMatchesExt;
+use crate::http_client::{MantaClient, OpenApiResultExt};
+use crate::output;
+
+/// CLI adapter for `manta get reservations`.
+///
+/// Consumes clap matches for the `reservations` subcommand
+/// (`--output`), issues a single `get_reservations` call, and renders
+/// the list.
+///
+/// # Errors
+///
+/// Returns an error if the HTTP request fails (including the 501 a
+/// server without `[server.reservations]` answers) or JSON
+/// serialisation fails.
+pub async fn exec(
+  ctx: &AppContext<'_>,
+  token: &str,
+  cli_args: &clap::ArgMatches,
+) -> Result<(), Error> {
+  let client = MantaClient::from_app_ctx(ctx, Some(token))?;
+  let reservations = client
+    .openapi
+    .get_reservations(client.site_name())
+    .await
+    .into_anyhow()
+    .await?;
+
+  output::reservation::print(
+    &reservations,
+    cli_args.opt_str("output"),
+    ctx.expand_hostlists,
+  )?;
+  Ok(())
+}
@@ -5,6 +5,11 @@
 //! several filters and a `--most-recent` shortcut. The user-facing
 //! `runtime` value for `--type` is normalised to CFS's internal
 //! `dynamic`. Output is rendered by [`crate::output::session`].
+//!
+//! With `--failures`, the matching sessions that completed without
+//! succeeding have their logs summarised (see
+//! [`crate::dispatch::log::summarize_session_log`]) and only their
+//! failed tasks are printed, via [`crate::output::session_summary`].
 
 use anyhow::{Context, Error};
 
@@ -47,8 +52,10 @@ fn parse_session_params(cli_args: &clap::ArgMatches) -> GetSessionParams {
 ///
 /// Consumes clap matches for the `sessions` subcommand (`--group`,
 /// `--xnames`, `--min-age`, `--max-age`, `--type`, `--status`,
-/// `--name`, `--limit`, `--most-recent`, `--output`), calls the server
-/// once, and renders the response via [`crate::output::session::print`].
+/// `--name`, `--limit`, `--most-recent`, `--output`, `--failures`),
+/// calls the server once, and renders the response via
+/// [`crate::output::session::print`] or, with `--failures`,
+/// [`crate::output::session_summary::print_failures`].
 ///
 /// # Errors
 ///
@@ -90,11 +97,34 @@ pub async fn exec(
     .context("Failed to deserialize CFS sessions list")?;
 
   let output_opt = cli_args.opt_str("output");
+  if cli_args.get_flag("failures") {
+    let mut summaries = Vec::new();
+    for session in sessions.iter().filter(|s| is_failed(s)) {
+      match crate::dispatch::log::summarize_session_log(&client, &session.name)
+        .await
+      {
+        Ok(summary) => summaries.push(summary),
+        Err(e) => {
+          tracing::warn!("Skipping CFS session '{}': {e:#}", session.name)
+        }
+      }
+    }
+    return output::session_summary::print_failures(
+      &summaries,
+      output_opt,
+      ctx.expand_hostlists,
+    );
+  }
   output::session::print(&sessions, output_opt)?;
 
   Ok(())
 }
 
+/// Whether `session` completed without succeeding.
+fn is_failed(session: &CfsSessionGetResponse) -> bool {
+  session.get_completion_time().is_some() && !session.is_success()
+}
+
 #[cfg(test)]
 mod tests {
   use super::*;
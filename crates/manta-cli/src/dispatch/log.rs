@@ -12,25 +12,50 @@
 //! The `--timestamps` flag is forwarded to the server, which prefixes
 //! each line with the build pod's timestamp before re-emitting it as
 //! an SSE event.
+//!
+//! With `--archived`, `VALUE` is taken as a session name as-is (the
+//! session may no longer exist in CFS) and the log the server archived
+//! for it is fetched from `GET /sessions/{name}/logs/archive` and
+//! printed in one go.
+//!
+//! With `--summary`, the log (the archived copy when the server has
+//! one, the live stream otherwise) is fed through
+//! [`AnsibleLogParser`] and only the PLAY RECAP totals per layer and
+//! the failed tasks are printed, via
+//! [`crate::output::session_summary`].
+//!
+//! `--group` picks the group's most recent session instead of
+//! resolving `VALUE`. With `--running`, every running session of the
+//! group (or of the node `VALUE`) is followed at once: each stream is
+//! read by its own task and its lines are printed as they come,
+//! prefixed with the session name. `--layer` and `--grep` are passed to
+//! the server, which filters the lines before sending them.
 
 use crate::common::app_context::AppContext;
 use crate::common::authentication::get_api_token;
 use crate::common::clap_ext::ArgMatchesExt;
-use crate::http_client::{MantaClient, OpenApiResultExt};
+use crate::http_client::{MantaClient, OpenApiResultExt, SessionLogFilter};
 use anyhow::{Context, Error};
 use clap::ArgMatches;
+use manta_shared::types::ansible_summary::{
+  AnsibleLogParser, SessionLogSummary,
+};
+use manta_shared::types::api::session::ArchivedSessionLog;
 use manta_shared::types::dto::CfsSessionGetResponse;
 
 /// Dispatch the `manta log` command to stream CFS session logs.
 ///
 /// Resolves `VALUE` (a CFS session name or xname) via two server
-/// queries, then opens an SSE stream and prints every `data: …` line
-/// to stdout until the server closes the connection.
+/// queries, or `--group` to its most recent session, then opens an SSE
+/// stream and prints every `data: …` line to stdout until the server
+/// closes the connection. With `--running`, follows all running
+/// sessions of the target instead.
 ///
 /// # Errors
 ///
 /// - The auth token bootstrap fails.
-/// - Neither the by-name nor by-xname lookup matches a session.
+/// - Neither the by-name nor by-xname lookup matches a session, or
+///   the target has no (running) session.
 /// - The SSE stream cannot be opened (server unreachable, 4xx/5xx).
 /// - An I/O error occurs while reading from the stream.
 pub async fn handle_log(
@@ -39,12 +64,165 @@ pub async fn handle_log(
 ) -> Result<(), Error> {
   let token = get_api_token(ctx).await?;
 
-  let user_input = cli_log.req_str("VALUE")?;
+  let user_input = cli_log.opt_str("VALUE");
+  let group = cli_log.opt_str("group");
   let timestamps = cli_log.get_flag("timestamps");
+  let filter = SessionLogFilter {
+    layer: cli_log.opt_str("layer"),
+    grep: cli_log.opt_str("grep"),
+  };
 
-  use tokio::io::AsyncBufReadExt as _;
   let client = MantaClient::from_app_ctx(ctx, Some(&token))?;
 
+  if cli_log.get_flag("running") {
+    let sessions =
+      get_target_sessions(&client, group, user_input, Some("running"), None)
+        .await?;
+    if sessions.is_empty() {
+      anyhow::bail!(
+        "No running CFS session for '{}'",
+        group.or(user_input).unwrap_or_default()
+      );
+    }
+    return tail_sessions(&client, &sessions, timestamps, filter).await;
+  }
+
+  let archived = cli_log.get_flag("archived");
+  if archived {
+    let session_name = cli_log.req_str("VALUE")?;
+    if cli_log.get_flag("summary") {
+      let log =
+        fetch_archived_log(&client, session_name, false, filter).await?;
+      return crate::output::session_summary::print_summary(
+        &summarize_lines(session_name, &log.lines),
+        cli_log.opt_str("output"),
+        ctx.expand_hostlists,
+      );
+    }
+    return print_archived_log(&client, session_name, timestamps, filter).await;
+  }
+
+  let session_name = match (group, user_input) {
+    (Some(group), _) => {
+      get_target_sessions(&client, Some(group), None, None, Some(1))
+        .await?
+        .pop()
+        .context(format!("No CFS session found for group '{group}'"))?
+    }
+    (None, Some(user_input)) => {
+      resolve_session_name(&client, user_input).await?
+    }
+    (None, None) => cli_log.req_str("VALUE")?.to_string(),
+  };
+
+  if cli_log.get_flag("summary") {
+    let summary = summarize_session_log(&client, &session_name).await?;
+    return crate::output::session_summary::print_summary(
+      &summary,
+      cli_log.opt_str("output"),
+      ctx.expand_hostlists,
+    );
+  }
+
+  let mut log = client
+    .follow_session_logs(&session_name, timestamps, filter)
+    .await
+    .context("Failed to get CFS session log stream from server")?;
+  while let Some(line) = log
+    .next_line()
+    .await
+    .context("Failed to read CFS session log stream")?
+  {
+    println!("{line}");
+  }
+  println!("Log streaming ended");
+  Ok(())
+}
+
+/// Names of the sessions targeting `group` (or, without one, the
+/// nodes `xnames`), optionally only those in `status` and only the
+/// `limit` most recent.
+async fn get_target_sessions(
+  client: &MantaClient,
+  group: Option<&str>,
+  xnames: Option<&str>,
+  status: Option<&str>,
+  limit: Option<u8>,
+) -> Result<Vec<String>, Error> {
+  let raw = client
+    .openapi
+    .get_sessions(
+      group,
+      limit.map(i32::from),
+      None,
+      None,
+      None,
+      None,
+      status,
+      if group.is_some() { None } else { xnames },
+      client.site_name(),
+    )
+    .await
+    .into_anyhow()
+    .await
+    .context("Failed to query CFS sessions")?;
+  let sessions: Vec<CfsSessionGetResponse> = serde_json::from_value(raw)
+    .context("Failed to deserialize CFS sessions list")?;
+  Ok(sessions.into_iter().map(|session| session.name).collect())
+}
+
+/// Follow the logs of all `sessions` at once, printing each line as it
+/// arrives prefixed with its session name, until every stream ends.
+async fn tail_sessions(
+  client: &MantaClient,
+  sessions: &[String],
+  timestamps: bool,
+  filter: SessionLogFilter<'_>,
+) -> Result<(), Error> {
+  let width = sessions.iter().map(String::len).max().unwrap_or_default();
+  let (tx, mut rx) = tokio::sync::mpsc::unbounded_channel::<String>();
+  for session_name in sessions {
+    let mut log = client
+      .follow_session_logs(session_name, timestamps, filter)
+      .await
+      .context(format!(
+        "Failed to get log stream of CFS session '{session_name}'"
+      ))?;
+    let tx = tx.clone();
+    let prefix = format!("{session_name:<width$} | ");
+    tokio::spawn(async move {
+      loop {
+        match log.next_line().await {
+          Ok(Some(line)) => {
+            let _ = tx.send(format!("{prefix}{line}"));
+          }
+          Ok(None) => {
+            let _ = tx.send(format!("{prefix}Log streaming ended"));
+            break;
+          }
+          Err(e) => {
+            let _ = tx.send(format!("{prefix}Log stream failed: {e:#}"));
+            break;
+          }
+        }
+      }
+    });
+  }
+  // The receiver ends once every task has dropped its sender.
+  drop(tx);
+  while let Some(line) = rx.recv().await {
+    println!("{line}");
+  }
+  Ok(())
+}
+
+/// Resolve `user_input` to the name of a CFS session: the session
+/// itself when one has that name, otherwise the most recent session
+/// targeting `user_input` as an xname.
+async fn resolve_session_name(
+  client: &MantaClient,
+  user_input: &str,
+) -> Result<String, Error> {
   // Try user input as a session name first, then as an xname. The
   // generated `get_sessions` returns `serde_json::Value`; we round-trip
   // into the typed shape so the existing `.name` field access works.
@@ -67,10 +245,8 @@ pub async fn handle_log(
     .ok()
     .and_then(|v| serde_json::from_value::<Vec<CfsSessionGetResponse>>(v).ok());
 
-  let session_name = if let Some(first) =
-    by_name.as_ref().and_then(|sessions| sessions.first())
-  {
-    first.name.clone()
+  if let Some(first) = by_name.as_ref().and_then(|sessions| sessions.first()) {
+    Ok(first.name.clone())
   } else {
     let raw = client
       .openapi
@@ -91,29 +267,102 @@ pub async fn handle_log(
       .context("Failed to query CFS sessions by xname")?;
     let by_xname: Vec<CfsSessionGetResponse> = serde_json::from_value(raw)
       .context("Failed to deserialize CFS sessions list")?;
-    by_xname
-      .into_iter()
-      .next()
-      .context(format!("No CFS session found for '{user_input}'"))?
-      .name
-      .clone()
-  };
+    Ok(
+      by_xname
+        .into_iter()
+        .next()
+        .context(format!("No CFS session found for '{user_input}'"))?
+        .name,
+    )
+  }
+}
 
-  let reader = client
-    .stream_session_logs(&session_name, timestamps)
+/// Summarise the Ansible output of `session_name`: from the log the
+/// server archived when there is one, else from the live log stream
+/// (read to its end, so this waits for a running session to finish).
+///
+/// # Errors
+///
+/// Returns `Err` if neither the archive nor the stream is available,
+/// or the stream breaks while being read.
+pub(crate) async fn summarize_session_log(
+  client: &MantaClient,
+  session_name: &str,
+) -> Result<SessionLogSummary, Error> {
+  if let Ok(archived) =
+    fetch_archived_log(client, session_name, false, SessionLogFilter::default())
+      .await
+  {
+    return Ok(summarize_lines(session_name, &archived.lines));
+  }
+  let mut log = client
+    .follow_session_logs(session_name, false, SessionLogFilter::default())
     .await
-    .context("Failed to get CFS session log stream from server")?;
-
-  let mut lines = reader.lines();
-  while let Some(raw) = lines
+    .context(format!(
+      "Log of CFS session '{session_name}' is neither archived nor streamable"
+    ))?;
+  let mut parser = AnsibleLogParser::new();
+  while let Some(line) = log
     .next_line()
     .await
     .context("Failed to read CFS session log stream")?
   {
-    if let Some(content) = raw.strip_prefix("data: ") {
-      println!("{content}");
-    }
+    parser.push_line(&line);
+  }
+  Ok(parser.finish(session_name))
+}
+
+fn summarize_lines(session_name: &str, lines: &[String]) -> SessionLogSummary {
+  manta_shared::types::ansible_summary::summarize(
+    session_name,
+    lines.iter().map(String::as_str),
+  )
+}
+
+/// Print the log the server archived for `session_name`.
+async fn print_archived_log(
+  client: &MantaClient,
+  session_name: &str,
+  timestamps: bool,
+  filter: SessionLogFilter<'_>,
+) -> Result<(), Error> {
+  let archived =
+    fetch_archived_log(client, session_name, timestamps, filter).await?;
+  for line in &archived.lines {
+    println!("{line}");
+  }
+  if !archived.complete {
+    tracing::warn!(
+      "The log of CFS session '{session_name}' was archived incompletely; \
+       its end is missing"
+    );
   }
-  println!("Log streaming ended");
   Ok(())
 }
+
+/// Fetch the log the server archived for `session_name`, filtered by
+/// the server.
+async fn fetch_archived_log(
+  client: &MantaClient,
+  session_name: &str,
+  timestamps: bool,
+  filter: SessionLogFilter<'_>,
+) -> Result<ArchivedSessionLog, Error> {
+  let raw = client
+    .openapi
+    .get_session_log_archive(
+      session_name,
+      filter.grep,
+      filter.layer,
+      Some(timestamps),
+      client.site_name(),
+    )
+    .await
+    .into_anyhow()
+    .await
+    .context(format!(
+      "Failed to get archived log of CFS session '{session_name}'"
+    ))?;
+  serde_json::from_value(serde_json::to_value(raw)?)
+    .context("Failed to deserialize archived session log")
+}
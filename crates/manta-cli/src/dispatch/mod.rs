@@ -3,9 +3,10 @@
 //!
 //! The dispatch tree mirrors the user-facing CLI: each verb
 //! (`add`, `apply`, `delete`, `power`, `console`, `backup`, `restore`,
-//! `migrate`, `run`, `get`, `config`, `log`, `upgrade`, `gen-man`,
-//! `gen-autocomplete`) has its own module, and each leaf subcommand
-//! lives in a sibling file. Every leaf `exec()` is a thin CLI adapter:
+//! `migrate`, `run`, `get`, `snapshot`, `diff`, `config`, `log`,
+//! `upgrade`, `validate`, `gen-man`, `gen-autocomplete`) has its own
+//! module, and each leaf subcommand lives in a sibling file. Every
+//! leaf `exec()` is a thin CLI adapter:
 //! validate clap matches, build an OpenAPI request, dispatch via
 //! [`crate::http_client::MantaClient`], and hand the response to
 //! [`crate::output::action_result`] for printing.
@@ -20,6 +21,7 @@ pub mod backup;
 pub mod config;
 pub mod console;
 pub mod delete;
+pub mod diff;
 pub mod gen_autocomplete;
 pub mod gen_man;
 pub mod get;
@@ -29,4 +31,6 @@ pub mod power;
 pub mod process;
 pub mod restore;
 pub mod run;
+pub mod snapshot;
 pub mod upgrade;
+pub mod validate;
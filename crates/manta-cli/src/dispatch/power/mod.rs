@@ -21,7 +21,7 @@ use crate::common::authentication::get_api_token;
 use crate::common::clap_ext::ArgMatchesExt;
 use crate::http_client::{MantaClient, OpenApiResultExt};
 use crate::openapi_client::types::{PowerRequest, PowerTargetType};
-use crate::output::action_result;
+use crate::output::{self, action_result};
 
 /// Default seconds between snapshot polls when `cli.toml` does not
 /// set `power_poll_interval_secs`. Matches the historical csm-rs
@@ -194,7 +194,10 @@ async fn exec_power(
 ) -> Result<(), Error> {
   // Interactive context printed before the confirm prompt; intentionally
   // plain stdout so it doesn't get wrapped in a JSON envelope.
-  println!("{label}: {}", opts.target);
+  println!(
+    "{label}: {}",
+    output::hosts_expression(opts.target, ctx.expand_hostlists)
+  );
   if !opts.dry_run
     && !common::confirm::confirm(
       opts.action.confirmation_text(),
@@ -12,19 +12,23 @@
 //! 3. (Authenticated verbs only) [`SessionContext::build`] — one
 //!    `GET /groups/available` round-trip plus the JWT claims; cached
 //!    on `AppContext` for the duration of the command.
+//! 4. (Authenticated verbs only) Capability check — loads the site's
+//!    [`capabilities`] report (refetched once a day) and warns on
+//!    stderr when the command needs a feature the site lacks.
 //!
 //! This local gate is the only read-only enforcement in the system.
 
 use crate::common::app_context::AppContext;
 use crate::common::authentication::get_api_token;
+use crate::common::capabilities;
 use crate::common::session::SessionContext;
 use crate::http_client::MantaClient;
 use anyhow::{Error, bail};
 use clap::ArgMatches;
 
 use crate::dispatch::{
-  add, apply, backup, config, console, delete, gen_autocomplete, gen_man, get,
-  log, migrate, power, restore, run, upgrade,
+  add, apply, backup, config, console, delete, diff, gen_autocomplete, gen_man,
+  get, log, migrate, power, restore, run, snapshot, upgrade, validate,
 };
 
 /// Verbs that operate purely on the local config file (or local
@@ -33,6 +37,7 @@ use crate::dispatch::{
 /// handler that does NOT appear in that grep result belongs here.
 ///
 /// Today the no-auth set is: `gen-autocomplete`, `gen-man`, `upgrade`,
+/// `validate`,
 /// `config set site`, `config set log`, `config set read-only`,
 /// `config unset hsm`, `config unset auth`,
 /// `config unset read-only`. Every other verb (including `config show`
@@ -41,7 +46,7 @@ use crate::dispatch::{
 fn verb_skips_session(cli_root: &ArgMatches) -> bool {
   match cli_root.subcommand() {
     Some(("gen-autocomplete", _)) | Some(("gen-man", _)) => true,
-    Some(("upgrade", _)) => true,
+    Some(("upgrade", _)) | Some(("validate", _)) => true,
     Some(("config", config_m)) => match config_m.subcommand() {
       Some(("set", set_m)) => matches!(
         set_m.subcommand(),
@@ -92,6 +97,14 @@ pub async fn process_cli(
     let client = MantaClient::from_app_ctx(&ctx, Some(&token))?;
     ctx.session = Some(SessionContext::build(&client, &token).await?);
     ctx.token = Some(token);
+
+    // Step 4: warn (but proceed) when the site can't serve this verb.
+    if let Some(site) = ctx.site_name
+      && let Some(caps) =
+        capabilities::load_or_refresh(&client, ctx.manta_server_url, site).await
+    {
+      capabilities::warn_if_unsupported(cli_root, &caps);
+    }
   }
 
   match cli_root.subcommand() {
@@ -99,6 +112,8 @@ pub async fn process_cli(
     Some(("power", m)) => power::handle_power(m, &ctx).await?,
     Some(("add", m)) => add::handle_add(m, &ctx).await?,
     Some(("get", m)) => get::handle_get(m, &ctx).await?,
+    Some(("snapshot", m)) => snapshot::handle_snapshot(m, &ctx).await?,
+    Some(("diff", m)) => diff::handle_diff(m, &ctx).await?,
     Some(("apply", m)) => apply::handle_apply(m, &ctx).await?,
     Some(("log", m)) => log::handle_log(m, &ctx).await?,
     Some(("console", m)) => console::handle_console(m, &ctx).await?,
@@ -112,6 +127,7 @@ pub async fn process_cli(
     }
     Some(("gen-man", m)) => gen_man::handle_gen_man(m, &ctx).await?,
     Some(("upgrade", m)) => upgrade::handle_upgrade(m, &ctx).await?,
+    Some(("validate", m)) => validate::handle_validate(m, &ctx).await?,
     Some((other, _)) => bail!("Unknown command: {other}"),
     None => bail!("No command provided"),
   }
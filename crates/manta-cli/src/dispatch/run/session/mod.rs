@@ -6,7 +6,9 @@
 //! 1. For every `--repo-path`, open the git repo, capture name + HEAD
 //!    commit id, and (interactively) confirm any uncommitted changes.
 //!    Implemented in [`check_local_repos`], with git helpers in
-//!    [`local_git_repo`].
+//!    [`local_git_repo`]. With `--repo-url`/`--ref` pairs instead, no
+//!    repo is opened: the pairs are sent as `repo_refs` and the server
+//!    resolves each ref to a commit in the site's Gitea.
 //! 2. POST `/v2/sessions` (`create_session`) with the layer list,
 //!    the optional `--playbook`, `--ansible-limit`, `--ansible-verbosity`,
 //!    and `--ansible-passthrough`. Server returns the created CFS
@@ -14,6 +16,15 @@
 //! 3. If `--watch-logs` is set, stream the session log over SSE
 //!    (`stream_session_logs`) to stdout, optionally with timestamps.
 //!
+//! With `--rerun <SESSION>`, no repo is read: POST
+//! `/v2/sessions/{name}/rerun` (`rerun_session`) makes the server
+//! create a session with the original's configuration, targets and
+//! Ansible settings. Only the `--ansible-limit`, `--ansible-verbosity`
+//! and `--name` given on the command line are sent, as overrides (the
+//! verbosity default does not replace the original's). `--dry-run`
+//! is forwarded and the server answers with the session it would
+//! create.
+//!
 //! `--dry-run` prints the would-be request via
 //! [`crate::output::action_result::preview_request`] and skips both the
 //! POST and the log stream; the repo walk still runs (so the preview
@@ -24,12 +35,15 @@ use std::path::PathBuf;
 
 use anyhow::{Context, Error, bail};
 use clap::ArgMatches;
+use clap::parser::ValueSource;
 
 use crate::common::app_context::AppContext;
 use crate::common::clap_ext::ArgMatchesExt;
 use crate::common::confirm;
-use crate::http_client::{MantaClient, OpenApiResultExt};
-use crate::openapi_client::types::CreateSessionRequest;
+use crate::http_client::{MantaClient, OpenApiResultExt, SessionLogFilter};
+use crate::openapi_client::types::{
+  CreateSessionRequest, RemoteRepoRef, RerunSessionRequest,
+};
 use crate::output::action_result;
 
 mod local_git_repo;
@@ -46,18 +60,34 @@ const GITEA_REPO_NAME_PREFIX: &str = "cray/";
 /// # Errors
 ///
 /// Returns an error when `--repo-path` is missing, when [`run_session`]
-/// returns an error (repo walk, prompt declined, HTTP failure, or log
-/// stream failure).
+/// (or, with `--rerun`, [`rerun_session`]) returns an error (repo walk,
+/// prompt declined, HTTP failure, or log stream failure).
 pub async fn exec(
   cli_run_session: &ArgMatches,
   ctx: &AppContext<'_>,
   token: &str,
 ) -> Result<(), Error> {
-  let repo_path_vec: Vec<PathBuf> = cli_run_session
-    .get_many("repo-path")
-    .context("'repo-path' argument not provided")?
-    .cloned()
-    .collect();
+  if let Some(original) = cli_run_session.opt_str("rerun") {
+    return rerun_session(ctx, token, original, cli_run_session).await;
+  }
+
+  let layers = match cli_run_session.get_many::<String>("repo-url") {
+    Some(repo_urls) => Layers::Remote(remote_repo_refs(
+      repo_urls.cloned().collect(),
+      cli_run_session
+        .get_many::<String>("ref")
+        .context("'ref' argument not provided")?
+        .cloned()
+        .collect(),
+    )?),
+    None => Layers::Local(
+      cli_run_session
+        .get_many("repo-path")
+        .context("'repo-path' argument not provided")?
+        .cloned()
+        .collect(),
+    ),
+  };
 
   let group_name_arg_opt = cli_run_session.opt_str("group");
 
@@ -81,7 +111,7 @@ pub async fn exec(
       session_name: cfs_conf_sess_name_opt,
       playbook: playbook_file_name_opt,
       group_name: group_name_arg_opt,
-      repos: &repo_path_vec,
+      layers,
       ansible_limit: group_members_opt,
       ansible_verbosity,
       ansible_passthrough,
@@ -98,7 +128,7 @@ struct SessionParams<'a> {
   session_name: Option<&'a str>,
   playbook: Option<&'a str>,
   group_name: Option<&'a str>,
-  repos: &'a [PathBuf],
+  layers: Layers,
   ansible_limit: Option<&'a str>,
   ansible_verbosity: Option<&'a str>,
   ansible_passthrough: Option<&'a str>,
@@ -108,8 +138,37 @@ struct SessionParams<'a> {
   dry_run: bool,
 }
 
+/// Where the session's configuration layers come from.
+enum Layers {
+  /// Local git repos, each contributing its HEAD commit.
+  Local(Vec<PathBuf>),
+  /// Repos in the system's VCS at a branch, tag or commit.
+  Remote(Vec<RemoteRepoRef>),
+}
+
+/// Pair each `--repo-url` with the `--ref` at the same position.
+fn remote_repo_refs(
+  repo_urls: Vec<String>,
+  git_refs: Vec<String>,
+) -> Result<Vec<RemoteRepoRef>, Error> {
+  if repo_urls.len() != git_refs.len() {
+    bail!(
+      "Got {} --repo-url but {} --ref; give one --ref per --repo-url",
+      repo_urls.len(),
+      git_refs.len()
+    );
+  }
+  Ok(
+    repo_urls
+      .into_iter()
+      .zip(git_refs)
+      .map(|(repo_url, git_ref)| RemoteRepoRef { repo_url, git_ref })
+      .collect(),
+  )
+}
+
 /// Create a dynamic-target CFS session: pushes the local repos'
-/// HEADs to gitea, POSTs `/sessions`, and (if `watch_logs`) streams
+/// HEADs (or the remote refs) to gitea, POSTs `/sessions`, and (if `watch_logs`) streams
 /// the session log to stdout via SSE before printing the action
 /// result.
 ///
@@ -125,7 +184,6 @@ async fn run_session(
   let cfs_conf_sess_name = p.session_name;
   let playbook_yaml_file_name_opt = p.playbook;
   let group_name_opt = p.group_name;
-  let repos_paths = p.repos;
   let ansible_limit_opt = p.ansible_limit;
   let ansible_verbosity = p.ansible_verbosity;
   let ansible_passthrough = p.ansible_passthrough;
@@ -133,9 +191,15 @@ async fn run_session(
   let timestamps = p.timestamps;
   let output_opt = p.output;
 
-  // Check local repos (user interaction: confirm dialogs)
-  let (repo_name_vec, repo_last_commit_id_vec) =
-    check_local_repos(repos_paths, p.dry_run)?;
+  // Check local repos (user interaction: confirm dialogs); remote
+  // refs are resolved by the server.
+  let (repo_name_vec, repo_last_commit_id_vec, repo_refs) = match p.layers {
+    Layers::Local(repos_paths) => {
+      let (names, commits) = check_local_repos(&repos_paths, p.dry_run)?;
+      (names, commits, Vec::new())
+    }
+    Layers::Remote(repo_refs) => (Vec::new(), Vec::new(), repo_refs),
+  };
 
   let req = CreateSessionRequest {
     cfs_conf_sess_name: cfs_conf_sess_name.map(str::to_string),
@@ -143,6 +207,7 @@ async fn run_session(
     hsm_group: group_name_opt.map(str::to_string),
     repo_names: repo_name_vec,
     repo_last_commit_ids: repo_last_commit_id_vec,
+    repo_refs,
     ansible_limit: ansible_limit_opt.map(str::to_string),
     ansible_verbosity: ansible_verbosity.map(str::to_string),
     ansible_passthrough: ansible_passthrough.map(str::to_string),
@@ -172,20 +237,20 @@ async fn run_session(
   if watch_logs {
     tracing::info!("Fetching logs ...");
 
-    use tokio::io::AsyncBufReadExt as _;
-    let reader = client
-      .stream_session_logs(&cfs_session_name, timestamps)
+    let mut log = client
+      .follow_session_logs(
+        &cfs_session_name,
+        timestamps,
+        SessionLogFilter::default(),
+      )
       .await
       .context("Failed to get CFS session log stream from server")?;
-    let mut lines = reader.lines();
-    while let Some(raw) = lines
+    while let Some(line) = log
       .next_line()
       .await
       .context("Failed to read CFS session log stream")?
     {
-      if let Some(content) = raw.strip_prefix("data: ") {
-        println!("{content}");
-      }
+      println!("{line}");
     }
   }
 
@@ -207,6 +272,83 @@ async fn run_session(
   Ok(())
 }
 
+/// Re-run CFS session `original` through the server, with the
+/// `--ansible-limit`, `--ansible-verbosity` and `--name` typed on the
+/// command line as overrides.
+async fn rerun_session(
+  ctx: &AppContext<'_>,
+  shasta_token: &str,
+  original: &str,
+  cli_run_session: &ArgMatches,
+) -> Result<(), Error> {
+  let from_command_line = |id: &'static str| {
+    (cli_run_session.value_source(id) == Some(ValueSource::CommandLine))
+      .then(|| cli_run_session.opt_string(id))
+      .flatten()
+  };
+  let ansible_verbosity = from_command_line("ansible-verbosity")
+    .map(|verbosity| verbosity.parse::<i32>())
+    .transpose()
+    .context("Invalid --ansible-verbosity")?;
+  let output_opt = cli_run_session.opt_str("output");
+  let req = RerunSessionRequest {
+    session_name: cli_run_session.opt_string("name"),
+    ansible_limit: from_command_line("ansible-limit"),
+    ansible_verbosity,
+    dry_run: Some(cli_run_session.get_flag("dry-run")),
+  };
+
+  let client = MantaClient::from_app_ctx(ctx, Some(shasta_token))?;
+  let rerun = client
+    .openapi
+    .rerun_session(original, client.site_name(), &req)
+    .await
+    .into_anyhow()
+    .await
+    .context(format!("Failed to re-run CFS session '{original}'"))?;
+
+  if rerun.dry_run {
+    return action_result::print_with_data(
+      &format!(
+        "Dry run: would re-run CFS session '{original}' as '{}' \
+         (configuration: '{}', limit: '{}')",
+        rerun.session_name,
+        rerun.configuration_name,
+        rerun.ansible_limit.as_deref().unwrap_or("-"),
+      ),
+      &rerun,
+      output_opt,
+    );
+  }
+
+  if cli_run_session.get_flag("watch-logs") {
+    let mut log = client
+      .follow_session_logs(
+        &rerun.session_name,
+        cli_run_session.get_flag("timestamps"),
+        SessionLogFilter::default(),
+      )
+      .await
+      .context("Failed to get CFS session log stream from server")?;
+    while let Some(line) = log
+      .next_line()
+      .await
+      .context("Failed to read CFS session log stream")?
+    {
+      println!("{line}");
+    }
+  }
+
+  action_result::print_with_data(
+    &format!(
+      "CFS session '{}' created, re-running '{original}' (configuration: '{}')",
+      rerun.session_name, rerun.configuration_name
+    ),
+    &rerun,
+    output_opt,
+  )
+}
+
 /// Walk every `--repo` path: open the git repo, capture its name and
 /// HEAD commit, and check for uncommitted changes. Returns parallel
 /// `(repo_names, last_commit_ids)` vectors used to build the
@@ -370,4 +512,66 @@ mod tests {
       "expected -d short alias to parse: {result:?}"
     );
   }
+
+  /// `--rerun` needs neither `--repo-path` nor `--ansible-limit`, and
+  /// rejects the arguments that only make sense for a new session.
+  #[test]
+  fn rerun_replaces_repo_and_limit() {
+    let parse = |args: &[&str]| {
+      crate::build::build_cli()
+        .try_get_matches_from(["manta", "run", "session"].iter().chain(args))
+    };
+    assert!(parse(&["--rerun", "batcher-1"]).is_ok());
+    assert!(
+      parse(&["--rerun", "batcher-1", "-l", "x1000c0s0b0n0", "-v", "4"])
+        .is_ok()
+    );
+    assert!(parse(&["--rerun", "batcher-1", "-r", "/tmp/repo"]).is_err());
+    assert!(parse(&["--rerun", "batcher-1", "-H", "compute"]).is_err());
+    assert!(parse(&["--name", "s", "-r", "/tmp/repo"]).is_err());
+  }
+
+  /// `--repo-url` with `--ref` replaces `--repo-path`; the two can't
+  /// be mixed and a `--ref` needs a `--repo-url`.
+  #[test]
+  fn repo_url_with_ref_replaces_repo_path() {
+    let parse = |args: &[&str]| {
+      crate::build::build_cli().try_get_matches_from(
+        ["manta", "run", "session", "-n", "s", "-l", "x1000c0s0b0n0"]
+          .iter()
+          .chain(args),
+      )
+    };
+    let matches = parse(&[
+      "--repo-url",
+      "cray/csm-config",
+      "--ref",
+      "main",
+      "-u",
+      "https://vcs.example.com/vcs/cray/cos-config.git",
+      "--ref",
+      "v1.2.0",
+    ])
+    .unwrap();
+    let (_, run) = matches.subcommand().unwrap();
+    let (_, session) = run.subcommand().unwrap();
+    let refs = super::remote_repo_refs(
+      session.get_many("repo-url").unwrap().cloned().collect(),
+      session.get_many("ref").unwrap().cloned().collect(),
+    )
+    .unwrap();
+    assert_eq!(refs[1].git_ref, "v1.2.0");
+    assert!(parse(&["--repo-url", "cray/csm-config"]).is_err());
+    assert!(parse(&["--ref", "main", "-r", "/tmp/repo"]).is_err());
+    assert!(
+      parse(&["-u", "csm-config", "--ref", "main", "-r", "/tmp/repo"]).is_err()
+    );
+    assert!(
+      super::remote_repo_refs(
+        vec!["a".into(), "b".into()],
+        vec!["main".into()]
+      )
+      .is_err()
+    );
+  }
 }
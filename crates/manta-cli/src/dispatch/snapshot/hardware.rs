@@ -0,0 +1,105 @@
+//! Implements the `manta snapshot hardware` command.
+//!
+//! By default hits `POST /hardware-snapshots`, which inventories the
+//! group server-side and stores the result; the stored snapshot's
+//! listing entry is printed so its id can be passed to
+//! `manta diff hardware`. With `--local FILE` the inventory is read
+//! through `GET /groups/hardware` instead and written to `FILE` by
+//! [`fetch_live_snapshot`], which `manta diff hardware` also uses for
+//! its `live` side.
+
+use anyhow::{Context, Error};
+use chrono::{SecondsFormat, Utc};
+use manta_shared::types::api::hardware::HardwareSnapshot;
+use manta_shared::types::dto::NodeSummary;
+use manta_shared::types::hardware_diff::snapshot_nodes;
+
+use crate::common::app_context::AppContext;
+use crate::common::clap_ext::ArgMatchesExt;
+use crate::http_client::{MantaClient, OpenApiResultExt};
+use crate::openapi_client::types::TakeHardwareSnapshotRequest;
+use crate::output;
+
+/// Id recorded on snapshots built CLI-side.
+pub const LOCAL_SNAPSHOT_ID: &str = "local";
+
+/// Read the current inventory of `group` into an unsaved snapshot.
+///
+/// # Errors
+///
+/// Returns an error if the HTTP request fails or the response does not
+/// carry node summaries.
+pub async fn fetch_live_snapshot(
+  client: &MantaClient,
+  group: &str,
+  taken_by: &str,
+) -> Result<HardwareSnapshot, Error> {
+  let json = client
+    .openapi
+    .get_groups_hardware(Some(group), client.site_name())
+    .await
+    .into_anyhow()
+    .await?;
+  let summaries: Vec<NodeSummary> =
+    serde_json::from_value(json["node_summaries"].clone())
+      .context("Failed to deserialize node summaries")?;
+  let (nodes, unreadable) = snapshot_nodes(&summaries);
+  Ok(HardwareSnapshot {
+    id: LOCAL_SNAPSHOT_ID.to_string(),
+    site: client.site_name().to_string(),
+    group: json["hsm_group_name"].as_str().unwrap_or(group).to_string(),
+    taken_at: Utc::now().to_rfc3339_opts(SecondsFormat::Secs, true),
+    taken_by: taken_by.to_string(),
+    nodes,
+    unreadable,
+  })
+}
+
+/// CLI adapter for `manta snapshot hardware`.
+///
+/// Consumes clap matches for the `snapshot hardware` subcommand
+/// (`--group`, optional `--local`, `--output`).
+///
+/// # Errors
+///
+/// Returns an error if the HTTP request fails (including the 501 a
+/// server without `[server.hardware_snapshots]` answers), or the local
+/// file cannot be written.
+pub async fn exec(
+  ctx: &AppContext<'_>,
+  token: &str,
+  cli_args: &clap::ArgMatches,
+) -> Result<(), Error> {
+  let group = cli_args.req_str("group")?;
+  let client = MantaClient::from_app_ctx(ctx, Some(token))?;
+
+  if let Some(path) = cli_args.opt_str("local") {
+    let taken_by = ctx.session.as_ref().map_or("", |s| s.username.as_str());
+    let snapshot = fetch_live_snapshot(&client, group, taken_by).await?;
+    std::fs::write(path, serde_json::to_vec_pretty(&snapshot)?)
+      .with_context(|| format!("Failed to write snapshot to '{path}'"))?;
+    eprintln!(
+      "Wrote hardware snapshot of '{}' ({} nodes) to {path}",
+      snapshot.group,
+      snapshot.nodes.len()
+    );
+    return Ok(());
+  }
+
+  let info = client
+    .openapi
+    .post_hardware_snapshot(
+      client.site_name(),
+      &TakeHardwareSnapshotRequest {
+        group: group.to_string(),
+      },
+    )
+    .await
+    .into_anyhow()
+    .await?;
+  output::hardware_snapshot::print_list(
+    std::slice::from_ref(&info),
+    cli_args.opt_str("output"),
+  )?;
+  Ok(())
+}
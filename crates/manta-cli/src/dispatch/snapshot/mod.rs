@@ -0,0 +1,30 @@
+//! `manta snapshot` subcommands — record inventory snapshots.
+//!
+//! - [`hardware`] — `manta snapshot hardware`: store a group's
+//!   per-node hardware inventory on the server or in a local file.
+
+pub mod hardware;
+
+use crate::common::app_context::AppContext;
+use crate::common::authentication::get_api_token;
+use anyhow::{Error, bail};
+use clap::ArgMatches;
+
+/// Dispatch `manta snapshot` subcommands.
+///
+/// # Errors
+///
+/// Returns an error when token acquisition fails, no subcommand is
+/// provided or the name is unknown, or the leaf handler fails.
+pub async fn handle_snapshot(
+  cli_snapshot: &ArgMatches,
+  ctx: &AppContext<'_>,
+) -> Result<(), Error> {
+  let token = get_api_token(ctx).await?;
+  match cli_snapshot.subcommand() {
+    Some(("hardware", m)) => hardware::exec(ctx, &token, m).await?,
+    Some((other, _)) => bail!("Unknown 'snapshot' subcommand: {other}"),
+    None => bail!("No 'snapshot' subcommand provided"),
+  }
+  Ok(())
+}
@@ -0,0 +1,34 @@
+//! `manta validate` subcommands — offline checks that never contact
+//! the manta server.
+//!
+//! - [`sat_file`] — `manta validate sat-file`: render a SAT template
+//!   and check it against the typed SAT schema and its in-file
+//!   cross-references.
+
+pub mod sat_file;
+
+use crate::common::app_context::AppContext;
+use anyhow::{Error, bail};
+use clap::ArgMatches;
+
+/// Dispatch `manta validate` subcommands.
+///
+/// Like `gen_man` and `upgrade`, this handler does NOT call
+/// `get_api_token(ctx)`: validation is purely local.
+///
+/// # Errors
+///
+/// Returns an error when no subcommand is provided or the name is
+/// unknown, or when the leaf handler fails (including when the file
+/// is invalid).
+pub async fn handle_validate(
+  cli_validate: &ArgMatches,
+  _ctx: &AppContext<'_>,
+) -> Result<(), Error> {
+  match cli_validate.subcommand() {
+    Some(("sat-file", m)) => sat_file::exec(m)?,
+    Some((other, _)) => bail!("Unknown 'validate' subcommand: {other}"),
+    None => bail!("No 'validate' subcommand provided"),
+  }
+  Ok(())
+}
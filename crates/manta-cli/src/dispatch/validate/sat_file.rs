@@ -0,0 +1,59 @@
+//! Implements `manta validate sat-file`.
+//!
+//! Runs the client-side half of `manta apply sat-file` and stops
+//! before anything is sent: Jinja2 rendering, the typed schema check
+//! ([`crate::dispatch::apply::sat_file::validate`]), and the plan
+//! builder's cross-reference checks (dangling `image_ref`, duplicate
+//! `ref_name`, image cycles).
+
+use anyhow::{Context, Error};
+use clap::ArgMatches;
+
+use crate::dispatch::apply::sat_file::{
+  plan, render::SatTemplateInputs, render::render_jinja2_sat_file_yaml,
+  validate::check_schema,
+};
+
+/// Render and validate the SAT file named in `m`, printing a one-line
+/// summary on success.
+///
+/// # Errors
+///
+/// Returns an error when the template or values file cannot be read,
+/// when Jinja2 rendering fails, when the rendered file fails the
+/// schema check (every problem is listed with its line and column),
+/// or when the plan builder rejects a cross-reference.
+pub fn exec(m: &ArgMatches) -> Result<(), Error> {
+  let inputs = SatTemplateInputs::read(m)?;
+
+  let rendered_yaml = render_jinja2_sat_file_yaml(
+    &inputs.sat_file_content,
+    &inputs.values_file_contents,
+    inputs.values_cli_opt.as_deref(),
+    Some(inputs.bundle_dir()),
+  )
+  .context("Failed to render SAT Jinja2 template")?;
+
+  check_schema(&rendered_yaml)?;
+
+  let mut sat_file: serde_json::Value = serde_yaml::from_str(&rendered_yaml)
+    .context("Rendered SAT template is not valid YAML")?;
+  let plan = plan::build_plan(&mut sat_file, false, false)?;
+
+  let (n_cfg, n_img, n_st) =
+    plan.iter().fold((0_usize, 0, 0), |(c, i, s), e| match e {
+      plan::SatElement::Configuration(_) => (c + 1, i, s),
+      plan::SatElement::Image(_) => (c, i + 1, s),
+      plan::SatElement::SessionTemplate(_) => (c, i, s + 1),
+    });
+  let n_hw = sat_file
+    .get("hardware")
+    .and_then(serde_json::Value::as_array)
+    .map_or(0, Vec::len);
+  println!(
+    "SAT file '{}' is valid: {n_cfg} configurations, {n_img} images, \
+     {n_st} session templates, {n_hw} hardware entries",
+    inputs.sat_template_file.display()
+  );
+  Ok(())
+}
@@ -42,6 +42,7 @@ mod wire;
 pub use client::{
   AuthServerUnreachable, MantaClient, OpenApiResultExt, SiteNotFound,
 };
+pub use streaming::SessionLogFilter;
 pub(super) use wire::ws_base_url;
 
 #[cfg(test)]
@@ -163,4 +164,100 @@ mod tests {
         .expect("scheme-less host must succeed");
     assert_eq!(c.base_url(), "http://stub.invalid:8080/v2");
   }
+
+  // ---- SSE parsing ----
+
+  use super::streaming::{SseEvent, SseParser};
+
+  fn parse(body: &str) -> Vec<SseEvent> {
+    let mut parser = SseParser::default();
+    body.lines().filter_map(|l| parser.push_line(l)).collect()
+  }
+
+  #[test]
+  fn sse_events_carry_their_id_and_data() {
+    let events = parse("id: 7\ndata: TASK [x]\n\n:\n\nid: 8\ndata:\n\n");
+    assert_eq!(
+      events,
+      vec![
+        SseEvent {
+          id: Some("7".into()),
+          event: None,
+          data: Some("TASK [x]".into()),
+        },
+        SseEvent {
+          id: Some("8".into()),
+          event: None,
+          data: Some(String::new()),
+        },
+      ]
+    );
+  }
+
+  #[test]
+  fn sse_end_event_and_multiline_data() {
+    let events = parse("data: a\ndata: b\n\nevent: end\n\n");
+    assert_eq!(events[0].data.as_deref(), Some("a\nb"));
+    assert_eq!(events[1].event.as_deref(), Some("end"));
+    assert_eq!(events.len(), 2);
+  }
+
+  /// Serve one SSE `body` per connection, in order, and return the
+  /// `Last-Event-ID` each request carried.
+  async fn serve_sse(
+    bodies: Vec<&'static str>,
+  ) -> (String, tokio::task::JoinHandle<Vec<Option<String>>>) {
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let url = format!("http://{}", listener.local_addr().unwrap());
+    let server = tokio::spawn(async move {
+      let mut last_event_ids = Vec::new();
+      for body in bodies {
+        let (mut socket, _) = listener.accept().await.unwrap();
+        let mut request = Vec::new();
+        let mut buf = [0u8; 1024];
+        while !request.windows(4).any(|w| w == b"\r\n\r\n") {
+          let n = socket.read(&mut buf).await.unwrap();
+          request.extend_from_slice(&buf[..n]);
+        }
+        let request = String::from_utf8_lossy(&request).to_lowercase();
+        last_event_ids.push(
+          request
+            .lines()
+            .find_map(|l| l.strip_prefix("last-event-id: "))
+            .map(|id| id.trim().to_string()),
+        );
+        let response = format!(
+          "HTTP/1.1 200 OK\r\ncontent-type: text/event-stream\r\n\
+           connection: close\r\n\r\n{body}"
+        );
+        socket.write_all(response.as_bytes()).await.unwrap();
+        socket.shutdown().await.unwrap();
+      }
+      last_event_ids
+    });
+    (url, server)
+  }
+
+  #[tokio::test]
+  async fn follower_resumes_after_an_upstream_error_event() {
+    let (url, server) = serve_sse(vec![
+      "id: 1\ndata: one\n\nevent: error\ndata: pod log read failed\n\n",
+      "id: 2\ndata: two\n\nevent: end\n\n",
+    ])
+    .await;
+    let client = MantaClient::new(&url, "alps").unwrap();
+    let mut follower = client
+      .follow_session_logs("batcher-1", false, Default::default())
+      .await
+      .unwrap();
+
+    let mut lines = Vec::new();
+    while let Some(line) = follower.next_line().await.unwrap() {
+      lines.push(line);
+    }
+    assert_eq!(lines, ["one", "two"]);
+    assert_eq!(server.await.unwrap(), [None, Some("1".to_string())]);
+  }
 }
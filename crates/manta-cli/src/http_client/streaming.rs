@@ -4,6 +4,15 @@
 //! `GET /sessions/{name}/logs` returns a `text/event-stream` body the
 //! CLI tails line-by-line.
 //!
+//! Each log line arrives as an event numbered with its line number,
+//! and the stream closes with an `end` event once the pod log is
+//! exhausted, or with an `error` event when the server lost the pod
+//! log. [`SessionLogFollower`] uses them to survive dropped
+//! connections: when the body ends (or fails) before `end`, or brings
+//! an `error`, it reconnects with exponential backoff, sending the last
+//! id it received as `Last-Event-ID`, and the server resumes after that
+//! line.
+//!
 //! ## Why not auto-generated
 //!
 //! progenitor's `get_session_logs` deserialises the response as a
@@ -19,20 +28,250 @@
 //! else should be added to the server with `#[utoipa::path(...)]`
 //! and consumed through the regenerated `client.openapi.*` methods.
 
-use anyhow::{Context, bail};
+use std::pin::Pin;
+use std::time::Duration;
+
+use anyhow::bail;
 use futures::TryStreamExt;
-use tokio::io::{AsyncBufRead, BufReader};
+use tokio::io::{AsyncBufRead, AsyncBufReadExt, BufReader, Lines};
 use tokio_util::io::StreamReader;
 
 use super::MantaClient;
 use super::client::unwrap_error_body;
 
+/// Reconnect attempts in a row, without a log line in between, before
+/// [`SessionLogFollower::next_line`] gives up.
+const MAX_RECONNECT_ATTEMPTS: u32 = 10;
+/// Wait before the first reconnect; doubled on each further attempt.
+const INITIAL_RECONNECT_DELAY: Duration = Duration::from_secs(1);
+/// Longest wait between two reconnect attempts.
+const MAX_RECONNECT_DELAY: Duration = Duration::from_secs(30);
+/// SSE event type the server sends after the last log line.
+const LOG_END_EVENT: &str = "end";
+/// SSE event type the server sends when reading the pod log failed;
+/// the log is incomplete and the stream is about to close.
+const LOG_ERROR_EVENT: &str = "error";
+
+/// Server-side filters for [`MantaClient::follow_session_logs`] (the
+/// `layer` and `grep` query parameters).
+#[derive(Debug, Default, Clone, Copy)]
+pub struct SessionLogFilter<'a> {
+  /// Only this configuration layer: its index, or its layer,
+  /// repository or playbook name.
+  pub layer: Option<&'a str>,
+  /// Only the lines matching this regular expression.
+  pub grep: Option<&'a str>,
+}
+
+/// One dispatched SSE event.
+#[derive(Debug, Default, PartialEq, Eq)]
+pub(super) struct SseEvent {
+  pub(super) id: Option<String>,
+  pub(super) event: Option<String>,
+  pub(super) data: Option<String>,
+}
+
+/// Assembles SSE events from the lines of a `text/event-stream` body.
+#[derive(Debug, Default)]
+pub(super) struct SseParser {
+  event: SseEvent,
+}
+
+impl SseParser {
+  /// Feed one line; returns the event a blank line completes.
+  pub(super) fn push_line(&mut self, line: &str) -> Option<SseEvent> {
+    if line.is_empty() {
+      let event = std::mem::take(&mut self.event);
+      return (event != SseEvent::default()).then_some(event);
+    }
+    let (field, value) = line.split_once(':').unwrap_or((line, ""));
+    let value = value.strip_prefix(' ').unwrap_or(value).to_string();
+    match field {
+      "id" => self.event.id = Some(value),
+      "event" => self.event.event = Some(value),
+      "data" => match &mut self.event.data {
+        Some(data) => {
+          data.push('\n');
+          data.push_str(&value);
+        }
+        None => self.event.data = Some(value),
+      },
+      // Comments (keep-alives) and unknown fields.
+      _ => {}
+    }
+    None
+  }
+}
+
+type EventLines = Lines<Pin<Box<dyn AsyncBufRead + Send>>>;
+
+/// Why (re)connecting to the log stream failed.
+enum ConnectError {
+  /// Worth retrying: transport failure or a 5xx.
+  Transient(anyhow::Error),
+  /// Retrying won't help: the server rejected the request.
+  Fatal(anyhow::Error),
+}
+
+impl ConnectError {
+  fn into_inner(self) -> anyhow::Error {
+    match self {
+      Self::Transient(e) | Self::Fatal(e) => e,
+    }
+  }
+}
+
+/// A CFS session log followed over SSE, reconnecting transparently
+/// when the connection drops. Owns everything it needs, so it can be
+/// moved into a spawned task.
+pub struct SessionLogFollower {
+  raw: reqwest::Client,
+  url: String,
+  site_name: String,
+  query: Vec<(&'static str, String)>,
+  session_name: String,
+  lines: Option<EventLines>,
+  parser: SseParser,
+  last_event_id: Option<String>,
+  ended: bool,
+}
+
+impl SessionLogFollower {
+  // `&mut self` although nothing is mutated: the follower is `Send`
+  // but not `Sync`, and the future must be `Send` to be spawned.
+  async fn connect(&mut self) -> Result<EventLines, ConnectError> {
+    let mut builder = self
+      .raw
+      .get(&self.url)
+      .header("X-Manta-Site", &self.site_name)
+      .query(&self.query);
+    if let Some(id) = &self.last_event_id {
+      builder = builder.header("Last-Event-ID", id);
+    }
+    MantaClient::log_request_as_curl(&builder);
+    let resp = builder.send().await.map_err(|e| {
+      ConnectError::Transient(
+        anyhow::Error::new(e).context("HTTP GET session logs failed"),
+      )
+    })?;
+
+    let status = resp.status();
+    if !status.is_success() {
+      let body = resp.text().await.unwrap_or_default();
+      let error = anyhow::anyhow!(
+        "GET session logs returned {status}: {}",
+        unwrap_error_body(&body)
+      );
+      return Err(if status.is_server_error() {
+        ConnectError::Transient(error)
+      } else {
+        ConnectError::Fatal(error)
+      });
+    }
+
+    let byte_stream = resp.bytes_stream().map_err(std::io::Error::other);
+    let reader: Pin<Box<dyn AsyncBufRead + Send>> =
+      Box::pin(BufReader::new(StreamReader::new(byte_stream)));
+    Ok(reader.lines())
+  }
+
+  /// The next log line, or `None` once the server has sent the whole
+  /// log.
+  ///
+  /// # Errors
+  ///
+  /// - The server rejects a reconnect with a 4xx (e.g. the token
+  ///   expired, or the session was deleted).
+  /// - [`MAX_RECONNECT_ATTEMPTS`] reconnects in a row fail.
+  pub async fn next_line(&mut self) -> anyhow::Result<Option<String>> {
+    let mut attempts = 0;
+    loop {
+      let Some(lines) = self.lines.as_mut() else {
+        if self.ended {
+          return Ok(None);
+        }
+        attempts += 1;
+        if attempts > MAX_RECONNECT_ATTEMPTS {
+          bail!(
+            "Lost the log stream of CFS session '{}' and could not \
+             reconnect after {MAX_RECONNECT_ATTEMPTS} attempts",
+            self.session_name
+          );
+        }
+        let delay = INITIAL_RECONNECT_DELAY
+          .saturating_mul(1 << (attempts - 1).min(16))
+          .min(MAX_RECONNECT_DELAY);
+        tracing::warn!(
+          "Log stream of CFS session '{}' dropped; reconnecting in {}s \
+           (attempt {attempts}/{MAX_RECONNECT_ATTEMPTS})",
+          self.session_name,
+          delay.as_secs()
+        );
+        tokio::time::sleep(delay).await;
+        match self.connect().await {
+          Ok(lines) => self.lines = Some(lines),
+          Err(ConnectError::Transient(e)) => {
+            tracing::warn!("Reconnect failed: {e:#}");
+          }
+          Err(ConnectError::Fatal(e)) => return Err(e),
+        }
+        continue;
+      };
+
+      match lines.next_line().await {
+        Ok(Some(line)) => {
+          let Some(event) = self.parser.push_line(&line) else {
+            continue;
+          };
+          match event.event.as_deref() {
+            Some(LOG_END_EVENT) => {
+              self.ended = true;
+              continue;
+            }
+            // Resume from the last line received, as for a dropped
+            // connection.
+            Some(LOG_ERROR_EVENT) => {
+              tracing::warn!(
+                "Server lost the log stream of CFS session '{}': {}",
+                self.session_name,
+                event.data.as_deref().unwrap_or("unknown error")
+              );
+              self.lines = None;
+              self.parser = SseParser::default();
+              continue;
+            }
+            _ => {}
+          }
+          if event.id.is_some() {
+            self.last_event_id = event.id;
+          }
+          if let Some(data) = event.data {
+            return Ok(Some(data));
+          }
+        }
+        // The body ended: done when the server said so, a dropped
+        // connection otherwise.
+        Ok(None) => {
+          self.lines = None;
+          self.parser = SseParser::default();
+        }
+        Err(e) => {
+          tracing::debug!("Reading the log stream failed: {e}");
+          self.lines = None;
+          self.parser = SseParser::default();
+        }
+      }
+    }
+  }
+}
+
 impl MantaClient {
-  /// Stream CFS session logs from `GET /sessions/{name}/logs` (SSE).
+  /// Follow the CFS session log from `GET /sessions/{name}/logs` (SSE),
+  /// keeping only the lines `filter` selects. The filtering happens on
+  /// the server, so the lines left out never cross the wire.
   ///
-  /// Returns a buffered reader over the SSE byte stream. The caller is
-  /// responsible for stripping the `data: ` prefix that the server wraps
-  /// around each log line.
+  /// The first connection is made here; later ones, after a dropped
+  /// connection, by [`SessionLogFollower::next_line`].
   ///
   /// # Errors
   ///
@@ -41,33 +280,32 @@ impl MantaClient {
   /// - The server responded with a non-2xx status; the body is
   ///   pulled through [`super::client::unwrap_error_body`] before
   ///   being formatted into the error message.
-  pub async fn stream_session_logs(
+  pub async fn follow_session_logs(
     &self,
     session_name: &str,
     timestamps: bool,
-  ) -> anyhow::Result<impl AsyncBufRead + Send + Unpin> {
-    let url = format!("{}/sessions/{}/logs", self.base_url(), session_name);
-    let builder = self
-      .raw
-      .get(&url)
-      .header("X-Manta-Site", self.site_name())
-      .query(&[("timestamps", timestamps.to_string())]);
-    Self::log_request_as_curl(&builder);
-    let resp = builder
-      .send()
-      .await
-      .context("HTTP GET session logs failed")?;
-
-    if !resp.status().is_success() {
-      let status = resp.status();
-      let body = resp.text().await.unwrap_or_default();
-      bail!(
-        "GET session logs returned {status}: {}",
-        unwrap_error_body(&body)
-      );
+    filter: SessionLogFilter<'_>,
+  ) -> anyhow::Result<SessionLogFollower> {
+    let mut query = vec![("timestamps", timestamps.to_string())];
+    if let Some(layer) = filter.layer {
+      query.push(("layer", layer.to_string()));
     }
-
-    let byte_stream = resp.bytes_stream().map_err(std::io::Error::other);
-    Ok(BufReader::new(StreamReader::new(byte_stream)))
+    if let Some(grep) = filter.grep {
+      query.push(("grep", grep.to_string()));
+    }
+    let mut follower = SessionLogFollower {
+      raw: self.raw.clone(),
+      url: format!("{}/sessions/{}/logs", self.base_url(), session_name),
+      site_name: self.site_name().to_string(),
+      query,
+      session_name: session_name.to_string(),
+      lines: None,
+      parser: SseParser::default(),
+      last_event_id: None,
+      ended: false,
+    };
+    follower.lines =
+      Some(follower.connect().await.map_err(ConnectError::into_inner)?);
+    Ok(follower)
   }
 }
@@ -135,6 +135,7 @@ async fn run_cli(
     sat_file_not_visible_budget_secs: configuration
       .sat_file_not_visible_budget_secs,
     read_only: configuration.read_only,
+    expand_hostlists: cli_matches.get_flag("expand"),
     settings: &settings,
     token: None,
     session: None,
@@ -0,0 +1,85 @@
+//! Renderer for [`EphemeralEnv`] (the caller's ephemeral SSH
+//! environments).
+//!
+//! Called by `manta get ephemeral-environments`. Supported output
+//! formats: `"json"` and the default table. Expiry is shown the same
+//! way as for [`super::reservation`]: local time next to the time left.
+
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use comfy_table::{ContentArrangement, Table};
+
+use super::reservation::{local_time, time_left};
+use crate::openapi_client::types::EphemeralEnv;
+
+/// Build the ephemeral environments table against `now`.
+fn build_table(envs: &[EphemeralEnv], now: DateTime<Utc>) -> Table {
+  let mut table = Table::new();
+  table.set_content_arrangement(ContentArrangement::Dynamic);
+  table.set_header(vec![
+    "ID", "Image ID", "Status", "Hostname", "Created", "Expires", "Left",
+  ]);
+  for env in envs {
+    let (expires, left) = env.expires_at.as_deref().map_or_else(
+      || ("-".to_string(), "-".to_string()),
+      |at| (local_time(at), time_left(at, now)),
+    );
+    table.add_row(vec![
+      env.id.clone(),
+      env.image_id.clone(),
+      env.status.clone().unwrap_or_default(),
+      env.hostname.clone().unwrap_or_default(),
+      env.created.as_deref().map(local_time).unwrap_or_default(),
+      expires,
+      left,
+    ]);
+  }
+  table
+}
+
+/// Print ephemeral environments in the requested format (`"json"` or
+/// table).
+///
+/// # Errors
+///
+/// Returns `Err` if JSON serialisation fails (JSON path only).
+pub fn print(envs: &[EphemeralEnv], output_opt: Option<&str>) -> Result<()> {
+  if output_opt.is_some_and(|o| o == "json") {
+    println!(
+      "{}",
+      serde_json::to_string_pretty(envs)
+        .context("Failed to serialize ephemeral environments to JSON")?
+    );
+  } else {
+    println!("{}", build_table(envs, Utc::now()));
+  }
+  Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use serde_json::json;
+
+  #[test]
+  fn table_shows_time_left_and_dashes_without_expiry() {
+    let now = DateTime::parse_from_rfc3339("2026-10-19T08:00:00Z")
+      .unwrap()
+      .with_timezone(&Utc);
+    let envs: Vec<EphemeralEnv> = serde_json::from_value(json!([
+      {
+        "id": "4f1c",
+        "image_id": "img-a",
+        "status": "waiting_on_user",
+        "hostname": "4f1c.ims.example.com",
+        "expires_at": "2026-10-19T10:15:00Z",
+      },
+      { "id": "9b2e", "image_id": "img-b" },
+    ]))
+    .unwrap();
+    let rendered = build_table(&envs, now).to_string();
+    assert!(rendered.contains("4f1c.ims.example.com"));
+    assert!(rendered.contains("2h 15m"));
+    assert!(rendered.contains("9b2e"));
+  }
+}
@@ -1,19 +1,23 @@
 //! Renderer for [`Group`] (HSM groups).
 //!
-//! Called by `manta get group`. Supported output formats:
-//! **table only** — JSON is handled by the dispatcher directly off
-//! the wire type. Member xnames are sorted and collapsed via
-//! [`nodeset::NodeSet`] so a long group prints as a compact range
-//! expression.
+//! Called by `manta get group`. Supported output formats: the table,
+//! `"json"`, an Ansible inventory ([`print_ansible_inventory`]) and
+//! Prometheus HTTP SD targets ([`print_prometheus_sd`]). Member xnames
+//! in the table are sorted and collapsed via [`super::node_list`] so a
+//! long group prints as a compact range expression (in full with
+//! `--expand`).
 
 use anyhow::{Context, Result};
 use comfy_table::{ContentArrangement, Table};
-use nodeset::NodeSet;
+use manta_shared::types::api::group::PrometheusTargetGroup;
+use manta_shared::types::dto::NodeDetails;
+use manta_shared::types::inventory_export::AnsibleInventory;
 
 use crate::openapi_client::types::Group;
 
-/// Print HSM groups as a formatted table.
-pub fn print_table(group_vec: &[Group]) {
+/// Print HSM groups as a formatted table; `expand` lists members in
+/// full instead of in hostlist notation.
+pub fn print_table(group_vec: &[Group], expand: bool) {
   let mut table = Table::new();
   table.set_content_arrangement(ContentArrangement::Dynamic);
 
@@ -32,14 +36,12 @@ pub fn print_table(group_vec: &[Group]) {
       .and_then(|m| m.ids.clone())
       .unwrap_or_default();
     group_members.sort();
-    let node_group: NodeSet =
-      group_members.join(", ").parse().unwrap_or_default();
 
     table.add_row(vec![
       group.label.clone(),
       group.description.clone().unwrap_or_default(),
       group_members.len().to_string(),
-      node_group.to_string(),
+      super::node_list(&group_members, expand),
       group.tags.clone().unwrap_or_default().join("\n"),
     ]);
   }
@@ -57,7 +59,11 @@ pub fn print_table(group_vec: &[Group]) {
 /// # Errors
 ///
 /// Returns `Err` if JSON serialisation fails (JSON path only).
-pub fn print(groups: &[Group], output_opt: Option<&str>) -> Result<()> {
+pub fn print(
+  groups: &[Group],
+  output_opt: Option<&str>,
+  expand: bool,
+) -> Result<()> {
   if output_opt.is_some_and(|o| o == "json") {
     println!(
       "{}",
@@ -65,19 +71,72 @@ pub fn print(groups: &[Group], output_opt: Option<&str>) -> Result<()> {
         .context("Failed to serialize groups to JSON")?
     );
   } else {
-    print_table(groups);
+    print_table(groups, expand);
   }
   Ok(())
 }
 
+/// Render `groups` and their members' `nodes` as an Ansible inventory
+/// in `format` (`"yaml"`, or INI otherwise).
+///
+/// # Errors
+///
+/// Returns `Err` if two group labels map to the same Ansible group
+/// name, or if YAML serialisation fails.
+pub fn render_ansible_inventory(
+  groups: &[Group],
+  nodes: &[NodeDetails],
+  format: &str,
+) -> Result<String> {
+  let labels: Vec<String> = groups.iter().map(|g| g.label.clone()).collect();
+  let inventory =
+    AnsibleInventory::build(&labels, nodes).map_err(anyhow::Error::msg)?;
+  if format == "yaml" {
+    inventory
+      .to_yaml()
+      .context("Failed to serialize Ansible inventory to YAML")
+  } else {
+    Ok(inventory.to_ini())
+  }
+}
+
+/// Print an Ansible inventory, see [`render_ansible_inventory`].
+///
+/// # Errors
+///
+/// Returns `Err` if two group labels map to the same Ansible group
+/// name, or if YAML serialisation fails.
+pub fn print_ansible_inventory(
+  groups: &[Group],
+  nodes: &[NodeDetails],
+  format: &str,
+) -> Result<()> {
+  print!("{}", render_ansible_inventory(groups, nodes, format)?);
+  Ok(())
+}
+
+/// Print Prometheus HTTP SD targets as the JSON document a `file_sd`
+/// or `http_sd` config reads.
+///
+/// # Errors
+///
+/// Returns `Err` if JSON serialisation fails.
+pub fn print_prometheus_sd(targets: &[PrometheusTargetGroup]) -> Result<()> {
+  println!(
+    "{}",
+    serde_json::to_string_pretty(targets)
+      .context("Failed to serialize Prometheus targets to JSON")?
+  );
+  Ok(())
+}
+
 #[cfg(test)]
 mod tests {
   //! Smoke tests for the HSM group renderer. Two interesting paths:
-  //! (a) member list is sorted before joining into a `NodeSet` (a
-  //! sort regression would silently re-order the output);
-  //! (b) `NodeSet::parse` falls back to `unwrap_or_default` if the
-  //! join string is unparseable, so an exotic member list shouldn't
-  //! crash the renderer.
+  //! (a) member list is sorted before it is compressed (a sort
+  //! regression would silently re-order the expanded output);
+  //! (b) names the compressor cannot fold are passed through, so an
+  //! exotic member list shouldn't crash the renderer.
 
   use super::*;
   use serde_json::json;
@@ -88,7 +147,7 @@ mod tests {
 
   #[test]
   fn print_empty_list_does_not_panic() {
-    print_table(&[]);
+    print_table(&[], false);
   }
 
   #[test]
@@ -97,12 +156,12 @@ mod tests {
       "label": "empty",
       "description": "no members",
     }));
-    print_table(&[g]);
+    print_table(&[g], false);
   }
 
   #[test]
-  fn print_group_with_xname_members_renders_via_nodeset() {
-    // NodeSet recognizes xname syntax and collapses sequences.
+  fn print_group_with_xname_members_renders_compressed() {
+    // The compressor folds xname sequences into bracket ranges.
     let g = from_json(json!({
       "label": "compute",
       "description": "compute nodes",
@@ -110,18 +169,18 @@ mod tests {
         "ids": ["x3000c0s1b0n0", "x3000c0s1b0n1", "x3000c0s1b0n2"]
       },
     }));
-    print_table(&[g]);
+    print_table(&[g], false);
   }
 
   #[test]
   fn print_group_with_unparseable_members_falls_back_to_default() {
-    // `NodeSet::parse` fails on arbitrary strings → the
-    // `unwrap_or_default()` ensures we still render something.
+    // Names without a number cannot be folded and are printed as
+    // they are.
     let g = from_json(json!({
       "label": "oddballs",
       "members": { "ids": ["foo", "bar", "baz"] },
     }));
-    print_table(&[g]);
+    print_table(&[g], false);
   }
 
   #[test]
@@ -132,6 +191,30 @@ mod tests {
       "tags": ["a100", "epyc", "ib-2x"],
       "members": { "ids": ["x3000c0s1b0n0"] },
     }));
-    print_table(&[g]);
+    print_table(&[g], false);
+  }
+
+  #[test]
+  fn ansible_inventory_covers_only_listed_groups() {
+    let groups = [from_json(json!({ "label": "compute" }))];
+    let nodes: Vec<NodeDetails> = serde_json::from_value(json!([{
+      "xname": "x3000c0s1b0n0",
+      "nid": "nid000001",
+      "hsm": "compute, tenant-a",
+      "power_status": "ON",
+      "desired_configuration": "cfg",
+      "configuration_status": "configured",
+      "enabled": "true",
+      "error_count": "0",
+      "boot_image_id": "img",
+      "boot_configuration": "cfg",
+      "kernel_params": "",
+    }]))
+    .unwrap();
+    let ini = render_ansible_inventory(&groups, &nodes, "ini").unwrap();
+    assert!(ini.starts_with("[compute]\nx3000c0s1b0n0 "));
+    assert!(!ini.contains("tenant"));
+    let yaml = render_ansible_inventory(&groups, &nodes, "yaml").unwrap();
+    assert!(yaml.contains("boot_image: img"));
   }
 }
@@ -12,12 +12,22 @@
 //!   for downstream diffing.
 //! - [`print_nodes_list`] — `"json"` or `"table"` (the per-node
 //!   details matrix).
+//! - [`print_plan_explanation`] — the `--explain` tables of
+//!   `manta apply hardware group`: scarcity weights, then one row per
+//!   added / kept / released node with per-component contributions.
+//! - [`print_slurm_conf`] — `manta get hardware group -o slurm`:
+//!   `slurm.conf` `NodeName=` lines for the group, nodes with identical
+//!   hardware folded into one hostlist range, followed by the matching
+//!   `gres.conf` lines.
 
-use std::collections::{HashMap, HashSet};
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
 
 use anyhow::{Context, Error, bail};
 use comfy_table::{Cell, Color, Table};
+use manta_shared::common::hostlist;
+use manta_shared::types::api::hw_cluster::HwPlanExplanation;
 use manta_shared::types::dto::NodeSummary;
+use manta_shared::types::hardware_diff::node_hardware;
 use serde_json::Value;
 
 // ---------------------------------------------------------------------------
@@ -237,6 +247,314 @@ pub fn print_nodes_list(json: &Value, output: &str) -> Result<(), Error> {
   Ok(())
 }
 
+// ---------------------------------------------------------------------------
+// Slurm node configuration
+// ---------------------------------------------------------------------------
+
+/// Words that name a vendor or product line rather than the part
+/// itself; dropped before picking a Slurm type or feature name.
+const SLURM_VENDOR_WORDS: &[&str] = &[
+  "amd",
+  "nvidia",
+  "intel",
+  "tesla",
+  "instinct",
+  "radeon",
+  "processor",
+];
+
+/// First significant word of a component model, lowercased:
+/// `NVIDIA A100-SXM4-80GB` → `a100`, `AMD Instinct MI250X` → `mi250x`,
+/// `Intel(R) Xeon(R) Platinum 8380` → `xeon`. Used as the GRES type
+/// for accelerators and the feature tag for processors.
+fn slurm_model_name(model: &str) -> Option<String> {
+  let model = model
+    .to_lowercase()
+    .replace("(r)", " ")
+    .replace("(tm)", " ");
+  model
+    .split_whitespace()
+    .find(|word| !SLURM_VENDOR_WORDS.contains(word))
+    .and_then(|word| word.split('-').next())
+    .filter(|word| !word.is_empty())
+    .map(String::from)
+}
+
+/// Core count advertised in a processor model name
+/// (`AMD EPYC 7742 64-Core Processor` → 64). Intel and most ARM names
+/// don't carry one.
+fn cores_in_model(model: &str) -> Option<usize> {
+  model
+    .split_whitespace()
+    .find_map(|word| word.to_lowercase().strip_suffix("-core")?.parse().ok())
+}
+
+/// Collapse node names into a Slurm hostlist (`nid[001000-001003]`).
+fn slurm_hostlist(names: &[String]) -> String {
+  hostlist::compress(names)
+}
+
+/// Settings for what the inventory can't tell: hardware threads and
+/// the memory the OS keeps for itself. Both err low, since Slurm
+/// drains a node whose definition promises more than `slurmd` finds.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SlurmOptions {
+  /// Hardware threads per core (`ThreadsPerCore=`).
+  pub threads_per_core: usize,
+  /// Share of the DIMM total, in percent, left out of `RealMemory=`.
+  pub memory_reserve_percent: usize,
+}
+
+impl SlurmOptions {
+  /// `RealMemory=` for `dimm_total_mib` of installed memory: the
+  /// reserve taken off, then rounded down to a whole GiB.
+  fn real_memory_mib(&self, dimm_total_mib: usize) -> usize {
+    let usable = dimm_total_mib * (100 - self.memory_reserve_percent) / 100;
+    usable / 1024 * 1024
+  }
+}
+
+/// Hardware of one node as Slurm sees it. Nodes with equal specs share
+/// a `NodeName=` line.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+struct SlurmNodeSpec {
+  sockets: usize,
+  cores_per_socket: Option<usize>,
+  threads_per_core: usize,
+  real_memory_mib: usize,
+  /// GPU type → count.
+  gres: BTreeMap<String, usize>,
+  features: BTreeSet<String>,
+}
+
+impl SlurmNodeSpec {
+  /// `None` for a node whose inventory came back empty (the server
+  /// could not fetch it): guessing would write a wrong definition.
+  fn from_summary(ns: &NodeSummary, options: &SlurmOptions) -> Option<Self> {
+    if ns.processors.is_empty() && ns.memory.is_empty() {
+      return None;
+    }
+    let hw = node_hardware(ns)?;
+    let mut gres: BTreeMap<String, usize> = BTreeMap::new();
+    for (model, count) in &hw.accelerators {
+      if let Some(name) = slurm_model_name(model) {
+        *gres.entry(name).or_default() += count;
+      }
+    }
+    let mut features: BTreeSet<String> = hw
+      .processors
+      .keys()
+      .filter_map(|m| slurm_model_name(m))
+      .collect();
+    features.extend(gres.keys().cloned());
+    if !gres.is_empty() {
+      features.insert("gpu".to_string());
+    }
+    Some(Self {
+      sockets: hw.processors.values().sum(),
+      cores_per_socket: hw.processors.keys().find_map(|m| cores_in_model(m)),
+      threads_per_core: options.threads_per_core,
+      real_memory_mib: options.real_memory_mib(hw.memory_mib),
+      gres,
+      features,
+    })
+  }
+
+  fn node_line(&self, hostlist: &str) -> String {
+    let mut line = format!("NodeName={hostlist}");
+    if let Some(cores) = self.cores_per_socket {
+      line.push_str(&format!(
+        " CPUs={}",
+        self.sockets * cores * self.threads_per_core
+      ));
+    }
+    line.push_str(&format!(" Sockets={}", self.sockets));
+    if let Some(cores) = self.cores_per_socket {
+      line.push_str(&format!(
+        " CoresPerSocket={cores} ThreadsPerCore={}",
+        self.threads_per_core
+      ));
+    }
+    line.push_str(&format!(" RealMemory={}", self.real_memory_mib));
+    if !self.gres.is_empty() {
+      let gres: Vec<String> = self
+        .gres
+        .iter()
+        .map(|(name, count)| format!("gpu:{name}:{count}"))
+        .collect();
+      line.push_str(&format!(" Gres={}", gres.join(",")));
+    }
+    if !self.features.is_empty() {
+      let features: Vec<&str> =
+        self.features.iter().map(String::as_str).collect();
+      line.push_str(&format!(" Feature={}", features.join(",")));
+    }
+    line
+  }
+}
+
+/// Render the `slurm.conf` node definitions and `gres.conf` lines for
+/// `node_summaries`. Nodes are named by `names` (xname → NID) where
+/// known, by xname otherwise.
+fn build_slurm_conf(
+  group_name: &str,
+  node_summaries: &[NodeSummary],
+  names: &HashMap<String, String>,
+  options: &SlurmOptions,
+) -> String {
+  let mut by_spec: BTreeMap<SlurmNodeSpec, Vec<String>> = BTreeMap::new();
+  let mut skipped: Vec<String> = Vec::new();
+  for ns in node_summaries {
+    let name = names.get(&ns.xname).unwrap_or(&ns.xname).clone();
+    match SlurmNodeSpec::from_summary(ns, options) {
+      Some(spec) => by_spec.entry(spec).or_default().push(name),
+      None => skipped.push(ns.xname.clone()),
+    }
+  }
+  let mut groups: Vec<(String, SlurmNodeSpec)> = by_spec
+    .into_iter()
+    .map(|(spec, names)| (slurm_hostlist(&names), spec))
+    .collect();
+  groups.sort();
+
+  let mut out = format!(
+    "# slurm.conf node definitions for group '{group_name}', generated by manta\n"
+  );
+  if !skipped.is_empty() {
+    out.push_str(&format!(
+      "# skipped, no hardware inventory: {}\n",
+      slurm_hostlist(&skipped)
+    ));
+  }
+  for (hostlist, spec) in &groups {
+    out.push_str(&spec.node_line(hostlist));
+    out.push('\n');
+  }
+  let gres_lines: Vec<String> = groups
+    .iter()
+    .flat_map(|(hostlist, spec)| {
+      spec.gres.iter().map(move |(name, count)| {
+        format!("NodeName={hostlist} Name=gpu Type={name} Count={count}")
+      })
+    })
+    .collect();
+  if !gres_lines.is_empty() {
+    out.push_str(
+      "\n# gres.conf (add File= or AutoDetect= for device binding)\n",
+    );
+    for line in gres_lines {
+      out.push_str(&line);
+      out.push('\n');
+    }
+  }
+  out
+}
+
+/// Print Slurm node configuration for the hardware cluster in `json`
+/// (the `GET /groups/hardware` response). `names` maps xnames to the
+/// NIDs Slurm knows them by; `options` fills in what the inventory
+/// doesn't report.
+///
+/// # Errors
+///
+/// Returns an error when `json["node_summaries"]` is missing or
+/// shape-incompatible with `Vec<NodeSummary>`.
+pub fn print_slurm_conf(
+  json: &Value,
+  names: &HashMap<String, String>,
+  options: &SlurmOptions,
+) -> Result<(), Error> {
+  let node_summaries: Vec<NodeSummary> =
+    serde_json::from_value(json["node_summaries"].clone())
+      .context("Failed to deserialize node summaries")?;
+  let group_name = json["hsm_group_name"].as_str().unwrap_or("");
+  print!(
+    "{}",
+    build_slurm_conf(group_name, &node_summaries, names, options)
+  );
+  Ok(())
+}
+
+/// Build the two `--explain` tables: scarcity weight per component,
+/// and per node its decision, score, each component as
+/// `count (contribution)`, and the pattern terms it satisfies / fails.
+fn build_plan_explanation_tables(
+  explanation: &HwPlanExplanation,
+) -> (Table, Table) {
+  let mut weights = Table::new();
+  weights.set_header(["Component", "Scarcity weight"]);
+  for (component, weight) in &explanation.scarcity_weights {
+    weights.add_row([component.clone(), format!("{weight:.2}")]);
+  }
+
+  let mut components: Vec<&String> = explanation
+    .nodes
+    .iter()
+    .flat_map(|node| node.components.keys())
+    .collect();
+  components.sort();
+  components.dedup();
+
+  let mut nodes = Table::new();
+  nodes.set_header(
+    ["Node", "Decision", "Score"]
+      .into_iter()
+      .map(str::to_string)
+      .chain(components.iter().map(|c| (*c).clone()))
+      .chain(["Satisfies".to_string(), "Fails".to_string()]),
+  );
+  for node in &explanation.nodes {
+    let decision = serde_json::to_value(node.decision)
+      .ok()
+      .and_then(|v| v.as_str().map(str::to_string))
+      .unwrap_or_default();
+    let mut row = vec![
+      Cell::new(&node.xname),
+      Cell::new(decision),
+      Cell::new(format!("{:.2}", node.score)),
+    ];
+    for component in &components {
+      row.push(match node.components.get(*component) {
+        Some(qty) => {
+          let contribution =
+            node.contributions.get(*component).copied().unwrap_or(0.0);
+          let color = if contribution > 0.0 {
+            Color::Green
+          } else {
+            Color::Yellow
+          };
+          Cell::new(format!("{qty} ({contribution:+.2})")).fg(color)
+        }
+        None => Cell::new("-"),
+      });
+    }
+    row.push(Cell::new(node.satisfies.join(", ")));
+    row.push(Cell::new(node.fails.join(", ")).fg(Color::Red));
+    nodes.add_row(row);
+  }
+  (weights, nodes)
+}
+
+/// Print the `explanation` object of a hardware pin/unpin result as
+/// tables. Does nothing when the result carries no explanation.
+///
+/// # Errors
+///
+/// Returns an error when `json["explanation"]` is present but does not
+/// deserialize into a [`HwPlanExplanation`].
+pub fn print_plan_explanation(json: &Value) -> Result<(), Error> {
+  let Some(explanation) = json.get("explanation") else {
+    return Ok(());
+  };
+  let explanation: HwPlanExplanation =
+    serde_json::from_value(explanation.clone())
+      .context("Failed to deserialize hardware plan explanation")?;
+  let (weights, nodes) = build_plan_explanation_tables(&explanation);
+  println!("{weights}");
+  println!("{nodes}");
+  Ok(())
+}
+
 #[cfg(test)]
 mod tests {
   //! Smoke tests for the public renderer entry points. Each format
@@ -309,4 +627,126 @@ mod tests {
     let payload = json!({ "node_summaries": [] });
     assert!(print_nodes_list(&payload, "table").is_ok());
   }
+
+  #[test]
+  fn plan_explanation_tables_show_contributions_and_terms() {
+    let payload = json!({
+      "explanation": {
+        "scarcity_weights": { "a100": 3.0, "epyc": 1.5 },
+        "nodes": [{
+          "xname": "x1000c0s1b0n0",
+          "decision": "added",
+          "components": { "a100": 4, "epyc": 2 },
+          "contributions": { "a100": 12.0, "epyc": -3.0 },
+          "score": 9.0,
+          "satisfies": ["a100>=4"]
+        }]
+      }
+    });
+    let explanation: HwPlanExplanation =
+      serde_json::from_value(payload["explanation"].clone()).unwrap();
+    let (weights, nodes) = build_plan_explanation_tables(&explanation);
+    let weights = weights.to_string();
+    let nodes = nodes.to_string();
+    assert!(weights.contains("3.00"), "{weights}");
+    assert!(nodes.contains("added"), "{nodes}");
+    assert!(nodes.contains("4 (+12.00)"), "{nodes}");
+    assert!(nodes.contains("2 (-3.00)"), "{nodes}");
+    assert!(nodes.contains("a100>=4"), "{nodes}");
+    assert!(print_plan_explanation(&payload).is_ok());
+    assert!(print_plan_explanation(&json!({})).is_ok());
+  }
+
+  fn gpu_node(xname: &str) -> serde_json::Value {
+    json!({
+      "xname": xname,
+      "type": "Node",
+      "processors": [
+        { "xname": format!("{xname}p0"), "type": "Processor", "info": "AMD EPYC 7763 64-Core Processor" },
+        { "xname": format!("{xname}p1"), "type": "Processor", "info": "AMD EPYC 7763 64-Core Processor" }
+      ],
+      "memory": [
+        { "xname": format!("{xname}d0"), "type": "Memory", "info": "262144 MiB" },
+        { "xname": format!("{xname}d1"), "type": "Memory", "info": "262144 MiB" }
+      ],
+      "node_accels": (0..4).map(|i| json!({
+        "xname": format!("{xname}a{i}"), "type": "NodeAccel", "info": "NVIDIA A100-SXM4-80GB"
+      })).collect::<Vec<_>>(),
+      "node_hsn_nics": []
+    })
+  }
+
+  #[test]
+  fn slurm_model_names_and_cores() {
+    assert_eq!(
+      slurm_model_name("NVIDIA A100-SXM4-80GB").as_deref(),
+      Some("a100")
+    );
+    assert_eq!(
+      slurm_model_name("AMD Instinct MI250X").as_deref(),
+      Some("mi250x")
+    );
+    assert_eq!(
+      slurm_model_name("Intel(R) Xeon(R) Platinum 8380 CPU @ 2.30GHz")
+        .as_deref(),
+      Some("xeon")
+    );
+    assert_eq!(cores_in_model("AMD EPYC 7763 64-Core Processor"), Some(64));
+    assert_eq!(cores_in_model("Intel(R) Xeon(R) Platinum 8380"), None);
+  }
+
+  #[test]
+  fn slurm_real_memory_leaves_the_reserve_and_rounds_to_gib() {
+    let options = |memory_reserve_percent| SlurmOptions {
+      threads_per_core: 1,
+      memory_reserve_percent,
+    };
+    // 512 GiB of DIMMs, 3 % reserved: 508559 MiB, rounded to 496 GiB.
+    assert_eq!(options(3).real_memory_mib(524_288), 507_904);
+    assert_eq!(options(0).real_memory_mib(524_288), 524_288);
+    assert_eq!(options(0).real_memory_mib(1_500), 1_024);
+  }
+
+  #[test]
+  fn slurm_conf_folds_identical_nodes_into_a_hostlist() {
+    let summaries: Vec<NodeSummary> = serde_json::from_value(json!([
+      gpu_node("x1000c0s0b0n0"),
+      gpu_node("x1000c0s0b0n1"),
+      gpu_node("x1000c0s1b0n0"),
+      { "xname": "x1000c0s2b0n0", "type": "Node", "processors": [],
+        "memory": [], "node_accels": [], "node_hsn_nics": [] },
+      { "xname": "x1000c0s3b0n0", "type": "Node",
+        "processors": [{ "xname": "p", "type": "Processor",
+                         "info": "Intel(R) Xeon(R) Platinum 8380" }],
+        "memory": [{ "xname": "d", "type": "Memory", "info": "131072 MiB" }],
+        "node_accels": [], "node_hsn_nics": [] }
+    ]))
+    .unwrap();
+    let names = HashMap::from([
+      ("x1000c0s0b0n0".to_string(), "nid001000".to_string()),
+      ("x1000c0s0b0n1".to_string(), "nid001001".to_string()),
+      ("x1000c0s1b0n0".to_string(), "nid001002".to_string()),
+    ]);
+
+    let options = SlurmOptions {
+      threads_per_core: 2,
+      memory_reserve_percent: 3,
+    };
+    let conf = build_slurm_conf("compute", &summaries, &names, &options);
+    let lines: Vec<&str> = conf.lines().collect();
+    assert_eq!(
+      lines,
+      vec![
+        "# slurm.conf node definitions for group 'compute', generated by manta",
+        "# skipped, no hardware inventory: x1000c0s2b0n0",
+        "NodeName=nid[001000-001002] CPUs=256 Sockets=2 CoresPerSocket=64 \
+         ThreadsPerCore=2 RealMemory=507904 Gres=gpu:a100:4 \
+         Feature=a100,epyc,gpu",
+        "NodeName=x1000c0s3b0n0 Sockets=1 RealMemory=126976 Feature=xeon",
+        "",
+        "# gres.conf (add File= or AutoDetect= for device binding)",
+        "NodeName=nid[001000-001002] Name=gpu Type=a100 Count=4",
+      ]
+    );
+  }
 }
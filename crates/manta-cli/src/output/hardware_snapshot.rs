@@ -0,0 +1,196 @@
+//! Renderers for hardware snapshots and the diff between two of them.
+//!
+//! Called by `manta snapshot hardware`, `manta get hardware snapshots`
+//! ([`print_list`]) and `manta diff hardware` ([`print_diff`]).
+//! Supported output formats: `"json"` and the default table.
+
+use anyhow::{Context, Result};
+use chrono::{DateTime, Local};
+use comfy_table::{ContentArrangement, Table};
+use manta_shared::common::DATETIME_FORMAT;
+use manta_shared::types::hardware_diff::{HardwareDiff, NodeChangeKind};
+
+use crate::openapi_client::types::HardwareSnapshotInfo;
+
+/// Local wall-clock rendering of an RFC 3339 instant; the raw string
+/// when it does not parse.
+fn local_time(raw: &str) -> String {
+  DateTime::parse_from_rfc3339(raw).map_or_else(
+    |_| raw.to_string(),
+    |at| at.with_timezone(&Local).format(DATETIME_FORMAT).to_string(),
+  )
+}
+
+fn build_list_table(snapshots: &[HardwareSnapshotInfo]) -> Table {
+  let mut table = Table::new();
+  table.set_content_arrangement(ContentArrangement::Dynamic);
+  table.set_header(vec!["Group", "Taken", "Taken by", "# nodes", "ID"]);
+  for s in snapshots {
+    table.add_row(vec![
+      s.group.clone(),
+      local_time(&s.taken_at),
+      s.taken_by.clone(),
+      s.node_count.to_string(),
+      s.id.clone(),
+    ]);
+  }
+  table
+}
+
+/// Print stored snapshots in the requested format (`"json"` or table).
+///
+/// # Errors
+///
+/// Returns `Err` if JSON serialisation fails (JSON path only).
+pub fn print_list(
+  snapshots: &[HardwareSnapshotInfo],
+  output_opt: Option<&str>,
+) -> Result<()> {
+  if output_opt.is_some_and(|o| o == "json") {
+    println!(
+      "{}",
+      serde_json::to_string_pretty(snapshots)
+        .context("Failed to serialize hardware snapshots to JSON")?
+    );
+  } else {
+    println!("{}", build_list_table(snapshots));
+  }
+  Ok(())
+}
+
+/// One row per changed component; the node and kind of change are only
+/// shown on a node's first row.
+fn build_diff_table(diff: &HardwareDiff) -> Table {
+  let mut table = Table::new();
+  table.set_content_arrangement(ContentArrangement::Dynamic);
+  table.set_header(vec!["Node", "Change", "Component", "Before", "After"]);
+  for node in &diff.changes {
+    for (i, c) in node.components.iter().enumerate() {
+      let (xname, change) = if i == 0 {
+        let change = match node.change {
+          NodeChangeKind::Added => "added",
+          NodeChangeKind::Removed => "removed",
+          NodeChangeKind::Changed => "changed",
+        };
+        (node.xname.clone(), change.to_string())
+      } else {
+        (String::new(), String::new())
+      };
+      table.add_row(vec![
+        xname,
+        change,
+        c.component.clone(),
+        c.before.to_string(),
+        c.after.to_string(),
+      ]);
+    }
+  }
+  table
+}
+
+/// Print the diff between the snapshots labelled `from` and `to` in
+/// the requested format (`"json"` or table). Nodes left out because
+/// their inventory was unreadable are listed after the table, in
+/// hostlist notation unless `expand`.
+///
+/// # Errors
+///
+/// Returns `Err` if JSON serialisation fails (JSON path only).
+pub fn print_diff(
+  from: &str,
+  to: &str,
+  diff: &HardwareDiff,
+  output_opt: Option<&str>,
+  expand: bool,
+) -> Result<()> {
+  if output_opt.is_some_and(|o| o == "json") {
+    println!(
+      "{}",
+      serde_json::to_string_pretty(&serde_json::json!({
+        "from": from,
+        "to": to,
+        "changes": diff.changes,
+        "unreadable": diff.unreadable,
+      }))
+      .context("Failed to serialize hardware diff to JSON")?
+    );
+    return Ok(());
+  }
+  if diff.is_empty() {
+    println!("No hardware changes between {from} and {to}");
+  } else {
+    println!("{}", build_diff_table(diff));
+  }
+  if !diff.unreadable.is_empty() {
+    println!(
+      "Not compared (inventory unreadable): {}",
+      super::node_list(&diff.unreadable, expand)
+    );
+  }
+  Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use manta_shared::types::hardware_diff::{
+    ComponentChange, NodeHardwareChange,
+  };
+  use serde_json::json;
+
+  #[test]
+  fn diff_table_shows_node_once_per_change() {
+    let diff = HardwareDiff {
+      changes: vec![NodeHardwareChange {
+        xname: "x1000c0s0b0n0".to_string(),
+        change: NodeChangeKind::Changed,
+        components: vec![
+          ComponentChange {
+            component: "accelerator: NVIDIA A100".to_string(),
+            before: 4,
+            after: 3,
+          },
+          ComponentChange {
+            component: "dimms".to_string(),
+            before: 16,
+            after: 15,
+          },
+        ],
+      }],
+      unreadable: vec![],
+    };
+    let rendered = build_diff_table(&diff).to_string();
+    assert_eq!(rendered.matches("x1000c0s0b0n0").count(), 1);
+    assert_eq!(rendered.matches("changed").count(), 1);
+    assert!(rendered.contains("accelerator: NVIDIA A100"));
+    assert!(rendered.contains("dimms"));
+  }
+
+  #[test]
+  fn list_table_shows_group_and_node_count() {
+    let s: HardwareSnapshotInfo = serde_json::from_value(json!({
+      "id": "3f2a",
+      "site": "alps",
+      "group": "compute",
+      "taken_at": "2026-10-19T08:00:00Z",
+      "taken_by": "alice",
+      "node_count": 512,
+    }))
+    .unwrap();
+    let rendered = build_list_table(&[s]).to_string();
+    assert!(rendered.contains("compute"));
+    assert!(rendered.contains("512"));
+    assert!(rendered.contains("3f2a"));
+  }
+
+  #[test]
+  fn print_empty_diff_and_list_succeed() {
+    let diff = HardwareDiff {
+      changes: vec![],
+      unreadable: vec!["x1000c0s0b0n0".to_string()],
+    };
+    assert!(print_diff("a", "live", &diff, None, false).is_ok());
+    assert!(print_diff("a", "live", &diff, Some("json"), true).is_ok());
+    assert!(print_list(&[], None).is_ok());
+  }
+}
@@ -6,28 +6,91 @@
 //! `"json"`, sometimes more) sourced from the
 //! `-o/--output` clap flag declared in `crate::build::output_flag`.
 //!
+//! Node lists in tables go through [`node_list`] (and hosts
+//! expressions echoed in confirmation prompts through
+//! [`hosts_expression`]), which compress them into hostlist notation
+//! unless the global `--expand` is set.
+//!
 //! - [`action_result`] — mutating verbs (status line / JSON envelope)
 //! - [`boot_parameters`] — BSS boot parameters (JSON only)
 //! - [`config_summary`] — `manta config show` (table or JSON)
 //! - [`configuration`] — CFS configurations (table)
+//! - [`ephemeral_env`] — ephemeral SSH environments (table or JSON)
 //! - [`group`] — HSM groups (table)
 //! - [`hardware`] — hardware inventory (multiple table formats + JSON)
+//! - [`hardware_snapshot`] — hardware snapshots and diffs (table or JSON)
 //! - [`image`] — IMS images (table)
 //! - [`kernel_parameters`] — BSS kernel-parameter grouping (table)
 //! - [`node`] — HSM nodes (table or summary table)
 //! - [`redfish_endpoints`] — Redfish endpoints (table or JSON)
+//! - [`reservation`] — hardware reservations (table or JSON)
 //! - [`session`] — CFS sessions (table or JSON)
+//! - [`session_summary`] — Ansible summaries of session logs (table or JSON)
 //! - [`template`] — BOS session templates (table or JSON)
 
 pub mod action_result;
 pub mod boot_parameters;
 pub mod config_summary;
 pub mod configuration;
+pub mod ephemeral_env;
 pub mod group;
 pub mod hardware;
+pub mod hardware_snapshot;
 pub mod image;
 pub mod kernel_parameters;
 pub mod node;
 pub mod redfish_endpoints;
+pub mod reservation;
 pub mod session;
+pub mod session_summary;
 pub mod template;
+
+use manta_shared::common::hostlist;
+
+/// Node names for a table cell or confirmation prompt: compressed into
+/// hostlist notation (`nid[000001-000128]`), or comma-separated in full
+/// when `expand` (the global `--expand` flag) is set.
+pub fn node_list<S: AsRef<str>>(names: &[S], expand: bool) -> String {
+  if expand {
+    names
+      .iter()
+      .map(AsRef::as_ref)
+      .collect::<Vec<&str>>()
+      .join(",")
+  } else {
+    hostlist::compress(names)
+  }
+}
+
+/// A user-supplied hosts expression echoed in a confirmation prompt:
+/// its plain names folded as in [`node_list`], or unchanged when
+/// `expand` is set.
+pub fn hosts_expression(expression: &str, expand: bool) -> String {
+  if expand {
+    expression.to_string()
+  } else {
+    hostlist::compress_expression(expression)
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn node_list_compresses_unless_expanded() {
+    let names = ["nid000002", "nid000001", "nid000003"];
+    assert_eq!(node_list(&names, false), "nid[000001-000003]");
+    assert_eq!(node_list(&names, true), "nid000002,nid000001,nid000003");
+  }
+
+  #[test]
+  fn hosts_expression_folds_plain_names_only() {
+    let expr = "x1000c0s0b0n0,x1000c0s0b0n1,nid[001-004]";
+    assert_eq!(
+      hosts_expression(expr, false),
+      "x1000c0s0b0n[0-1],nid[001-004]"
+    );
+    assert_eq!(hosts_expression(expr, true), expr);
+  }
+}
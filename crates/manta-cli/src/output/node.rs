@@ -189,13 +189,20 @@ pub fn print_summary(node_details_list: Vec<NodeDetails>) {
   );
 }
 
+/// `names` comma-joined, never in hostlist notation: scripts feed the
+/// one-line output to tools such as `pdsh -w` or split it on commas.
+fn one_line(names: &[String]) -> String {
+  names.join(",")
+}
+
 /// Render a node-details list according to the flags passed by the CLI.
 ///
 /// Called by both `dispatch/get/nodes::exec` and
 /// `dispatch/get/group_nodes::exec`; the only behavioural difference
 /// between the two callers is the `xnames_only` flag (`false` for
 /// `nodes`, true when `--xnames-only-one-line` is passed for
-/// `group-nodes`).
+/// `group-nodes`). The one-line NID / xname lists are always printed
+/// in full (see [`one_line`]).
 ///
 /// # Errors
 ///
@@ -226,7 +233,7 @@ pub fn render_node_details(
           .context("Failed to serialize node NID list")?
       );
     } else {
-      println!("{}", node_nid_list.join(","));
+      println!("{}", one_line(&node_nid_list));
     }
   } else if xnames_only {
     let node_xname_list: Vec<String> =
@@ -239,7 +246,7 @@ pub fn render_node_details(
           .context("Failed to serialize node xname list")?
       );
     } else {
-      println!("{}", node_xname_list.join(","));
+      println!("{}", one_line(&node_xname_list));
     }
   } else {
     match output_opt {
@@ -410,4 +417,13 @@ mod tests {
     n3.power_status = "OFF".to_string();
     print_summary(vec![n1, n2, n3]);
   }
+
+  #[test]
+  fn one_line_lists_are_never_compressed() {
+    let names: Vec<String> = ["nid000001", "nid000002", "nid000003"]
+      .iter()
+      .map(|n| (*n).to_string())
+      .collect();
+    assert_eq!(one_line(&names), "nid000001,nid000002,nid000003");
+  }
 }
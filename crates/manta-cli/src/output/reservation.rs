@@ -0,0 +1,154 @@
+//! Renderer for [`HwReservation`] (time-limited hardware pins).
+//!
+//! Called by `manta get reservations`. Supported output formats:
+//! `"json"` and the default table. Reserved xnames are collapsed via
+//! [`super::node_list`] (listed in full with `--expand`); expiry is shown in local time next to the
+//! time left, so an operator can see at a glance what is about to be
+//! released.
+
+use anyhow::{Context, Result};
+use chrono::{DateTime, Local, Utc};
+use comfy_table::{ContentArrangement, Table};
+use manta_shared::common::DATETIME_FORMAT;
+
+use crate::openapi_client::types::HwReservation;
+
+/// Time left until `expires_at`, coarsest two units (`2d 4h`, `35m`).
+/// `expired` once the instant has passed (the reaper has not run yet);
+/// the raw string when it does not parse.
+pub(super) fn time_left(expires_at: &str, now: DateTime<Utc>) -> String {
+  let Ok(at) = DateTime::parse_from_rfc3339(expires_at) else {
+    return expires_at.to_string();
+  };
+  let left = at.with_timezone(&Utc) - now;
+  if left.num_seconds() <= 0 {
+    return "expired".to_string();
+  }
+  let (days, hours, minutes) = (
+    left.num_days(),
+    left.num_hours() % 24,
+    (left.num_minutes() % 60).max(1),
+  );
+  if days > 0 {
+    format!("{days}d {hours}h")
+  } else if hours > 0 {
+    format!("{hours}h {minutes}m")
+  } else {
+    format!("{minutes}m")
+  }
+}
+
+/// Local wall-clock rendering of an RFC 3339 instant; the raw string
+/// when it does not parse.
+pub(super) fn local_time(raw: &str) -> String {
+  DateTime::parse_from_rfc3339(raw).map_or_else(
+    |_| raw.to_string(),
+    |at| at.with_timezone(&Local).format(DATETIME_FORMAT).to_string(),
+  )
+}
+
+/// Build the reservations table against `now`.
+fn build_table(
+  reservations: &[HwReservation],
+  now: DateTime<Utc>,
+  expand: bool,
+) -> Table {
+  let mut table = Table::new();
+  table.set_content_arrangement(ContentArrangement::Dynamic);
+  table.set_header(vec![
+    "Target Group",
+    "Parent Group",
+    "Owner",
+    "Pattern",
+    "# nodes",
+    "Nodes",
+    "Expires",
+    "Left",
+    "ID",
+  ]);
+  for r in reservations {
+    let mut nodes = r.nodes.clone();
+    nodes.sort();
+    table.add_row(vec![
+      r.target_cluster.clone(),
+      r.parent_cluster.clone(),
+      r.owner.clone(),
+      r.pattern.clone(),
+      r.nodes.len().to_string(),
+      super::node_list(&nodes, expand),
+      local_time(&r.expires_at),
+      time_left(&r.expires_at, now),
+      r.id.clone(),
+    ]);
+  }
+  table
+}
+
+/// Print reservations in the requested format (`"json"` or table).
+///
+/// # Errors
+///
+/// Returns `Err` if JSON serialisation fails (JSON path only).
+pub fn print(
+  reservations: &[HwReservation],
+  output_opt: Option<&str>,
+  expand: bool,
+) -> Result<()> {
+  if output_opt.is_some_and(|o| o == "json") {
+    println!(
+      "{}",
+      serde_json::to_string_pretty(reservations)
+        .context("Failed to serialize reservations to JSON")?
+    );
+  } else {
+    println!("{}", build_table(reservations, Utc::now(), expand));
+  }
+  Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use serde_json::json;
+
+  fn now() -> DateTime<Utc> {
+    DateTime::parse_from_rfc3339("2026-10-19T08:00:00Z")
+      .unwrap()
+      .with_timezone(&Utc)
+  }
+
+  #[test]
+  fn time_left_uses_the_two_coarsest_units() {
+    assert_eq!(time_left("2026-10-22T12:30:00Z", now()), "3d 4h");
+    assert_eq!(time_left("2026-10-19T10:15:00Z", now()), "2h 15m");
+    assert_eq!(time_left("2026-10-19T08:00:20Z", now()), "1m");
+    assert_eq!(time_left("2026-10-19T07:00:00Z", now()), "expired");
+    assert_eq!(time_left("soon", now()), "soon");
+  }
+
+  #[test]
+  fn table_collapses_nodes_and_lists_owner() {
+    let r: HwReservation = serde_json::from_value(json!({
+      "id": "3f2a",
+      "site": "alps",
+      "target_cluster": "tenant-a",
+      "parent_cluster": "nodes_free",
+      "pattern": "a100>=8",
+      "nodes": ["x1000c0s0b0n1", "x1000c0s0b0n0"],
+      "owner": "alice",
+      "created_at": "2026-10-19T07:00:00Z",
+      "expires_at": "2026-10-22T08:00:00Z",
+    }))
+    .unwrap();
+    let rendered = build_table(&[r], now(), false).to_string();
+    assert!(rendered.contains("alice"));
+    assert!(rendered.contains("x1000c0s0b0n[0-1]"));
+    assert!(rendered.contains("3d 0h"));
+  }
+
+  #[test]
+  fn print_empty_list_succeeds() {
+    assert!(print(&[], None, false).is_ok());
+    assert!(print(&[], Some("json"), false).is_ok());
+  }
+}
@@ -0,0 +1,213 @@
+//! Renderers for Ansible summaries of CFS session logs
+//! ([`SessionLogSummary`]).
+//!
+//! Called by `manta log --summary` ([`print_summary`]) and
+//! `manta get sessions --failures` ([`print_failures`]). Supported
+//! output formats: `"json"` and the default table. In tables, hosts
+//! that failed the same task with the same error share one row, their
+//! names compressed into hostlist notation unless `expand`.
+
+use std::collections::BTreeMap;
+
+use anyhow::{Context, Result};
+use comfy_table::{ContentArrangement, Table};
+use manta_shared::types::ansible_summary::SessionLogSummary;
+
+/// Longest error message shown in a table cell; JSON keeps it whole.
+const MAX_MESSAGE_CHARS: usize = 300;
+
+fn short_message(message: &str) -> String {
+  let first_line = message.lines().next().unwrap_or_default();
+  let mut short: String = first_line.chars().take(MAX_MESSAGE_CHARS).collect();
+  if short.len() < message.len() {
+    short.push('…');
+  }
+  short
+}
+
+/// Failure rows keyed by everything but the host, in log order.
+type FailureRows<'a> =
+  BTreeMap<(usize, usize, &'a str, String, String), Vec<&'a str>>;
+
+fn failure_rows(summary: &SessionLogSummary) -> FailureRows<'_> {
+  let mut rows: FailureRows = BTreeMap::new();
+  let mut order: BTreeMap<(usize, &str, String), usize> = BTreeMap::new();
+  for (layer, failure) in summary.failures() {
+    let error = if failure.unreachable {
+      format!("UNREACHABLE: {}", short_message(&failure.message))
+    } else {
+      short_message(&failure.message)
+    };
+    let task = match &failure.item {
+      Some(item) => format!("{} (item={item})", failure.task),
+      None => failure.task.clone(),
+    };
+    let next = order.len();
+    let position = *order
+      .entry((layer.index, failure.task.as_str(), error.clone()))
+      .or_insert(next);
+    rows
+      .entry((layer.index, position, failure.task.as_str(), task, error))
+      .or_default()
+      .push(&failure.host);
+  }
+  rows
+}
+
+fn build_failure_table(
+  summaries: &[SessionLogSummary],
+  with_session: bool,
+  expand: bool,
+) -> Table {
+  let mut table = Table::new();
+  table.set_content_arrangement(ContentArrangement::Dynamic);
+  let mut header = vec!["Layer", "Task", "Hosts", "Error"];
+  if with_session {
+    header.insert(0, "Session");
+  }
+  table.set_header(header);
+  for summary in summaries {
+    for ((layer, _, _, task, error), mut hosts) in failure_rows(summary) {
+      hosts.sort_unstable();
+      hosts.dedup();
+      let mut row = vec![
+        summary.layers[layer].label(),
+        task,
+        super::node_list(&hosts, expand),
+        error,
+      ];
+      if with_session {
+        row.insert(0, summary.session_name.clone());
+      }
+      table.add_row(row);
+    }
+  }
+  table
+}
+
+fn build_recap_table(summary: &SessionLogSummary) -> Table {
+  let mut table = Table::new();
+  table.set_content_arrangement(ContentArrangement::Dynamic);
+  table.set_header(vec![
+    "Layer",
+    "Hosts",
+    "ok",
+    "changed",
+    "failed",
+    "unreachable",
+    "skipped",
+    "rescued",
+    "ignored",
+  ]);
+  for layer in &summary.layers {
+    let total = |count: fn(&_) -> u32| -> String {
+      layer.recap.iter().map(count).sum::<u32>().to_string()
+    };
+    let hosts = if layer.recap.is_empty() {
+      "no recap".to_string()
+    } else {
+      layer.recap.len().to_string()
+    };
+    table.add_row(vec![
+      layer.label(),
+      hosts,
+      total(|r| r.ok),
+      total(|r| r.changed),
+      total(|r| r.failed),
+      total(|r| r.unreachable),
+      total(|r| r.skipped),
+      total(|r| r.rescued),
+      total(|r| r.ignored),
+    ]);
+  }
+  table
+}
+
+/// Print the summary of one session log in the requested format
+/// (`"json"` or table): per-layer recap totals, then the failed tasks.
+///
+/// # Errors
+///
+/// Returns `Err` if JSON serialisation fails (JSON path only).
+pub fn print_summary(
+  summary: &SessionLogSummary,
+  output_opt: Option<&str>,
+  expand: bool,
+) -> Result<()> {
+  if output_opt.is_some_and(|o| o == "json") {
+    println!(
+      "{}",
+      serde_json::to_string_pretty(summary)
+        .context("Failed to serialize session log summary to JSON")?
+    );
+    return Ok(());
+  }
+  if summary.layers.is_empty() {
+    println!("No Ansible output in the log of {}", summary.session_name);
+    return Ok(());
+  }
+  println!("{}", build_recap_table(summary));
+  if summary.has_failures() {
+    println!(
+      "{}",
+      build_failure_table(std::slice::from_ref(summary), false, expand)
+    );
+  } else {
+    println!("No failed tasks");
+  }
+  Ok(())
+}
+
+/// Print the failed tasks of several sessions in the requested format
+/// (`"json"`: the full summaries, or table: one row per session, layer,
+/// task and error).
+///
+/// # Errors
+///
+/// Returns `Err` if JSON serialisation fails (JSON path only).
+pub fn print_failures(
+  summaries: &[SessionLogSummary],
+  output_opt: Option<&str>,
+  expand: bool,
+) -> Result<()> {
+  if output_opt.is_some_and(|o| o == "json") {
+    println!(
+      "{}",
+      serde_json::to_string_pretty(summaries)
+        .context("Failed to serialize session failures to JSON")?
+    );
+  } else if summaries.iter().any(SessionLogSummary::has_failures) {
+    println!("{}", build_failure_table(summaries, true, expand));
+  } else {
+    println!("No failed tasks found");
+  }
+  Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use manta_shared::types::ansible_summary::summarize;
+
+  #[test]
+  fn hosts_failing_the_same_task_share_a_row() {
+    let log = "Running site.yml from repo https://vcs/cray/cfg.git\n\
+               PLAY [p] ***\n\
+               TASK [r : install] ***\n\
+               fatal: [x1000c0s0b0n0]: FAILED! => {\"msg\": \"boom\"}\n\
+               fatal: [x1000c0s0b0n1]: FAILED! => {\"msg\": \"boom\"}\n\
+               fatal: [x1000c0s1b0n0]: UNREACHABLE! => {\"msg\": \"ssh\"}\n";
+    let summary = summarize("batcher-1", log.lines());
+    let table = build_failure_table(&[summary], true, false).to_string();
+    assert!(table.contains("x1000c0s0b0n[0-1]"), "{table}");
+    assert!(table.contains("UNREACHABLE: ssh"), "{table}");
+    assert!(table.contains("cfg/site.yml"), "{table}");
+    assert_eq!(table.matches("batcher-1").count(), 2, "{table}");
+  }
+
+  #[test]
+  fn long_messages_are_cut_to_their_first_line() {
+    assert_eq!(short_message("first\nsecond"), "first…");
+    assert_eq!(short_message("only"), "only");
+  }
+}
@@ -220,3 +220,95 @@ fn backend_command_without_a_site_errors_clearly() {
     .failure()
     .stderr(predicate::str::contains("No site selected"));
 }
+
+/// `validate sat-file` is purely local: it renders and checks the file
+/// with no site selected and no server reachable.
+#[test]
+fn validate_sat_file_accepts_valid_file_offline() {
+  let (dir, path) = site_less_config();
+  let sat = dir.path().join("sat.yaml");
+  fs::write(
+    &sat,
+    "images:\n\
+     - name: \"{{ prefix }}-compute\"\n  \
+       base:\n    \
+         ims:\n      \
+           name: base-image\n      \
+           type: image\n",
+  )
+  .unwrap();
+  Command::cargo_bin("manta")
+    .unwrap()
+    .env("MANTA_CLI_CONFIG", &path)
+    .args(["validate", "sat-file", "-t"])
+    .arg(&sat)
+    .args(["-V", "prefix=test"])
+    .assert()
+    .success()
+    .stdout(predicate::str::contains(
+      "is valid: 0 configurations, 1 images",
+    ));
+}
+
+/// A bundle template can include a fragment next to it, and repeated
+/// `-f` files layer with the later one winning.
+#[test]
+fn validate_sat_file_renders_bundle_with_layered_values() {
+  let (dir, path) = site_less_config();
+  let sat = dir.path().join("sat.yaml");
+  fs::write(&sat, "{% include 'images.yaml' %}\n").unwrap();
+  fs::write(
+    dir.path().join("images.yaml"),
+    "images:\n\
+     - name: \"{{ prefix }}-compute\"\n  \
+       base:\n    \
+         ims:\n      \
+           name: base-image\n      \
+           type: {{ kind }}\n",
+  )
+  .unwrap();
+  let base = dir.path().join("base.yaml");
+  fs::write(&base, "prefix: base\nkind: recipe\n").unwrap();
+  let prod = dir.path().join("prod.yaml");
+  fs::write(&prod, "kind: image\n").unwrap();
+  Command::cargo_bin("manta")
+    .unwrap()
+    .env("MANTA_CLI_CONFIG", &path)
+    .args(["validate", "sat-file", "-t"])
+    .arg(&sat)
+    .arg("-f")
+    .arg(&base)
+    .arg("-f")
+    .arg(&prod)
+    .assert()
+    .success()
+    .stdout(predicate::str::contains(
+      "is valid: 0 configurations, 1 images",
+    ));
+}
+
+/// Schema errors name the field and the line in the rendered file.
+#[test]
+fn validate_sat_file_reports_unknown_field_with_line() {
+  let (dir, path) = site_less_config();
+  let sat = dir.path().join("sat.yaml");
+  fs::write(
+    &sat,
+    "configurations:\n\
+     - name: cfg\n  \
+       layers:\n  \
+       - git:\n      \
+           url: https://git.example/repo.git\n      \
+           brnach: main\n",
+  )
+  .unwrap();
+  Command::cargo_bin("manta")
+    .unwrap()
+    .env("MANTA_CLI_CONFIG", &path)
+    .args(["validate", "sat-file", "-t"])
+    .arg(&sat)
+    .assert()
+    .failure()
+    .stderr(predicate::str::contains("line 6, column 7"))
+    .stderr(predicate::str::contains("unknown field `brnach`"));
+}
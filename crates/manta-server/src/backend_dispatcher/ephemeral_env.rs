@@ -0,0 +1,443 @@
+//! [`EphemeralEnvTrait`] and its impl for [`StaticBackendDispatcher`].
+//!
+//! Unlike its siblings, the trait is defined here rather than in
+//! `manta-backend-dispatcher`, which has no interface for IMS jobs.
+//! An ephemeral environment is an IMS customize job whose root archive
+//! is named [`EPHEMERAL_IMAGE_NAME`]; it belongs to the user whose
+//! registered SSH public key it was created with (IMS keys are keyed
+//! by username).
+//!
+//! The CSM branch creates the job through [`csm_rs::ShastaClient`] and
+//! lists / deletes jobs on `/ims/v3/jobs` with `reqwest`, since
+//! `csm-rs` neither deletes jobs nor keeps the SSH connection info
+//! when reading them. OpenCHAMI has no image management service, so
+//! every method on the OCHAMI branch returns
+//! [`Error::UnsupportedBackend`], which the ephemeral environment
+//! handlers answer with 501.
+
+use std::time::Duration;
+
+use csm_rs::ShastaClient;
+use manta_shared::types::api::responses::EphemeralEnv;
+use reqwest::StatusCode;
+
+use super::*;
+
+/// Root archive name of every ephemeral environment's IMS job; tells
+/// them apart from other customize jobs.
+const EPHEMERAL_IMAGE_NAME: &str = "__ephemeral_image";
+
+/// Bound on each IMS call made with `reqwest`.
+const IMS_REQUEST_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Ephemeral SSH environments booted from an existing image.
+///
+/// `base_url` / `root_cert` are the site's API endpoint and root CA
+/// (`InfraContext::shasta_base_url` / `shasta_root_cert`), which the
+/// wrapped backend clients don't expose. Environments carry no expiry
+/// here; that is tracked by the service layer.
+pub trait EphemeralEnvTrait {
+  /// Boot an environment from `image_id` reachable with `username`'s
+  /// registered SSH key. The returned environment has a hostname.
+  fn create_ephemeral_env(
+    &self,
+    base_url: &str,
+    root_cert: &[u8],
+    token: &str,
+    username: &str,
+    image_id: &str,
+  ) -> impl Future<Output = Result<EphemeralEnv, Error>> + Send;
+
+  /// Every environment of `username` on the site.
+  fn get_ephemeral_envs(
+    &self,
+    base_url: &str,
+    root_cert: &[u8],
+    token: &str,
+    username: &str,
+  ) -> impl Future<Output = Result<Vec<EphemeralEnv>, Error>> + Send;
+
+  /// Delete environment `id`. With `owner` set, an environment of
+  /// another user counts as missing.
+  fn delete_ephemeral_env(
+    &self,
+    base_url: &str,
+    root_cert: &[u8],
+    token: &str,
+    id: &str,
+    owner: Option<&str>,
+  ) -> impl Future<Output = Result<(), Error>> + Send;
+}
+
+impl EphemeralEnvTrait for StaticBackendDispatcher {
+  /// CSM: look the user's public SSH key up in IMS, POST a customize
+  /// job referencing `image_id` and the key id, and read the job id
+  /// and SSH hostname from the response.
+  async fn create_ephemeral_env(
+    &self,
+    base_url: &str,
+    root_cert: &[u8],
+    token: &str,
+    username: &str,
+    image_id: &str,
+  ) -> Result<EphemeralEnv, Error> {
+    match self {
+      CSM(_) => {
+        csm_create(base_url, root_cert, token, username, image_id).await
+      }
+      OCHAMI(_) => Err(unsupported()),
+    }
+  }
+
+  /// CSM: the ephemeral customize jobs created with the user's key.
+  async fn get_ephemeral_envs(
+    &self,
+    base_url: &str,
+    root_cert: &[u8],
+    token: &str,
+    username: &str,
+  ) -> Result<Vec<EphemeralEnv>, Error> {
+    match self {
+      CSM(_) => csm_list(base_url, root_cert, token, username).await,
+      OCHAMI(_) => Err(unsupported()),
+    }
+  }
+
+  /// CSM: `DELETE /ims/v3/jobs/{id}` once the job is known to be an
+  /// ephemeral environment (of `owner`, when set).
+  async fn delete_ephemeral_env(
+    &self,
+    base_url: &str,
+    root_cert: &[u8],
+    token: &str,
+    id: &str,
+    owner: Option<&str>,
+  ) -> Result<(), Error> {
+    match self {
+      CSM(_) => csm_delete(base_url, root_cert, token, id, owner).await,
+      OCHAMI(_) => Err(unsupported()),
+    }
+  }
+}
+
+fn unsupported() -> Error {
+  Error::UnsupportedBackend(
+    "Ephemeral environments are not implemented for the OpenCHAMI backend \
+     (it has no image management service)"
+      .to_string(),
+  )
+}
+
+async fn csm_create(
+  base_url: &str,
+  root_cert: &[u8],
+  token: &str,
+  username: &str,
+  image_id: &str,
+) -> Result<EphemeralEnv, Error> {
+  let shasta = shasta_client(base_url, root_cert)?;
+  let key_id = user_public_key_id(&shasta, token, username).await?;
+  tracing::info!("SSH key found with ID {}", key_id);
+
+  let job = shasta
+    .ims_job_post_customize(token, EPHEMERAL_IMAGE_NAME, image_id, &key_id)
+    .await
+    .map_err(|e| {
+      Error::BadRequest(format!(
+        "Could not create ephemeral environment based on image ID {image_id}: {e}"
+      ))
+    })?;
+
+  let env = to_ephemeral_env(&job).ok_or_else(|| {
+    Error::MissingField(
+      "IMS customize job response missing server-generated 'id'".to_string(),
+    )
+  })?;
+  if env.hostname.is_none() {
+    return Err(Error::MissingField(
+      "Failed to get SSH container hostname from ephemeral env response"
+        .to_string(),
+    ));
+  }
+  Ok(EphemeralEnv {
+    image_id: image_id.to_string(),
+    ..env
+  })
+}
+
+async fn csm_list(
+  base_url: &str,
+  root_cert: &[u8],
+  token: &str,
+  username: &str,
+) -> Result<Vec<EphemeralEnv>, Error> {
+  let shasta = shasta_client(base_url, root_cert)?;
+  let key_id = user_public_key_id(&shasta, token, username).await?;
+  let client = ims_http_client(root_cert)?;
+  let jobs = get_jobs(&client, base_url, token).await?;
+  Ok(
+    jobs
+      .iter()
+      .filter(|job| {
+        is_ephemeral_env(job)
+          && job.get("public_key_id").and_then(Value::as_str)
+            == Some(key_id.as_str())
+      })
+      .filter_map(to_ephemeral_env)
+      .collect(),
+  )
+}
+
+async fn csm_delete(
+  base_url: &str,
+  root_cert: &[u8],
+  token: &str,
+  id: &str,
+  owner: Option<&str>,
+) -> Result<(), Error> {
+  let client = ims_http_client(root_cert)?;
+  let not_found =
+    || Error::NotFound(format!("No ephemeral environment '{id}'"));
+  let job = get_job(&client, base_url, token, id)
+    .await?
+    .filter(is_ephemeral_env)
+    .ok_or_else(not_found)?;
+
+  if let Some(owner) = owner {
+    let shasta = shasta_client(base_url, root_cert)?;
+    let key_id = user_public_key_id(&shasta, token, owner).await?;
+    if job.get("public_key_id").and_then(Value::as_str) != Some(&key_id) {
+      return Err(not_found());
+    }
+  }
+
+  delete_job(&client, base_url, token, id).await
+}
+
+fn shasta_client(
+  base_url: &str,
+  root_cert: &[u8],
+) -> Result<ShastaClient, Error> {
+  ShastaClient::new(base_url, root_cert.to_vec()).map_err(|e| {
+    Error::BadRequest(format!("Could not build Shasta HTTP client: {e}"))
+  })
+}
+
+/// `reqwest` client for IMS calls, trusting the site's root CA.
+fn ims_http_client(root_cert: &[u8]) -> Result<reqwest::Client, Error> {
+  Ok(
+    reqwest::Client::builder()
+      .timeout(IMS_REQUEST_TIMEOUT)
+      .add_root_certificate(reqwest::Certificate::from_pem(root_cert).map_err(
+        |e| Error::BadRequest(format!("Invalid site root CA certificate: {e}")),
+      )?)
+      .build()?,
+  )
+}
+
+/// IMS id of `username`'s registered SSH public key.
+async fn user_public_key_id(
+  shasta: &ShastaClient,
+  token: &str,
+  username: &str,
+) -> Result<String, Error> {
+  tracing::info!("Looking for user '{}' public SSH key", username);
+  if let Ok(Some(user_public_ssh_key)) =
+    shasta.ims_public_keys_v3_get_single(token, username).await
+  {
+    user_public_ssh_key.id.ok_or_else(|| {
+      Error::MissingField(
+        "IMS public-key response missing server-generated 'id'".to_string(),
+      )
+    })
+  } else {
+    Err(Error::NotFound(format!(
+      "User '{username}' does not have an SSH public key in Alps. \
+       Please contact platform sys admins."
+    )))
+  }
+}
+
+/// `/ims/v3/jobs`, or `/ims/v3/jobs/{id}`.
+fn job_url(base_url: &str, id: Option<&str>) -> Result<reqwest::Url, Error> {
+  let mut url = reqwest::Url::parse(&format!(
+    "{}/ims/v3/jobs",
+    base_url.trim_end_matches('/')
+  ))
+  .map_err(|e| Error::Message(format!("Invalid IMS URL: {e}")))?;
+  if let Some(id) = id {
+    url
+      .path_segments_mut()
+      .map_err(|()| Error::Message("Invalid IMS URL".to_string()))?
+      .push(id);
+  }
+  Ok(url)
+}
+
+/// Every IMS job on the site.
+async fn get_jobs(
+  client: &reqwest::Client,
+  base_url: &str,
+  token: &str,
+) -> Result<Vec<Value>, Error> {
+  let response = client
+    .get(job_url(base_url, None)?)
+    .bearer_auth(token)
+    .send()
+    .await?;
+  let status = response.status();
+  if !status.is_success() {
+    let body = response.text().await.unwrap_or_default();
+    return Err(Error::Message(format!(
+      "IMS returned {status} listing jobs: {body}"
+    )));
+  }
+  Ok(response.json().await?)
+}
+
+/// IMS job `id`, or `None` when IMS has no such job.
+async fn get_job(
+  client: &reqwest::Client,
+  base_url: &str,
+  token: &str,
+  id: &str,
+) -> Result<Option<Value>, Error> {
+  let response = client
+    .get(job_url(base_url, Some(id))?)
+    .bearer_auth(token)
+    .send()
+    .await?;
+  let status = response.status();
+  if status == StatusCode::NOT_FOUND {
+    return Ok(None);
+  }
+  if !status.is_success() {
+    let body = response.text().await.unwrap_or_default();
+    return Err(Error::Message(format!(
+      "IMS returned {status} fetching job '{id}': {body}"
+    )));
+  }
+  Ok(Some(response.json().await?))
+}
+
+/// Delete IMS job `id`. A job gone in between counts as deleted.
+async fn delete_job(
+  client: &reqwest::Client,
+  base_url: &str,
+  token: &str,
+  id: &str,
+) -> Result<(), Error> {
+  let response = client
+    .delete(job_url(base_url, Some(id))?)
+    .bearer_auth(token)
+    .send()
+    .await?;
+  let status = response.status();
+  if status.is_success() || status == StatusCode::NOT_FOUND {
+    return Ok(());
+  }
+  let body = response.text().await.unwrap_or_default();
+  Err(Error::Message(format!(
+    "IMS returned {status} deleting job '{id}': {body}"
+  )))
+}
+
+/// Whether IMS job `job` is an ephemeral environment.
+fn is_ephemeral_env(job: &Value) -> bool {
+  job.get("job_type").and_then(Value::as_str) == Some("customize")
+    && job.get("image_root_archive_name").and_then(Value::as_str)
+      == Some(EPHEMERAL_IMAGE_NAME)
+}
+
+/// SSH hostname of an IMS customize job.
+fn ssh_hostname(job: &Value) -> Option<&str> {
+  job
+    .pointer("/ssh_containers/0/connection_info/customer_access/host")
+    .and_then(Value::as_str)
+}
+
+/// The listing entry of IMS job `job`, with no expiry; `None` when the
+/// job has no id.
+fn to_ephemeral_env(job: &Value) -> Option<EphemeralEnv> {
+  let field =
+    |key: &str| job.get(key).and_then(Value::as_str).map(str::to_string);
+  Some(EphemeralEnv {
+    id: field("id")?,
+    image_id: field("artifact_id").unwrap_or_default(),
+    status: field("status"),
+    hostname: ssh_hostname(job).map(str::to_string),
+    created: field("created"),
+    expires_at: None,
+  })
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use serde_json::json;
+  use wiremock::matchers::{header, method, path};
+  use wiremock::{Mock, MockServer, ResponseTemplate};
+
+  #[test]
+  fn only_ephemeral_customize_jobs_are_listed() {
+    let job = json!({
+      "id": "job-1",
+      "job_type": "customize",
+      "image_root_archive_name": EPHEMERAL_IMAGE_NAME,
+      "artifact_id": "img-1",
+      "status": "waiting_on_user",
+      "created": "2026-10-19T08:00:00Z",
+      "ssh_containers": [{
+        "connection_info": {"customer_access": {"host": "jdoe.ims.local"}}
+      }],
+    });
+    assert!(is_ephemeral_env(&job));
+    let env = to_ephemeral_env(&job).unwrap();
+    assert_eq!(env.id, "job-1");
+    assert_eq!(env.image_id, "img-1");
+    assert_eq!(env.hostname.as_deref(), Some("jdoe.ims.local"));
+
+    let sat_job = json!({
+      "id": "job-2",
+      "job_type": "customize",
+      "image_root_archive_name": "compute-image",
+    });
+    assert!(!is_ephemeral_env(&sat_job));
+  }
+
+  #[tokio::test]
+  async fn delete_job_accepts_a_job_already_gone() {
+    let srv = MockServer::start().await;
+    Mock::given(method("DELETE"))
+      .and(path("/ims/v3/jobs/gone"))
+      .and(header("Authorization", "Bearer t"))
+      .respond_with(ResponseTemplate::new(404))
+      .mount(&srv)
+      .await;
+    Mock::given(method("DELETE"))
+      .and(path("/ims/v3/jobs/stuck"))
+      .respond_with(ResponseTemplate::new(500))
+      .mount(&srv)
+      .await;
+
+    let client = reqwest::Client::new();
+    assert!(delete_job(&client, &srv.uri(), "t", "gone").await.is_ok());
+    assert!(matches!(
+      delete_job(&client, &srv.uri(), "t", "stuck").await,
+      Err(Error::Message(msg)) if msg.contains("500")
+    ));
+  }
+
+  #[tokio::test]
+  async fn ochami_sites_have_no_ephemeral_envs() {
+    let backend =
+      StaticBackendDispatcher::new("ochami", "http://stub.invalid", b"")
+        .unwrap();
+    assert!(matches!(
+      backend
+        .get_ephemeral_envs("http://stub.invalid", b"", "t", "jdoe")
+        .await,
+      Err(Error::UnsupportedBackend(_))
+    ));
+  }
+}
@@ -0,0 +1,285 @@
+//! [`GitRefTrait`] and its impl for [`StaticBackendDispatcher`].
+//!
+//! Like [`super::ephemeral_env`], the trait is defined here because
+//! `manta-backend-dispatcher` has no interface for the site's VCS:
+//! `ApplySessionTrait` takes layers as repository names and commit
+//! SHAs already resolved.
+//!
+//! The CSM branch asks Gitea's REST API with `reqwest`; `csm-rs` only
+//! reads branches and tags by exact kind, while a ref given by a user
+//! may name either, or a commit. OpenCHAMI has no CFS and no VCS, so
+//! the OCHAMI branch returns [`Error::UnsupportedBackend`].
+
+use std::time::Duration;
+
+use reqwest::StatusCode;
+
+use super::*;
+
+/// Bound on each Gitea call, so a hung VCS fails the request instead
+/// of holding it open.
+const GITEA_REQUEST_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Resolution of Git refs in the site's configuration repositories.
+///
+/// `gitea_base_url` / `root_cert` are the site's VCS endpoint and
+/// root CA (`InfraContext::gitea_base_url` / `shasta_root_cert`).
+pub trait GitRefTrait {
+  /// The commit SHA `git_ref` names in repository `repo`
+  /// (`<org>/<repo>`). The ref is tried as a branch, then as a tag,
+  /// then as a commit, and the first match wins, the order `git`
+  /// itself uses when a branch and a tag share a name.
+  fn resolve_git_ref(
+    &self,
+    gitea_base_url: &str,
+    root_cert: &[u8],
+    gitea_token: &str,
+    repo: &str,
+    git_ref: &str,
+  ) -> impl Future<Output = Result<String, Error>> + Send;
+}
+
+impl GitRefTrait for StaticBackendDispatcher {
+  /// CSM: `GET /api/v1/repos/{repo}/{branches,tags,git/commits}/{ref}`
+  /// on Gitea, in that order.
+  async fn resolve_git_ref(
+    &self,
+    gitea_base_url: &str,
+    root_cert: &[u8],
+    gitea_token: &str,
+    repo: &str,
+    git_ref: &str,
+  ) -> Result<String, Error> {
+    match self {
+      CSM(_) => {
+        let client = gitea_http_client(root_cert)?;
+        resolve(&client, gitea_base_url, gitea_token, repo, git_ref).await
+      }
+      OCHAMI(_) => Err(Error::UnsupportedBackend(
+        "Git refs can't be resolved on the OpenCHAMI backend (it has no \
+         configuration framework service or VCS)"
+          .to_string(),
+      )),
+    }
+  }
+}
+
+/// `reqwest` client for Gitea calls, trusting the site's root CA.
+fn gitea_http_client(root_cert: &[u8]) -> Result<reqwest::Client, Error> {
+  Ok(
+    reqwest::Client::builder()
+      .timeout(GITEA_REQUEST_TIMEOUT)
+      .add_root_certificate(reqwest::Certificate::from_pem(root_cert).map_err(
+        |e| Error::BadRequest(format!("Invalid site root CA certificate: {e}")),
+      )?)
+      .build()?,
+  )
+}
+
+/// `/api/v1/repos/{repo}/{collection}/{git_ref}`. Every part is added
+/// as path segments, so `?`, `#` or `..` in `repo` or `git_ref` can't
+/// turn the call into another endpoint. Each `/`-separated component
+/// of `git_ref` is its own segment, so a branch such as `feature/x` is
+/// not sent as `feature%2Fx`, which Gitea doesn't find.
+fn ref_url(
+  gitea_base_url: &str,
+  repo: &str,
+  collection: &str,
+  git_ref: &str,
+) -> Result<reqwest::Url, Error> {
+  let bad_part = |part: &str| part.is_empty() || part == "." || part == "..";
+  if repo.split('/').any(bad_part) {
+    return Err(Error::BadRequest(format!(
+      "'{repo}' is not a valid Gitea repository"
+    )));
+  }
+  if git_ref.split('/').any(bad_part) {
+    return Err(Error::BadRequest(format!(
+      "'{git_ref}' is not a valid Git ref"
+    )));
+  }
+  let mut url = reqwest::Url::parse(gitea_base_url)
+    .map_err(|e| Error::Message(format!("Invalid Gitea URL: {e}")))?;
+  url
+    .path_segments_mut()
+    .map_err(|()| Error::Message("Invalid Gitea URL".to_string()))?
+    .pop_if_empty()
+    .extend(["api", "v1", "repos"])
+    .extend(repo.split('/'))
+    .extend(collection.split('/'))
+    .extend(git_ref.split('/'));
+  Ok(url)
+}
+
+/// The commit SHA `git_ref` names in Gitea repository `repo`.
+async fn resolve(
+  client: &reqwest::Client,
+  gitea_base_url: &str,
+  gitea_token: &str,
+  repo: &str,
+  git_ref: &str,
+) -> Result<String, Error> {
+  // Branch: `commit.id`; tag: `commit.sha`; commit: `sha`.
+  let lookups: [(&str, &[&str]); 3] = [
+    ("branches", &["commit", "id"]),
+    ("tags", &["commit", "sha"]),
+    ("git/commits", &["sha"]),
+  ];
+  for (collection, sha_path) in lookups {
+    let response = client
+      .get(ref_url(gitea_base_url, repo, collection, git_ref)?)
+      .header("Authorization", format!("token {gitea_token}"))
+      .send()
+      .await?;
+    let status = response.status();
+    // Gitea answers 422 for a commit lookup whose ref isn't hex.
+    if status == StatusCode::NOT_FOUND
+      || status == StatusCode::UNPROCESSABLE_ENTITY
+    {
+      continue;
+    }
+    if !status.is_success() {
+      let body = response.text().await.unwrap_or_default();
+      return Err(Error::Message(format!(
+        "Gitea returned {status} looking up '{git_ref}' in '{repo}': {body}"
+      )));
+    }
+    let body: Value = response.json().await?;
+    return sha_path
+      .iter()
+      .try_fold(&body, |value, key| value.get(key))
+      .and_then(Value::as_str)
+      .map(str::to_string)
+      .ok_or_else(|| {
+        Error::MissingField(format!(
+          "Gitea {collection} response for '{git_ref}' in '{repo}' has no \
+           commit SHA"
+        ))
+      });
+  }
+  Err(Error::NotFound(format!(
+    "No branch, tag or commit '{git_ref}' in Gitea repository '{repo}'"
+  )))
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use wiremock::matchers::{header, method, path};
+  use wiremock::{Mock, MockServer, ResponseTemplate};
+
+  #[tokio::test]
+  async fn branch_then_tag_then_commit() {
+    let srv = MockServer::start().await;
+    let repo = "/api/v1/repos/cray/csm-config";
+    Mock::given(method("GET"))
+      .and(path(format!("{repo}/branches/main")))
+      .and(header("Authorization", "token t"))
+      .respond_with(
+        ResponseTemplate::new(200)
+          .set_body_json(serde_json::json!({"commit": {"id": "b1"}})),
+      )
+      .mount(&srv)
+      .await;
+    Mock::given(method("GET"))
+      .and(path(format!("{repo}/tags/v1.0")))
+      .respond_with(
+        ResponseTemplate::new(200)
+          .set_body_json(serde_json::json!({"commit": {"sha": "t1"}})),
+      )
+      .mount(&srv)
+      .await;
+    Mock::given(method("GET"))
+      .and(path(format!("{repo}/git/commits/abc123")))
+      .respond_with(
+        ResponseTemplate::new(200)
+          .set_body_json(serde_json::json!({"sha": "abc123full"})),
+      )
+      .mount(&srv)
+      .await;
+
+    let client = reqwest::Client::new();
+    let uri = srv.uri();
+    let resolve =
+      |git_ref| resolve(&client, &uri, "t", "cray/csm-config", git_ref);
+    assert_eq!(resolve("main").await.unwrap(), "b1");
+    assert_eq!(resolve("v1.0").await.unwrap(), "t1");
+    assert_eq!(resolve("abc123").await.unwrap(), "abc123full");
+    assert!(matches!(resolve("nope").await, Err(Error::NotFound(_))));
+  }
+
+  #[tokio::test]
+  async fn slash_branches_keep_their_slashes() {
+    let srv = MockServer::start().await;
+    Mock::given(method("GET"))
+      .and(path("/api/v1/repos/cray/csm-config/branches/feature/x"))
+      .respond_with(
+        ResponseTemplate::new(200)
+          .set_body_json(serde_json::json!({"commit": {"id": "f1"}})),
+      )
+      .mount(&srv)
+      .await;
+
+    let client = reqwest::Client::new();
+    let uri = srv.uri();
+    let resolve =
+      |git_ref| resolve(&client, &uri, "t", "cray/csm-config", git_ref);
+    assert_eq!(resolve("feature/x").await.unwrap(), "f1");
+    for bad in ["feature//x", "../x", "x/"] {
+      assert!(
+        matches!(resolve(bad).await, Err(Error::BadRequest(_))),
+        "{bad}"
+      );
+    }
+  }
+
+  #[test]
+  fn ref_urls_stay_on_the_ref_endpoints() {
+    let url = |repo, git_ref| {
+      ref_url("https://vcs.example.com/vcs/", repo, "branches", git_ref)
+    };
+    assert_eq!(
+      url("cray/csm-config", "feature/x").unwrap().as_str(),
+      "https://vcs.example.com/vcs/api/v1/repos/cray/csm-config/branches/\
+       feature/x"
+    );
+    // Query and fragment characters stay inside their segment.
+    let escaped = url("a/b?x", "main").unwrap();
+    assert_eq!(escaped.query(), None);
+    assert_eq!(escaped.path(), "/vcs/api/v1/repos/a/b%3Fx/branches/main");
+    let escaped = url("a/b", "x#y").unwrap();
+    assert_eq!(escaped.fragment(), None);
+    assert!(matches!(url("../admin", "main"), Err(Error::BadRequest(_))));
+  }
+
+  #[tokio::test]
+  async fn gitea_errors_are_reported() {
+    let srv = MockServer::start().await;
+    Mock::given(method("GET"))
+      .respond_with(ResponseTemplate::new(401))
+      .mount(&srv)
+      .await;
+    let result = resolve(
+      &reqwest::Client::new(),
+      &srv.uri(),
+      "t",
+      "cray/csm-config",
+      "main",
+    )
+    .await;
+    assert!(matches!(result, Err(Error::Message(msg)) if msg.contains("401")));
+  }
+
+  #[tokio::test]
+  async fn ochami_sites_resolve_no_refs() {
+    let backend =
+      StaticBackendDispatcher::new("ochami", "http://stub.invalid", b"")
+        .unwrap();
+    assert!(matches!(
+      backend
+        .resolve_git_ref("http://stub.invalid", b"", "t", "cray/c", "main")
+        .await,
+      Err(Error::UnsupportedBackend(_))
+    ));
+  }
+}
@@ -26,6 +26,10 @@
 //! shape, so the rule is: any trait a handler reaches through must
 //! have a sibling file under this module, even if the body is a
 //! straight `dispatch!` forward.
+//!
+//! [`EphemeralEnvTrait`] and [`GitRefTrait`] are defined here rather
+//! than in `manta-backend-dispatcher`; see [`ephemeral_env`] and
+//! [`git_ref`].
 
 use std::collections::HashMap;
 use std::pin::Pin;
@@ -138,7 +142,9 @@ mod component;
 mod component_ethernet_interface;
 mod console;
 mod delete_configurations;
+pub mod ephemeral_env;
 mod get_images;
+pub mod git_ref;
 mod group;
 mod hardware_inventory;
 mod ims;
@@ -147,3 +153,6 @@ mod migrate_restore;
 mod pcs;
 mod redfish_endpoint;
 mod sat;
+
+pub use ephemeral_env::EphemeralEnvTrait;
+pub use git_ref::GitRefTrait;
@@ -133,6 +133,144 @@ pub struct ServerSettings {
   /// and `key`.
   #[serde(default)]
   pub allow_http: bool,
+  /// Time-limited hardware reservations (`expires_at` on a pin). When
+  /// unset (default), pins with an expiry and `GET /reservations`
+  /// return `501 Not Implemented`.
+  #[serde(default)]
+  pub reservations: Option<ReservationSettings>,
+  /// Hardware inventory snapshots (`/hardware-snapshots`) and the
+  /// optional scheduled watch. When unset (default), the snapshot
+  /// endpoints return `501 Not Implemented`.
+  #[serde(default)]
+  pub hardware_snapshots: Option<HardwareSnapshotSettings>,
+  /// Archive of CFS session logs, readable after the session's pod is
+  /// gone. When unset (default), nothing is archived and
+  /// `GET /sessions/{name}/logs/archive` returns `501 Not Implemented`.
+  #[serde(default)]
+  pub session_logs: Option<SessionLogSettings>,
+  /// Expiry of ephemeral environments (`ttl_secs` on
+  /// `POST /ephemeral-env`). When unset (default), environments live
+  /// until deleted and a requested TTL returns
+  /// `501 Not Implemented`.
+  #[serde(default)]
+  pub ephemeral_envs: Option<EphemeralEnvSettings>,
+}
+
+/// Reservation tracking and automatic release. Lives under
+/// `[server.reservations]` in `server.toml`.
+///
+/// The release runs in the background with no caller token, so the
+/// server authenticates as a service account whose password is read
+/// from the environment at startup — never from the config file.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct ReservationSettings {
+  /// JSON file holding the active reservations. Survives restarts;
+  /// created on the first reservation.
+  pub state_file: String,
+  /// How often the reaper looks for expired reservations, in seconds.
+  #[serde(default = "default_reservation_check_interval_secs")]
+  pub check_interval_secs: u64,
+  /// Username the reaper authenticates as. Needs write access to every
+  /// group reservations may touch.
+  pub service_account_user: String,
+  /// Environment variable holding the service account's password.
+  pub service_account_password_env: String,
+}
+
+/// Hardware snapshot storage. Lives under
+/// `[server.hardware_snapshots]` in `server.toml`.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct HardwareSnapshotSettings {
+  /// Directory holding one JSON file per snapshot; created if missing.
+  pub dir: String,
+  /// Scheduled snapshots of selected groups, with changes sent to the
+  /// auditor. Unset (default) means snapshots are only taken on
+  /// request.
+  #[serde(default)]
+  pub watch: Option<HardwareWatchSettings>,
+}
+
+/// Scheduled hardware change detection. Lives under
+/// `[server.hardware_snapshots.watch]` in `server.toml`.
+///
+/// Like the reservation reaper, the watcher runs with no caller token
+/// and authenticates as a service account whose password is read from
+/// the environment at startup.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct HardwareWatchSettings {
+  /// Groups to watch.
+  pub groups: Vec<WatchedGroup>,
+  /// How often the watched groups are snapshotted, in seconds.
+  #[serde(default = "default_hardware_watch_interval_secs")]
+  pub interval_secs: u64,
+  /// Username the watcher authenticates as. Needs read access to every
+  /// watched group.
+  pub service_account_user: String,
+  /// Environment variable holding the service account's password.
+  pub service_account_password_env: String,
+}
+
+/// CFS session log archive. Lives under `[server.session_logs]` in
+/// `server.toml`.
+///
+/// The server archives the log of every session it streams through
+/// `GET /sessions/{name}/logs`, and of every session it creates
+/// (`POST /sessions`, `POST /sat-file/images/cfs-session`). Like the
+/// reservation reaper, the archiver outlives the request that started
+/// it and authenticates as a service account whose password is read
+/// from the environment at startup.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct SessionLogSettings {
+  /// Directory holding one sub-directory per site with the archived
+  /// logs; created if missing.
+  pub dir: String,
+  /// Archives older than this many days are deleted. `0` keeps them
+  /// forever.
+  #[serde(default = "default_session_log_retention_days")]
+  pub retention_days: u64,
+  /// Upper bound on the number of archives kept across all sites; the
+  /// oldest are deleted first. Unset (default) means no limit.
+  #[serde(default)]
+  pub max_archives: Option<usize>,
+  /// Username the archiver authenticates as. Needs read access to the
+  /// sessions of every group whose logs are archived.
+  pub service_account_user: String,
+  /// Environment variable holding the service account's password.
+  pub service_account_password_env: String,
+}
+
+/// Ephemeral environment expiry. Lives under `[server.ephemeral_envs]`
+/// in `server.toml`.
+///
+/// Like the reservation reaper, the reaper deleting expired
+/// environments runs with no caller token and authenticates as a
+/// service account whose password is read from the environment at
+/// startup.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct EphemeralEnvSettings {
+  /// JSON file holding the expiry of every live environment. Survives
+  /// restarts; created on the first environment.
+  pub state_file: String,
+  /// Lifetime of an environment created without a TTL, in seconds.
+  #[serde(default = "default_ephemeral_env_ttl_secs")]
+  pub default_ttl_secs: u64,
+  /// How often the reaper looks for expired environments, in seconds.
+  #[serde(default = "default_reservation_check_interval_secs")]
+  pub check_interval_secs: u64,
+  /// Username the reaper authenticates as. Needs to delete other
+  /// users' IMS jobs.
+  pub service_account_user: String,
+  /// Environment variable holding the service account's password.
+  pub service_account_password_env: String,
+}
+
+/// One group the hardware watcher snapshots.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct WatchedGroup {
+  /// Site name, as in `[sites.<name>]`.
+  pub site: String,
+  /// Group name.
+  pub group: String,
 }
 
 impl ServerSettings {
@@ -165,6 +303,22 @@ fn default_request_timeout_secs() -> u64 {
   600
 }
 
+fn default_reservation_check_interval_secs() -> u64 {
+  60
+}
+
+fn default_hardware_watch_interval_secs() -> u64 {
+  86_400
+}
+
+fn default_session_log_retention_days() -> u64 {
+  30
+}
+
+fn default_ephemeral_env_ttl_secs() -> u64 {
+  86_400
+}
+
 /// Top-level configuration for the `manta-server` binary.
 ///
 /// Persisted as TOML under `~/.config/manta/server.toml` and loaded
@@ -250,6 +404,10 @@ mod tests {
         shutdown_grace_period_secs: 30,
         migrate_backup_root: None,
         allow_http: false,
+        reservations: None,
+        hardware_snapshots: None,
+        session_logs: None,
+        ephemeral_envs: None,
       },
       sites,
       auditor: None,
@@ -339,4 +497,102 @@ mod tests {
     assert!(parsed.cert.is_none());
     assert!(parsed.key.is_none());
   }
+
+  /// `[server.reservations]` is opt-in; when present the check
+  /// interval falls back to one minute.
+  #[test]
+  fn server_settings_reservations_section() {
+    let parsed: ServerSettings = toml::from_str(
+      r#"
+      console_inactivity_timeout_secs = 1800
+    "#,
+    )
+    .unwrap();
+    assert!(parsed.reservations.is_none());
+
+    let parsed: ServerSettings = toml::from_str(
+      r#"
+      console_inactivity_timeout_secs = 1800
+      [reservations]
+      state_file = "/var/lib/manta/reservations.json"
+      service_account_user = "manta-reaper"
+      service_account_password_env = "MANTA_REAPER_PASSWORD"
+    "#,
+    )
+    .unwrap();
+    let reservations = parsed.reservations.expect("section parsed");
+    assert_eq!(reservations.check_interval_secs, 60);
+    assert_eq!(reservations.service_account_user, "manta-reaper");
+  }
+
+  /// `[server.ephemeral_envs]` is opt-in; environments created without
+  /// a TTL live a day.
+  #[test]
+  fn server_settings_ephemeral_envs_section() {
+    let parsed: ServerSettings = toml::from_str(
+      r#"
+      console_inactivity_timeout_secs = 1800
+      [ephemeral_envs]
+      state_file = "/var/lib/manta/ephemeral-envs.json"
+      service_account_user = "manta-reaper"
+      service_account_password_env = "MANTA_REAPER_PASSWORD"
+    "#,
+    )
+    .unwrap();
+    let envs = parsed.ephemeral_envs.expect("section parsed");
+    assert_eq!(envs.default_ttl_secs, 86_400);
+    assert_eq!(envs.check_interval_secs, 60);
+  }
+
+  #[test]
+  fn server_settings_hardware_snapshots_section() {
+    let parsed: ServerSettings = toml::from_str(
+      r#"
+      console_inactivity_timeout_secs = 1800
+      [hardware_snapshots]
+      dir = "/var/lib/manta/hw-snapshots"
+    "#,
+    )
+    .unwrap();
+    let snapshots = parsed.hardware_snapshots.expect("section parsed");
+    assert!(snapshots.watch.is_none());
+
+    let parsed: ServerSettings = toml::from_str(
+      r#"
+      console_inactivity_timeout_secs = 1800
+      [hardware_snapshots]
+      dir = "/var/lib/manta/hw-snapshots"
+      [hardware_snapshots.watch]
+      groups = [{ site = "alps", group = "compute" }]
+      service_account_user = "manta-watch"
+      service_account_password_env = "MANTA_WATCH_PASSWORD"
+    "#,
+    )
+    .unwrap();
+    let watch = parsed
+      .hardware_snapshots
+      .and_then(|s| s.watch)
+      .expect("watch parsed");
+    assert_eq!(watch.interval_secs, 86_400);
+    assert_eq!(watch.groups[0].site, "alps");
+    assert_eq!(watch.groups[0].group, "compute");
+  }
+
+  #[test]
+  fn server_settings_session_logs_section() {
+    let parsed: ServerSettings = toml::from_str(
+      r#"
+      console_inactivity_timeout_secs = 1800
+      [session_logs]
+      dir = "/var/lib/manta/session-logs"
+      service_account_user = "manta-archiver"
+      service_account_password_env = "MANTA_ARCHIVER_PASSWORD"
+    "#,
+    )
+    .unwrap();
+    let logs = parsed.session_logs.expect("section parsed");
+    assert_eq!(logs.retention_days, 30);
+    assert!(logs.max_archives.is_none());
+    assert_eq!(logs.service_account_user, "manta-archiver");
+  }
 }
@@ -29,6 +29,14 @@ use manta_shared::common::{config as manta_config, log_ops};
 use manta_server::config::{BackendTechnology, ServerConfiguration};
 use manta_server::dispatcher::StaticBackendDispatcher;
 use manta_server::server;
+use manta_server::service::ephemeral_env::{EphemeralEnvStore, EphemeralEnvs};
+use manta_server::service::hardware_snapshot::{
+  HardwareSnapshots, SnapshotStore, SnapshotWatch,
+};
+use manta_server::service::reservation::{
+  ReservationStore, Reservations, ServiceAccount,
+};
+use manta_server::service::session_log::{SessionLogStore, SessionLogs};
 
 /// URL path suffix appended to a site's `shasta_base_url` to reach the
 /// CSM API root (e.g. `https://api.cluster.local/apis`). Stripped from
@@ -200,6 +208,66 @@ fn print_startup_summary(
       .auth_rate_limit_per_minute
       .map_or_else(|| "<disabled>".to_string(), |n| n.to_string())
   );
+  println!(
+    "  reservations:                     {}",
+    configuration.server.reservations.as_ref().map_or_else(
+      || "<disabled>".to_string(),
+      |r| format!(
+        "{} (every {}s as '{}')",
+        r.state_file, r.check_interval_secs, r.service_account_user
+      )
+    )
+  );
+  println!(
+    "  hardware_snapshots:               {}",
+    configuration
+      .server
+      .hardware_snapshots
+      .as_ref()
+      .map_or_else(
+        || "<disabled>".to_string(),
+        |h| match h.watch.as_ref() {
+          Some(w) => format!(
+            "{} (watching {} group(s) every {}s as '{}')",
+            h.dir,
+            w.groups.len(),
+            w.interval_secs,
+            w.service_account_user
+          ),
+          None => h.dir.clone(),
+        }
+      )
+  );
+  println!(
+    "  session_logs:                     {}",
+    configuration.server.session_logs.as_ref().map_or_else(
+      || "<disabled>".to_string(),
+      |l| format!(
+        "{} (retention {}, max {})",
+        l.dir,
+        if l.retention_days == 0 {
+          "forever".to_string()
+        } else {
+          format!("{}d", l.retention_days)
+        },
+        l.max_archives
+          .map_or_else(|| "unlimited".to_string(), |n| n.to_string())
+      )
+    )
+  );
+  println!(
+    "  ephemeral_envs:                   {}",
+    configuration.server.ephemeral_envs.as_ref().map_or_else(
+      || "<disabled>".to_string(),
+      |e| format!(
+        "{} (default TTL {}s, every {}s as '{}')",
+        e.state_file,
+        e.default_ttl_secs,
+        e.check_interval_secs,
+        e.service_account_user
+      )
+    )
+  );
   println!("  log_filter:                       {}", configuration.log);
   println!();
   println!("[auditor]");
@@ -352,6 +420,170 @@ async fn run_server(
     None => None,
   };
 
+  // Reservations need the service-account password up front: a reaper
+  // that discovers a missing secret only when the first reservation
+  // expires would leave nodes pinned past their deadline.
+  let reservations = match configuration.server.reservations.as_ref() {
+    Some(r) => {
+      let password =
+        std::env::var(&r.service_account_password_env).map_err(|_| {
+          format!(
+            "[server.reservations] service_account_password_env names \
+             '{}', which is not set. Export the service account's \
+             password there or remove the section to disable \
+             reservations.",
+            r.service_account_password_env
+          )
+        })?;
+      let store = ReservationStore::open(&r.state_file).map_err(|e| {
+        format!(
+          "[server.reservations] state_file '{}' could not be loaded: {e}",
+          r.state_file
+        )
+      })?;
+      Some(Reservations {
+        store,
+        service_account: ServiceAccount {
+          username: r.service_account_user.clone(),
+          password,
+        },
+        check_interval: std::time::Duration::from_secs(
+          r.check_interval_secs.max(1),
+        ),
+      })
+    }
+    None => None,
+  };
+
+  // Same up-front password check as reservations: a watcher that
+  // cannot log in would silently stop reporting changes.
+  let hardware_snapshots =
+    match configuration.server.hardware_snapshots.as_ref() {
+      Some(h) => {
+        let store = SnapshotStore::open(&h.dir).map_err(|e| {
+          format!(
+            "[server.hardware_snapshots] dir '{}' could not be created: {e}",
+            h.dir
+          )
+        })?;
+        let watch = match h.watch.as_ref() {
+          Some(w) => {
+            if let Some(g) = w
+              .groups
+              .iter()
+              .find(|g| !configuration.sites.contains_key(&g.site))
+            {
+              return Err(
+                format!(
+                  "[server.hardware_snapshots.watch] group '{}' names site \
+                   '{}', which has no [sites.{}] block.",
+                  g.group, g.site, g.site
+                )
+                .into(),
+              );
+            }
+            let password = std::env::var(&w.service_account_password_env)
+              .map_err(|_| {
+                format!(
+                  "[server.hardware_snapshots.watch] \
+                   service_account_password_env names '{}', which is not \
+                   set. Export the service account's password there or \
+                   remove the section to disable the watch.",
+                  w.service_account_password_env
+                )
+              })?;
+            Some(SnapshotWatch {
+              targets: w
+                .groups
+                .iter()
+                .map(|g| (g.site.clone(), g.group.clone()))
+                .collect(),
+              interval: std::time::Duration::from_secs(w.interval_secs.max(1)),
+              service_account: ServiceAccount {
+                username: w.service_account_user.clone(),
+                password,
+              },
+            })
+          }
+          None => None,
+        };
+        Some(HardwareSnapshots { store, watch })
+      }
+      None => None,
+    };
+
+  // Same up-front password check as reservations: an archiver that
+  // cannot log in would silently archive nothing.
+  let session_logs = match configuration.server.session_logs.as_ref() {
+    Some(l) => {
+      let password =
+        std::env::var(&l.service_account_password_env).map_err(|_| {
+          format!(
+            "[server.session_logs] service_account_password_env names \
+             '{}', which is not set. Export the service account's \
+             password there or remove the section to disable the session \
+             log archive.",
+            l.service_account_password_env
+          )
+        })?;
+      let store = SessionLogStore::open(
+        &l.dir,
+        (l.retention_days > 0)
+          .then(|| std::time::Duration::from_secs(l.retention_days * 86_400)),
+        l.max_archives,
+      )
+      .map_err(|e| {
+        format!(
+          "[server.session_logs] dir '{}' could not be opened: {e}",
+          l.dir
+        )
+      })?;
+      Some(SessionLogs {
+        store,
+        service_account: ServiceAccount {
+          username: l.service_account_user.clone(),
+          password,
+        },
+      })
+    }
+    None => None,
+  };
+
+  // Same up-front password check as reservations: environments would
+  // outlive their TTL if the reaper could not log in.
+  let ephemeral_envs = match configuration.server.ephemeral_envs.as_ref() {
+    Some(e) => {
+      let password =
+        std::env::var(&e.service_account_password_env).map_err(|_| {
+          format!(
+            "[server.ephemeral_envs] service_account_password_env names \
+             '{}', which is not set. Export the service account's \
+             password there or remove the section to disable ephemeral \
+             environment expiry.",
+            e.service_account_password_env
+          )
+        })?;
+      let store = EphemeralEnvStore::open(&e.state_file).map_err(|err| {
+        format!(
+          "[server.ephemeral_envs] state_file '{}' could not be loaded: {err}",
+          e.state_file
+        )
+      })?;
+      Some(EphemeralEnvs {
+        store,
+        default_ttl: std::time::Duration::from_secs(e.default_ttl_secs.max(1)),
+        service_account: ServiceAccount {
+          username: e.service_account_user.clone(),
+          password,
+        },
+        check_interval: std::time::Duration::from_secs(
+          e.check_interval_secs.max(1),
+        ),
+      })
+    }
+    None => None,
+  };
+
   print_startup_summary(
     &configuration,
     &listen_addr,
@@ -432,6 +664,10 @@ async fn run_server(
     request_timeout,
     shutdown_grace_period,
     migrate_backup_root,
+    reservations,
+    hardware_snapshots,
+    session_logs,
+    ephemeral_envs,
   });
 
   server::start_server(
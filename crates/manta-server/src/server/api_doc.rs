@@ -34,6 +34,7 @@ use super::handlers;
     handlers::get_redfish_endpoints,
     handlers::get_groups_nodes,
     handlers::get_groups_hardware,
+    handlers::get_groups_prometheus_sd,
     handlers::get_clusters_deprecated,
     handlers::get_hardware_clusters_deprecated,
     handlers::get_hardware_nodes_list,
@@ -50,9 +51,11 @@ use super::handlers;
     handlers::add_redfish_endpoint,
     handlers::update_redfish_endpoint,
     handlers::delete_session,
+    handlers::delete_sessions,
     handlers::delete_images,
     handlers::delete_configurations,
     handlers::create_session,
+    handlers::rerun_session,
     handlers::apply_boot_config,
     handlers::apply_runtime_configuration,
     handlers::apply_kernel_parameters,
@@ -62,10 +65,13 @@ use super::handlers;
     handlers::migrate_backup,
     handlers::migrate_restore,
     handlers::create_ephemeral_env,
+    handlers::get_ephemeral_envs,
+    handlers::delete_ephemeral_env,
     handlers::post_power,
     handlers::get_power_transition,
     handlers::post_template_session,
     handlers::get_session_logs,
+    handlers::get_session_log_archive,
     handlers::post_sat_configuration,
     handlers::post_sat_image_cfs_session,
     handlers::post_sat_image_stamp,
@@ -73,11 +79,16 @@ use super::handlers;
     handlers::add_hw_component,
     handlers::delete_hw_component,
     handlers::apply_hw_configuration,
+    handlers::get_reservations,
+    handlers::post_hardware_snapshot,
+    handlers::get_hardware_snapshots,
+    handlers::get_hardware_snapshot,
     handlers::console_node_ws,
     handlers::console_session_ws,
     handlers::auth_token,
     handlers::auth_validate,
     handlers::get_available_groups,
+    handlers::get_site_capabilities,
   ),
   components(schemas(
     handlers::ErrorResponse,
@@ -86,6 +97,14 @@ use super::handlers;
     handlers::AddNodesToGroupResponse,
     handlers::DeleteBootParametersRequest,
     handlers::CreateSessionRequest,
+    manta_shared::types::api::session::RemoteRepoRef,
+    handlers::RerunSessionRequest,
+    handlers::RerunSessionResponse,
+    handlers::DeleteSessionsResponse,
+    handlers::SessionsDeletionPlan,
+    handlers::SessionDeletionItem,
+    handlers::BootImageInUse,
+    handlers::SessionDeletionResult,
     handlers::ApplyBootConfigRequest,
     handlers::ApplyRuntimeConfigurationRequest,
     handlers::KernelParamOp,
@@ -105,17 +124,29 @@ use super::handlers;
     handlers::AddHwComponentRequest,
     handlers::DeleteHwComponentRequest,
     handlers::HwClusterMode,
+    handlers::HwLocality,
+    handlers::HwSolver,
     handlers::ApplyHwConfigurationRequest,
+    handlers::HwReservation,
+    handlers::TakeHardwareSnapshotRequest,
+    handlers::HardwareSnapshot,
+    handlers::HardwareSnapshotInfo,
+    handlers::ArchivedSessionLog,
+    handlers::NodeHardware,
     manta_shared::types::auth::AuthTokenRequest,
     manta_shared::types::auth::AuthTokenResponse,
     manta_shared::types::auth::ValidateTokenRequest,
     crate::service::boot_parameters::UpdateBootParametersParams,
     manta_shared::types::api::redfish_endpoints::UpdateRedfishEndpointParams,
     manta_shared::types::dto::NodeDetails,
+    manta_shared::types::api::group::PrometheusTargetGroup,
     manta_shared::types::api::responses::CreatedResponse,
     manta_shared::types::api::responses::AddNodeResponse,
     manta_shared::types::api::responses::CreateSessionResponse,
     manta_shared::types::api::responses::EphemeralEnvResponse,
+    handlers::EphemeralEnv,
+    handlers::SiteCapabilities,
+    handlers::SiteFeature,
     manta_shared::types::api::responses::CompletedResponse,
     manta_shared::types::api::responses::MigrateNodesPairResult,
     manta_shared::types::api::responses::MigrateNodesResponse,
@@ -29,10 +29,10 @@
 //!
 //! Service functions reach the backend through `infra.backend.*`
 //! (calling the trait method belonging to the desired interface, e.g.
-//! `infra.backend.get_bootparameters(...)`). When a function needs to
-//! build a direct CSM HTTP client — e.g. for IMS customize jobs that
-//! aren't routed through the dispatcher — it uses
-//! `infra.shasta_base_url` and `infra.shasta_root_cert` together.
+//! `infra.backend.get_bootparameters(...)`). Trait methods whose
+//! backend client doesn't expose its endpoint — e.g. the IMS jobs
+//! behind `EphemeralEnvTrait` — take `infra.shasta_base_url` and
+//! `infra.shasta_root_cert` as arguments.
 //! Vault- and k8s-dependent paths gate on `infra.vault_base_url` /
 //! `infra.k8s_api_url` being `Some`; when either is `None` the handler
 //! returns 501.
@@ -2,14 +2,20 @@
 //!
 //! Audit emission is opt-in via the `[auditor.kafka]` section of
 //! `server.toml`; when absent, [`super::super::ServerState::auditor`]
-//! is `None` and [`send_auth_audit`] becomes a no-op. Failures inside
-//! [`send_auth_audit`] log a warning and never bubble up, so an
-//! unreachable Kafka broker cannot abort the outer auth flow.
+//! is `None` and [`send_auth_audit`] / [`send_reservation_audit`] /
+//! [`send_hardware_change_audit`] become no-ops. Failures inside any of
+//! them log a warning and never bubble up, so an unreachable Kafka
+//! broker cannot abort the outer auth flow, a reservation release or a
+//! hardware watch pass.
 
 use manta_shared::common::error::MantaError;
 use serde::{Deserialize, Serialize};
 
+use manta_shared::types::hardware_diff::HardwareDiff;
+
 use crate::server::common::kafka::Kafka;
+use crate::service::hardware_snapshot::HardwareSnapshot;
+use crate::service::reservation::HwReservation;
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
 /// Wraps a [`Kafka`] instance for sending audit messages.
@@ -90,6 +96,82 @@ pub async fn send_auth_audit(
   .await;
 }
 
+/// Build the JSON payload that [`send_reservation_audit`] sends to
+/// Kafka. `event` is `reservation_created`, `reservation_released` or
+/// `reservation_release_failed`; `detail` carries the released xnames
+/// or the failure message.
+pub(crate) fn build_reservation_audit_message(
+  event: &str,
+  reservation: &HwReservation,
+  detail: serde_json::Value,
+) -> serde_json::Value {
+  serde_json::json!({
+    "event": event,
+    "reservation_id": reservation.id,
+    "site": reservation.site,
+    "owner": reservation.owner,
+    "target_cluster": reservation.target_cluster,
+    "parent_cluster": reservation.parent_cluster,
+    "nodes": reservation.nodes,
+    "expires_at": reservation.expires_at,
+    "detail": detail,
+  })
+}
+
+/// Send a structured audit event for a reservation's lifecycle.
+///
+/// Kafka-only and fire-and-forget, like [`send_auth_audit`]: the
+/// reaper has already moved the nodes by the time it reports, so a
+/// broker outage must not undo or retry the release.
+pub async fn send_reservation_audit(
+  kafka_opt: Option<&Kafka>,
+  event: &str,
+  reservation: &HwReservation,
+  detail: serde_json::Value,
+) {
+  let Some(kafka) = kafka_opt else { return };
+  send_audit_message(
+    kafka,
+    build_reservation_audit_message(event, reservation, detail),
+  )
+  .await;
+}
+
+/// Build the JSON payload that [`send_hardware_change_audit`] sends to
+/// Kafka: the `hardware_changed` event for one group, naming the
+/// baseline and new snapshots and carrying the diff between them.
+pub(crate) fn build_hardware_change_audit_message(
+  before: &HardwareSnapshot,
+  after: &HardwareSnapshot,
+  diff: &HardwareDiff,
+) -> serde_json::Value {
+  serde_json::json!({
+    "event": "hardware_changed",
+    "site": after.site,
+    "group": after.group,
+    "from_snapshot": before.id,
+    "to_snapshot": after.id,
+    "changes": diff.changes,
+    "unreadable": diff.unreadable,
+  })
+}
+
+/// Send a `hardware_changed` audit event raised by the hardware
+/// watcher. Kafka-only and fire-and-forget, like the other senders.
+pub async fn send_hardware_change_audit(
+  kafka_opt: Option<&Kafka>,
+  before: &HardwareSnapshot,
+  after: &HardwareSnapshot,
+  diff: &HardwareDiff,
+) {
+  let Some(kafka) = kafka_opt else { return };
+  send_audit_message(
+    kafka,
+    build_hardware_change_audit_message(before, after, diff),
+  )
+  .await;
+}
+
 #[cfg(test)]
 mod tests {
   use super::*;
@@ -131,4 +213,77 @@ mod tests {
     assert_eq!(msg["site"], "");
     assert_eq!(msg["event"], "auth_attempt");
   }
+
+  // ---- build_reservation_audit_message ----
+
+  #[test]
+  fn reservation_audit_has_expected_wire_shape() {
+    let reservation = HwReservation {
+      id: "3f2a".to_string(),
+      site: "alps".to_string(),
+      target_cluster: "tenant-a".to_string(),
+      parent_cluster: "nodes_free".to_string(),
+      pattern: "a100>=8".to_string(),
+      nodes: vec!["x1000c0s0b0n0".to_string()],
+      owner: "alice".to_string(),
+      created_at: "2026-10-01T08:00:00Z".to_string(),
+      expires_at: "2026-10-04T08:00:00Z".to_string(),
+    };
+    let msg = build_reservation_audit_message(
+      "reservation_released",
+      &reservation,
+      serde_json::json!({ "released": ["x1000c0s0b0n0"] }),
+    );
+    assert_eq!(msg["event"], "reservation_released");
+    assert_eq!(msg["reservation_id"], "3f2a");
+    assert_eq!(msg["owner"], "alice");
+    assert_eq!(msg["target_cluster"], "tenant-a");
+    assert_eq!(msg["parent_cluster"], "nodes_free");
+    assert_eq!(msg["nodes"][0], "x1000c0s0b0n0");
+    assert_eq!(msg["detail"]["released"][0], "x1000c0s0b0n0");
+  }
+
+  // ---- build_hardware_change_audit_message ----
+
+  #[test]
+  fn hardware_change_audit_has_expected_wire_shape() {
+    use manta_shared::types::api::hardware::NodeHardware;
+    use manta_shared::types::hardware_diff::diff_snapshots;
+
+    let before = HardwareSnapshot {
+      id: "s1".to_string(),
+      site: "alps".to_string(),
+      group: "compute".to_string(),
+      taken_at: "2026-10-01T08:00:00Z".to_string(),
+      taken_by: "manta-watch".to_string(),
+      nodes: [(
+        "x1000c0s0b0n0".to_string(),
+        NodeHardware {
+          memory_mib: 2048,
+          dimms: 2,
+          ..NodeHardware::default()
+        },
+      )]
+      .into(),
+      unreadable: Vec::new(),
+    };
+    let mut after = before.clone();
+    after.id = "s2".to_string();
+    after.nodes.get_mut("x1000c0s0b0n0").unwrap().dimms = 1;
+
+    let msg = build_hardware_change_audit_message(
+      &before,
+      &after,
+      &diff_snapshots(&before, &after),
+    );
+    assert_eq!(msg["event"], "hardware_changed");
+    assert_eq!(msg["site"], "alps");
+    assert_eq!(msg["group"], "compute");
+    assert_eq!(msg["from_snapshot"], "s1");
+    assert_eq!(msg["to_snapshot"], "s2");
+    assert_eq!(msg["changes"][0]["xname"], "x1000c0s0b0n0");
+    assert_eq!(msg["changes"][0]["change"], "changed");
+    assert_eq!(msg["changes"][0]["components"][0]["component"], "dimms");
+    assert_eq!(msg["changes"][0]["components"][0]["after"], 1);
+  }
 }
@@ -1,6 +1,7 @@
 //! Vault client used by handlers that need backend-specific secrets
 //! (Gitea token for `create_session`, Kubernetes credentials for the
-//! console and log-streaming handlers).
+//! console and log-streaming handlers, `vault://` references in SAT
+//! entries).
 //!
 //! ## Token-fetch flow
 //!
@@ -138,6 +139,34 @@ pub mod http_client {
     Ok(secret_value["data"].clone())
   }
 
+  /// Read the K/V v2 secret stored at `manta/data/<site_name>/<path>`
+  /// and return its field map (the inner `.data.data` object).
+  ///
+  /// `vault_auth_token` comes from [`auth_oidc_jwt`]; callers reading
+  /// several secrets for one request authenticate once and reuse it.
+  /// Used by `service::secrets` to resolve SAT secret references.
+  ///
+  /// # Errors
+  ///
+  /// Any error produced by [`get_secret`].
+  pub async fn get_site_secret(
+    vault_auth_token: &str,
+    vault_base_url: &str,
+    site_name: &str,
+    path: &str,
+  ) -> Result<Value, Error> {
+    let vault_secret = get_secret(
+      vault_auth_token,
+      vault_base_url,
+      &format!(
+        "{VAULT_API_PREFIX}/{VAULT_SECRET_PATH_PREFIX}/{site_name}/{path}"
+      ),
+    )
+    .await?;
+
+    Ok(vault_secret["data"].clone())
+  }
+
   /// Retrieve the Gitea VCS token from Vault.
   ///
   /// Reads `manta/data/<site_name>/vcs.data.token` after exchanging
@@ -0,0 +1,117 @@
+//! Background task that deletes expired ephemeral environments.
+//!
+//! Spawned by [`super::start_server`] when `[server.ephemeral_envs]` is
+//! configured. Every `check_interval` it looks for environments past
+//! their expiry and, per site, authenticates as the service account and
+//! deletes them through the site's [`EphemeralEnvTrait`]. A deleted
+//! environment (or one already gone) is dropped from the store; a
+//! failed one stays and is retried on the next pass.
+
+use std::collections::BTreeMap;
+use std::sync::Arc;
+
+use manta_backend_dispatcher::error::Error;
+
+use super::ServerState;
+use crate::backend_dispatcher::EphemeralEnvTrait;
+use crate::service::ephemeral_env::{self, EphemeralEnvLease, EphemeralEnvs};
+
+/// Start the reaper loop on the current Tokio runtime. No-op when
+/// ephemeral environment expiry is disabled.
+pub fn spawn(state: Arc<ServerState>) {
+  let Some(check_interval) =
+    state.ephemeral_envs.as_ref().map(|e| e.check_interval)
+  else {
+    return;
+  };
+  tokio::spawn(async move {
+    let mut ticker = tokio::time::interval(check_interval);
+    ticker.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+    loop {
+      ticker.tick().await;
+      if let Some(envs) = state.ephemeral_envs.as_ref() {
+        delete_expired(&state, envs).await;
+      }
+    }
+  });
+}
+
+/// One reaper pass: delete every due environment, grouped by site so
+/// each site's service-account token is fetched once.
+async fn delete_expired(state: &ServerState, envs: &EphemeralEnvs) {
+  let due = ephemeral_env::due_for_deletion(
+    &envs.store.list().await,
+    chrono::Utc::now(),
+  );
+  let mut by_site: BTreeMap<String, Vec<EphemeralEnvLease>> = BTreeMap::new();
+  for lease in due {
+    by_site.entry(lease.site.clone()).or_default().push(lease);
+  }
+
+  for (site, due) in by_site {
+    let infra = match state.infra_context(&site) {
+      Ok(infra) => infra,
+      Err(e) => {
+        tracing::warn!(
+          "{} ephemeral environment(s) on site '{site}' expired but the \
+           site is unavailable, retrying next pass: {e}",
+          due.len()
+        );
+        continue;
+      }
+    };
+    let account = &envs.service_account;
+    let token = match crate::service::auth::get_api_token(
+      &infra,
+      &account.username,
+      &account.password,
+    )
+    .await
+    {
+      Ok(token) => token,
+      Err(e) => {
+        tracing::warn!(
+          "ephemeral environment reaper: service account '{}' login on \
+           site '{site}' failed, retrying next pass: {e}",
+          account.username
+        );
+        continue;
+      }
+    };
+
+    for lease in &due {
+      match infra
+        .backend
+        .delete_ephemeral_env(
+          infra.shasta_base_url,
+          infra.shasta_root_cert,
+          &token,
+          &lease.id,
+          None,
+        )
+        .await
+      {
+        Ok(()) | Err(Error::NotFound(_)) => {
+          tracing::info!(
+            "ephemeral environment {} of '{}' expired at {}: deleted",
+            lease.id,
+            lease.owner,
+            lease.expires_at
+          );
+          if let Err(e) = envs.store.remove(&site, &lease.id).await {
+            tracing::error!(
+              "ephemeral environment {} deleted but could not be removed \
+               from the state file: {e}",
+              lease.id
+            );
+          }
+        }
+        Err(e) => tracing::warn!(
+          "ephemeral environment {} expired but could not be deleted, \
+           retrying next pass: {e}",
+          lease.id
+        ),
+      }
+    }
+  }
+}
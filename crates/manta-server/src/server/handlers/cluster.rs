@@ -2,6 +2,8 @@
 //!
 //! - `GET /v2/groups/nodes` → [`get_groups_nodes`] (canonical) —
 //!   wraps `service::cluster::get_cluster_nodes`.
+//! - `GET /v2/groups/prometheus-sd` → [`get_groups_prometheus_sd`] —
+//!   the same membership as Prometheus HTTP service-discovery targets.
 //! - `GET /v2/clusters` → [`get_clusters_deprecated`] —
 //!   deprecated alias; logs a server-side warning and forwards.
 
@@ -46,6 +48,45 @@ pub async fn get_groups_nodes(
   Ok(Json(nodes))
 }
 
+// ---------------------------------------------------------------------------
+// GET /v2/groups/prometheus-sd
+// ---------------------------------------------------------------------------
+
+pub use manta_shared::types::api::queries::PrometheusSdQuery;
+
+/// GET /groups/prometheus-sd — group members as Prometheus
+/// `http_sd_config` targets.
+#[utoipa::path(get, path = "/groups/prometheus-sd", tag = "groups",
+  params(PrometheusSdQuery, SiteHeader),
+  security(("bearerAuth" = [])),
+  responses(
+    (status = 200, description = "Prometheus HTTP SD target groups", body = Vec<manta_shared::types::api::group::PrometheusTargetGroup>),
+    (status = 400, description = "Group not accessible",              body = ErrorResponse),
+    (status = 401, description = "Unauthorized",                      body = ErrorResponse),
+    (status = 500, description = "Internal error",                    body = ErrorResponse),
+  )
+)]
+#[tracing::instrument(skip_all)]
+pub async fn get_groups_prometheus_sd(
+  ctx: RequestCtx,
+  Query(q): Query<PrometheusSdQuery>,
+) -> Result<impl IntoResponse, (StatusCode, Json<ErrorResponse>)> {
+  let infra = ctx.infra();
+
+  let targets = service::cluster::get_prometheus_targets(
+    &infra,
+    &ctx.token,
+    q.group.as_deref(),
+    q.port.unwrap_or(
+      manta_shared::types::inventory_export::DEFAULT_PROMETHEUS_PORT,
+    ),
+  )
+  .await
+  .map_err(to_handler_error)?;
+
+  Ok(Json(targets))
+}
+
 /// DEPRECATED alias for `GET /groups/nodes`. Logs a server-side warning,
 /// then delegates to the canonical handler. Old path kept for one
 /// release.
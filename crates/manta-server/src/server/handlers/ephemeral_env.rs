@@ -1,15 +1,46 @@
-//! Ephemeral environment handler.
+//! Ephemeral environment handlers.
 //!
-//! `POST /v2/ephemeral-env` → [`create_ephemeral_env`] — wraps
-//! `service::ephemeral_env::exec`. Launches a short-lived CFS
-//! environment booted from a caller-supplied IMS image and returns
-//! the hostname the user can attach to via the console endpoint.
+//! - `POST   /v2/ephemeral-env`      → [`create_ephemeral_env`] —
+//!   launches a short-lived CFS environment booted from a
+//!   caller-supplied IMS image and returns the hostname the user can
+//!   SSH into, optionally expiring after `ttl_secs`.
+//! - `GET    /v2/ephemeral-env`      → [`get_ephemeral_envs`] — the
+//!   caller's environments.
+//! - `DELETE /v2/ephemeral-env/{id}` → [`delete_ephemeral_env`]
+//!
+//! All wrap `service::ephemeral_env`, which reaches the site's
+//! backend through `EphemeralEnvTrait`; a backend without ephemeral
+//! environments (OpenCHAMI) answers 501 via [`to_ephemeral_env_error`].
+//! Expiry needs `[server.ephemeral_envs]`; without it a requested TTL
+//! returns 501 too.
+
+use std::time::Duration;
 
-use axum::{Json, http::StatusCode, response::IntoResponse};
+use axum::{Json, extract::Path, http::StatusCode, response::IntoResponse};
+use manta_backend_dispatcher::error::Error as BackendError;
 use serde::Deserialize;
 use utoipa::ToSchema;
 
-use super::{ErrorResponse, RequestCtx, SiteHeader, to_handler_error};
+use super::{
+  ErrorResponse, RequestCtx, SiteHeader, require_ephemeral_envs,
+  to_handler_error,
+};
+use crate::service::ephemeral_env;
+pub use manta_shared::types::api::responses::EphemeralEnv;
+
+/// [`to_handler_error`], except that a backend without ephemeral
+/// environments is a 501 rather than the usual 400 for
+/// `UnsupportedBackend`: the request is fine, the site can't serve it.
+fn to_ephemeral_env_error(
+  e: BackendError,
+) -> (StatusCode, Json<ErrorResponse>) {
+  match e {
+    BackendError::UnsupportedBackend(error) => {
+      (StatusCode::NOT_IMPLEMENTED, Json(ErrorResponse { error }))
+    }
+    e => to_handler_error(e),
+  }
+}
 
 // ---------------------------------------------------------------------------
 // POST /v2/ephemeral-env — Create ephemeral CFS environment
@@ -20,6 +51,11 @@ use super::{ErrorResponse, RequestCtx, SiteHeader, to_handler_error};
 pub struct CreateEphemeralEnvRequest {
   /// IMS image ID to boot the ephemeral environment from.
   pub image_id: String,
+  /// Seconds after which the server deletes the environment. Defaults
+  /// to the server's `default_ttl_secs`; needs
+  /// `[server.ephemeral_envs]`.
+  #[serde(default)]
+  pub ttl_secs: Option<u64>,
 }
 
 /// `POST /v2/ephemeral-env` — launch an ephemeral CFS environment from an IMS image.
@@ -29,8 +65,10 @@ pub struct CreateEphemeralEnvRequest {
   security(("bearerAuth" = [])),
   responses(
     (status = 201, description = "Ephemeral env created", body = manta_shared::types::api::responses::EphemeralEnvResponse),
+    (status = 400, description = "Bad request",           body = ErrorResponse),
     (status = 401, description = "Unauthorized",          body = ErrorResponse),
     (status = 500, description = "Internal error",        body = ErrorResponse),
+    (status = 501, description = "TTL requested but expiry not configured, or backend without ephemeral environments", body = ErrorResponse),
   )
 )]
 #[tracing::instrument(skip_all)]
@@ -38,16 +76,100 @@ pub async fn create_ephemeral_env(
   ctx: RequestCtx,
   Json(body): Json<CreateEphemeralEnvRequest>,
 ) -> Result<impl IntoResponse, (StatusCode, Json<ErrorResponse>)> {
-  tracing::info!("create_ephemeral_env image_id={}", body.image_id);
+  tracing::info!(
+    "create_ephemeral_env image_id={} ttl_secs={:?}",
+    body.image_id,
+    body.ttl_secs
+  );
+  if body.ttl_secs == Some(0) {
+    return Err((
+      StatusCode::BAD_REQUEST,
+      Json(ErrorResponse {
+        error: "ttl_secs must be positive".to_string(),
+      }),
+    ));
+  }
+  let envs = match body.ttl_secs {
+    Some(_) => Some(require_ephemeral_envs(&ctx.state)?),
+    None => ctx.state.ephemeral_envs.as_ref(),
+  };
+  let infra = ctx.infra();
+
+  let response = ephemeral_env::exec(
+    &infra,
+    &ctx.token,
+    &body.image_id,
+    body.ttl_secs.map(Duration::from_secs),
+    envs,
+  )
+  .await
+  .map_err(to_ephemeral_env_error)?;
+
+  Ok((StatusCode::CREATED, Json(response)))
+}
+
+// ---------------------------------------------------------------------------
+// GET /v2/ephemeral-env — The caller's ephemeral environments
+// ---------------------------------------------------------------------------
+
+/// `GET /v2/ephemeral-env` — list the caller's ephemeral environments.
+#[utoipa::path(get, path = "/ephemeral-env", tag = "ephemeral-env",
+  params(SiteHeader),
+  security(("bearerAuth" = [])),
+  responses(
+    (status = 200, description = "The caller's environments, oldest first", body = Vec<EphemeralEnv>),
+    (status = 401, description = "Unauthorized",   body = ErrorResponse),
+    (status = 404, description = "Caller has no SSH public key in IMS", body = ErrorResponse),
+    (status = 500, description = "Internal error", body = ErrorResponse),
+    (status = 501, description = "Backend without ephemeral environments", body = ErrorResponse),
+  )
+)]
+#[tracing::instrument(skip_all)]
+pub async fn get_ephemeral_envs(
+  ctx: RequestCtx,
+) -> Result<impl IntoResponse, (StatusCode, Json<ErrorResponse>)> {
   let infra = ctx.infra();
+  let envs = ephemeral_env::list(
+    &infra,
+    &ctx.token,
+    ctx.state.ephemeral_envs.as_ref().map(|e| &e.store),
+  )
+  .await
+  .map_err(to_ephemeral_env_error)?;
+  Ok(Json(envs))
+}
 
-  let hostname =
-    crate::service::ephemeral_env::exec(&infra, &ctx.token, &body.image_id)
-      .await
-      .map_err(to_handler_error)?;
+// ---------------------------------------------------------------------------
+// DELETE /v2/ephemeral-env/{id}
+// ---------------------------------------------------------------------------
 
-  Ok((
-    StatusCode::CREATED,
-    Json(serde_json::json!({ "hostname": hostname })),
-  ))
+/// `DELETE /v2/ephemeral-env/{id}` — delete one of the caller's
+/// ephemeral environments (any, for admins).
+#[utoipa::path(delete, path = "/ephemeral-env/{id}", tag = "ephemeral-env",
+  params(("id" = String, Path, description = "IMS customize job id"), SiteHeader),
+  security(("bearerAuth" = [])),
+  responses(
+    (status = 204, description = "Environment deleted"),
+    (status = 401, description = "Unauthorized",   body = ErrorResponse),
+    (status = 404, description = "Not found",      body = ErrorResponse),
+    (status = 500, description = "Internal error", body = ErrorResponse),
+    (status = 501, description = "Backend without ephemeral environments", body = ErrorResponse),
+  )
+)]
+#[tracing::instrument(skip_all)]
+pub async fn delete_ephemeral_env(
+  ctx: RequestCtx,
+  Path(id): Path<String>,
+) -> Result<impl IntoResponse, (StatusCode, Json<ErrorResponse>)> {
+  tracing::info!("delete_ephemeral_env id={}", id);
+  let infra = ctx.infra();
+  ephemeral_env::delete(
+    &infra,
+    &ctx.token,
+    &id,
+    ctx.state.ephemeral_envs.as_ref().map(|e| &e.store),
+  )
+  .await
+  .map_err(to_ephemeral_env_error)?;
+  Ok(StatusCode::NO_CONTENT)
 }
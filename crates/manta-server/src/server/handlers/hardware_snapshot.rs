@@ -0,0 +1,117 @@
+//! Hardware snapshot handlers.
+//!
+//! - `POST /v2/hardware-snapshots`      → [`post_hardware_snapshot`]
+//! - `GET  /v2/hardware-snapshots`      → [`get_hardware_snapshots`]
+//! - `GET  /v2/hardware-snapshots/{id}` → [`get_hardware_snapshot`]
+//!
+//! All wrap `crate::service::hardware_snapshot::*`. Snapshots are only
+//! stored and returned here; `manta diff hardware` compares them
+//! client-side, and the scheduled watch lives in
+//! [`crate::server::hardware_watch`].
+
+use axum::{
+  Json,
+  extract::{Path, Query},
+  http::StatusCode,
+  response::IntoResponse,
+};
+
+use super::{
+  ErrorResponse, RequestCtx, SiteHeader, require_hardware_snapshots,
+  to_handler_error,
+};
+use crate::service;
+
+pub use manta_shared::types::api::hardware::{
+  HardwareSnapshot, HardwareSnapshotInfo, NodeHardware,
+  TakeHardwareSnapshotRequest,
+};
+pub use manta_shared::types::api::queries::HardwareSnapshotsQuery;
+
+/// `POST /v2/hardware-snapshots` — snapshot a group's hardware inventory.
+#[utoipa::path(post, path = "/hardware-snapshots", tag = "hardware",
+  request_body = TakeHardwareSnapshotRequest,
+  params(SiteHeader),
+  security(("bearerAuth" = [])),
+  responses(
+    (status = 201, description = "Snapshot stored",                body = HardwareSnapshotInfo),
+    (status = 400, description = "Group not accessible",           body = ErrorResponse),
+    (status = 401, description = "Unauthorized",                   body = ErrorResponse),
+    (status = 501, description = "Hardware snapshots not configured", body = ErrorResponse),
+  )
+)]
+#[tracing::instrument(skip_all)]
+pub async fn post_hardware_snapshot(
+  ctx: RequestCtx,
+  Json(body): Json<TakeHardwareSnapshotRequest>,
+) -> Result<impl IntoResponse, (StatusCode, Json<ErrorResponse>)> {
+  let snapshots = require_hardware_snapshots(&ctx.state)?;
+  let infra = ctx.infra();
+  let info = service::hardware_snapshot::create_snapshot(
+    &infra,
+    &ctx.token,
+    &snapshots.store,
+    &body.group,
+  )
+  .await
+  .map_err(to_handler_error)?;
+  Ok((StatusCode::CREATED, Json(info)))
+}
+
+/// `GET /v2/hardware-snapshots` — list stored snapshots on the site.
+#[utoipa::path(get, path = "/hardware-snapshots", tag = "hardware",
+  params(HardwareSnapshotsQuery, SiteHeader),
+  security(("bearerAuth" = [])),
+  responses(
+    (status = 200, description = "Stored snapshots, newest first",     body = Vec<HardwareSnapshotInfo>),
+    (status = 401, description = "Unauthorized",                       body = ErrorResponse),
+    (status = 501, description = "Hardware snapshots not configured",  body = ErrorResponse),
+  )
+)]
+#[tracing::instrument(skip_all)]
+pub async fn get_hardware_snapshots(
+  ctx: RequestCtx,
+  Query(q): Query<HardwareSnapshotsQuery>,
+) -> Result<impl IntoResponse, (StatusCode, Json<ErrorResponse>)> {
+  let snapshots = require_hardware_snapshots(&ctx.state)?;
+  let infra = ctx.infra();
+  let list = service::hardware_snapshot::list_snapshots(
+    &infra,
+    &ctx.token,
+    &snapshots.store,
+    q.group.as_deref(),
+  )
+  .await
+  .map_err(to_handler_error)?;
+  Ok(Json(list))
+}
+
+/// `GET /v2/hardware-snapshots/{id}` — one stored snapshot with its
+/// per-node inventory.
+#[utoipa::path(get, path = "/hardware-snapshots/{id}", tag = "hardware",
+  params(("id" = String, Path, description = "Snapshot id"), SiteHeader),
+  security(("bearerAuth" = [])),
+  responses(
+    (status = 200, description = "Snapshot",                           body = HardwareSnapshot),
+    (status = 401, description = "Unauthorized",                       body = ErrorResponse),
+    (status = 404, description = "No such snapshot",                   body = ErrorResponse),
+    (status = 501, description = "Hardware snapshots not configured",  body = ErrorResponse),
+  )
+)]
+#[tracing::instrument(skip_all)]
+pub async fn get_hardware_snapshot(
+  ctx: RequestCtx,
+  Path(id): Path<String>,
+) -> Result<impl IntoResponse, (StatusCode, Json<ErrorResponse>)> {
+  let snapshots = require_hardware_snapshots(&ctx.state)?;
+  let infra = ctx.infra();
+  let snapshot = service::hardware_snapshot::get_snapshot(
+    &infra,
+    &ctx.token,
+    &snapshots.store,
+    &id,
+  )
+  .await
+  .map_err(to_handler_error)?;
+  Ok(Json(snapshot))
+}
@@ -7,16 +7,21 @@
 //! All wrap `crate::service::hw_cluster::*`. Each handler runs
 //! `service::authorization::validate_user_group_access` against both
 //! the target cluster and (where applicable) the parent cluster
-//! before mutating state.
+//! before mutating state. A pin carrying `expires_at` is also recorded
+//! as a reservation (`crate::service::reservation`).
 
 use axum::{Json, extract::Path, http::StatusCode, response::IntoResponse};
 
-use super::{ErrorResponse, RequestCtx, SiteHeader, to_handler_error};
+use super::{
+  ErrorResponse, RequestCtx, SiteHeader, require_reservations,
+  serialize_or_500, to_handler_error,
+};
+use crate::server::common::audit::send_reservation_audit;
 use crate::service;
 
 pub use manta_shared::types::api::hw_cluster::{
   AddHwComponentRequest, ApplyHwConfigurationRequest, DeleteHwComponentRequest,
-  HwClusterMode,
+  HwClusterMode, HwLocality, HwSolver,
 };
 
 /// `POST /v2/hardware-clusters/{target}/members` — move nodes matching a hardware pattern into a cluster.
@@ -157,8 +162,10 @@ pub async fn delete_hw_component(
   responses(
     // dry_run/real result union — kept as Value until the union shape is formalised
     (status = 200, description = "Configuration applied or preview", body = serde_json::Value),
+    (status = 400, description = "Invalid pattern or expires_at",    body = ErrorResponse),
     (status = 401, description = "Unauthorized",                     body = ErrorResponse),
     (status = 500, description = "Internal error",                   body = ErrorResponse),
+    (status = 501, description = "expires_at set but reservations not configured", body = ErrorResponse),
   )
 )]
 #[tracing::instrument(skip_all)]
@@ -188,6 +195,28 @@ pub async fn apply_hw_configuration(
   .await
   .map_err(to_handler_error)?;
 
+  // Validate the expiry before touching any group so a bad value (or
+  // a server without reservations) never leaves an untracked pin.
+  let now = chrono::Utc::now();
+  let reservation_plan = match body.expires_at.as_deref() {
+    Some(raw) => {
+      if body.mode != service::hw_cluster::HwClusterMode::Pin {
+        return Err(to_handler_error(
+          manta_backend_dispatcher::error::Error::BadRequest(
+            "'expires_at' is only valid when pinning".to_string(),
+          ),
+        ));
+      }
+      let reservations = require_reservations(&ctx.state)?;
+      let expires_at = service::reservation::parse_expiry(raw, now)
+        .map_err(to_handler_error)?;
+      let owner = service::reservation::owner_from_token(&ctx.token)
+        .map_err(to_handler_error)?;
+      Some((reservations, expires_at, owner))
+    }
+    None => None,
+  };
+
   let result = crate::service::hw_cluster::apply_hw_configuration(
     &infra,
     &ctx.token,
@@ -199,16 +228,60 @@ pub async fn apply_hw_configuration(
       dryrun: body.dry_run,
       create_target_group: body.create_target_hsm_group,
       delete_empty_parent_group: body.delete_empty_parent_hsm_group,
+      locality: body.locality,
+      solver: body.solver,
+      explain: body.explain,
     },
   )
   .await
   .map_err(to_handler_error)?;
 
-  Ok(Json(serde_json::json!({
+  let mut response = serde_json::json!({
     "dry_run": body.dry_run,
     "target_cluster": target,
     "target_nodes": result.target_nodes,
     "parent_cluster": body.parent_cluster,
     "parent_nodes": result.parent_nodes,
-  })))
+    "solver": result.solver,
+  });
+  if let Some(explanation) = result.explanation {
+    response["explanation"] = serialize_or_500(&explanation)?;
+  }
+  // A dry run, or a pin that added nothing, has nothing to release.
+  if let Some((reservations, expires_at, owner)) = reservation_plan
+    && !body.dry_run
+    && !result.nodes_added.is_empty()
+  {
+    let reservation = service::reservation::new_reservation(
+      &ctx.site_name,
+      &target,
+      &body.parent_cluster,
+      &body.pattern,
+      result.nodes_added,
+      &owner,
+      now,
+      expires_at,
+    );
+    reservations
+      .store
+      .insert(reservation.clone())
+      .await
+      .map_err(|e| {
+        to_handler_error(manta_backend_dispatcher::error::Error::Message(
+          format!(
+            "nodes were pinned but the reservation could not be recorded, \
+             so they will not be released automatically: {e}"
+          ),
+        ))
+      })?;
+    send_reservation_audit(
+      ctx.state.auditor.as_ref(),
+      "reservation_created",
+      &reservation,
+      serde_json::Value::Null,
+    )
+    .await;
+    response["reservation"] = serialize_or_500(&reservation)?;
+  }
+  Ok(Json(response))
 }
@@ -37,6 +37,7 @@ mod console;
 mod ephemeral_env;
 mod group;
 mod hardware;
+mod hardware_snapshot;
 mod hw_cluster;
 mod image;
 mod kernel_parameters;
@@ -44,9 +45,11 @@ mod migrate;
 mod node;
 mod power;
 mod redfish_endpoints;
+mod reservation;
 mod runtime_configuration;
 mod sat_file;
 mod session;
+mod site;
 mod template;
 
 pub use analysis::*;
@@ -58,6 +61,7 @@ pub use console::*;
 pub use ephemeral_env::*;
 pub use group::*;
 pub use hardware::*;
+pub use hardware_snapshot::*;
 pub use hw_cluster::*;
 pub use image::*;
 pub use kernel_parameters::*;
@@ -65,9 +69,11 @@ pub use migrate::*;
 pub use node::*;
 pub use power::*;
 pub use redfish_endpoints::*;
+pub use reservation::*;
 pub use runtime_configuration::*;
 pub use sat_file::*;
 pub use session::*;
+pub use site::*;
 pub use template::*;
 
 // ---------------------------------------------------------------------------
@@ -280,6 +286,18 @@ fn format_with_causes(e: &(dyn std::error::Error + 'static)) -> String {
 // ineffectual `Drop` here.
 #[allow(clippy::needless_pass_by_value)]
 pub fn to_handler_error(e: BackendError) -> (StatusCode, Json<ErrorResponse>) {
+  to_handler_error_redacted(e, &|s| s.to_string())
+}
+
+/// [`to_handler_error`] with `redact` applied to both the logged error
+/// chain and the response body. Used by handlers that substituted
+/// secrets into a backend payload (see `service::secrets`), since a
+/// backend error may echo that payload back.
+#[allow(clippy::needless_pass_by_value)]
+pub fn to_handler_error_redacted(
+  e: BackendError,
+  redact: &dyn Fn(&str) -> String,
+) -> (StatusCode, Json<ErrorResponse>) {
   let status = match &e {
     BackendError::NotFound(_)
     | BackendError::SessionNotFound
@@ -310,13 +328,13 @@ pub fn to_handler_error(e: BackendError) -> (StatusCode, Json<ErrorResponse>) {
     }
     _ => StatusCode::INTERNAL_SERVER_ERROR,
   };
-  let chain = format_with_causes(&e);
+  let chain = redact(&format_with_causes(&e));
   if status == StatusCode::INTERNAL_SERVER_ERROR {
     tracing::error!("Internal error: {}", chain);
   } else {
     tracing::debug!("Service error {}: {}", status, chain);
   }
-  let error_body = categorise_backend_error_body(&e);
+  let error_body = redact(&categorise_backend_error_body(&e));
   (status, Json(ErrorResponse { error: error_body }))
 }
 
@@ -371,6 +389,90 @@ pub(super) fn require_k8s_url(
   require_url(url, "k8s_api_url")
 }
 
+/// Resolve [`ServerState::reservations`] or answer 501, the same shape
+/// as the vault / k8s guards.
+pub(super) fn require_reservations(
+  state: &ServerState,
+) -> Result<
+  &crate::service::reservation::Reservations,
+  (StatusCode, Json<ErrorResponse>),
+> {
+  state.reservations.as_ref().ok_or_else(|| {
+    (
+      StatusCode::NOT_IMPLEMENTED,
+      Json(ErrorResponse {
+        error: "reservations not configured on this server \
+                ([server.reservations] in server.toml)"
+          .to_string(),
+      }),
+    )
+  })
+}
+
+/// Resolve [`ServerState::hardware_snapshots`] or answer 501, the same
+/// shape as [`require_reservations`].
+pub(super) fn require_hardware_snapshots(
+  state: &ServerState,
+) -> Result<
+  &crate::service::hardware_snapshot::HardwareSnapshots,
+  (StatusCode, Json<ErrorResponse>),
+> {
+  state.hardware_snapshots.as_ref().ok_or_else(|| {
+    (
+      StatusCode::NOT_IMPLEMENTED,
+      Json(ErrorResponse {
+        error: "hardware snapshots not configured on this server \
+                ([server.hardware_snapshots] in server.toml)"
+          .to_string(),
+      }),
+    )
+  })
+}
+
+/// Resolve [`ServerState::ephemeral_envs`] or answer 501, the same
+/// shape as [`require_reservations`].
+pub(super) fn require_ephemeral_envs(
+  state: &ServerState,
+) -> Result<
+  &crate::service::ephemeral_env::EphemeralEnvs,
+  (StatusCode, Json<ErrorResponse>),
+> {
+  state.ephemeral_envs.as_ref().ok_or_else(|| {
+    (
+      StatusCode::NOT_IMPLEMENTED,
+      Json(ErrorResponse {
+        error: "ephemeral environment expiry not configured on this server \
+                ([server.ephemeral_envs] in server.toml)"
+          .to_string(),
+      }),
+    )
+  })
+}
+
+/// Resolve [`ServerState::session_logs`] or answer 501, the same shape
+/// as [`require_reservations`].
+pub(super) fn require_session_logs(
+  state: &ServerState,
+) -> Result<
+  &crate::service::session_log::SessionLogStore,
+  (StatusCode, Json<ErrorResponse>),
+> {
+  state
+    .session_logs
+    .as_ref()
+    .map(|l| &l.store)
+    .ok_or_else(|| {
+      (
+        StatusCode::NOT_IMPLEMENTED,
+        Json(ErrorResponse {
+          error: "session log archive not configured on this server \
+                ([server.session_logs] in server.toml)"
+            .to_string(),
+        }),
+      )
+    })
+}
+
 fn require_url<'a>(
   url: Option<&'a str>,
   field: &str,
@@ -0,0 +1,43 @@
+//! Hardware reservation handlers.
+//!
+//! - `GET /v2/reservations` → [`get_reservations`]
+//!
+//! Reservations are created by `POST /v2/hardware-clusters/{target}/configuration`
+//! with `expires_at` (see [`super::apply_hw_configuration`]) and
+//! released by [`crate::server::reservation_reaper`]; this module only
+//! lists them.
+
+use axum::{Json, http::StatusCode, response::IntoResponse};
+
+use super::{
+  ErrorResponse, RequestCtx, SiteHeader, require_reservations, to_handler_error,
+};
+use crate::service;
+
+pub use manta_shared::types::api::hw_cluster::HwReservation;
+
+/// `GET /v2/reservations` — list active hardware reservations on the site.
+#[utoipa::path(get, path = "/reservations", tag = "hardware",
+  params(SiteHeader),
+  security(("bearerAuth" = [])),
+  responses(
+    (status = 200, description = "Active reservations, soonest expiry first", body = Vec<HwReservation>),
+    (status = 401, description = "Unauthorized",                               body = ErrorResponse),
+    (status = 501, description = "Reservations not configured",                body = ErrorResponse),
+  )
+)]
+#[tracing::instrument(skip_all)]
+pub async fn get_reservations(
+  ctx: RequestCtx,
+) -> Result<impl IntoResponse, (StatusCode, Json<ErrorResponse>)> {
+  let reservations = require_reservations(&ctx.state)?;
+  let infra = ctx.infra();
+  let list = service::reservation::list_reservations(
+    &infra,
+    &ctx.token,
+    &reservations.store,
+  )
+  .await
+  .map_err(to_handler_error)?;
+  Ok(Json(list))
+}
@@ -41,6 +41,11 @@
 //! Each handler calls the matching function in
 //! `crate::service::sat_file`, which enforces the CLAUDE.md boundary
 //! rule (handlers → service → backend).
+//!
+//! The three entry-carrying handlers first resolve any
+//! `((vault://<path>#<key>))` secret placeholders the CLI rendered
+//! (`crate::service::secrets`), and scrub the resolved values from
+//! their response and error bodies before returning.
 
 use axum::{Json, http::StatusCode, response::IntoResponse};
 use manta_backend_dispatcher::types::bos::session::{
@@ -50,10 +55,11 @@ use manta_backend_dispatcher::types::cfs::session::CfsSessionGetResponse;
 use manta_backend_dispatcher::types::ims::Image;
 
 use crate::service::authorization::validate_user_group_vec_access;
+use crate::service::secrets::resolve_secret_refs;
 
 use super::{
   ErrorResponse, RequestCtx, SiteHeader, require_k8s_url, require_vault,
-  to_handler_error,
+  serialize_or_500, to_handler_error, to_handler_error_redacted,
 };
 
 // ---------------------------------------------------------------------------
@@ -101,6 +107,11 @@ pub async fn post_sat_configuration(
     .await
     .map_err(to_handler_error)?;
 
+  let mut configuration = body.configuration;
+  let secrets = resolve_secret_refs(&infra, &ctx.token, &mut configuration)
+    .await
+    .map_err(to_handler_error)?;
+
   // CFS configurations are not HSM-group-scoped — the SAT
   // `configurations[]` entry only carries name + layers (git URL,
   // branch, playbook), with no group field. Access control here
@@ -113,13 +124,15 @@ pub async fn post_sat_configuration(
     vault_base_url,
     k8s_api_url,
     &gitea_token,
-    body.configuration,
+    configuration,
     body.dry_run,
     body.overwrite,
   )
   .await
-  .map_err(to_handler_error)?;
+  .map_err(|e| to_handler_error_redacted(e, &|s| secrets.redact(s)))?;
 
+  let mut cfg = serialize_or_500(&cfg)?;
+  secrets.redact_value(&mut cfg);
   Ok(Json(cfg))
 }
 
@@ -165,21 +178,36 @@ pub async fn post_sat_image_cfs_session(
     .await
     .map_err(to_handler_error)?;
 
+  let mut image = body.image;
+  let secrets = resolve_secret_refs(&infra, &ctx.token, &mut image)
+    .await
+    .map_err(to_handler_error)?;
+
   let session = crate::service::sat_file::create_image_cfs_session(
     &infra,
     &ctx.token,
     vault_base_url,
     k8s_api_url,
-    body.image,
+    image,
     body.ref_lookup,
     body.ansible_verbosity,
     body.ansible_passthrough.as_deref(),
     body.dry_run,
   )
   .await
-  .map_err(to_handler_error)?;
+  .map_err(|e| to_handler_error_redacted(e, &|s| secrets.redact(s)))?;
+
+  if !body.dry_run {
+    crate::server::session_log_archiver::follow(
+      ctx.state.clone(),
+      ctx.site_name.clone(),
+      session.name.clone(),
+    );
+  }
 
-  Ok((StatusCode::CREATED, Json::<CfsSessionGetResponse>(session)))
+  let mut session = serialize_or_500::<CfsSessionGetResponse>(&session)?;
+  secrets.redact_value(&mut session);
+  Ok((StatusCode::CREATED, Json(session)))
 }
 
 // ---------------------------------------------------------------------------
@@ -281,17 +309,22 @@ pub async fn post_sat_session_template(
   // validates inline (non-admin callers), and forwards the list to the
   // backend — eliminating the duplicate get_group_name_available fetch
   // that previously appeared here.
+  let mut session_template = body.session_template;
+  let secrets = resolve_secret_refs(&infra, &ctx.token, &mut session_template)
+    .await
+    .map_err(to_handler_error)?;
+
   let (template, session) = crate::service::sat_file::apply_session_template(
     &infra,
     &ctx.token,
-    body.session_template,
+    session_template,
     body.ref_lookup,
     &target_groups,
     body.create_bos_session,
     body.dry_run,
   )
   .await
-  .map_err(to_handler_error)?;
+  .map_err(|e| to_handler_error_redacted(e, &|s| secrets.redact(s)))?;
 
   // Dry-run + create_bos_session: the backend has returned a mock
   // template but no session (it never actually created one). Synthesise
@@ -327,7 +360,10 @@ pub async fn post_sat_session_template(
     }
   };
 
-  Ok(Json(PostSatSessionTemplateResponse { template, session }))
+  let mut response =
+    serialize_or_500(&PostSatSessionTemplateResponse { template, session })?;
+  secrets.redact_value(&mut response);
+  Ok(Json(response))
 }
 
 /// Build a `BosSession` that mirrors what a real session created from
@@ -2,10 +2,22 @@
 //!
 //! - `GET    /v2/sessions`              → [`get_sessions`]
 //! - `POST   /v2/sessions`              → [`create_session`]
+//! - `POST   /v2/sessions/{name}/rerun` → [`rerun_session`] — a new
+//!   session with the parameters of `{name}`.
 //! - `DELETE /v2/sessions/{name}`       → [`delete_session`]
 //!   — with `?dry_run=true`, returns the deletion plan only.
+//! - `DELETE /v2/sessions`              → [`delete_sessions`] — every
+//!   session matching the filters, with a combined plan and a result
+//!   per session.
 //! - `GET    /v2/sessions/{name}/logs`  → [`get_session_logs`] —
 //!   Server-Sent Events stream from the CFS session's pod log.
+//! - `GET    /v2/sessions/{name}/logs/archive` →
+//!   [`get_session_log_archive`] — the log kept by the server's session
+//!   log archive, readable after the pod is gone.
+//!
+//! With `[server.session_logs]` configured, streaming a session's logs
+//! and creating (or re-running) a session both start
+//! [`crate::server::session_log_archiver::follow`].
 //!
 //! All wrap `crate::service::session::*` and (for create) the
 //! backend `CfsTrait` for the actual CFS object. The log stream
@@ -17,7 +29,7 @@ use std::convert::Infallible;
 use axum::{
   Json,
   extract::{Path, Query},
-  http::StatusCode,
+  http::{HeaderMap, StatusCode},
   response::{
     IntoResponse,
     sse::{Event, KeepAlive, Sse},
@@ -27,16 +39,20 @@ use futures::{AsyncBufReadExt, StreamExt};
 use manta_backend_dispatcher::types::{K8sAuth, K8sDetails};
 
 use super::{
-  ErrorResponse, RequestCtx, SiteHeader, require_k8s_url, require_vault,
-  serialize_or_500, to_handler_error, validate_repo_list_lengths,
+  ErrorResponse, RequestCtx, SiteHeader, require_k8s_url, require_session_logs,
+  require_vault, serialize_or_500, to_handler_error,
+  validate_repo_list_lengths,
 };
+use crate::server::session_log_archiver;
 use crate::service;
 
 // ---------------------------------------------------------------------------
 // GET /v2/sessions
 // ---------------------------------------------------------------------------
 
-pub use manta_shared::types::api::queries::{DeleteSessionQuery, SessionQuery};
+pub use manta_shared::types::api::queries::{
+  DeleteSessionQuery, DeleteSessionsQuery, SessionQuery,
+};
 
 /// GET /sessions — list CFS sessions with optional filters.
 #[utoipa::path(get, path = "/sessions", tag = "sessions",
@@ -133,6 +149,89 @@ pub async fn delete_session(
   Ok((StatusCode::OK, Json(serde_json::json!({ "deleted": name }))))
 }
 
+// ---------------------------------------------------------------------------
+// DELETE /v2/sessions — bulk deletion, with ?dry_run=true support
+// ---------------------------------------------------------------------------
+
+pub use manta_shared::types::api::session::{
+  BootImageInUse, DeleteSessionsResponse, SessionDeletionItem,
+  SessionDeletionResult, SessionsDeletionPlan,
+};
+
+/// `DELETE /v2/sessions` — cancel and delete every CFS session matching
+/// the filters; `?dry_run=true` previews.
+///
+/// Sessions are deleted a few at a time and a failure doesn't stop the
+/// others: the response is 200 with one result per session.
+#[utoipa::path(delete, path = "/sessions", tag = "sessions",
+  params(DeleteSessionsQuery, SiteHeader),
+  security(("bearerAuth" = [])),
+  responses(
+    (status = 200, description = "Deletion plan and per-session results", body = DeleteSessionsResponse),
+    (status = 400, description = "Bad request",                           body = ErrorResponse),
+    (status = 401, description = "Unauthorized",                          body = ErrorResponse),
+    (status = 500, description = "Internal error",                        body = ErrorResponse),
+  )
+)]
+#[tracing::instrument(skip_all)]
+pub async fn delete_sessions(
+  ctx: RequestCtx,
+  Query(q): Query<DeleteSessionsQuery>,
+) -> Result<impl IntoResponse, (StatusCode, Json<ErrorResponse>)> {
+  tracing::info!("delete_sessions dry_run={}", q.dry_run);
+  let names: Vec<String> = q
+    .names
+    .as_deref()
+    .unwrap_or_default()
+    .split(',')
+    .map(str::trim)
+    .filter(|v| !v.is_empty())
+    .map(str::to_string)
+    .collect();
+  if q.status.is_none() && q.min_age.is_none() && names.is_empty() {
+    return Err((
+      StatusCode::BAD_REQUEST,
+      Json(ErrorResponse {
+        error: "Give at least one of status, min_age or names".to_string(),
+      }),
+    ));
+  }
+  let infra = ctx.infra();
+
+  let params = service::session::GetSessionParams {
+    group: q.hsm_group,
+    xnames: Vec::new(),
+    min_age: q.min_age,
+    max_age: None,
+    session_type: q.session_type,
+    status: q.status,
+    name: None,
+    limit: None,
+  };
+  let deletion_ctx = service::session::prepare_sessions_deletion(
+    &infra, &ctx.token, &params, &names,
+  )
+  .await
+  .map_err(to_handler_error)?;
+
+  let results = if q.dry_run {
+    Vec::new()
+  } else {
+    service::session::execute_sessions_deletion(
+      &infra,
+      &ctx.token,
+      &deletion_ctx,
+    )
+    .await
+  };
+
+  Ok(Json(DeleteSessionsResponse {
+    plan: deletion_ctx.plan(),
+    dry_run: q.dry_run,
+    results,
+  }))
+}
+
 // ---------------------------------------------------------------------------
 // POST /v2/sessions — Create CFS session
 // ---------------------------------------------------------------------------
@@ -140,6 +239,10 @@ pub async fn delete_session(
 pub use manta_shared::types::api::session::CreateSessionRequest;
 
 /// `POST /v2/sessions` — create a CFS session from one or more git repositories.
+///
+/// Layers come either from `repo_names` + `repo_last_commit_ids`, or
+/// from `repo_refs`, whose refs are resolved against the site's Gitea
+/// ([`service::git_ref::resolve_all`]).
 #[utoipa::path(post, path = "/sessions", tag = "sessions",
   params(SiteHeader),
   request_body = CreateSessionRequest,
@@ -148,6 +251,7 @@ pub use manta_shared::types::api::session::CreateSessionRequest;
     (status = 201, description = "Session created",               body = manta_shared::types::api::responses::CreateSessionResponse),
     (status = 400, description = "Bad request",                   body = ErrorResponse),
     (status = 401, description = "Unauthorized",                  body = ErrorResponse),
+    (status = 404, description = "Git ref not found in Gitea",    body = ErrorResponse),
     (status = 500, description = "Internal error",                body = ErrorResponse),
     (status = 501, description = "Vault not configured",          body = ErrorResponse),
   )
@@ -158,7 +262,21 @@ pub async fn create_session(
   Json(body): Json<CreateSessionRequest>,
 ) -> Result<impl IntoResponse, (StatusCode, Json<ErrorResponse>)> {
   validate_repo_list_lengths(&body.repo_names, &body.repo_last_commit_ids)?;
-  tracing::info!("create_session repos={:?}", body.repo_names);
+  if body.repo_names.is_empty() == body.repo_refs.is_empty() {
+    return Err((
+      StatusCode::BAD_REQUEST,
+      Json(ErrorResponse {
+        error: "Give the layers either as repo_names with \
+                repo_last_commit_ids or as repo_refs"
+          .to_string(),
+      }),
+    ));
+  }
+  tracing::info!(
+    "create_session repos={:?} refs={:?}",
+    body.repo_names,
+    body.repo_refs
+  );
   let infra = ctx.infra();
 
   // Authorization: requested HSM group must be accessible to the token.
@@ -193,13 +311,19 @@ pub async fn create_session(
     .await
     .map_err(to_handler_error)?;
 
-  let repo_name_refs: Vec<&str> = body
-    .repo_names
-    .iter()
-    .map(std::string::String::as_str)
-    .collect();
-  let repo_commit_refs: Vec<&str> = body
-    .repo_last_commit_ids
+  // Layers given by Git ref are resolved to commits up front, so the
+  // backend sees the same shape as for a local checkout.
+  let (repo_names, repo_last_commit_ids) = if body.repo_refs.is_empty() {
+    (body.repo_names, body.repo_last_commit_ids)
+  } else {
+    service::git_ref::resolve_all(&infra, &gitea_token, &body.repo_refs)
+      .await
+      .map_err(to_handler_error)?
+  };
+
+  let repo_name_refs: Vec<&str> =
+    repo_names.iter().map(std::string::String::as_str).collect();
+  let repo_commit_refs: Vec<&str> = repo_last_commit_ids
     .iter()
     .map(std::string::String::as_str)
     .collect();
@@ -222,6 +346,12 @@ pub async fn create_session(
   .await
   .map_err(to_handler_error)?;
 
+  session_log_archiver::follow(
+    ctx.state.clone(),
+    ctx.site_name.clone(),
+    session_name.clone(),
+  );
+
   Ok((
     StatusCode::CREATED,
     Json(serde_json::json!({
@@ -231,18 +361,110 @@ pub async fn create_session(
   ))
 }
 
+// ---------------------------------------------------------------------------
+// POST /v2/sessions/{name}/rerun — Re-run a CFS session
+// ---------------------------------------------------------------------------
+
+pub use manta_shared::types::api::session::{
+  RerunSessionRequest, RerunSessionResponse,
+};
+
+/// `POST /v2/sessions/{name}/rerun` — create a new CFS session with the
+/// configuration, target and Ansible settings of session `{name}`.
+///
+/// `ansible_limit` and `ansible_verbosity` in the body replace the
+/// original's; with `dry_run` the session is described, not created.
+#[utoipa::path(post, path = "/sessions/{name}/rerun", tag = "sessions",
+  params(("name" = String, Path, description = "Session to re-run"), SiteHeader),
+  request_body = RerunSessionRequest,
+  security(("bearerAuth" = [])),
+  responses(
+    (status = 201, description = "Session created",               body = RerunSessionResponse),
+    (status = 200, description = "Dry run: the session that would be created", body = RerunSessionResponse),
+    (status = 400, description = "Bad request",                   body = ErrorResponse),
+    (status = 401, description = "Unauthorized",                  body = ErrorResponse),
+    (status = 404, description = "Session not found",             body = ErrorResponse),
+    (status = 500, description = "Internal error",                body = ErrorResponse),
+  )
+)]
+#[tracing::instrument(skip_all, fields(session = %name))]
+pub async fn rerun_session(
+  ctx: RequestCtx,
+  Path(name): Path<String>,
+  Json(body): Json<RerunSessionRequest>,
+) -> Result<impl IntoResponse, (StatusCode, Json<ErrorResponse>)> {
+  let infra = ctx.infra();
+  let rerun = service::session::rerun_session(&infra, &ctx.token, &name, &body)
+    .await
+    .map_err(to_handler_error)?;
+
+  if rerun.dry_run {
+    return Ok((StatusCode::OK, Json(rerun)));
+  }
+  session_log_archiver::follow(
+    ctx.state.clone(),
+    ctx.site_name.clone(),
+    rerun.session_name.clone(),
+  );
+  Ok((StatusCode::CREATED, Json(rerun)))
+}
+
 // ---------------------------------------------------------------------------
 // GET /v2/sessions/{name}/logs — Stream CFS session logs via SSE
 // ---------------------------------------------------------------------------
 
 pub use manta_shared::types::api::queries::SessionLogsQuery;
 
+/// SSE event type sent once the pod log has been streamed to its end,
+/// so clients can tell a finished log from a dropped connection.
+const LOG_END_EVENT: &str = "end";
+
+/// SSE event type sent, with the error as data, when reading the pod
+/// log fails mid-stream. The stream closes after it without `end`, so
+/// clients reconnect and resume rather than take the log as complete.
+const LOG_ERROR_EVENT: &str = "error";
+
+/// The `Last-Event-ID` a reconnecting client sends: the number of the
+/// last log line it received.
+fn last_event_id(
+  headers: &HeaderMap,
+) -> Result<Option<u64>, (StatusCode, Json<ErrorResponse>)> {
+  headers
+    .get("last-event-id")
+    .map(|value| {
+      value
+        .to_str()
+        .ok()
+        .and_then(|value| value.trim().parse().ok())
+        .ok_or_else(|| {
+          (
+            StatusCode::BAD_REQUEST,
+            Json(ErrorResponse {
+              error: "Last-Event-ID must be a log line number".to_string(),
+            }),
+          )
+        })
+    })
+    .transpose()
+}
+
 /// `GET /v2/sessions/{name}/logs` — stream CFS session pod logs via Server-Sent Events.
+///
+/// Every log line is an event whose id is the line's number in the
+/// full (unfiltered) log, from 1. A client reconnecting with
+/// `Last-Event-ID: N` gets the lines after line `N` only; the pod log
+/// is re-read from the top and the lines already delivered skipped.
+/// The stream closes with an `end` event once the pod log is read to
+/// its end, or with an `error` event when reading it fails; only the
+/// former means the log is complete. (`Last-Event-ID` is left
+/// out of the spec: the generated client cannot take an optional
+/// header, and only the hand-rolled SSE client sends it.)
 #[utoipa::path(get, path = "/sessions/{name}/logs", tag = "sessions",
   params(("name" = String, Path, description = "Session name"), SessionLogsQuery, SiteHeader),
   security(("bearerAuth" = [])),
   responses(
     (status = 200, description = "SSE log stream"),
+    (status = 400, description = "Invalid grep pattern or Last-Event-ID", body = ErrorResponse),
     (status = 401, description = "Unauthorized",                   body = ErrorResponse),
     (status = 500, description = "Internal error",                 body = ErrorResponse),
     (status = 501, description = "Vault or k8s not configured",    body = ErrorResponse),
@@ -253,11 +475,13 @@ pub async fn get_session_logs(
   ctx: RequestCtx,
   Path(name): Path<String>,
   Query(q): Query<SessionLogsQuery>,
+  headers: HeaderMap,
 ) -> Result<
   Sse<impl futures::Stream<Item = Result<Event, Infallible>>>,
   (StatusCode, Json<ErrorResponse>),
 > {
   let infra = ctx.infra();
+  let resume_after = last_event_id(&headers)?.unwrap_or(0);
 
   let k8s_api_url = require_k8s_url(infra.k8s_api_url)?;
   let vault_base_url = require_vault(infra.vault_base_url)?;
@@ -267,9 +491,19 @@ pub async fn get_session_logs(
   // credentials, kernel-cmdline secrets, and ansible variable dumps;
   // without this check any authenticated user could stream any
   // session's logs.
-  service::session::validate_session_access(&infra, &ctx.token, &name)
-    .await
-    .map_err(to_handler_error)?;
+  let session =
+    service::session::validate_session_access(&infra, &ctx.token, &name)
+      .await
+      .map_err(to_handler_error)?;
+  let filter = service::session_log_filter::for_request(
+    &infra,
+    &ctx.token,
+    Some(&session),
+    q.layer.as_deref(),
+    q.grep.as_deref(),
+  )
+  .await
+  .map_err(to_handler_error)?;
 
   let k8s = K8sDetails {
     api_url: k8s_api_url.to_string(),
@@ -288,11 +522,118 @@ pub async fn get_session_logs(
   .await
   .map_err(to_handler_error)?;
 
-  let sse_stream = logs_stream.lines().map(|result| {
-    Ok::<Event, Infallible>(
-      Event::default().data(result.unwrap_or_else(|e| format!("error: {e}"))),
-    )
-  });
+  session_log_archiver::follow(ctx.state.clone(), ctx.site_name.clone(), name);
+
+  // The filter sees every line, skipped or not, so it tracks layers
+  // the same way on every reconnect. The state is dropped after the
+  // closing `end` or `error` event, which ends the stream.
+  let sse_stream = futures::stream::unfold(
+    Some((logs_stream.lines(), filter, 0u64)),
+    move |state| async move {
+      let (mut lines, mut filter, mut line_number) = state?;
+      loop {
+        let event = match lines.next().await {
+          Some(Ok(line)) => {
+            line_number += 1;
+            if !filter.keep(&line) || line_number <= resume_after {
+              continue;
+            }
+            Event::default().id(line_number.to_string()).data(line)
+          }
+          Some(Err(e)) => {
+            tracing::warn!("log stream of CFS session broke off: {e}");
+            let event =
+              Event::default().event(LOG_ERROR_EVENT).data(e.to_string());
+            return Some((Ok::<Event, Infallible>(event), None));
+          }
+          None => {
+            return Some((Ok(Event::default().event(LOG_END_EVENT)), None));
+          }
+        };
+        return Some((Ok(event), Some((lines, filter, line_number))));
+      }
+    },
+  );
 
   Ok(Sse::new(sse_stream).keep_alive(KeepAlive::default()))
 }
+
+// ---------------------------------------------------------------------------
+// GET /v2/sessions/{name}/logs/archive — Archived CFS session log
+// ---------------------------------------------------------------------------
+
+pub use manta_shared::types::api::session::ArchivedSessionLog;
+
+/// `GET /v2/sessions/{name}/logs/archive` — the archived log of a CFS
+/// session, available after its pod (or the session itself) is gone.
+#[utoipa::path(get, path = "/sessions/{name}/logs/archive", tag = "sessions",
+  params(("name" = String, Path, description = "Session name"), SessionLogsQuery, SiteHeader),
+  security(("bearerAuth" = [])),
+  responses(
+    (status = 200, description = "Archived log",                   body = ArchivedSessionLog),
+    (status = 400, description = "Invalid grep pattern",           body = ErrorResponse),
+    (status = 401, description = "Unauthorized",                   body = ErrorResponse),
+    (status = 404, description = "No archived log for the session", body = ErrorResponse),
+    (status = 500, description = "Internal error",                 body = ErrorResponse),
+    (status = 501, description = "Session log archive not configured", body = ErrorResponse),
+  )
+)]
+#[tracing::instrument(skip_all)]
+pub async fn get_session_log_archive(
+  ctx: RequestCtx,
+  Path(name): Path<String>,
+  Query(q): Query<SessionLogsQuery>,
+) -> Result<Json<ArchivedSessionLog>, (StatusCode, Json<ErrorResponse>)> {
+  let store = require_session_logs(&ctx.state)?;
+  let infra = ctx.infra();
+  let mut log = service::session_log::get_archived_log(
+    &infra,
+    &ctx.token,
+    store,
+    &name,
+    q.timestamps,
+  )
+  .await
+  .map_err(to_handler_error)?;
+  if q.layer.is_some() || q.grep.is_some() {
+    // The session may be gone; without it, layer names are matched
+    // against the log's repositories and playbooks only.
+    let session = match q.layer {
+      Some(_) => {
+        service::session::fetch_session_by_name(&infra, &ctx.token, &name)
+          .await
+          .ok()
+      }
+      None => None,
+    };
+    let mut filter = service::session_log_filter::for_request(
+      &infra,
+      &ctx.token,
+      session.as_ref(),
+      q.layer.as_deref(),
+      q.grep.as_deref(),
+    )
+    .await
+    .map_err(to_handler_error)?;
+    log.lines.retain(|line| filter.keep(line));
+  }
+  Ok(Json(log))
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn last_event_id_is_an_optional_line_number() {
+    let mut headers = HeaderMap::new();
+    assert!(matches!(last_event_id(&headers), Ok(None)));
+    headers.insert("Last-Event-ID", " 42".parse().unwrap());
+    assert!(matches!(last_event_id(&headers), Ok(Some(42))));
+    headers.insert("Last-Event-ID", "abc".parse().unwrap());
+    assert!(matches!(
+      last_event_id(&headers),
+      Err((StatusCode::BAD_REQUEST, _))
+    ));
+  }
+}
@@ -0,0 +1,48 @@
+//! Site handlers.
+//!
+//! - `GET /v2/sites/{name}/capabilities` → [`get_site_capabilities`] —
+//!   which site-dependent features the site serves, so the CLI can hide
+//!   or warn on commands that would only get a 501.
+//!
+//! The site comes from the path rather than `X-Manta-Site`: the CLI
+//! asks about a site before talking to it.
+
+use std::sync::Arc;
+
+use axum::{
+  Json,
+  extract::{Path, State},
+  http::StatusCode,
+  response::IntoResponse,
+};
+
+use super::{BearerToken, ErrorResponse, ServerState, to_handler_error};
+use crate::service;
+pub use manta_shared::types::api::site::{SiteCapabilities, SiteFeature};
+
+// ---------------------------------------------------------------------------
+// GET /v2/sites/{name}/capabilities
+// ---------------------------------------------------------------------------
+
+/// `GET /v2/sites/{name}/capabilities` — what the site supports.
+#[utoipa::path(get, path = "/sites/{name}/capabilities", tag = "sites",
+  params(("name" = String, Path, description = "Site name")),
+  security(("bearerAuth" = [])),
+  responses(
+    (status = 200, description = "The site's capabilities", body = SiteCapabilities),
+    (status = 401, description = "Unauthorized", body = ErrorResponse),
+    (status = 404, description = "Unknown site", body = ErrorResponse),
+  )
+)]
+#[tracing::instrument(skip_all)]
+pub async fn get_site_capabilities(
+  State(state): State<Arc<ServerState>>,
+  BearerToken(_token): BearerToken,
+  Path(name): Path<String>,
+) -> Result<impl IntoResponse, (StatusCode, Json<ErrorResponse>)> {
+  let infra = state.infra_context(&name).map_err(to_handler_error)?;
+  Ok(Json(service::site::capabilities(
+    &infra,
+    state.session_logs.is_some(),
+  )))
+}
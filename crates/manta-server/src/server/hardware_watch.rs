@@ -0,0 +1,155 @@
+//! Background task that watches groups for hardware changes.
+//!
+//! Spawned by [`super::start_server`] when
+//! `[server.hardware_snapshots.watch]` is configured. Every `interval`
+//! it authenticates per site as the service account, snapshots each
+//! watched group and compares the result with the group's newest stored
+//! snapshot. A difference is logged and sent to the Kafka auditor as a
+//! `hardware_changed` event, and the new snapshot is stored as the next
+//! baseline. An unchanged inventory stores nothing, so the store only
+//! grows when the hardware does change.
+
+use std::collections::BTreeMap;
+use std::sync::Arc;
+
+use manta_shared::types::hardware_diff::diff_snapshots;
+
+use super::ServerState;
+use super::common::app_context::InfraContext;
+use super::common::audit::send_hardware_change_audit;
+use crate::service::hardware_snapshot::{
+  self, HardwareSnapshots, SnapshotStore,
+};
+
+/// Start the watch loop on the current Tokio runtime. No-op when no
+/// watch is configured.
+pub fn spawn(state: Arc<ServerState>) {
+  let Some(interval) = state
+    .hardware_snapshots
+    .as_ref()
+    .and_then(|s| s.watch.as_ref())
+    .map(|w| w.interval)
+  else {
+    return;
+  };
+  tokio::spawn(async move {
+    let mut ticker = tokio::time::interval(interval);
+    ticker.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+    loop {
+      ticker.tick().await;
+      if let Some(snapshots) = state.hardware_snapshots.as_ref() {
+        check_all(&state, snapshots).await;
+      }
+    }
+  });
+}
+
+/// One watch pass over every target, grouped by site so each site's
+/// service-account token is fetched once. Failures are logged and the
+/// target is retried on the next pass.
+async fn check_all(state: &ServerState, snapshots: &HardwareSnapshots) {
+  let Some(watch) = snapshots.watch.as_ref() else {
+    return;
+  };
+  let mut by_site: BTreeMap<&str, Vec<&str>> = BTreeMap::new();
+  for (site, group) in &watch.targets {
+    by_site.entry(site).or_default().push(group);
+  }
+
+  for (site, groups) in by_site {
+    let infra = match state.infra_context(site) {
+      Ok(infra) => infra,
+      Err(e) => {
+        tracing::warn!("hardware watch: site '{site}' unavailable: {e}");
+        continue;
+      }
+    };
+    let account = &watch.service_account;
+    let token = match crate::service::auth::get_api_token(
+      &infra,
+      &account.username,
+      &account.password,
+    )
+    .await
+    {
+      Ok(token) => token,
+      Err(e) => {
+        tracing::warn!(
+          "hardware watch: service account '{}' login on site '{site}' \
+           failed: {e}",
+          account.username
+        );
+        continue;
+      }
+    };
+    for group in groups {
+      if let Err(e) = check_group(
+        state,
+        &snapshots.store,
+        &infra,
+        &token,
+        &account.username,
+        group,
+      )
+      .await
+      {
+        tracing::warn!(
+          "hardware watch: could not check group '{group}' on site \
+           '{site}': {e}"
+        );
+      }
+    }
+  }
+}
+
+/// Snapshot `group`, compare it with the stored baseline and record the
+/// outcome.
+async fn check_group(
+  state: &ServerState,
+  store: &SnapshotStore,
+  infra: &InfraContext<'_>,
+  token: &str,
+  taken_by: &str,
+  group: &str,
+) -> Result<(), manta_backend_dispatcher::error::Error> {
+  let current = hardware_snapshot::take_snapshot(
+    infra,
+    token,
+    group,
+    taken_by,
+    chrono::Utc::now(),
+  )
+  .await?;
+
+  let Some(baseline) = store.latest(infra.site_name, group).await? else {
+    tracing::info!(
+      "hardware watch: no snapshot of '{group}' on site '{}' yet, storing \
+       {} as the baseline",
+      infra.site_name,
+      current.id
+    );
+    return store.save(&current).await;
+  };
+
+  let diff = diff_snapshots(&baseline, &current);
+  if diff.is_empty() {
+    return Ok(());
+  }
+  tracing::warn!(
+    "hardware watch: {} node(s) of '{group}' on site '{}' changed since \
+     snapshot {}; new baseline {}",
+    diff.changes.len(),
+    infra.site_name,
+    baseline.id,
+    current.id
+  );
+  store.save(&current).await?;
+  send_hardware_change_audit(
+    state.auditor.as_ref(),
+    &baseline,
+    &current,
+    &diff,
+  )
+  .await;
+  Ok(())
+}
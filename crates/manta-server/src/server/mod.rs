@@ -19,12 +19,24 @@
 //!     Kafka audit producer, JWT claim extractors, Vault client).
 //!   - [`api_doc`] — utoipa OpenAPI document served at
 //!     `GET /openapi.json` + `GET /docs`.
+//!   - [`reservation_reaper`] — background task releasing expired
+//!     hardware reservations.
+//!   - [`hardware_watch`] — background task snapshotting watched
+//!     groups and auditing hardware changes.
+//!   - [`session_log_archiver`] — per-session background task copying
+//!     a CFS session's log into the archive.
+//!   - [`ephemeral_env_reaper`] — background task deleting expired
+//!     ephemeral environments.
 
 pub mod api_doc;
 pub mod auth_middleware;
 pub mod common;
+pub mod ephemeral_env_reaper;
 pub mod handlers;
+pub mod hardware_watch;
+pub mod reservation_reaper;
 pub mod routes;
+pub mod session_log_archiver;
 
 use std::collections::HashMap;
 use std::net::SocketAddr;
@@ -102,6 +114,23 @@ pub struct ServerState {
   /// migrate_backup_root`. The path is stored already-canonicalised
   /// so per-request validation is a single `starts_with` against this.
   pub migrate_backup_root: Option<std::path::PathBuf>,
+  /// Time-limited hardware reservations and the credentials used to
+  /// release them. `None` (no `[server.reservations]`) makes pins with
+  /// an expiry and `GET /reservations` return 501.
+  pub reservations: Option<crate::service::reservation::Reservations>,
+  /// Hardware snapshot store and the optional scheduled watch. `None`
+  /// (no `[server.hardware_snapshots]`) makes `/hardware-snapshots`
+  /// return 501.
+  pub hardware_snapshots:
+    Option<crate::service::hardware_snapshot::HardwareSnapshots>,
+  /// Archive of CFS session logs and the credentials the archiver
+  /// reads them with. `None` (no `[server.session_logs]`) archives
+  /// nothing and makes `/sessions/{name}/logs/archive` return 501.
+  pub session_logs: Option<crate::service::session_log::SessionLogs>,
+  /// Ephemeral environment expiry and the credentials used to delete
+  /// expired environments. `None` (no `[server.ephemeral_envs]`) keeps
+  /// environments until deleted and makes a requested TTL return 501.
+  pub ephemeral_envs: Option<crate::service::ephemeral_env::EphemeralEnvs>,
 }
 
 impl ServerState {
@@ -180,6 +209,11 @@ pub async fn start_server(
   // Read shutdown-grace before `state` is moved into the router.
   let shutdown_grace_period = state.shutdown_grace_period;
 
+  reservation_reaper::spawn(Arc::clone(&state));
+  hardware_watch::spawn(Arc::clone(&state));
+  ephemeral_env_reaper::spawn(Arc::clone(&state));
+  session_log_archiver::spawn_pruner(Arc::clone(&state));
+
   // Both `request_timeout` and `power_timeout` are now applied **inside**
   // `build_router` so the per-route `/power` override actually wins —
   // see the comment on `build_router` for why a global outer layer
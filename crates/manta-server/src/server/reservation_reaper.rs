@@ -0,0 +1,158 @@
+//! Background task that releases expired hardware reservations.
+//!
+//! Spawned by [`super::start_server`] when `[server.reservations]` is
+//! configured. Every `check_interval` it looks for reservations past
+//! their expiry and, per site, authenticates as the service account
+//! and returns the reserved hardware to the parent group (see
+//! [`crate::service::reservation::release`]). A released reservation
+//! is dropped from the store; a failed one stays and is retried on the
+//! next pass. Both outcomes are sent to the Kafka auditor, a failure
+//! only the first time it happens so retries don't flood the topic.
+
+use std::collections::{BTreeMap, HashSet};
+use std::sync::Arc;
+
+use super::ServerState;
+use super::common::audit::send_reservation_audit;
+use crate::service::reservation::{self, HwReservation, Reservations};
+
+/// Start the reaper loop on the current Tokio runtime. No-op when
+/// reservations are disabled.
+pub fn spawn(state: Arc<ServerState>) {
+  let Some(check_interval) =
+    state.reservations.as_ref().map(|r| r.check_interval)
+  else {
+    return;
+  };
+  tokio::spawn(async move {
+    let mut failure_audited = HashSet::new();
+    let mut ticker = tokio::time::interval(check_interval);
+    ticker.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+    loop {
+      ticker.tick().await;
+      if let Some(reservations) = state.reservations.as_ref() {
+        release_expired(&state, reservations, &mut failure_audited).await;
+      }
+    }
+  });
+}
+
+/// One reaper pass: release every due reservation, grouped by site so
+/// each site's service-account token is fetched once.
+/// `failure_audited` holds the ids whose failure was already audited
+/// and lives across passes.
+async fn release_expired(
+  state: &ServerState,
+  reservations: &Reservations,
+  failure_audited: &mut HashSet<String>,
+) {
+  let due = reservation::due_for_release(
+    &reservations.store.list().await,
+    chrono::Utc::now(),
+  );
+  let mut by_site: BTreeMap<String, Vec<HwReservation>> = BTreeMap::new();
+  for r in due {
+    by_site.entry(r.site.clone()).or_default().push(r);
+  }
+
+  for (site, due) in by_site {
+    let infra = match state.infra_context(&site) {
+      Ok(infra) => infra,
+      Err(e) => {
+        fail_all(
+          state,
+          failure_audited,
+          &due,
+          &format!("site unavailable: {e}"),
+        )
+        .await;
+        continue;
+      }
+    };
+    let account = &reservations.service_account;
+    let token = match crate::service::auth::get_api_token(
+      &infra,
+      &account.username,
+      &account.password,
+    )
+    .await
+    {
+      Ok(token) => token,
+      Err(e) => {
+        fail_all(
+          state,
+          failure_audited,
+          &due,
+          &format!("service account '{}' login failed: {e}", account.username),
+        )
+        .await;
+        continue;
+      }
+    };
+
+    for r in &due {
+      match reservation::release(&infra, &token, r).await {
+        Ok(released) => {
+          tracing::info!(
+            "reservation {} expired: released {:?} from '{}' to '{}'",
+            r.id,
+            released,
+            r.target_cluster,
+            r.parent_cluster
+          );
+          failure_audited.remove(&r.id);
+          if let Err(e) = reservations.store.remove(&r.id).await {
+            tracing::error!(
+              "reservation {} released but could not be removed from the \
+               state file: {e}",
+              r.id
+            );
+          }
+          send_reservation_audit(
+            state.auditor.as_ref(),
+            "reservation_released",
+            r,
+            serde_json::json!({ "released": released }),
+          )
+          .await;
+        }
+        Err(e) => {
+          fail_all(
+            state,
+            failure_audited,
+            std::slice::from_ref(r),
+            &e.to_string(),
+          )
+          .await
+        }
+      }
+    }
+  }
+}
+
+/// Log a failed release, and audit it unless an earlier pass already
+/// did; the reservations stay in the store for the next pass.
+async fn fail_all(
+  state: &ServerState,
+  failure_audited: &mut HashSet<String>,
+  due: &[HwReservation],
+  error: &str,
+) {
+  for r in due {
+    tracing::warn!(
+      "reservation {} expired but could not be released, retrying next \
+       pass: {error}",
+      r.id
+    );
+    if !failure_audited.insert(r.id.clone()) {
+      continue;
+    }
+    send_reservation_audit(
+      state.auditor.as_ref(),
+      "reservation_release_failed",
+      r,
+      serde_json::json!({ "error": error }),
+    )
+    .await;
+  }
+}
@@ -54,9 +54,27 @@ pub fn build_router(state: Arc<ServerState>) -> Router {
     .route("/boot-parameters", get(handlers::get_boot_parameters))
     .route("/kernel-parameters", get(handlers::get_kernel_parameters))
     .route("/redfish-endpoints", get(handlers::get_redfish_endpoints))
+    .route("/reservations", get(handlers::get_reservations))
+    .route(
+      "/sites/{name}/capabilities",
+      get(handlers::get_site_capabilities),
+    )
+    .route(
+      "/hardware-snapshots",
+      get(handlers::get_hardware_snapshots)
+        .post(handlers::post_hardware_snapshot),
+    )
+    .route(
+      "/hardware-snapshots/{id}",
+      get(handlers::get_hardware_snapshot),
+    )
     // Canonical (group-centric) read endpoints
     .route("/groups/nodes", get(handlers::get_groups_nodes))
     .route("/groups/hardware", get(handlers::get_groups_hardware))
+    .route(
+      "/groups/prometheus-sd",
+      get(handlers::get_groups_prometheus_sd),
+    )
     // Deprecated aliases retained for one release. Each handler logs
     // a server-side warning and forwards to the canonical impl.
     .route("/clusters", get(handlers::get_clusters_deprecated))
@@ -99,7 +117,11 @@ pub fn build_router(state: Arc<ServerState>) -> Router {
     // Sessions (delete with dry_run)
     .route("/sessions/{name}", delete(handlers::delete_session))
     // Sessions (create)
-    .route("/sessions", post(handlers::create_session))
+    .route(
+      "/sessions",
+      post(handlers::create_session).delete(handlers::delete_sessions),
+    )
+    .route("/sessions/{name}/rerun", post(handlers::rerun_session))
     // Images (delete with dry_run)
     .route("/images", delete(handlers::delete_images))
     // Configurations (delete with dry_run)
@@ -130,7 +152,14 @@ pub fn build_router(state: Arc<ServerState>) -> Router {
     .route("/migrate/backup", post(handlers::migrate_backup))
     .route("/migrate/restore", post(handlers::migrate_restore))
     // Ephemeral environment
-    .route("/ephemeral-env", post(handlers::create_ephemeral_env))
+    .route(
+      "/ephemeral-env",
+      post(handlers::create_ephemeral_env).get(handlers::get_ephemeral_envs),
+    )
+    .route(
+      "/ephemeral-env/{id}",
+      delete(handlers::delete_ephemeral_env),
+    )
     // Power management — POST starts a PCS transition and returns
     // immediately; GET snapshots the transition for the CLI poll loop.
     .route("/power", post(handlers::post_power))
@@ -145,6 +174,10 @@ pub fn build_router(state: Arc<ServerState>) -> Router {
     )
     // CFS session logs (SSE)
     .route("/sessions/{name}/logs", get(handlers::get_session_logs))
+    .route(
+      "/sessions/{name}/logs/archive",
+      get(handlers::get_session_log_archive),
+    )
     // SAT file apply — per-element endpoints. The CLI's `build_plan`
     // walks the SAT file and dispatches one POST per artifact;
     // `images[]` further splits into the three-step
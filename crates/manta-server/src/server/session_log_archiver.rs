@@ -0,0 +1,169 @@
+//! Background task that copies a CFS session's log into the session
+//! log archive.
+//!
+//! [`follow`] is called by the handlers that stream a session's logs
+//! or create a session. It returns at once; the spawned task logs in
+//! as the configured service account, so it does not depend on the
+//! caller's token outliving the pod's start-up, opens its own log
+//! stream, writes every line to the [`SessionLogStore`] and publishes
+//! the archive when the stream ends. The store keeps one writer per
+//! session, so following a session that is already being archived, or
+//! that already has a complete archive, is a no-op.
+//!
+//! [`spawn_pruner`] drops archives past retention or over the count
+//! limit every [`PRUNE_INTERVAL`], on the blocking pool.
+//!
+//! [`SessionLogStore`]: crate::service::session_log::SessionLogStore
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use futures::{AsyncBufReadExt, StreamExt};
+use manta_backend_dispatcher::error::Error;
+use manta_backend_dispatcher::types::{K8sAuth, K8sDetails};
+
+use super::ServerState;
+use crate::service::session;
+
+/// Attempts at opening the log stream of a session whose pod may not
+/// be scheduled yet.
+const OPEN_ATTEMPTS: u32 = 10;
+
+/// Delay between two attempts.
+const OPEN_RETRY_DELAY: Duration = Duration::from_secs(30);
+
+/// Delay between two prunes of the archive.
+const PRUNE_INTERVAL: Duration = Duration::from_secs(60 * 60);
+
+/// Start the pruning loop on the current Tokio runtime. No-op when the
+/// server has no `[server.session_logs]`. The store already pruned
+/// itself when it was opened, so the first prune waits a full
+/// interval.
+pub fn spawn_pruner(state: Arc<ServerState>) {
+  if state.session_logs.is_none() {
+    return;
+  }
+  tokio::spawn(async move {
+    let mut ticker = tokio::time::interval(PRUNE_INTERVAL);
+    ticker.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+    ticker.tick().await;
+    loop {
+      ticker.tick().await;
+      let state = Arc::clone(&state);
+      let pruned = tokio::task::spawn_blocking(move || {
+        state
+          .session_logs
+          .as_ref()
+          .map(|logs| logs.store.prune(chrono::Utc::now()))
+      })
+      .await;
+      match pruned {
+        Ok(Some(Ok(deleted))) if deleted > 0 => {
+          tracing::info!("pruned {deleted} session log archive(s)");
+        }
+        Ok(Some(Err(e))) => {
+          tracing::warn!("could not prune the session log archive: {e}");
+        }
+        Err(e) => tracing::warn!("session log prune task failed: {e}"),
+        Ok(_) => {}
+      }
+    }
+  });
+}
+
+/// Archive the log of `session_name` on `site` in the background.
+/// No-op when the server has no `[server.session_logs]`.
+pub fn follow(state: Arc<ServerState>, site: String, session_name: String) {
+  if state.session_logs.is_none() {
+    return;
+  }
+  tokio::spawn(async move {
+    if let Err(e) = archive(&state, &site, &session_name).await {
+      tracing::warn!(
+        "could not archive log of CFS session '{session_name}' on site \
+         '{site}': {e}"
+      );
+    }
+  });
+}
+
+async fn archive(
+  state: &ServerState,
+  site: &str,
+  session_name: &str,
+) -> Result<(), Error> {
+  let Some(logs) = state.session_logs.as_ref() else {
+    return Ok(());
+  };
+  let store = &logs.store;
+  let infra = state.infra_context(site)?;
+  let (Some(k8s_api_url), Some(vault_base_url)) =
+    (infra.k8s_api_url, infra.vault_base_url)
+  else {
+    tracing::debug!(
+      "site '{site}' has no k8s/Vault config; not archiving session logs"
+    );
+    return Ok(());
+  };
+  let Some(mut writer) = store.begin(site, session_name)? else {
+    return Ok(());
+  };
+
+  let account = &logs.service_account;
+  let login = || {
+    crate::service::auth::get_api_token(
+      &infra,
+      &account.username,
+      &account.password,
+    )
+  };
+  let mut token = login().await?;
+  let groups = session::fetch_session_by_name(&infra, &token, session_name)
+    .await?
+    .get_target_hsm()
+    .unwrap_or_default();
+  let k8s = K8sDetails {
+    api_url: k8s_api_url.to_string(),
+    authentication: K8sAuth::Vault {
+      base_url: vault_base_url.to_string(),
+    },
+  };
+
+  let mut attempt = 1;
+  let stream = loop {
+    match session::stream_logs(&infra, &token, session_name, true, &k8s).await {
+      Ok(stream) => break stream,
+      Err(e) if attempt < OPEN_ATTEMPTS => {
+        tracing::debug!(
+          "log stream of CFS session '{session_name}' not available yet \
+           (attempt {attempt}/{OPEN_ATTEMPTS}): {e}"
+        );
+        attempt += 1;
+        tokio::time::sleep(OPEN_RETRY_DELAY).await;
+        // The retries span minutes; start each with a fresh token.
+        token = login().await?;
+      }
+      Err(e) => return Err(e),
+    }
+  };
+
+  let mut lines = stream.lines();
+  let mut complete = true;
+  while let Some(line) = lines.next().await {
+    match line {
+      Ok(line) => writer.write_line(&line)?,
+      Err(e) => {
+        tracing::warn!(
+          "log stream of CFS session '{session_name}' broke off: {e}"
+        );
+        complete = false;
+        break;
+      }
+    }
+  }
+  writer.finish(groups, complete)?;
+  tracing::info!(
+    "archived log of CFS session '{session_name}' on site '{site}'"
+  );
+  Ok(())
+}
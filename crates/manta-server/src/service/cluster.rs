@@ -7,13 +7,51 @@
 
 use manta_backend_dispatcher::error::Error;
 use manta_backend_dispatcher::interfaces::hsm::group::GroupTrait;
+use manta_shared::types::api::group::PrometheusTargetGroup;
 use manta_shared::types::dto::NodeDetails;
+use manta_shared::types::inventory_export;
 
 use crate::server::common::app_context::InfraContext;
 use crate::service::authorization::validate_user_group_vec_access;
 use crate::service::node_details;
 pub use manta_shared::types::api::cluster::GetClusterParams;
 
+/// `[group]`, or every group the token can access when `group` is
+/// `None`, after checking the caller may access them.
+async fn target_groups(
+  infra: &InfraContext<'_>,
+  token: &str,
+  group: Option<&str>,
+) -> Result<Vec<String>, Error> {
+  let target_group_vec: Vec<String> = if let Some(group) = group {
+    vec![group.to_string()]
+  } else {
+    infra
+      .backend
+      .get_group_available(token)
+      .await?
+      .iter()
+      .map(|group| group.label.clone())
+      .collect()
+  };
+  validate_user_group_vec_access(infra, token, &target_group_vec).await?;
+  Ok(target_group_vec)
+}
+
+/// Node details of every member of `groups`.
+async fn group_node_details(
+  infra: &InfraContext<'_>,
+  token: &str,
+  groups: &[String],
+) -> Result<Vec<NodeDetails>, Error> {
+  let mut group_vec_node_list = infra
+    .backend
+    .get_member_vec_from_group_name_vec(token, groups)
+    .await?;
+  group_vec_node_list.sort();
+  node_details::get_node_details(infra, token, &group_vec_node_list).await
+}
+
 /// Fetch full node details for every member of the requested HSM
 /// groups.
 ///
@@ -34,31 +72,10 @@ pub async fn get_cluster_nodes(
   token: &str,
   params: &GetClusterParams,
 ) -> Result<Vec<NodeDetails>, Error> {
-  // Get list of target groups the user is asking for
-  let target_group_vec: Vec<String> = if let Some(group) = &params.group_name {
-    vec![group.clone()]
-  } else {
-    infra
-      .backend
-      .get_group_available(token)
-      .await?
-      .iter()
-      .map(|group| group.label.clone())
-      .collect()
-  };
-
-  // Validate groups and get list of groups available
-  validate_user_group_vec_access(infra, token, &target_group_vec).await?;
-
-  let mut group_vec_node_list = infra
-    .backend
-    .get_member_vec_from_group_name_vec(token, &target_group_vec)
-    .await?;
-
-  group_vec_node_list.sort();
-
+  let target_group_vec =
+    target_groups(infra, token, params.group_name.as_deref()).await?;
   let mut node_details_list =
-    node_details::get_node_details(infra, token, &group_vec_node_list).await?;
+    group_node_details(infra, token, &target_group_vec).await?;
 
   // Apply status filter
   if let Some(ref status) = params.status_filter {
@@ -72,3 +89,26 @@ pub async fn get_cluster_nodes(
 
   Ok(node_details_list)
 }
+
+/// Prometheus HTTP SD targets for the members of `group` (every
+/// accessible group when `None`), scraped on `port`. Labels are
+/// described in [`inventory_export::prometheus_targets`].
+///
+/// # Errors
+///
+/// Same as [`get_cluster_nodes`].
+pub async fn get_prometheus_targets(
+  infra: &InfraContext<'_>,
+  token: &str,
+  group: Option<&str>,
+  port: u16,
+) -> Result<Vec<PrometheusTargetGroup>, Error> {
+  let groups = target_groups(infra, token, group).await?;
+  let nodes = group_node_details(infra, token, &groups).await?;
+  Ok(inventory_export::prometheus_targets(
+    infra.site_name,
+    &groups,
+    &nodes,
+    port,
+  ))
+}
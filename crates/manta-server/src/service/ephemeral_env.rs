@@ -1,115 +1,405 @@
-//! Ephemeral CFS environment provisioning — launches a temporary IMS
-//! customize container booted from an existing IMS image and returns
-//! its SSH hostname.
+//! Ephemeral CFS environments — temporary containers booted from an
+//! existing image and reachable over SSH.
 //!
-//! Unlike most service modules this one bypasses the backend
-//! dispatcher and talks to CSM via [`csm_rs::ShastaClient`] directly:
-//! the IMS `jobs.customize` API is CSM-specific and isn't exposed
-//! through `manta-backend-dispatcher`. The handler is wired only on
-//! the CSM site.
+//! The backend work goes through [`EphemeralEnvTrait`] on the site's
+//! dispatcher: CSM runs them as IMS customize jobs, OpenCHAMI has no
+//! implementation and answers [`Error::UnsupportedBackend`]. This
+//! module adds who the caller is and when environments expire.
 //!
-//! Flow:
+//! Creation flow ([`exec`]):
 //!
 //! 1. Read the JWT's `preferred_username` claim.
-//! 2. Look the user's public SSH key up in IMS — registered keys are
-//!    keyed by username on the IMS side.
-//! 3. POST an IMS customize job referencing `image_id` and the key id.
-//! 4. Extract the SSH hostname from the response and return it.
+//! 2. Have the backend boot the environment with that user's SSH key.
+//! 3. With `[server.ephemeral_envs]` configured, record the
+//!    environment's expiry in the [`EphemeralEnvStore`]; the reaper in
+//!    [`crate::server::ephemeral_env_reaper`] deletes it once due.
 
-use csm_rs::ShastaClient;
+use std::path::PathBuf;
+use std::time::Duration;
+
+use chrono::{DateTime, SecondsFormat, Utc};
 use manta_backend_dispatcher::error::Error;
+use serde::{Deserialize, Serialize};
+use tokio::sync::Mutex;
 
+use crate::backend_dispatcher::EphemeralEnvTrait;
 use crate::server::common::app_context::InfraContext;
 use crate::server::common::jwt_ops;
+use crate::service::authorization;
+use crate::service::reservation::{ServiceAccount, persist};
 use crate::wire_conv;
+pub use manta_shared::types::api::responses::{
+  EphemeralEnv, EphemeralEnvResponse,
+};
+
+/// Everything the server needs to expire environments, built at
+/// startup from `[server.ephemeral_envs]`.
+pub struct EphemeralEnvs {
+  /// Expiry of every live environment, persisted to disk.
+  pub store: EphemeralEnvStore,
+  /// Lifetime of an environment created without a TTL.
+  pub default_ttl: Duration,
+  /// Credentials the reaper authenticates with.
+  pub service_account: ServiceAccount,
+  /// Delay between two reaper passes.
+  pub check_interval: Duration,
+}
+
+/// When an environment expires. Server-side only: the wire shows it as
+/// [`EphemeralEnv::expires_at`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct EphemeralEnvLease {
+  /// IMS customize job id.
+  pub id: String,
+  /// Site the job runs on.
+  pub site: String,
+  /// User who created the environment.
+  pub owner: String,
+  /// RFC 3339 expiry.
+  pub expires_at: String,
+}
+
+/// Environment expiries backed by a JSON file, written the same way as
+/// [`crate::service::reservation::ReservationStore`].
+pub struct EphemeralEnvStore {
+  path: PathBuf,
+  entries: Mutex<Vec<EphemeralEnvLease>>,
+}
 
-const EPHEMERAL_IMAGE_NAME: &str = "__ephemeral_image";
+impl EphemeralEnvStore {
+  /// Load the store from `path`. A missing file is an empty store; it
+  /// is created on the first insert.
+  ///
+  /// # Errors
+  ///
+  /// [`Error::IoError`] when the file exists but cannot be read, and
+  /// [`Error::SerdeError`] when it is not a JSON list of leases.
+  pub fn open(path: impl Into<PathBuf>) -> Result<Self, Error> {
+    let path = path.into();
+    let entries = match std::fs::read(&path) {
+      Ok(bytes) => serde_json::from_slice(&bytes)?,
+      Err(e) if e.kind() == std::io::ErrorKind::NotFound => Vec::new(),
+      Err(e) => return Err(e.into()),
+    };
+    Ok(Self {
+      path,
+      entries: Mutex::new(entries),
+    })
+  }
 
-/// Launch an ephemeral IMS customize container against `image_id` and
-/// return its SSH hostname.
+  /// Snapshot of every lease.
+  pub async fn list(&self) -> Vec<EphemeralEnvLease> {
+    self.entries.lock().await.clone()
+  }
+
+  /// Record `lease` and persist.
+  ///
+  /// # Errors
+  ///
+  /// [`Error::IoError`] / [`Error::SerdeError`] when the file cannot be
+  /// written; the in-memory list is left unchanged.
+  pub async fn insert(&self, lease: EphemeralEnvLease) -> Result<(), Error> {
+    let mut entries = self.entries.lock().await;
+    let mut updated = entries.clone();
+    updated.push(lease);
+    persist(&self.path, &updated).await?;
+    *entries = updated;
+    Ok(())
+  }
+
+  /// Drop the lease of job `id` on `site` (if any) and persist.
+  ///
+  /// # Errors
+  ///
+  /// [`Error::IoError`] / [`Error::SerdeError`] when the file cannot be
+  /// written; the in-memory list is left unchanged.
+  pub async fn remove(&self, site: &str, id: &str) -> Result<(), Error> {
+    let mut entries = self.entries.lock().await;
+    let updated: Vec<EphemeralEnvLease> = entries
+      .iter()
+      .filter(|l| !(l.site == site && l.id == id))
+      .cloned()
+      .collect();
+    persist(&self.path, &updated).await?;
+    *entries = updated;
+    Ok(())
+  }
+}
+
+/// Leases whose expiry is at or before `now`. As with reservations, an
+/// `expires_at` that does not parse counts as due.
+pub fn due_for_deletion(
+  entries: &[EphemeralEnvLease],
+  now: DateTime<Utc>,
+) -> Vec<EphemeralEnvLease> {
+  entries
+    .iter()
+    .filter(|l| {
+      DateTime::parse_from_rfc3339(&l.expires_at)
+        .map_or(true, |expires_at| expires_at <= now)
+    })
+    .cloned()
+    .collect()
+}
+
+/// `ttl` as a span that can be added to a timestamp.
+fn lifetime(ttl: Duration) -> Result<chrono::Duration, Error> {
+  chrono::Duration::from_std(ttl).map_err(|_| {
+    Error::BadRequest(format!("TTL of {}s is too long", ttl.as_secs()))
+  })
+}
+
+/// Launch an ephemeral environment against `image_id`.
+///
+/// The caller's preferred username is read from the JWT; the backend
+/// boots the environment reachable with that user's registered SSH
+/// public key and returns its hostname.
 ///
-/// The caller's preferred username is read from the JWT and used to
-/// look up their registered SSH public key in IMS. If no key is
-/// registered, returns `NotFound` with a message pointing the user
-/// at platform admins. The hostname is plucked from the IMS response
-/// at `/ssh_containers/0/connection_info/customer_access/host`; a
-/// missing field is reported as `MissingField` rather than a generic
-/// error so operators can tell schema drift from real failures.
+/// With `envs` set the environment expires after `ttl` (or
+/// [`EphemeralEnvs::default_ttl`]); without it, it lives until deleted
+/// and `ttl` must be `None` (the handler answers 501 otherwise).
 ///
 /// # Errors
 ///
 /// - [`Error::JwtMalformed`] (via `wire_conv::to_backend`) when the
 ///   JWT carries no `preferred_username`.
-/// - [`Error::BadRequest`] if the Shasta HTTP client cannot be built
-///   or the IMS customize job submission fails.
+/// - [`Error::UnsupportedBackend`] on a backend without ephemeral
+///   environments.
 /// - [`Error::NotFound`] when the caller has no SSH public key
 ///   registered in IMS.
-/// - [`Error::MissingField`] when the IMS response is missing the
-///   server-generated key id or the SSH container host field.
+/// - [`Error::BadRequest`] / [`Error::MissingField`] when the backend
+///   can't create the environment or its answer lacks the id or
+///   hostname.
+/// - [`Error::BadRequest`] when the TTL is too long, before anything
+///   is created.
+/// - [`Error::IoError`] / [`Error::SerdeError`] when the expiry cannot
+///   be recorded; the environment is deleted again.
 pub async fn exec(
   infra: &InfraContext<'_>,
   token: &str,
   image_id: &str,
-) -> Result<String, Error> {
-  let user_public_key_name =
+  ttl: Option<Duration>,
+  envs: Option<&EphemeralEnvs>,
+) -> Result<EphemeralEnvResponse, Error> {
+  let username =
     jwt_ops::get_preferred_username(token).map_err(wire_conv::to_backend)?;
 
-  tracing::info!("Looking for user '{}' public SSH key", user_public_key_name);
-
-  let shasta =
-    ShastaClient::new(infra.shasta_base_url, infra.shasta_root_cert.to_vec())
-      .map_err(|e| {
-      Error::BadRequest(format!("Could not build Shasta HTTP client: {e}"))
-    })?;
-
-  let user_public_ssh_id = if let Ok(Some(user_public_ssh_key)) = shasta
-    .ims_public_keys_v3_get_single(token, &user_public_key_name)
-    .await
-  {
-    user_public_ssh_key.id.ok_or_else(|| {
-      Error::MissingField(
-        "IMS public-key response missing server-generated 'id'".to_string(),
-      )
-    })?
-  } else {
-    return Err(Error::NotFound(format!(
-      "User '{user_public_key_name}' does not have an SSH public key in Alps. \
-       Please contact platform sys admins."
-    )));
-  };
+  // Checked before anything is booted, so a bad TTL leaves nothing
+  // behind.
+  let lifetime = envs
+    .map(|envs| lifetime(ttl.unwrap_or(envs.default_ttl)))
+    .transpose()?;
 
-  tracing::info!("SSH key found with ID {}", user_public_ssh_id);
   tracing::info!(
     "Creating ephemeral environment based on image ID {}",
     image_id
   );
-
-  let resp_json = shasta
-    .ims_job_post_customize(
+  let env = infra
+    .backend
+    .create_ephemeral_env(
+      infra.shasta_base_url,
+      infra.shasta_root_cert,
       token,
-      EPHEMERAL_IMAGE_NAME,
+      &username,
       image_id,
-      &user_public_ssh_id,
     )
-    .await
-    .map_err(|e| {
-      Error::BadRequest(format!(
-        "Could not create ephemeral environment based on image ID {image_id}: {e}"
-      ))
-    })?;
-
-  let hostname = resp_json
-    .pointer("/ssh_containers/0/connection_info/customer_access/host")
-    .and_then(|v| v.as_str())
-    .ok_or_else(|| {
-      Error::MissingField(
-        "Failed to get SSH container hostname from ephemeral env response"
-          .to_string(),
-      )
-    })?
-    .to_string();
-
-  tracing::info!("Ephemeral environment created — SSH hostname: {}", hostname);
-
-  Ok(hostname)
+    .await?;
+  let hostname = env.hostname.ok_or_else(|| {
+    Error::MissingField(
+      "Failed to get SSH container hostname from ephemeral env response"
+        .to_string(),
+    )
+  })?;
+  let id = env.id;
+
+  tracing::info!(
+    "Ephemeral environment {id} created — SSH hostname: {}",
+    hostname
+  );
+
+  let expires_at = match envs.zip(lifetime) {
+    Some((envs, lifetime)) => {
+      let expires_at =
+        (Utc::now() + lifetime).to_rfc3339_opts(SecondsFormat::Secs, true);
+      let recorded = envs
+        .store
+        .insert(EphemeralEnvLease {
+          id: id.clone(),
+          site: infra.site_name.to_string(),
+          owner: username,
+          expires_at: expires_at.clone(),
+        })
+        .await;
+      if let Err(e) = recorded {
+        // Nothing would ever reap it; take it down rather than leave
+        // it running with no expiry.
+        if let Err(delete_err) = infra
+          .backend
+          .delete_ephemeral_env(
+            infra.shasta_base_url,
+            infra.shasta_root_cert,
+            token,
+            &id,
+            None,
+          )
+          .await
+        {
+          tracing::warn!(
+            "Ephemeral environment {id} has no recorded expiry and could \
+             not be deleted: {delete_err}"
+          );
+        }
+        return Err(e);
+      }
+      Some(expires_at)
+    }
+    None => None,
+  };
+
+  Ok(EphemeralEnvResponse {
+    id,
+    hostname,
+    expires_at,
+  })
+}
+
+/// The caller's ephemeral environments on the site, oldest first, with
+/// their expiry when `store` tracks one.
+///
+/// # Errors
+///
+/// - [`Error::JwtMalformed`] when the JWT carries no
+///   `preferred_username`.
+/// - [`Error::UnsupportedBackend`] on a backend without ephemeral
+///   environments.
+/// - [`Error::NotFound`] when the caller has no SSH public key in IMS.
+/// - [`Error::BadRequest`] / [`Error::NetError`] / [`Error::Message`]
+///   when IMS can't be reached or answers with an error.
+pub async fn list(
+  infra: &InfraContext<'_>,
+  token: &str,
+  store: Option<&EphemeralEnvStore>,
+) -> Result<Vec<EphemeralEnv>, Error> {
+  let username =
+    jwt_ops::get_preferred_username(token).map_err(wire_conv::to_backend)?;
+  let mut envs = infra
+    .backend
+    .get_ephemeral_envs(
+      infra.shasta_base_url,
+      infra.shasta_root_cert,
+      token,
+      &username,
+    )
+    .await?;
+  let leases = match store {
+    Some(store) => store.list().await,
+    None => Vec::new(),
+  };
+
+  for env in &mut envs {
+    env.expires_at = leases
+      .iter()
+      .find(|l| l.site == infra.site_name && l.id == env.id)
+      .map(|l| l.expires_at.clone());
+  }
+  envs.sort_by(|a, b| a.created.cmp(&b.created));
+  Ok(envs)
+}
+
+/// Delete the caller's ephemeral environment `id` and forget its
+/// expiry. Admins may delete anybody's environment.
+///
+/// # Errors
+///
+/// - [`Error::NotFound`] when `id` is no ephemeral environment, or
+///   belongs to another user and the caller isn't an admin.
+/// - [`Error::JwtMalformed`] when the JWT carries no
+///   `preferred_username`.
+/// - [`Error::UnsupportedBackend`] on a backend without ephemeral
+///   environments.
+/// - [`Error::BadRequest`] / [`Error::NetError`] / [`Error::Message`]
+///   when IMS can't be reached or answers with an error.
+/// - [`Error::IoError`] / [`Error::SerdeError`] when the expiry can't
+///   be dropped from the store; the environment is already gone.
+pub async fn delete(
+  infra: &InfraContext<'_>,
+  token: &str,
+  id: &str,
+  store: Option<&EphemeralEnvStore>,
+) -> Result<(), Error> {
+  let username =
+    jwt_ops::get_preferred_username(token).map_err(wire_conv::to_backend)?;
+  let owner = (!authorization::is_admin(token)).then_some(username.as_str());
+  infra
+    .backend
+    .delete_ephemeral_env(
+      infra.shasta_base_url,
+      infra.shasta_root_cert,
+      token,
+      id,
+      owner,
+    )
+    .await?;
+  tracing::info!("Ephemeral environment {id} deleted");
+  if let Some(store) = store {
+    store.remove(infra.site_name, id).await?;
+  }
+  Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn lease(id: &str, expires_at: &str) -> EphemeralEnvLease {
+    EphemeralEnvLease {
+      id: id.to_string(),
+      site: "alps".to_string(),
+      owner: "jdoe".to_string(),
+      expires_at: expires_at.to_string(),
+    }
+  }
+
+  #[test]
+  fn due_leases_include_unparsable_expiries() {
+    let now = "2026-10-19T12:00:00Z".parse::<DateTime<Utc>>().unwrap();
+    let leases = [
+      lease("past", "2026-10-19T11:59:59Z"),
+      lease("future", "2026-10-19T12:00:01Z"),
+      lease("garbage", "tomorrow"),
+    ];
+    let due: Vec<String> = due_for_deletion(&leases, now)
+      .into_iter()
+      .map(|l| l.id)
+      .collect();
+    assert_eq!(due, ["past", "garbage"]);
+  }
+
+  #[test]
+  fn overlong_ttls_are_refused() {
+    assert_eq!(
+      lifetime(Duration::from_secs(3600)).unwrap(),
+      chrono::Duration::hours(1)
+    );
+    assert!(matches!(lifetime(Duration::MAX), Err(Error::BadRequest(_))));
+  }
+
+  #[tokio::test]
+  async fn store_survives_reopen() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("ephemeral-envs.json");
+    let store = EphemeralEnvStore::open(&path).unwrap();
+    store
+      .insert(lease("a", "2026-10-20T00:00:00Z"))
+      .await
+      .unwrap();
+    store
+      .insert(lease("b", "2026-10-21T00:00:00Z"))
+      .await
+      .unwrap();
+    store.remove("alps", "a").await.unwrap();
+
+    let reopened = EphemeralEnvStore::open(&path).unwrap();
+    assert_eq!(reopened.list().await, [lease("b", "2026-10-21T00:00:00Z")]);
+  }
 }
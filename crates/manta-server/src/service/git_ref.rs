@@ -0,0 +1,122 @@
+//! Resolution of remote Git references against a site's Gitea, so
+//! `POST /sessions` can build configuration layers from a repository
+//! URL and a branch, tag or commit instead of a local checkout's
+//! HEAD.
+//!
+//! A ref is resolved through the site's [`GitRefTrait`], which
+//! tries it as a branch, then as a tag, then as a commit.
+
+use manta_backend_dispatcher::error::Error;
+
+use crate::backend_dispatcher::GitRefTrait;
+use crate::server::common::app_context::InfraContext;
+pub use manta_shared::types::api::session::RemoteRepoRef;
+
+/// Gitea organisation CFS configuration repositories live in; assumed
+/// when a repository is given by bare name.
+const GITEA_ORG: &str = "cray";
+
+/// The `<org>/<repo>` path of `repo_url` in Gitea: the clone URL's
+/// path without its `/vcs/` prefix and `.git` suffix, or the value
+/// itself when it is no URL, with [`GITEA_ORG`] added to a bare name.
+///
+/// Each segment must be made of `[A-Za-z0-9._-]` and not be `.` or
+/// `..`: the path ends up in a Gitea API URL called with the site's
+/// VCS token, so nothing in it may redirect the call elsewhere.
+///
+/// # Errors
+///
+/// [`Error::BadRequest`] when no valid `<org>/<repo>` path can be
+/// taken from `repo_url`.
+pub fn repo_path(repo_url: &str) -> Result<String, Error> {
+  let path = match repo_url.trim().split_once("://") {
+    Some((_, rest)) => rest.split_once('/').map_or("", |(_, path)| path),
+    None => repo_url.trim(),
+  };
+  let path = path.trim_matches('/').trim_end_matches(".git");
+  let path = path.rsplit_once("vcs/").map_or(path, |(_, path)| path);
+  let segments: Vec<&str> = path.split('/').collect();
+  match segments.as_slice() {
+    [repo] if valid_segment(repo) => Ok(format!("{GITEA_ORG}/{repo}")),
+    [org, repo] if valid_segment(org) && valid_segment(repo) => {
+      Ok(format!("{org}/{repo}"))
+    }
+    _ => Err(Error::BadRequest(format!(
+      "Cannot tell the Gitea repository of '{repo_url}'; expected \
+       https://<host>/vcs/<org>/<repo>.git, <org>/<repo> or <repo>, with \
+       names made of letters, digits, '.', '_' and '-'"
+    ))),
+  }
+}
+
+/// Whether `segment` is a usable Gitea org or repository name.
+fn valid_segment(segment: &str) -> bool {
+  !segment.is_empty()
+    && segment != "."
+    && segment != ".."
+    && segment
+      .bytes()
+      .all(|b| b.is_ascii_alphanumeric() || matches!(b, b'.' | b'_' | b'-'))
+}
+
+/// Resolve every entry of `repo_refs` to its repository path (as
+/// taken by the backend's `apply_session`) and commit SHA, in order.
+///
+/// # Errors
+///
+/// - [`Error::BadRequest`] for a repository URL [`repo_path`] rejects,
+///   an invalid ref, or when the root CA can't be loaded.
+/// - [`Error::NotFound`] when a repository has no branch, tag or
+///   commit named by its ref (or does not exist).
+/// - [`Error::UnsupportedBackend`] on a backend without a VCS.
+/// - [`Error::NetError`] / [`Error::Message`] when Gitea can't be
+///   reached or answers with an unexpected error.
+pub async fn resolve_all(
+  infra: &InfraContext<'_>,
+  gitea_token: &str,
+  repo_refs: &[RemoteRepoRef],
+) -> Result<(Vec<String>, Vec<String>), Error> {
+  let mut repo_names = Vec::with_capacity(repo_refs.len());
+  let mut commits = Vec::with_capacity(repo_refs.len());
+  for repo_ref in repo_refs {
+    let repo = repo_path(&repo_ref.repo_url)?;
+    let commit = infra
+      .backend
+      .resolve_git_ref(
+        infra.gitea_base_url,
+        infra.shasta_root_cert,
+        gitea_token,
+        &repo,
+        &repo_ref.git_ref,
+      )
+      .await?;
+    tracing::info!("Resolved {repo}@{} to commit {commit}", repo_ref.git_ref);
+    repo_names.push(repo);
+    commits.push(commit);
+  }
+  Ok((repo_names, commits))
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn repo_path_accepts_urls_and_names() {
+    for url in [
+      "https://api-gw-service-nmn.local/vcs/cray/csm-config.git",
+      "https://api.cmn.example.com/vcs/cray/csm-config",
+      "cray/csm-config",
+      "csm-config",
+    ] {
+      assert_eq!(repo_path(url).unwrap(), "cray/csm-config", "{url}");
+    }
+    assert!(matches!(
+      repo_path("https://vcs.example.com/"),
+      Err(Error::BadRequest(_))
+    ));
+    for bad in ["a/b/c", "../admin", "a/b?x", "a/b#x", "./x", "a b/c"] {
+      assert!(matches!(repo_path(bad), Err(Error::BadRequest(_))), "{bad}");
+    }
+  }
+}
@@ -17,15 +17,13 @@ use std::time::Instant;
 
 use manta_backend_dispatcher::error::Error;
 use manta_backend_dispatcher::interfaces::hsm::group::GroupTrait;
-use manta_backend_dispatcher::interfaces::hsm::{
-  component::ComponentTrait, hardware_inventory::HardwareInventory,
-};
+use manta_backend_dispatcher::interfaces::hsm::hardware_inventory::HardwareInventory;
 use manta_backend_dispatcher::types::NodeSummary;
 use tokio::sync::Semaphore;
 
 use crate::server::common::app_context::InfraContext;
 use crate::service::authorization::validate_user_group_members_access;
-use crate::service::node_ops::from_hosts_expression_to_xname_vec;
+use crate::service::node_ops::from_user_hosts_expression_to_xname_vec;
 pub use manta_shared::types::api::hardware::{
   GetHardwareClusterParams, GetHardwareNodesListParams,
 };
@@ -205,8 +203,9 @@ pub struct HardwareNodesListResult {
 /// Fetch hardware inventory for the nodes named by
 /// `params.host_expression`.
 ///
-/// The expression is parsed by [`from_hosts_expression_to_xname_vec`]
-/// (hostlist notation, NIDs, or xnames; siblings are not expanded
+/// The expression is resolved by
+/// [`from_user_hosts_expression_to_xname_vec`] (hostlist notation,
+/// NIDs, xnames, or a set expression; siblings are not expanded
 /// here). An empty resolution yields `BadRequest` rather than a
 /// silent no-op. The caller's group access to every resolved xname is
 /// validated through [`validate_user_group_members_access`] before
@@ -227,14 +226,13 @@ pub async fn get_hardware_nodes_list(
   token: &str,
   params: &GetHardwareNodesListParams,
 ) -> Result<HardwareNodesListResult, Error> {
-  let node_metadata_available_vec =
-    infra.backend.get_node_metadata_available(token).await?;
-
-  let node_list = from_hosts_expression_to_xname_vec(
+  let node_list = from_user_hosts_expression_to_xname_vec(
+    infra,
+    token,
     &params.host_expression,
     false,
-    &node_metadata_available_vec,
-  )?;
+  )
+  .await?;
 
   if node_list.is_empty() {
     return Err(Error::BadRequest(
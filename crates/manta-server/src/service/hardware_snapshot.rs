@@ -0,0 +1,412 @@
+//! Hardware inventory snapshots — a group's per-node hardware recorded
+//! at one point in time, so a later inventory can be compared against
+//! it.
+//!
+//! [`take_snapshot`] reads the group through
+//! [`hardware::get_hardware_cluster`] and normalises each node with
+//! `manta_shared::types::hardware_diff::snapshot_nodes`. Snapshots live
+//! in a [`SnapshotStore`], one JSON file per snapshot in a directory.
+//! Comparing two of them is left to the caller (`manta diff hardware`
+//! or the watcher in [`crate::server::hardware_watch`]).
+//!
+//! The whole feature is opt-in through `[server.hardware_snapshots]`;
+//! without it [`crate::server::ServerState::hardware_snapshots`] is
+//! `None` and the handlers answer `501 Not Implemented`.
+
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::Duration;
+
+use chrono::{DateTime, SecondsFormat, Utc};
+use manta_backend_dispatcher::error::Error;
+use manta_backend_dispatcher::interfaces::hsm::group::GroupTrait;
+use manta_shared::types::hardware_diff::snapshot_nodes;
+
+use crate::server::common::app_context::InfraContext;
+use crate::service::authorization;
+use crate::service::hardware::{self, GetHardwareClusterParams};
+use crate::service::reservation::ServiceAccount;
+
+pub use manta_shared::types::api::hardware::{
+  HardwareSnapshot, HardwareSnapshotInfo,
+};
+
+/// Everything the server needs to store snapshots and, optionally,
+/// take them on a schedule. Built at startup from
+/// `[server.hardware_snapshots]`.
+pub struct HardwareSnapshots {
+  /// Stored snapshots.
+  pub store: SnapshotStore,
+  /// Scheduled snapshots; `None` when only on-demand snapshots are
+  /// enabled.
+  pub watch: Option<SnapshotWatch>,
+}
+
+/// Groups the watcher snapshots and compares on every pass.
+pub struct SnapshotWatch {
+  /// `(site, group)` pairs to watch.
+  pub targets: Vec<(String, String)>,
+  /// Delay between two passes.
+  pub interval: Duration,
+  /// Credentials the watcher reads the inventory with.
+  pub service_account: ServiceAccount,
+}
+
+/// Snapshots stored as `<id>.json` files in one directory.
+///
+/// Snapshots are immutable, so each file is written once (through a
+/// temporary file and a rename) and never locked. The newest snapshot
+/// of each group is indexed in memory, built by one scan when the
+/// store opens and kept current by [`Self::save`], so [`Self::latest`]
+/// — called for every watched group on every watcher pass — reads a
+/// single file. File access runs on the blocking pool.
+pub struct SnapshotStore {
+  dir: PathBuf,
+  /// `(site, group)` → `(taken_at, id)` of its newest snapshot.
+  latest: Mutex<HashMap<(String, String), (String, String)>>,
+}
+
+impl SnapshotStore {
+  /// Open the store in `dir`, creating the directory if needed, and
+  /// index the newest snapshot of each group.
+  ///
+  /// # Errors
+  ///
+  /// [`Error::IoError`] when the directory cannot be created or listed.
+  pub fn open(dir: impl Into<PathBuf>) -> Result<Self, Error> {
+    let dir = dir.into();
+    std::fs::create_dir_all(&dir)?;
+    let store = Self {
+      latest: Mutex::new(HashMap::new()),
+      dir,
+    };
+    for snapshot in read_all(&store.dir)? {
+      store.index(&snapshot);
+    }
+    Ok(store)
+  }
+
+  /// Record `snapshot` in the per-group index if it is the newest of
+  /// its group.
+  fn index(&self, snapshot: &HardwareSnapshot) {
+    let mut latest = self.latest.lock().unwrap_or_else(|e| e.into_inner());
+    let key = (snapshot.site.clone(), snapshot.group.clone());
+    if latest
+      .get(&key)
+      .is_none_or(|(taken_at, _)| *taken_at <= snapshot.taken_at)
+    {
+      latest.insert(key, (snapshot.taken_at.clone(), snapshot.id.clone()));
+    }
+  }
+
+  /// Write `snapshot` to `<id>.json`.
+  ///
+  /// # Errors
+  ///
+  /// [`Error::IoError`] / [`Error::SerdeError`] when the file cannot be
+  /// written.
+  pub async fn save(&self, snapshot: &HardwareSnapshot) -> Result<(), Error> {
+    let path = self.dir.join(format!("{}.json", snapshot.id));
+    let tmp = path.with_extension("json.tmp");
+    tokio::fs::write(&tmp, serde_json::to_vec_pretty(snapshot)?).await?;
+    tokio::fs::rename(&tmp, &path).await?;
+    self.index(snapshot);
+    Ok(())
+  }
+
+  /// Read the snapshot with `id`; `None` when there is none. Ids are
+  /// server-assigned UUIDs, so anything else (a path, say) is `None`
+  /// without touching the filesystem.
+  ///
+  /// # Errors
+  ///
+  /// [`Error::IoError`] / [`Error::SerdeError`] when the file exists
+  /// but cannot be read or parsed.
+  pub async fn load(
+    &self,
+    id: &str,
+  ) -> Result<Option<HardwareSnapshot>, Error> {
+    if uuid::Uuid::parse_str(id).is_err() {
+      return Ok(None);
+    }
+    match tokio::fs::read(self.dir.join(format!("{id}.json"))).await {
+      Ok(bytes) => Ok(Some(serde_json::from_slice(&bytes)?)),
+      Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(None),
+      Err(e) => Err(e.into()),
+    }
+  }
+
+  /// Every stored snapshot, newest first. Files that cannot be read
+  /// are logged and skipped so one bad file does not hide the rest.
+  ///
+  /// # Errors
+  ///
+  /// [`Error::IoError`] when the directory cannot be listed.
+  pub async fn list(&self) -> Result<Vec<HardwareSnapshot>, Error> {
+    let dir = self.dir.clone();
+    let mut snapshots = tokio::task::spawn_blocking(move || read_all(&dir))
+      .await
+      .map_err(|e| Error::Message(format!("hardware snapshot scan: {e}")))??;
+    snapshots.sort_by(|a: &HardwareSnapshot, b| b.taken_at.cmp(&a.taken_at));
+    Ok(snapshots)
+  }
+
+  /// Newest snapshot of `group` on `site`, read through the index.
+  ///
+  /// # Errors
+  ///
+  /// Propagates [`Self::load`] errors.
+  pub async fn latest(
+    &self,
+    site: &str,
+    group: &str,
+  ) -> Result<Option<HardwareSnapshot>, Error> {
+    let id = self
+      .latest
+      .lock()
+      .unwrap_or_else(|e| e.into_inner())
+      .get(&(site.to_string(), group.to_string()))
+      .map(|(_, id)| id.clone());
+    match id {
+      Some(id) => self.load(&id).await,
+      None => Ok(None),
+    }
+  }
+}
+
+/// Read and parse every `*.json` file in `dir`, skipping (and logging)
+/// the ones that fail. Blocking; see [`SnapshotStore::list`].
+fn read_all(dir: &Path) -> Result<Vec<HardwareSnapshot>, Error> {
+  let mut snapshots = Vec::new();
+  for entry in std::fs::read_dir(dir)? {
+    let path = entry?.path();
+    if path.extension().is_none_or(|ext| ext != "json") {
+      continue;
+    }
+    let parsed = std::fs::read(&path)
+      .map_err(Error::from)
+      .and_then(|bytes| Ok(serde_json::from_slice(&bytes)?));
+    match parsed {
+      Ok(snapshot) => snapshots.push(snapshot),
+      Err(e) => tracing::warn!(
+        "skipping unreadable hardware snapshot {}: {e}",
+        path.display()
+      ),
+    }
+  }
+  Ok(snapshots)
+}
+
+/// Read the current inventory of `group` into a new snapshot. Nothing
+/// is stored.
+///
+/// # Errors
+///
+/// Propagates [`hardware::get_hardware_cluster`] errors, including the
+/// access check on `group`.
+pub async fn take_snapshot(
+  infra: &InfraContext<'_>,
+  token: &str,
+  group: &str,
+  taken_by: &str,
+  now: DateTime<Utc>,
+) -> Result<HardwareSnapshot, Error> {
+  let result = hardware::get_hardware_cluster(
+    infra,
+    token,
+    &GetHardwareClusterParams {
+      group_name: Some(group.to_string()),
+      settings_hsm_group_name: None,
+    },
+  )
+  .await?;
+  let (nodes, unreadable) = snapshot_nodes(&result.node_summaries);
+  Ok(HardwareSnapshot {
+    id: uuid::Uuid::new_v4().to_string(),
+    site: infra.site_name.to_string(),
+    group: result.hsm_group_name,
+    taken_at: now.to_rfc3339_opts(SecondsFormat::Secs, true),
+    taken_by: taken_by.to_string(),
+    nodes,
+    unreadable,
+  })
+}
+
+/// Snapshot `group` as the caller and store it.
+///
+/// # Errors
+///
+/// [`take_snapshot`] errors, a token without `preferred_username`, or
+/// a store write failure.
+pub async fn create_snapshot(
+  infra: &InfraContext<'_>,
+  token: &str,
+  store: &SnapshotStore,
+  group: &str,
+) -> Result<HardwareSnapshotInfo, Error> {
+  let taken_by = crate::service::reservation::owner_from_token(token)?;
+  let snapshot =
+    take_snapshot(infra, token, group, &taken_by, Utc::now()).await?;
+  store.save(&snapshot).await?;
+  Ok(HardwareSnapshotInfo::from(&snapshot))
+}
+
+/// Stored snapshots on the caller's site, newest first, optionally
+/// limited to one group.
+///
+/// Admins see every snapshot on the site; other users see those of
+/// groups they can access.
+///
+/// # Errors
+///
+/// Store listing errors, or whatever `get_group_name_available`
+/// returns for a non-admin token.
+pub async fn list_snapshots(
+  infra: &InfraContext<'_>,
+  token: &str,
+  store: &SnapshotStore,
+  group: Option<&str>,
+) -> Result<Vec<HardwareSnapshotInfo>, Error> {
+  let mut snapshots: Vec<HardwareSnapshot> = store
+    .list()
+    .await?
+    .into_iter()
+    .filter(|s| s.site == infra.site_name)
+    .filter(|s| group.is_none_or(|group| s.group == group))
+    .collect();
+  if !authorization::is_admin(token) {
+    let available = infra.backend.get_group_name_available(token).await?;
+    snapshots.retain(|s| available.contains(&s.group));
+  }
+  Ok(snapshots.iter().map(HardwareSnapshotInfo::from).collect())
+}
+
+/// One stored snapshot, with its node inventory.
+///
+/// # Errors
+///
+/// [`Error::NotFound`] when no snapshot has `id` on the caller's site
+/// or the caller cannot access its group (the two are not told apart,
+/// so ids of other tenants' snapshots are not confirmed).
+pub async fn get_snapshot(
+  infra: &InfraContext<'_>,
+  token: &str,
+  store: &SnapshotStore,
+  id: &str,
+) -> Result<HardwareSnapshot, Error> {
+  let not_found =
+    || Error::NotFound(format!("hardware snapshot '{id}' not found"));
+  let snapshot = store
+    .load(id)
+    .await?
+    .filter(|s| s.site == infra.site_name)
+    .ok_or_else(not_found)?;
+  if !authorization::is_admin(token) {
+    let available = infra.backend.get_group_name_available(token).await?;
+    if !available.contains(&snapshot.group) {
+      return Err(not_found());
+    }
+  }
+  Ok(snapshot)
+}
+
+#[cfg(test)]
+mod tests {
+  use std::collections::BTreeMap;
+
+  use super::*;
+
+  fn snapshot(id: &str, group: &str, taken_at: &str) -> HardwareSnapshot {
+    HardwareSnapshot {
+      id: id.to_string(),
+      site: "alps".to_string(),
+      group: group.to_string(),
+      taken_at: taken_at.to_string(),
+      taken_by: "alice".to_string(),
+      nodes: BTreeMap::new(),
+      unreadable: Vec::new(),
+    }
+  }
+
+  #[tokio::test]
+  async fn store_lists_newest_first_and_finds_latest_per_group() {
+    let dir = tempfile::tempdir().unwrap();
+    let store = SnapshotStore::open(dir.path().join("snapshots")).unwrap();
+    let old = uuid::Uuid::new_v4().to_string();
+    let new = uuid::Uuid::new_v4().to_string();
+    let other = uuid::Uuid::new_v4().to_string();
+    store
+      .save(&snapshot(&old, "compute", "2026-10-01T08:00:00Z"))
+      .await
+      .unwrap();
+    store
+      .save(&snapshot(&new, "compute", "2026-10-02T08:00:00Z"))
+      .await
+      .unwrap();
+    store
+      .save(&snapshot(&other, "gpu", "2026-10-03T08:00:00Z"))
+      .await
+      .unwrap();
+    std::fs::write(dir.path().join("snapshots/junk.json"), "not json").unwrap();
+
+    let ids: Vec<String> = store
+      .list()
+      .await
+      .unwrap()
+      .into_iter()
+      .map(|s| s.id)
+      .collect();
+    assert_eq!(ids, [other.clone(), new.clone(), old.clone()]);
+    assert_eq!(
+      store.latest("alps", "compute").await.unwrap().unwrap().id,
+      new
+    );
+    assert!(store.latest("prealps", "compute").await.unwrap().is_none());
+    assert_eq!(
+      store.load(&old).await.unwrap().unwrap().taken_at,
+      "2026-10-01T08:00:00Z"
+    );
+  }
+
+  #[tokio::test]
+  async fn reopened_store_indexes_the_newest_snapshot_per_group() {
+    let dir = tempfile::tempdir().unwrap();
+    let store = SnapshotStore::open(dir.path()).unwrap();
+    let new = uuid::Uuid::new_v4().to_string();
+    let old = uuid::Uuid::new_v4().to_string();
+    // Saved out of order: the older snapshot must not become latest.
+    store
+      .save(&snapshot(&new, "compute", "2026-10-02T08:00:00Z"))
+      .await
+      .unwrap();
+    store
+      .save(&snapshot(&old, "compute", "2026-10-01T08:00:00Z"))
+      .await
+      .unwrap();
+    assert_eq!(
+      store.latest("alps", "compute").await.unwrap().unwrap().id,
+      new
+    );
+
+    let reopened = SnapshotStore::open(dir.path()).unwrap();
+    let latest = reopened.latest("alps", "compute").await.unwrap().unwrap();
+    assert_eq!(latest.id, new);
+    assert!(reopened.latest("alps", "gpu").await.unwrap().is_none());
+  }
+
+  #[tokio::test]
+  async fn store_load_rejects_non_uuid_ids() {
+    let dir = tempfile::tempdir().unwrap();
+    std::fs::write(dir.path().join("secret.json"), "{}").unwrap();
+    let store = SnapshotStore::open(dir.path().join("snapshots")).unwrap();
+    assert!(store.load("../secret").await.unwrap().is_none());
+    assert!(
+      store
+        .load(&uuid::Uuid::new_v4().to_string())
+        .await
+        .unwrap()
+        .is_none()
+    );
+  }
+}
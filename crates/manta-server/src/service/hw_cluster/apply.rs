@@ -28,8 +28,9 @@ use manta_backend_dispatcher::{
 };
 
 use super::{
-  AddHwResult, ApplyHwResult, DeleteHwResult, HwClusterMode,
-  MEMORY_CAPACITY_LCM, pin_unpin, scoring,
+  AddHwResult, ApplyHwResult, DeleteHwResult, HwClusterMode, HwLocality,
+  HwSolver, MEMORY_CAPACITY_LCM, NodeHwCountVec, pattern::HwPattern, pin_unpin,
+  scoring,
 };
 use crate::server::common::app_context::InfraContext;
 
@@ -62,6 +63,13 @@ pub struct ApplyHwConfigurationParams<'a> {
   pub create_target_group: bool,
   /// Delete the parent group when the move leaves it with no members.
   pub delete_empty_parent_group: bool,
+  /// Prefer keeping blades / chassis / cabinets whole; `None` ranks by
+  /// scarcity alone.
+  pub locality: Option<HwLocality>,
+  /// Pin selection algorithm; ignored by `Unpin`.
+  pub solver: HwSolver,
+  /// Build a [`super::HwPlanExplanation`] for the result.
+  pub explain: bool,
 }
 
 /// Service entry point for `POST /hardware-clusters/{target}/configuration`.
@@ -73,8 +81,9 @@ pub struct ApplyHwConfigurationParams<'a> {
 ///   `p.create_target_group` is false.
 /// - [`Error::BadRequest`] when a dry-run would require creating the
 ///   target group.
-/// - [`Error::InsufficientResources`] when the parent group cannot
-///   supply enough of any component named in the pattern.
+/// - [`Error::InsufficientResources`] when the eligible nodes of the
+///   target and parent groups cannot supply enough of any component
+///   named in the pattern; the message lists each shortfall.
 /// - [`Error::NetError`] / [`Error::CsmError`] from any of the
 ///   `get_group` / hardware-inventory / membership-mutation backend
 ///   calls.
@@ -91,9 +100,13 @@ pub async fn apply_hw_configuration(
     dryrun,
     create_target_group,
     delete_empty_parent_group,
+    locality,
+    solver,
+    explain,
   } = p;
-  let (user_defined_hw_component_vec, user_defined_hw_component_count_hashmap) =
+  let hw_pattern =
     pin_unpin::parse_hw_pattern_usize(target_group_name, pattern)?;
+  let user_defined_hw_component_vec = hw_pattern.component_names();
 
   pin_unpin::ensure_target_group_exists(
     infra,
@@ -138,31 +151,85 @@ pub async fn apply_hw_configuration(
     target_hsm_hw_component_summary
   );
 
+  let all_nodes_opt: Option<NodeHwCountVec> = explain.then(|| {
+    [
+      target_hsm_node_hw_component_count_vec.clone(),
+      parent_hsm_node_hw_component_count_vec.clone(),
+    ]
+    .concat()
+  });
+
+  // Nodes failing a per-node requirement or exclusion never take part
+  // in the selection; those already in the target go back to the parent.
+  let (target_eligible_vec, target_ineligible_vec) =
+    hw_pattern.partition_eligible(target_hsm_node_hw_component_count_vec);
+  let (parent_eligible_vec, parent_ineligible_vec) =
+    hw_pattern.partition_eligible(parent_hsm_node_hw_component_count_vec);
+
   pin_unpin::validate_resource_sufficiency(
-    &target_hsm_node_hw_component_count_vec,
-    &parent_hsm_node_hw_component_count_vec,
-    &user_defined_hw_component_count_hashmap,
+    &target_eligible_vec,
+    &parent_eligible_vec,
+    &hw_pattern.totals,
   )?;
 
-  let (
-    target_hsm_node_hw_component_count_vec,
-    parent_hsm_node_hw_component_count_vec,
-  ) = scoring::resolve_hw_description_to_xnames(
-    mode,
-    target_hsm_node_hw_component_count_vec,
-    parent_hsm_node_hw_component_count_vec,
-    &user_defined_hw_component_count_hashmap,
-  )?;
+  let eligible_pool_opt: Option<NodeHwCountVec> = explain.then(|| {
+    [target_eligible_vec.clone(), parent_eligible_vec.clone()].concat()
+  });
+
+  // The selection is CPU-bound: the exact solver alone may search for
+  // `EXACT_SOLVER_TIME_BUDGET`. Run it on the blocking pool so it
+  // doesn't park a worker that other requests are queued on.
+  let totals = hw_pattern.totals.clone();
+  let selection = tokio::task::spawn_blocking(move || {
+    scoring::resolve_hw_description_to_xnames(
+      mode,
+      target_eligible_vec,
+      parent_eligible_vec,
+      &totals,
+      locality,
+      solver,
+    )
+  })
+  .await
+  .map_err(|e| Error::Message(format!("hardware selection failed: {e}")))??;
+  let solver = selection.solver;
 
-  let target_hsm_node_vec: Vec<String> = target_hsm_node_hw_component_count_vec
+  let target_hsm_node_vec: Vec<String> = selection
+    .target
     .into_iter()
     .map(|(xname, _)| xname)
     .collect();
 
-  let parent_hsm_node_vec: Vec<String> = parent_hsm_node_hw_component_count_vec
+  let mut parent_hsm_node_vec: Vec<String> = selection
+    .parent
     .into_iter()
     .map(|(xname, _)| xname)
     .collect();
+  for (xname, _) in parent_ineligible_vec
+    .into_iter()
+    .chain(target_ineligible_vec)
+  {
+    if !parent_hsm_node_vec.contains(&xname)
+      && !target_hsm_node_vec.contains(&xname)
+    {
+      parent_hsm_node_vec.push(xname);
+    }
+  }
+
+  let explanation = all_nodes_opt.zip(eligible_pool_opt).map(
+    |(all_nodes, mut eligible_pool)| {
+      eligible_pool.sort_by(|a, b| a.0.cmp(&b.0));
+      eligible_pool.dedup_by(|a, b| a.0 == b.0);
+      scoring::explain_hw_plan(
+        &hw_pattern,
+        &all_nodes,
+        &eligible_pool,
+        &target_hsm_group_member_vec,
+        &target_hsm_node_vec,
+        &selection.pick_scores,
+      )
+    },
+  );
 
   pin_unpin::apply_group_updates(
     infra,
@@ -180,12 +247,116 @@ pub async fn apply_hw_configuration(
   )
   .await?;
 
+  let nodes_added: Vec<String> = target_hsm_node_vec
+    .iter()
+    .filter(|xname| !target_hsm_group_member_vec.contains(xname))
+    .cloned()
+    .collect();
+
   Ok(ApplyHwResult {
     target_nodes: target_hsm_node_vec,
     parent_nodes: parent_hsm_node_vec,
+    nodes_added,
+    solver,
+    explanation,
   })
 }
 
+// ── release_nodes ────────────────────────────────────────────────────────────
+
+/// Hand the hardware of an expired reservation back from
+/// `target_group_name` to `parent_group_name`.
+///
+/// `nodes` are the xnames the reservation's pin added and `pattern`
+/// the pin's hardware pattern; the inventory is read for the pattern's
+/// components and [`pin_unpin::select_release`] runs the unpin
+/// selection over the target's current members, so the nodes moved
+/// back may differ from the recorded ones. The parent is recreated if
+/// the original pin emptied and deleted it. Reserved nodes that
+/// already left the target (an earlier manual unpin) return nothing,
+/// so releasing twice is harmless. Returns the xnames actually moved.
+///
+/// # Errors
+///
+/// - [`Error::InvalidPattern`] when the recorded `pattern` no longer
+///   parses.
+/// - [`Error::NotFound`] when either group's membership cannot be read.
+/// - [`Error::InsufficientResources`] when the unpin selection runs
+///   out of candidates.
+/// - [`Error::BadRequest`] when recreating the parent or updating the
+///   memberships fails.
+pub async fn release_nodes(
+  infra: &InfraContext<'_>,
+  shasta_token: &str,
+  target_group_name: &str,
+  parent_group_name: &str,
+  pattern: &str,
+  nodes: &[String],
+) -> Result<Vec<String>, Error> {
+  let hw_component_vec = HwPattern::parse(pattern)?.component_names();
+
+  pin_unpin::ensure_target_group_exists(
+    infra,
+    shasta_token,
+    parent_group_name,
+    false,
+    true,
+  )
+  .await?;
+
+  let (
+    (target_member_vec, target_node_hw_component_count_vec, _target_summary),
+    (parent_member_vec, parent_node_hw_component_count_vec, _parent_summary),
+  ) = tokio::try_join!(
+    scoring::fetch_group_hw_inventory(
+      infra,
+      shasta_token,
+      &hw_component_vec,
+      target_group_name,
+      MEMORY_CAPACITY_LCM,
+    ),
+    scoring::fetch_group_hw_inventory(
+      infra,
+      shasta_token,
+      &hw_component_vec,
+      parent_group_name,
+      MEMORY_CAPACITY_LCM,
+    ),
+  )?;
+
+  let to_release = pin_unpin::select_release(
+    &target_node_hw_component_count_vec,
+    &parent_node_hw_component_count_vec,
+    nodes,
+  )?;
+  let plan = pin_unpin::plan_release(
+    &target_member_vec,
+    &parent_member_vec,
+    &to_release,
+  );
+  if plan.released.is_empty() {
+    return Ok(plan.released);
+  }
+
+  pin_unpin::apply_group_updates(
+    infra,
+    shasta_token,
+    pin_unpin::GroupUpdate {
+      target_group: target_group_name,
+      parent_group: parent_group_name,
+      old_target_members: &target_member_vec,
+      old_parent_members: &parent_member_vec,
+      new_target_members: &plan.target_members,
+      new_parent_members: &plan.parent_members,
+      dryrun: false,
+      delete_empty_parent: false,
+    },
+  )
+  .await?;
+
+  Ok(plan.released)
+}
+
 // ── add_hw_component ─────────────────────────────────────────────────────────
 
 /// Ensure the target HSM group exists for add-hw-component, creating it if needed.
@@ -352,6 +523,8 @@ pub async fn add_hw_component(
       .collect::<Vec<String>>(),
     &mut parent_hsm_node_hw_component_count_vec,
     &scarcity_scores,
+    None,
+    None,
   )?;
 
   let nodes_to_move: Vec<String> = hw_counters_to_move
@@ -639,6 +812,8 @@ pub async fn delete_hw_component(
       .collect::<Vec<String>>(),
     &mut target_hsm_node_hw_component_count_vec,
     &scarcity_scores,
+    None,
+    None,
   )?;
 
   let nodes_to_move: Vec<String> = hw_counters_to_move
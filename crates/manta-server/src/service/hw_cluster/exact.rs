@@ -0,0 +1,329 @@
+//! Exact node selection for PIN mode (`solver: exact`).
+//!
+//! [`super::pin_unpin::calculate_target_group_pin`] is a greedy loop:
+//! it keeps every useful target member it can and then pulls the
+//! best-scoring parent nodes until the totals are met, which can take
+//! more nodes — and so move more — than necessary. This module instead
+//! searches the candidate nodes for the plan that moves the fewest.
+//!
+//! # Objective
+//!
+//! A plan is a set of nodes for the new target. It is valid when it
+//! meets every requested total and carries no spare node (dropping any
+//! one of them would break a total). Among valid plans the search
+//! minimises *nodes moved*: parent nodes added plus target members
+//! released. With a locality objective, ties go to the plan spread over
+//! the fewest blades / chassis / cabinets. Scarcity scores play no
+//! part.
+//!
+//! # Search
+//!
+//! Depth-first branch and bound over include/exclude decisions, target
+//! members first (keeping one is free) and parent nodes in order of how
+//! much of the request they cover. A branch is cut when the remaining
+//! nodes can no longer meet a total, or when a lower bound on its moves
+//! — every remaining target member kept, plus the fewest parent nodes
+//! that could cover what those members leave short — cannot beat the
+//! best plan so far.
+//!
+//! The search is exponential in the worst case, so it runs against a
+//! deadline; on expiry it gives up and the caller falls back to the
+//! greedy selector.
+
+use std::collections::{HashMap, HashSet};
+use std::time::{Duration, Instant};
+
+use super::{HwLocality, NodeHwCountVec, scoring};
+
+/// Nodes visited between deadline checks.
+const DEADLINE_CHECK_INTERVAL: u64 = 1024;
+
+/// Find the valid pin plan that moves the fewest nodes (see the module
+/// docs). `pool` is the combined target + parent inventory;
+/// `target` holds the current target members. Returns the new target
+/// membership, or `None` when no plan exists or `budget` runs out
+/// first.
+//
+// `cast_precision_loss`: the coverage ratios only order the search;
+// component counts are far below f64's exact-integer range.
+#[allow(clippy::cast_precision_loss)]
+pub fn calculate_target_group_pin_exact(
+  requested: &HashMap<String, usize>,
+  target: &[(String, HashMap<String, usize>)],
+  pool: &[(String, HashMap<String, usize>)],
+  locality: Option<HwLocality>,
+  budget: Duration,
+) -> Option<NodeHwCountVec> {
+  let mut components: Vec<(&String, usize)> = requested
+    .iter()
+    .filter(|(_, qty)| **qty > 0)
+    .map(|(component, qty)| (component, *qty))
+    .collect();
+  components.sort();
+
+  let target_xnames: HashSet<&str> =
+    target.iter().map(|(xname, _)| xname.as_str()).collect();
+
+  // A node contributing nothing to the request never belongs to a
+  // plan without spare nodes, so only useful nodes are candidates.
+  let mut candidates: Vec<Candidate> = pool
+    .iter()
+    .enumerate()
+    .map(|(pool_idx, (xname, hw))| Candidate {
+      pool_idx,
+      in_target: target_xnames.contains(xname.as_str()),
+      counts: components
+        .iter()
+        .map(|(component, _)| hw.get(*component).copied().unwrap_or(0))
+        .collect(),
+    })
+    .filter(|c| c.counts.iter().any(|qty| *qty > 0))
+    .collect();
+  let coverage = |c: &Candidate| -> f64 {
+    c.counts
+      .iter()
+      .zip(&components)
+      .map(|(have, (_, want))| (*have).min(*want) as f64 / *want as f64)
+      .sum()
+  };
+  candidates.sort_by(|a, b| {
+    b.in_target
+      .cmp(&a.in_target)
+      .then_with(|| coverage(b).total_cmp(&coverage(a)))
+      .then_with(|| pool[a.pool_idx].0.cmp(&pool[b.pool_idx].0))
+  });
+
+  let mut search = Search::new(
+    components.iter().map(|(_, qty)| *qty).collect(),
+    candidates,
+    pool,
+    locality,
+    Instant::now() + budget,
+  );
+  let mut sums = vec![0; search.requested.len()];
+  let mut chosen = Vec::new();
+  search.visit(0, &mut sums, &mut chosen, target_xnames.len());
+
+  if search.timed_out {
+    return None;
+  }
+  search.best.map(|best| {
+    tracing::info!(
+      "Exact pin solver: {} node(s) moved, {} node(s) visited",
+      best.moves,
+      search.visited
+    );
+    best
+      .pool_idx
+      .into_iter()
+      .map(|idx| pool[idx].clone())
+      .collect()
+  })
+}
+
+/// A node the search may put in the target.
+struct Candidate {
+  /// Index into the combined pool.
+  pool_idx: usize,
+  /// Already a target member (keeping it costs no move).
+  in_target: bool,
+  /// Count of each requested component, in `Search::requested` order.
+  counts: Vec<usize>,
+}
+
+/// Best plan found so far.
+struct Plan {
+  moves: usize,
+  domains: usize,
+  pool_idx: Vec<usize>,
+}
+
+struct Search<'a> {
+  requested: Vec<usize>,
+  candidates: Vec<Candidate>,
+  pool: &'a [(String, HashMap<String, usize>)],
+  locality: Option<HwLocality>,
+  deadline: Instant,
+  /// Per position `i`: component totals over `candidates[i..]`.
+  suffix_all: Vec<Vec<usize>>,
+  /// Per position `i`: component totals over the target members in
+  /// `candidates[i..]`.
+  suffix_target: Vec<Vec<usize>>,
+  /// Per position `i`: the largest count of each component on a
+  /// single parent node in `candidates[i..]`.
+  suffix_parent_max: Vec<Vec<usize>>,
+  /// Per position `i`: target members in `candidates[i..]`.
+  suffix_target_count: Vec<usize>,
+  best: Option<Plan>,
+  visited: u64,
+  timed_out: bool,
+}
+
+impl<'a> Search<'a> {
+  fn new(
+    requested: Vec<usize>,
+    candidates: Vec<Candidate>,
+    pool: &'a [(String, HashMap<String, usize>)],
+    locality: Option<HwLocality>,
+    deadline: Instant,
+  ) -> Self {
+    let n = candidates.len();
+    let width = requested.len();
+    let mut suffix_all = vec![vec![0; width]; n + 1];
+    let mut suffix_target = vec![vec![0; width]; n + 1];
+    let mut suffix_parent_max = vec![vec![0; width]; n + 1];
+    let mut suffix_target_count = vec![0; n + 1];
+    for i in (0..n).rev() {
+      let c = &candidates[i];
+      for k in 0..width {
+        suffix_all[i][k] = suffix_all[i + 1][k] + c.counts[k];
+        suffix_target[i][k] =
+          suffix_target[i + 1][k] + if c.in_target { c.counts[k] } else { 0 };
+        suffix_parent_max[i][k] = if c.in_target {
+          suffix_parent_max[i + 1][k]
+        } else {
+          suffix_parent_max[i + 1][k].max(c.counts[k])
+        };
+      }
+      suffix_target_count[i] =
+        suffix_target_count[i + 1] + usize::from(c.in_target);
+    }
+    Self {
+      requested,
+      candidates,
+      pool,
+      locality,
+      deadline,
+      suffix_all,
+      suffix_target,
+      suffix_parent_max,
+      suffix_target_count,
+      best: None,
+      visited: 0,
+      timed_out: false,
+    }
+  }
+
+  /// Decide `candidates[i..]`. `moves` counts every target member not
+  /// yet kept as released, so it only drops when one is kept.
+  fn visit(
+    &mut self,
+    i: usize,
+    sums: &mut [usize],
+    chosen: &mut Vec<usize>,
+    moves: usize,
+  ) {
+    if self.timed_out {
+      return;
+    }
+    if self.visited.is_multiple_of(DEADLINE_CHECK_INTERVAL)
+      && Instant::now() >= self.deadline
+    {
+      self.timed_out = true;
+      return;
+    }
+    self.visited += 1;
+
+    if sums
+      .iter()
+      .zip(&self.requested)
+      .all(|(have, want)| have >= want)
+    {
+      // Adding anything else would be a spare node: this is a leaf.
+      self.record(sums, chosen, moves);
+      return;
+    }
+    if i == self.candidates.len() {
+      return;
+    }
+    let Some(lower_bound) = self.lower_bound(i, sums, moves) else {
+      return;
+    };
+    if !self.may_improve(lower_bound) {
+      return;
+    }
+
+    let in_target = self.candidates[i].in_target;
+    for (sum, qty) in sums.iter_mut().zip(&self.candidates[i].counts) {
+      *sum += qty;
+    }
+    chosen.push(i);
+    let moves_with = if in_target { moves - 1 } else { moves + 1 };
+    self.visit(i + 1, sums, chosen, moves_with);
+    chosen.pop();
+    for (sum, qty) in sums.iter_mut().zip(&self.candidates[i].counts) {
+      *sum -= qty;
+    }
+
+    self.visit(i + 1, sums, chosen, moves);
+  }
+
+  /// Fewest moves any completion of this branch can reach, or `None`
+  /// when no completion meets every total.
+  fn lower_bound(
+    &self,
+    i: usize,
+    sums: &[usize],
+    moves: usize,
+  ) -> Option<usize> {
+    let mut parents_needed = 0;
+    for (k, want) in self.requested.iter().enumerate() {
+      if sums[k] + self.suffix_all[i][k] < *want {
+        return None;
+      }
+      let short = want.saturating_sub(sums[k] + self.suffix_target[i][k]);
+      if short > 0 {
+        parents_needed =
+          parents_needed.max(short.div_ceil(self.suffix_parent_max[i][k]));
+      }
+    }
+    Some(moves - self.suffix_target_count[i] + parents_needed)
+  }
+
+  fn may_improve(&self, lower_bound: usize) -> bool {
+    self.best.as_ref().is_none_or(|best| {
+      lower_bound < best.moves
+        || (lower_bound == best.moves
+          && self.locality.is_some()
+          && best.domains > 1)
+    })
+  }
+
+  fn record(&mut self, sums: &[usize], chosen: &[usize], moves: usize) {
+    let has_spare = chosen.iter().any(|&idx| {
+      self.candidates[idx]
+        .counts
+        .iter()
+        .zip(sums)
+        .zip(&self.requested)
+        .all(|((qty, have), want)| have - qty >= *want)
+    });
+    if has_spare {
+      return;
+    }
+    let domains = self.locality.map_or(0, |level| {
+      chosen
+        .iter()
+        .map(|&idx| {
+          let xname = &self.pool[self.candidates[idx].pool_idx].0;
+          scoring::locality_domain(xname, level).unwrap_or(xname)
+        })
+        .collect::<HashSet<&str>>()
+        .len()
+    });
+    if self
+      .best
+      .as_ref()
+      .is_none_or(|best| (moves, domains) < (best.moves, best.domains))
+    {
+      self.best = Some(Plan {
+        moves,
+        domains,
+        pool_idx: chosen
+          .iter()
+          .map(|&idx| self.candidates[idx].pool_idx)
+          .collect(),
+      });
+    }
+  }
+}
@@ -6,7 +6,8 @@
 //! shared pool; a *target* group represents a sub-pool reserved for a
 //! particular workload. "Pinning" moves nodes from the parent into
 //! the target so that the target satisfies a user-supplied hardware
-//! pattern (e.g. `a100:8,milan:2` — eight A100s and two Milan CPUs).
+//! pattern (e.g. `a100>=8,node.mem>=512GiB,!mi250` — eight A100s on
+//! nodes with at least 512 GiB each and no MI250s; see `pattern`).
 //! "Unpinning" is the reverse: nodes are released back to the parent.
 //!
 //! Both operations are framed as a search over candidate moves where
@@ -16,13 +17,18 @@
 //!
 //! # Layout
 //!
-//! Split into four (private) sub-modules plus shared types:
+//! Split into six (private) sub-modules plus shared types:
 //!
 //! - `scoring` — pure-computation functions for component scarcity,
 //!   per-node scoring, candidate selection, pattern parsing, and the
 //!   parallel hw-inventory fetcher. Also hosts
 //!   `resolve_hw_description_to_xnames`, which dispatches between
 //!   pin and unpin.
+//! - `exact` — the optional branch-and-bound pin solver that
+//!   minimises nodes moved, with a time budget and greedy fallback.
+//! - `pattern` — the hardware pattern language: totals, per-node
+//!   requirements, exclusions, memory units, and caret-pointed parse
+//!   errors.
 //! - `pin_unpin` — the `calculate_target_group_pin` / `_unpin` node
 //!   selection algorithms plus the shared coordination helpers used
 //!   by `apply_hw_configuration` (pattern parsing, target-group
@@ -30,12 +36,13 @@
 //!   orchestration).
 //! - `apply` — high-level coordinators called by the server
 //!   handlers: `apply_hw_configuration`, `add_hw_component`,
-//!   `delete_hw_component`.
+//!   `delete_hw_component`, plus `release_nodes` for expired
+//!   reservations.
 //! - `hw_inventory_utils` — JSON-pointer helpers that extract memory,
 //!   processor, and accelerator data from raw HSM inventory payloads.
 //!
 //! Public types (`AddHwResult`, `DeleteHwResult`, `ApplyHwResult`,
-//! `NodeHwCountVec`, `HwClusterMode`) and shared constants live here
+//! `NodeHwCountVec`, `HwClusterMode`, `HwLocality`, `HwSolver`) and shared constants live here
 //! so all sub-modules can use them. The public surface is re-exported
 //! at the bottom of this file; callers (the `hw_cluster` handlers
 //! under `crate::server::handlers`) should depend only on those
@@ -44,7 +51,9 @@
 use std::collections::HashMap;
 
 mod apply;
+mod exact;
 mod hw_inventory_utils;
+mod pattern;
 mod pin_unpin;
 mod scoring;
 
@@ -52,13 +61,20 @@ mod scoring;
 /// Memory DIMMs come in multiples of 16 GiB (16384 MiB).
 pub(in crate::service::hw_cluster) const MEMORY_CAPACITY_LCM: u64 = 16384;
 
+/// How long the exact pin solver may search before the greedy
+/// selector takes over.
+pub(in crate::service::hw_cluster) const EXACT_SOLVER_TIME_BUDGET:
+  std::time::Duration = std::time::Duration::from_secs(2);
+
 /// Maximum number of concurrent hardware component queries.
 pub(in crate::service::hw_cluster) const HW_COMPONENT_CONCURRENCY_LIMIT: usize =
   5;
 
 // ── Public types ────────────────────────────────────────────────────────────
 
-pub use manta_shared::types::api::hw_cluster::HwClusterMode;
+pub use manta_shared::types::api::hw_cluster::{
+  HwClusterMode, HwLocality, HwPlanExplanation, HwSolver,
+};
 
 /// A list of nodes paired with their per-component counts.
 pub type NodeHwCountVec = Vec<(String, HashMap<String, usize>)>;
@@ -91,13 +107,20 @@ pub struct ApplyHwResult {
   pub target_nodes: Vec<String>,
   /// Final membership of the parent group after pin/unpin completes.
   pub parent_nodes: Vec<String>,
+  /// Xnames that joined the target group (empty for an unpin).
+  pub nodes_added: Vec<String>,
+  /// Solver that produced the plan: `Greedy` when it was asked for,
+  /// when unpinning, or when the exact search ran out of time.
+  pub solver: HwSolver,
+  /// Per-node reasoning, when the caller asked for it.
+  pub explanation: Option<HwPlanExplanation>,
 }
 
 // ── External API (re-exported from sub-modules) ─────────────────────────────
 
 pub use apply::{
   ApplyHwConfigurationParams, add_hw_component, apply_hw_configuration,
-  delete_hw_component,
+  delete_hw_component, release_nodes,
 };
 
 #[cfg(test)]
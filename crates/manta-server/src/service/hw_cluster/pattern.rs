@@ -0,0 +1,386 @@
+//! Hardware pattern language for `apply hardware group` (pin/unpin).
+//!
+//! A pattern is a comma-separated list of clauses, case-insensitive:
+//!
+//! | Clause            | Meaning                                                  |
+//! |-------------------|----------------------------------------------------------|
+//! | `a100>=8`         | the target group holds at least 8 A100s in total         |
+//! | `a100=8`, `a100:8`| same — whole nodes move, so totals are always minimums   |
+//! | `mem>=512GiB`     | total memory; `KiB`/`MiB`/`GiB`/`TiB` units               |
+//! | `node.a100>=4`    | every node in the target has at least 4 A100s            |
+//! | `node.a100=4`     | every node has exactly 4 (`<=` caps it)                  |
+//! | `!mi250`          | no node in the target has an MI250                       |
+//!
+//! The legacy `a100:4:epyc:10` chain is still accepted as a single
+//! clause. Memory without a unit keeps its legacy meaning of 16 GiB
+//! DIMM units ([`MEMORY_CAPACITY_LCM`]); memory with a unit is
+//! converted to those units (rounded up for minimums, down for caps)
+//! because that is how node inventories are counted.
+//!
+//! Per-node requirements and exclusions are eligibility filters: a
+//! node that fails one never joins the target and, when already there,
+//! is released to the parent. Totals drive the scarcity-weighted
+//! selection in `pin_unpin` exactly as the legacy counts did.
+//!
+//! Every error names the offending clause and underlines it in the
+//! pattern.
+
+use std::collections::HashMap;
+
+use manta_backend_dispatcher::error::Error;
+
+use super::{MEMORY_CAPACITY_LCM, NodeHwCountVec};
+
+/// Component key node inventories use for memory.
+const MEMORY: &str = "memory";
+
+/// Comparison in a per-node requirement.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Cmp {
+  /// `=` / `:` — exactly.
+  Eq,
+  /// `>=` — at least.
+  Ge,
+  /// `<=` — at most.
+  Le,
+}
+
+/// `node.<component><op><qty>` — a requirement every target node must
+/// meet on its own.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NodeRequirement {
+  /// Component name (`memory` for `mem`/`memory`).
+  pub component: String,
+  /// How the node's count is compared against `qty`.
+  pub cmp: Cmp,
+  /// Required count (memory in 16 GiB units).
+  pub qty: usize,
+}
+
+impl std::fmt::Display for NodeRequirement {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    let op = match self.cmp {
+      Cmp::Eq => "=",
+      Cmp::Ge => ">=",
+      Cmp::Le => "<=",
+    };
+    write!(
+      f,
+      "node.{}{op}{}",
+      self.component,
+      quantity_term(&self.component, self.qty)
+    )
+  }
+}
+
+impl NodeRequirement {
+  fn holds(&self, node_hw: &HashMap<String, usize>) -> bool {
+    let have = node_hw.get(&self.component).copied().unwrap_or(0);
+    match self.cmp {
+      Cmp::Eq => have == self.qty,
+      Cmp::Ge => have >= self.qty,
+      Cmp::Le => have <= self.qty,
+    }
+  }
+}
+
+/// A parsed hardware pattern.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct HwPattern {
+  /// Minimum total per component across the target group (memory in
+  /// 16 GiB units). Never empty.
+  pub totals: HashMap<String, usize>,
+  /// Requirements each target node must meet.
+  pub per_node: Vec<NodeRequirement>,
+  /// Components no target node may carry.
+  pub excluded: Vec<String>,
+}
+
+impl HwPattern {
+  /// Parse `pattern`. See the module docs for the grammar.
+  ///
+  /// # Errors
+  ///
+  /// Returns [`Error::InvalidPattern`] naming and underlining the
+  /// first bad clause: unknown operator, missing or non-numeric count,
+  /// a unit on anything but memory, an unknown unit, a component given
+  /// twice, an upper bound on a total, or an exclusion of memory. Also
+  /// when the pattern asks for no total at all.
+  pub fn parse(pattern: &str) -> Result<Self, Error> {
+    let lower = pattern.to_lowercase();
+    let mut parsed = Self::default();
+    let mut offset = 0;
+    for raw in lower.split(',') {
+      let lead = raw.len() - raw.trim_start().len();
+      let clause = Clause {
+        pattern: &lower,
+        text: raw.trim(),
+        start: offset + lead,
+      };
+      parsed.add_clause(&clause)?;
+      offset += raw.len() + 1;
+    }
+    if parsed.totals.is_empty() {
+      return Err(Error::InvalidPattern(format!(
+        "hardware pattern '{pattern}' asks for no component count; add \
+         at least one total such as 'a100>=8'"
+      )));
+    }
+    Ok(parsed)
+  }
+
+  /// Every component the pattern mentions, sorted — the names node
+  /// inventories must be matched against.
+  pub fn component_names(&self) -> Vec<String> {
+    let mut names: Vec<String> = self
+      .totals
+      .keys()
+      .chain(self.per_node.iter().map(|r| &r.component))
+      .chain(&self.excluded)
+      .cloned()
+      .collect();
+    names.sort();
+    names.dedup();
+    names
+  }
+
+  /// Whether a node with these component counts may be in the target.
+  pub fn is_eligible(&self, node_hw: &HashMap<String, usize>) -> bool {
+    self.per_node.iter().all(|r| r.holds(node_hw))
+      && !self
+        .excluded
+        .iter()
+        .any(|excluded| carries(node_hw, excluded))
+  }
+
+  /// Pattern terms `node_hw` satisfies: totals it contributes to,
+  /// per-node requirements it meets, exclusions it respects.
+  pub fn terms_satisfied_by(
+    &self,
+    node_hw: &HashMap<String, usize>,
+  ) -> Vec<String> {
+    let mut components: Vec<(&String, &usize)> = self.totals.iter().collect();
+    components.sort();
+    components
+      .into_iter()
+      .filter(|(component, _)| node_hw.get(*component).is_some_and(|q| *q > 0))
+      .map(|(component, qty)| {
+        format!("{component}>={}", quantity_term(component, *qty))
+      })
+      .chain(
+        self
+          .per_node
+          .iter()
+          .filter(|r| r.holds(node_hw))
+          .map(ToString::to_string),
+      )
+      .chain(
+        self
+          .excluded
+          .iter()
+          .filter(|excluded| !carries(node_hw, excluded))
+          .map(|excluded| format!("!{excluded}")),
+      )
+      .collect()
+  }
+
+  /// Per-node requirements and exclusions `node_hw` fails.
+  pub fn terms_failed_by(
+    &self,
+    node_hw: &HashMap<String, usize>,
+  ) -> Vec<String> {
+    self
+      .per_node
+      .iter()
+      .filter(|r| !r.holds(node_hw))
+      .map(ToString::to_string)
+      .chain(
+        self
+          .excluded
+          .iter()
+          .filter(|excluded| carries(node_hw, excluded))
+          .map(|excluded| format!("!{excluded}")),
+      )
+      .collect()
+  }
+
+  /// Split `nodes` into `(eligible, ineligible)`.
+  pub fn partition_eligible(
+    &self,
+    nodes: NodeHwCountVec,
+  ) -> (NodeHwCountVec, NodeHwCountVec) {
+    nodes
+      .into_iter()
+      .partition(|(_, node_hw)| self.is_eligible(node_hw))
+  }
+
+  fn add_clause(&mut self, clause: &Clause<'_>) -> Result<(), Error> {
+    let text = clause.text;
+    if text.is_empty() {
+      return Err(clause.error("empty clause"));
+    }
+    if let Some(name) = text.strip_prefix('!') {
+      let name = component_name(name).map_err(|e| clause.error(&e))?;
+      if name == MEMORY {
+        return Err(clause.error("memory cannot be excluded"));
+      }
+      self.excluded.push(name);
+      return Ok(());
+    }
+    if let Some(req) = text.strip_prefix("node.") {
+      let (name, cmp, value) = split_comparison(req)
+        .ok_or_else(|| clause.error("expected node.<component><op><count>"))?;
+      let component = component_name(name).map_err(|e| clause.error(&e))?;
+      let qty = quantity(&component, value, cmp == Cmp::Le)
+        .map_err(|e| clause.error(&e))?;
+      self.per_node.push(NodeRequirement {
+        component,
+        cmp,
+        qty,
+      });
+      return Ok(());
+    }
+    if let Some((name, cmp, value)) = split_comparison(text) {
+      if cmp == Cmp::Le {
+        return Err(clause.error(
+          "totals cannot have an upper bound; whole nodes move, so use \
+           '>=' or cap each node with 'node.<component><=<count>'",
+        ));
+      }
+      return self.add_total(clause, name, value);
+    }
+    // Legacy `<component>:<count>[:<component>:<count>...]` chain.
+    let parts: Vec<&str> = text.split(':').collect();
+    if parts.len() < 2 || !parts.len().is_multiple_of(2) {
+      return Err(clause.error(&format!(
+        "missing count for '{}'; expected <component>:<count> or \
+         <component>>=<count>",
+        parts.last().copied().unwrap_or_default()
+      )));
+    }
+    for pair in parts.chunks_exact(2) {
+      self.add_total(clause, pair[0], pair[1])?;
+    }
+    Ok(())
+  }
+
+  fn add_total(
+    &mut self,
+    clause: &Clause<'_>,
+    name: &str,
+    value: &str,
+  ) -> Result<(), Error> {
+    let component = component_name(name).map_err(|e| clause.error(&e))?;
+    let qty =
+      quantity(&component, value, false).map_err(|e| clause.error(&e))?;
+    if self.totals.insert(component.clone(), qty).is_some() {
+      return Err(clause.error(&format!("'{component}' is given twice")));
+    }
+    Ok(())
+  }
+}
+
+/// Whether the node has any component whose name contains `excluded`.
+fn carries(node_hw: &HashMap<String, usize>, excluded: &str) -> bool {
+  node_hw
+    .iter()
+    .any(|(component, qty)| *qty > 0 && component.contains(excluded))
+}
+
+/// Render a count the way a user would write it: memory in GiB.
+fn quantity_term(component: &str, qty: usize) -> String {
+  if component == MEMORY {
+    format!("{}GiB", qty as u64 * MEMORY_CAPACITY_LCM / 1024)
+  } else {
+    qty.to_string()
+  }
+}
+
+/// One comma-separated clause and where it sits in the pattern, for
+/// error underlining.
+struct Clause<'a> {
+  pattern: &'a str,
+  text: &'a str,
+  start: usize,
+}
+
+impl Clause<'_> {
+  fn error(&self, msg: &str) -> Error {
+    let indent = self.pattern[..self.start].chars().count();
+    let width = self.text.chars().count().max(1);
+    Error::InvalidPattern(format!(
+      "invalid hardware pattern clause '{}': {msg}\n  {}\n  {}{}",
+      self.text,
+      self.pattern,
+      " ".repeat(indent),
+      "^".repeat(width)
+    ))
+  }
+}
+
+/// Split `name>=v`, `name<=v`, `name=v` into parts; `None` when the
+/// clause has no comparison operator.
+fn split_comparison(text: &str) -> Option<(&str, Cmp, &str)> {
+  for (op, cmp) in [(">=", Cmp::Ge), ("<=", Cmp::Le), ("=", Cmp::Eq)] {
+    if let Some((name, value)) = text.split_once(op) {
+      return Some((name.trim(), cmp, value.trim()));
+    }
+  }
+  // `node.a100:4` reads like the legacy pair syntax.
+  text
+    .split_once(':')
+    .filter(|(_, value)| !value.contains(':'))
+    .map(|(name, value)| (name.trim(), Cmp::Eq, value.trim()))
+}
+
+/// Normalise a component name; `mem` is an alias of `memory`.
+fn component_name(name: &str) -> Result<String, String> {
+  let name = name.trim();
+  if name.is_empty() {
+    return Err("missing component name".to_string());
+  }
+  if !name
+    .chars()
+    .all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.'))
+  {
+    return Err(format!(
+      "'{name}' is not a component name (letters, digits, '-', '_', '.')"
+    ));
+  }
+  Ok(if name == "mem" { MEMORY } else { name }.to_string())
+}
+
+/// Parse a count. Memory may carry a binary unit and is converted to
+/// 16 GiB units, rounding down when `cap` is set and up otherwise.
+fn quantity(component: &str, value: &str, cap: bool) -> Result<usize, String> {
+  let digits_end = value
+    .find(|c: char| !c.is_ascii_digit())
+    .unwrap_or(value.len());
+  let (digits, unit) = value.split_at(digits_end);
+  let n: u64 = digits
+    .parse()
+    .map_err(|_| format!("'{value}' is not a non-negative count"))?;
+  if unit.is_empty() {
+    return usize::try_from(n).map_err(|_| format!("'{value}' is too large"));
+  }
+  if component != MEMORY {
+    return Err(format!("'{value}' has a unit, but only memory takes one"));
+  }
+  let mib = match unit.trim() {
+    "kib" => n / 1024,
+    "mib" => n,
+    "gib" => n.saturating_mul(1024),
+    "tib" => n.saturating_mul(1024 * 1024),
+    other => {
+      return Err(format!(
+        "unknown memory unit '{other}'; use KiB, MiB, GiB or TiB"
+      ));
+    }
+  };
+  let units = if cap {
+    mib / MEMORY_CAPACITY_LCM
+  } else {
+    mib.div_ceil(MEMORY_CAPACITY_LCM)
+  };
+  usize::try_from(units).map_err(|_| format!("'{value}' is too large"))
+}
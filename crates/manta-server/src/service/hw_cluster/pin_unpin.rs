@@ -9,14 +9,20 @@
 //! nodes. Each iteration:
 //!
 //! 1. Recomputes per-node scores from the current combined inventory
-//!    (see `scoring::calculate_group_node_scores_from_final_hsm`).
+//!    (see `scoring::calculate_group_node_scores_from_final_hsm`),
+//!    plus the optional locality bonus
+//!    (`scoring::apply_locality_bonus`).
 //! 2. Picks the highest-scoring candidate — Pin prefers existing
 //!    target-group members so memberships are kept stable; Unpin
 //!    treats target and parent as a single pool.
-//! 3. Records the move and removes the node from the working sets.
+//! 3. Records the move (and, for `--explain`, the score that decided
+//!    it) and removes the node from the working sets.
 //! 4. Repeats until `keep_iterating_final_hsm` reports the combined
 //!    summary has converged on the user-requested counts.
 //!
+//! A Pin request with `solver: exact` tries `super::exact` first and
+//! only lands here when that search runs out of time.
+//!
 //! # Rollback semantics
 //!
 //! Selection itself is in-memory and side-effect-free: the working
@@ -44,7 +50,7 @@ use manta_backend_dispatcher::{
   error::Error, interfaces::hsm::group::GroupTrait, types::Group,
 };
 
-use super::{NodeHwCountVec, scoring};
+use super::{HwLocality, NodeHwCountVec, pattern::HwPattern, scoring};
 use crate::server::common::app_context::InfraContext;
 
 // ── Pin algorithm ────────────────────────────────────────────────────────────
@@ -57,7 +63,9 @@ use crate::server::common::app_context::InfraContext;
 /// `scoring::get_best_candidate_in_target_and_parent_hsm`), records the
 /// move, and recomputes scores against the smaller combined pool.
 /// Terminates when the running combined summary matches
-/// `user_defined_hsm_hw_components_count_hashmap`.
+/// `user_defined_hsm_hw_components_count_hashmap`. With `pick_scores`
+/// set, each pick's contributions are recorded there as they stood
+/// when it was picked (see `scoring::pick_contributions`).
 ///
 /// # Errors
 ///
@@ -70,7 +78,11 @@ use crate::server::common::app_context::InfraContext;
 // well within `usize` range. The `f64 as usize` casts used as hashmap
 // keys for bucketing nodes are intentional truncation; Rust's saturating
 // `as` semantics handle any non-finite edge case.
-#[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+#[allow(
+  clippy::cast_possible_truncation,
+  clippy::cast_sign_loss,
+  clippy::too_many_arguments
+)]
 pub fn calculate_target_group_pin(
   user_defined_hsm_hw_components_count_hashmap: &HashMap<String, usize>,
   user_defined_hw_component_vec: &[String],
@@ -78,6 +90,8 @@ pub fn calculate_target_group_pin(
   target_hsm_node_hw_component_count_vec: &mut NodeHwCountVec,
   parent_hsm_node_hw_component_count_vec: &mut NodeHwCountVec,
   hw_component_scarcity_scores_hashmap: &HashMap<String, f64>,
+  locality: Option<HwLocality>,
+  mut pick_scores: Option<&mut scoring::PickScores>,
 ) -> Result<NodeHwCountVec, Error> {
   let mut combination_target_parent_hsm_hw_component_summary_hashmap: HashMap<
     String,
@@ -115,6 +129,11 @@ pub fn calculate_target_group_pin(
     HashMap<String, usize>,
   )> = Vec::new();
 
+  // Summaries the current target / parent scores were computed
+  // against, so a pick's contributions can be recorded as they stood.
+  let mut target_scored_against = target_hsm_hw_component_summary_hashmap;
+  let mut parent_scored_against = parent_hsm_hw_component_summary_hashmap;
+
   let (mut best_candidate, mut best_candidate_counters) =
     scoring::get_best_candidate_in_target_and_parent_hsm(
       &mut target_hsm_node_score_tuple_vec,
@@ -164,6 +183,27 @@ pub fn calculate_target_group_pin(
       &parent_hsm_node_score_tuple_vec,
     );
 
+    if let Some(pick_scores) = pick_scores.as_deref_mut() {
+      let scored_against = if target_hsm_node_hw_component_count_vec
+        .iter()
+        .any(|(node, _)| node.eq(&best_candidate.0))
+      {
+        &target_scored_against
+      } else {
+        &parent_scored_against
+      };
+      pick_scores.insert(
+        best_candidate.0.clone(),
+        scoring::pick_contributions(
+          &best_candidate_counters,
+          scored_against,
+          user_defined_hsm_hw_components_count_hashmap,
+          hw_component_scarcity_scores_hashmap,
+          best_candidate.1,
+        ),
+      );
+    }
+
     nodes_migrated_from_combination_target_parent_hsm
       .push((best_candidate.0.clone(), best_candidate_counters.clone()));
 
@@ -184,6 +224,10 @@ pub fn calculate_target_group_pin(
       scoring::calculate_group_hw_component_summary(
         combination_target_parent_hsm_node_hw_component_count_vec,
       );
+    target_scored_against
+      .clone_from(&combination_target_parent_hsm_hw_component_summary_hashmap);
+    parent_scored_against
+      .clone_from(&combination_target_parent_hsm_hw_component_summary_hashmap);
 
     target_hsm_node_score_tuple_vec
       .retain(|(node, _)| !node.eq(&best_candidate.0));
@@ -207,6 +251,18 @@ pub fn calculate_target_group_pin(
         hw_component_scarcity_scores_hashmap,
       );
 
+    for score_tuple_vec in [
+      &mut target_hsm_node_score_tuple_vec,
+      &mut parent_hsm_node_score_tuple_vec,
+    ] {
+      scoring::apply_locality_bonus(
+        score_tuple_vec,
+        locality,
+        &nodes_migrated_from_combination_target_parent_hsm,
+        combination_target_parent_hsm_node_hw_component_count_vec,
+      );
+    }
+
     (best_candidate, best_candidate_counters) =
       scoring::get_best_candidate_in_target_and_parent_hsm(
         &mut target_hsm_node_score_tuple_vec,
@@ -252,7 +308,8 @@ pub fn calculate_target_group_pin(
 ///
 /// Unlike pin, there's no preference for keeping target members in
 /// place: a single `get_best_candidate_in_hsm` call picks the best
-/// candidate from the merged pool each iteration.
+/// candidate from the merged pool each iteration. `pick_scores` works
+/// as in [`calculate_target_group_pin`].
 ///
 /// # Errors
 ///
@@ -263,6 +320,8 @@ pub fn calculate_target_group_unpin(
   user_defined_hw_component_vec: &[String],
   combination_target_parent_hsm_node_hw_component_count_vec: &mut NodeHwCountVec,
   hw_component_scarcity_scores_hashmap: &HashMap<String, f64>,
+  locality: Option<HwLocality>,
+  mut pick_scores: Option<&mut scoring::PickScores>,
 ) -> Result<NodeHwCountVec, Error> {
   let mut combination_target_parent_hsm_hw_component_summary_hashmap: HashMap<
     String,
@@ -330,6 +389,19 @@ pub fn calculate_target_group_unpin(
       &combination_target_parent_hsm_node_score_tuple_vec,
     );
 
+    if let Some(pick_scores) = pick_scores.as_deref_mut() {
+      pick_scores.insert(
+        best_candidate.0.clone(),
+        scoring::pick_contributions(
+          &best_candidate_counters,
+          &combination_target_parent_hsm_hw_component_summary_hashmap,
+          user_defined_hsm_hw_components_count_hashmap,
+          hw_component_scarcity_scores_hashmap,
+          best_candidate.1,
+        ),
+      );
+    }
+
     nodes_migrated_from_combination_target_parent_hsm
       .push((best_candidate.0.clone(), best_candidate_counters.clone()));
 
@@ -356,6 +428,13 @@ pub fn calculate_target_group_unpin(
         hw_component_scarcity_scores_hashmap,
       );
 
+    scoring::apply_locality_bonus(
+      &mut target_hsm_node_score_tuple_vec,
+      locality,
+      &nodes_migrated_from_combination_target_parent_hsm,
+      combination_target_parent_hsm_node_hw_component_count_vec,
+    );
+
     (best_candidate, best_candidate_counters) =
       scoring::get_best_candidate_in_hsm(
         &mut target_hsm_node_score_tuple_vec,
@@ -387,71 +466,30 @@ pub fn calculate_target_group_unpin(
 
 // ── apply_hw_configuration support ───────────────────────────────────────────
 
-/// Parse user pattern `"a100:4:epyc:10"` into hw component names and a
-/// hashmap of `{component -> usize count}`. The target group name is
-/// prepended internally before splitting, so the input pattern itself
-/// must *not* include a leading group prefix.
+/// Parse the user's hardware pattern for `target_hsm_group_name`.
+/// See [`super::pattern`] for the grammar; the group name is only
+/// used for logging.
 ///
 /// # Errors
 ///
-/// Returns [`Error::InvalidPattern`] if the pattern lacks a colon
-/// separator, has an odd number of component:count pairs, or a count
-/// element fails to parse as `usize`.
+/// Returns [`Error::InvalidPattern`] pointing at the offending clause.
 pub fn parse_hw_pattern_usize(
   target_hsm_group_name: &str,
   pattern: &str,
-) -> Result<(Vec<String>, HashMap<String, usize>), Error> {
-  let pattern = format!("{target_hsm_group_name}:{pattern}");
-  tracing::info!("pattern: {}", pattern);
-
-  let pattern_lowercase = pattern.to_lowercase();
-
-  let (_group_name, pattern_hw_component) =
-    pattern_lowercase.split_once(':').ok_or_else(|| {
-      Error::InvalidPattern(
-        "Invalid pattern format: \
-         expected 'group:component:count'"
-          .to_string(),
-      )
-    })?;
-
-  let pattern_element_vec: Vec<&str> =
-    pattern_hw_component.split(':').collect();
-
-  if !pattern_element_vec.len().is_multiple_of(2) {
-    return Err(Error::InvalidPattern(
-      "Error in pattern: odd number of elements. \
-       Expected pairs of <hw component>:<count>. \
-       eg a100:4:epyc:10:instinct:8"
-        .to_string(),
-    ));
-  }
+) -> Result<HwPattern, Error> {
+  tracing::info!("pattern for '{}': {}", target_hsm_group_name, pattern);
 
-  let mut hw_component_count: HashMap<String, usize> = HashMap::new();
-
-  for chunk in pattern_element_vec.chunks_exact(2) {
-    if let Ok(count) = chunk[1].parse::<usize>() {
-      hw_component_count.insert(chunk[0].to_string(), count);
-    } else {
-      return Err(Error::InvalidPattern(
-        "Error in pattern. Please make sure to follow \
-         <hsm name>:<hw component>:<counter>:... \
-         eg <tasna>:a100:4:epyc:10:instinct:8"
-          .to_string(),
-      ));
-    }
-  }
+  let hw_pattern = HwPattern::parse(pattern)?;
 
   tracing::info!(
-    "User defined hw components with counters: {:?}",
-    hw_component_count
+    "User defined hw components with counters: {:?}, per node: {:?}, \
+     excluded: {:?}",
+    hw_pattern.totals,
+    hw_pattern.per_node,
+    hw_pattern.excluded
   );
 
-  let mut hw_component_vec: Vec<String> =
-    hw_component_count.keys().cloned().collect();
-  hw_component_vec.sort();
-
-  Ok((hw_component_vec, hw_component_count))
+  Ok(hw_pattern)
 }
 
 /// Ensure the target HSM group exists, creating it if
@@ -545,20 +583,124 @@ pub fn validate_resource_sufficiency(
   let combined_summary =
     scoring::calculate_group_hw_component_summary(&combined);
 
-  for (hw_component, qty) in requested {
-    if combined_summary
-      .get(hw_component)
-      .is_none_or(|value| value < qty)
-    {
-      return Err(Error::InsufficientResources(
-        "There are not enough resources \
-         to fulfil user request."
-          .to_string(),
-      ));
-    }
+  let mut shortfalls: Vec<String> = requested
+    .iter()
+    .filter_map(|(hw_component, qty)| {
+      let available = combined_summary.get(hw_component).copied().unwrap_or(0);
+      (available < *qty).then(|| {
+        format!("{hw_component}: requested {qty}, available {available}")
+      })
+    })
+    .collect();
+
+  if shortfalls.is_empty() {
+    return Ok(());
   }
+  shortfalls.sort();
+  Err(Error::InsufficientResources(format!(
+    "There are not enough resources to fulfil user request ({})",
+    shortfalls.join("; ")
+  )))
+}
 
-  Ok(())
+/// Pick the target nodes to hand back when a reservation expires.
+///
+/// The hardware to return is what the reserved nodes still in the
+/// target carry; reserved nodes that already left took theirs with
+/// them. [`calculate_target_group_unpin`] then drains that much from
+/// the target's *current* members, so nodes the group picked up since
+/// the reservation compete with the recorded ones and the target keeps
+/// its scarcest hardware. When the reserved nodes carry none of the
+/// pattern's components the recorded nodes are returned as they are.
+///
+/// # Errors
+///
+/// Returns [`Error::InsufficientResources`] when the unpin selection
+/// runs out of candidates.
+pub fn select_release(
+  target_node_hw_component_count_vec: &NodeHwCountVec,
+  parent_node_hw_component_count_vec: &NodeHwCountVec,
+  reserved: &[String],
+) -> Result<Vec<String>, Error> {
+  let still_reserved: NodeHwCountVec = target_node_hw_component_count_vec
+    .iter()
+    .filter(|(xname, _)| reserved.contains(xname))
+    .cloned()
+    .collect();
+  let reserved_summary =
+    scoring::calculate_group_hw_component_summary(&still_reserved);
+  if reserved_summary.values().all(|qty| *qty == 0) {
+    return Ok(still_reserved.into_iter().map(|(xname, _)| xname).collect());
+  }
+
+  let final_target_summary: HashMap<String, usize> =
+    scoring::calculate_group_hw_component_summary(
+      target_node_hw_component_count_vec,
+    )
+    .into_iter()
+    .map(|(hw_component, qty)| {
+      let returned = reserved_summary.get(&hw_component).copied();
+      (hw_component, qty.saturating_sub(returned.unwrap_or(0)))
+    })
+    .collect();
+
+  let combined = [
+    target_node_hw_component_count_vec.clone(),
+    parent_node_hw_component_count_vec.clone(),
+  ]
+  .concat();
+  let scarcity_scores =
+    scoring::calculate_hw_component_scarcity_scores(&combined);
+
+  let mut hw_component_vec: Vec<String> =
+    final_target_summary.keys().cloned().collect();
+  hw_component_vec.sort();
+  let released = calculate_target_group_unpin(
+    &final_target_summary,
+    &hw_component_vec,
+    &mut target_node_hw_component_count_vec.clone(),
+    &scarcity_scores,
+    None,
+    None,
+  )?;
+  Ok(released.into_iter().map(|(xname, _)| xname).collect())
+}
+
+/// Memberships after handing reserved nodes back to the parent; see
+/// [`plan_release`].
+#[derive(Debug, PartialEq, Eq)]
+pub struct ReleasePlan {
+  /// Target membership without the released nodes.
+  pub target_members: Vec<String>,
+  /// Parent membership plus the released nodes.
+  pub parent_members: Vec<String>,
+  /// Nodes to release that were still in the target.
+  pub released: Vec<String>,
+}
+
+/// Plan moving `reserved` (usually [`select_release`]'s pick) from the
+/// target back to the parent. Nodes no longer in the target are
+/// ignored, and nodes already in the parent are not added twice.
+pub fn plan_release(
+  target_members: &[String],
+  parent_members: &[String],
+  reserved: &[String],
+) -> ReleasePlan {
+  let (released, target_members): (Vec<String>, Vec<String>) = target_members
+    .iter()
+    .cloned()
+    .partition(|xname| reserved.contains(xname));
+  let mut parent_members = parent_members.to_vec();
+  for xname in &released {
+    if !parent_members.contains(xname) {
+      parent_members.push(xname.clone());
+    }
+  }
+  ReleasePlan {
+    target_members,
+    parent_members,
+    released,
+  }
 }
 
 /// Inputs to [`apply_group_updates`] bundled to avoid a ten-arg
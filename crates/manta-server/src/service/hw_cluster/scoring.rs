@@ -28,6 +28,16 @@
 //! [`get_best_candidate_in_hsm`] and
 //! [`get_best_candidate_in_target_and_parent_hsm`].
 //!
+//! # Locality
+//!
+//! With an [`HwLocality`] objective, [`apply_locality_bonus`] lifts
+//! every useful node (positive score) whose blade — or chassis /
+//! cabinet, at those levels — already gave a node to the selection
+//! above any plain scarcity score. Selection then drains a domain
+//! before opening the next, so blades are not split between target
+//! and parent and whole chassis stay free. Nodes that don't help the
+//! pattern never get the bonus, so locality never costs extra nodes.
+//!
 //! # Concurrency
 //!
 //! [`get_group_node_hw_component_counter`] fans out per-node inventory
@@ -36,19 +46,27 @@
 //! normalised against `MEMORY_CAPACITY_LCM` (16 GiB) so the scorer
 //! sees integer DIMM counts rather than raw MiB.
 
-use std::{collections::HashMap, sync::Arc, time::Instant};
+use std::{
+  collections::{BTreeMap, HashMap},
+  sync::Arc,
+  time::Instant,
+};
 
 use comfy_table::Color;
 use manta_backend_dispatcher::{
   error::Error,
   interfaces::hsm::{group::GroupTrait, hardware_inventory::HardwareInventory},
 };
+use manta_shared::types::api::hw_cluster::{
+  HwNodeDecision, HwNodeExplanation, HwPlanExplanation,
+};
 use serde_json::Value;
 use tokio::sync::Semaphore;
 
 use super::{
-  HW_COMPONENT_CONCURRENCY_LIMIT, HwClusterMode, NodeHwCountVec,
-  hw_inventory_utils, pin_unpin,
+  EXACT_SOLVER_TIME_BUDGET, HW_COMPONENT_CONCURRENCY_LIMIT, HwClusterMode,
+  HwLocality, HwSolver, NodeHwCountVec, exact, hw_inventory_utils,
+  pattern::HwPattern, pin_unpin,
 };
 use crate::dispatcher::StaticBackendDispatcher;
 use crate::server::common::app_context::InfraContext;
@@ -114,11 +132,8 @@ pub fn calculate_hw_component_scarcity_scores(
 /// the user wants pull positively when the parent has surplus,
 /// negatively otherwise; components the user doesn't ask for always
 /// pull negatively. Weighted by the precomputed scarcity scores so
-/// rare hw dominates.
-//
-// Same `cast_precision_loss` justification as above — qty is a per-node
-// component count, never large enough to overflow f64's mantissa.
-#[allow(clippy::cast_precision_loss)]
+/// rare hw dominates. Per-component terms come from
+/// [`node_score_contributions`].
 pub fn calculate_group_node_scores_from_final_hsm(
   parent_group_node_hw_component_count_vec: &[(
     String,
@@ -128,39 +143,195 @@ pub fn calculate_group_node_scores_from_final_hsm(
   final_group_summary_hashmap: &HashMap<String, usize>,
   hw_component_scarcity_scores_hashmap: &HashMap<String, f64>,
 ) -> Vec<(String, f64)> {
-  let mut node_score_vec: Vec<(String, f64)> = Vec::new();
+  parent_group_node_hw_component_count_vec
+    .iter()
+    .map(|(xname, hw_component_count)| {
+      let node_score = node_score_contributions(
+        hw_component_count,
+        parent_group_hw_component_summary_hashmap,
+        final_group_summary_hashmap,
+        hw_component_scarcity_scores_hashmap,
+      )
+      .into_iter()
+      .map(|(_, contribution)| contribution)
+      .sum();
+      (xname.clone(), node_score)
+    })
+    .collect()
+}
 
-  for (xname, hw_component_count) in parent_group_node_hw_component_count_vec {
-    let mut node_score: f64 = 0.0;
-    for (hw_component, qty) in hw_component_count {
-      let scarcity_score = hw_component_scarcity_scores_hashmap
+/// Per-component terms of a node's score under the rubric in this
+/// module's header, in the node's component order. Their sum is the
+/// score [`calculate_group_node_scores_from_final_hsm`] reports.
+//
+// Same `cast_precision_loss` justification as the scarcity scores — qty
+// is a per-node component count, never large enough to overflow f64's
+// mantissa.
+#[allow(clippy::cast_precision_loss)]
+pub fn node_score_contributions<'a>(
+  hw_component_count: &'a HashMap<String, usize>,
+  parent_group_hw_component_summary_hashmap: &HashMap<String, usize>,
+  final_group_summary_hashmap: &HashMap<String, usize>,
+  hw_component_scarcity_scores_hashmap: &HashMap<String, f64>,
+) -> Vec<(&'a str, f64)> {
+  hw_component_count
+    .iter()
+    .map(|(hw_component, qty)| {
+      let weighted = hw_component_scarcity_scores_hashmap
         .get(hw_component)
         .copied()
-        .unwrap_or(0.0);
+        .unwrap_or(0.0)
+        * *qty as f64;
+
+      let wanted = final_group_summary_hashmap.get(hw_component).is_some_and(
+        |final_qty| {
+          *final_qty
+            < parent_group_hw_component_summary_hashmap
+              .get(hw_component)
+              .copied()
+              .unwrap_or(0)
+        },
+      );
 
-      if final_group_summary_hashmap.get(hw_component).is_none() {
-        node_score -= scarcity_score * *qty as f64;
-      } else {
-        let final_qty = final_group_summary_hashmap
-          .get(hw_component)
-          .copied()
-          .unwrap_or(0);
-        let parent_qty = parent_group_hw_component_summary_hashmap
-          .get(hw_component)
-          .copied()
-          .unwrap_or(0);
-
-        if final_qty < parent_qty {
-          node_score += scarcity_score * *qty as f64;
-        } else {
-          node_score -= scarcity_score * *qty as f64;
-        }
+      (
+        hw_component.as_str(),
+        if wanted { weighted } else { -weighted },
+      )
+    })
+    .collect()
+}
+
+/// Per-component score contributions of every node the greedy
+/// selector picked, keyed by xname, as they stood at the moment of the
+/// pick (not in the first round). Feeds [`explain_hw_plan`].
+pub type PickScores = HashMap<String, BTreeMap<String, f64>>;
+
+/// Contribution key for a pick's locality bonus; see
+/// [`apply_locality_bonus`].
+pub const LOCALITY_CONTRIBUTION: &str = "locality";
+
+/// A picked node's [`node_score_contributions`] against the summary it
+/// was scored against, plus a [`LOCALITY_CONTRIBUTION`] entry for the
+/// part of `score` the components don't account for, so the entries
+/// always sum to `score`.
+pub fn pick_contributions(
+  hw_component_count: &HashMap<String, usize>,
+  scored_against_summary: &HashMap<String, usize>,
+  final_group_summary_hashmap: &HashMap<String, usize>,
+  hw_component_scarcity_scores_hashmap: &HashMap<String, f64>,
+  score: f64,
+) -> BTreeMap<String, f64> {
+  let mut contributions: BTreeMap<String, f64> = node_score_contributions(
+    hw_component_count,
+    scored_against_summary,
+    final_group_summary_hashmap,
+    hw_component_scarcity_scores_hashmap,
+  )
+  .into_iter()
+  .map(|(component, contribution)| (component.to_string(), contribution))
+  .collect();
+  let bonus = score - contributions.values().sum::<f64>();
+  if bonus.abs() > 1e-9 {
+    contributions.insert(LOCALITY_CONTRIBUTION.to_string(), bonus);
+  }
+  contributions
+}
+
+/// The xname prefix naming `xname`'s `level` domain — `x1000c0s1` for
+/// a blade, `x1000c0` for a chassis, `x1000` for a cabinet. `None`
+/// when the xname doesn't start `x<n>c<n>s<n>`.
+pub fn locality_domain(xname: &str, level: HwLocality) -> Option<&str> {
+  let bytes = xname.as_bytes();
+  let mut ends = [0_usize; 3];
+  let mut i = 0;
+  for (end, tag) in ends.iter_mut().zip([b'x', b'c', b's']) {
+    if bytes.get(i) != Some(&tag) {
+      return None;
+    }
+    i += 1;
+    let digits = bytes[i..].iter().take_while(|b| b.is_ascii_digit()).count();
+    if digits == 0 {
+      return None;
+    }
+    i += digits;
+    *end = i;
+  }
+  let end = match level {
+    HwLocality::Cabinet => ends[0],
+    HwLocality::Chassis => ends[1],
+    HwLocality::Blade => ends[2],
+  };
+  Some(&xname[..end])
+}
+
+/// Re-rank `node_score_vec` for a locality objective; a no-op when
+/// `locality` is `None`. `selected` holds the nodes already picked,
+/// `pool` those still available (candidates included).
+///
+/// A node with a positive score whose domain already has a selected
+/// node gains `max_score * (1 + fill)`, where `fill` is the share of
+/// the domain already selected, so the fullest domain is finished
+/// first. Blades always count; chassis and cabinet levels add their
+/// own bonus on top.
+//
+// Same `cast_precision_loss` justification as the scarcity scores —
+// these are per-domain node counts.
+#[allow(clippy::cast_precision_loss)]
+pub fn apply_locality_bonus(
+  node_score_vec: &mut [(String, f64)],
+  locality: Option<HwLocality>,
+  selected: &[(String, HashMap<String, usize>)],
+  pool: &[(String, HashMap<String, usize>)],
+) {
+  let Some(locality) = locality else {
+    return;
+  };
+  let max_score = node_score_vec
+    .iter()
+    .map(|(_, score)| *score)
+    .fold(0.0_f64, f64::max);
+  if max_score <= 0.0 || selected.is_empty() {
+    return;
+  }
+
+  let mut levels = vec![HwLocality::Blade];
+  if locality != HwLocality::Blade {
+    levels.push(locality);
+  }
+
+  for level in levels {
+    let selected_by_domain = count_nodes_by_domain(selected, level);
+    let pool_by_domain = count_nodes_by_domain(pool, level);
+
+    for (xname, score) in node_score_vec.iter_mut() {
+      if *score <= 0.0 {
+        continue;
       }
+      let Some(domain) = locality_domain(xname, level) else {
+        continue;
+      };
+      let Some(&taken) = selected_by_domain.get(domain) else {
+        continue;
+      };
+      let left = pool_by_domain.get(domain).copied().unwrap_or(0);
+      let fill = taken as f64 / (taken + left) as f64;
+      *score += max_score * (1.0 + fill);
     }
-    node_score_vec.push((xname.clone(), node_score));
   }
+}
 
-  node_score_vec
+/// Number of `nodes` in each `level` domain.
+fn count_nodes_by_domain(
+  nodes: &[(String, HashMap<String, usize>)],
+  level: HwLocality,
+) -> HashMap<&str, usize> {
+  let mut counts: HashMap<&str, usize> = HashMap::new();
+  for (xname, _) in nodes {
+    if let Some(domain) = locality_domain(xname, level) {
+      *counts.entry(domain).or_insert(0) += 1;
+    }
+  }
+  counts
 }
 
 /// Check whether further iteration is needed to satisfy the target hw
@@ -525,11 +696,116 @@ pub fn get_best_candidate_in_target_and_parent_hsm(
   }
 }
 
+/// What the combined pool should still hold once the target has the
+/// `requested` counts: pool supply minus the request, for requested
+/// components only. Components the pool lacks keep the requested
+/// value.
+pub fn calculate_final_pool_summary(
+  pool_summary: &HashMap<String, usize>,
+  requested: &HashMap<String, usize>,
+) -> HashMap<String, usize> {
+  let mut final_summary = requested.clone();
+  for (hw_component, qty) in pool_summary {
+    final_summary
+      .entry(hw_component.clone())
+      .and_modify(|current_qty| *current_qty = qty - *current_qty);
+  }
+  final_summary
+}
+
+/// Explain a finished plan: for every node added to, kept in, or
+/// released from the target, its component counts, per-component
+/// score contributions and score, plus the pattern terms it satisfies
+/// or fails. `all_nodes` covers both groups, ineligible nodes
+/// included.
+///
+/// Nodes the greedy selector picked are scored as recorded in
+/// `pick_scores`, i.e. with the numbers that actually decided the pick.
+/// Everything else (released nodes, every node of an exact-solver
+/// plan) is scored against the eligible pool as it stood before any
+/// move.
+pub fn explain_hw_plan(
+  hw_pattern: &HwPattern,
+  all_nodes: &[(String, HashMap<String, usize>)],
+  eligible_pool: &[(String, HashMap<String, usize>)],
+  target_before: &[String],
+  target_after: &[String],
+  pick_scores: &PickScores,
+) -> HwPlanExplanation {
+  let pool_summary = calculate_group_hw_component_summary(eligible_pool);
+  let scarcity = calculate_hw_component_scarcity_scores(eligible_pool);
+  let final_summary =
+    calculate_final_pool_summary(&pool_summary, &hw_pattern.totals);
+
+  let mut nodes: Vec<HwNodeExplanation> = Vec::new();
+  let mut seen: Vec<&str> = Vec::new();
+  for (xname, node_hw) in all_nodes {
+    if seen.contains(&xname.as_str()) {
+      continue;
+    }
+    seen.push(xname);
+    let decision =
+      match (target_before.contains(xname), target_after.contains(xname)) {
+        (false, true) => HwNodeDecision::Added,
+        (true, true) => HwNodeDecision::Kept,
+        (true, false) => HwNodeDecision::Released,
+        (false, false) => continue,
+      };
+    let contributions: BTreeMap<String, f64> =
+      pick_scores.get(xname).cloned().unwrap_or_else(|| {
+        node_score_contributions(
+          node_hw,
+          &pool_summary,
+          &final_summary,
+          &scarcity,
+        )
+        .into_iter()
+        .map(|(component, contribution)| (component.to_string(), contribution))
+        .collect()
+      });
+    nodes.push(HwNodeExplanation {
+      xname: xname.clone(),
+      decision,
+      components: node_hw.iter().map(|(c, q)| (c.clone(), *q)).collect(),
+      score: contributions.values().sum(),
+      contributions,
+      satisfies: hw_pattern.terms_satisfied_by(node_hw),
+      fails: hw_pattern.terms_failed_by(node_hw),
+    });
+  }
+  nodes.sort_by(|a, b| {
+    b.score
+      .partial_cmp(&a.score)
+      .unwrap_or(std::cmp::Ordering::Equal)
+      .then(a.xname.cmp(&b.xname))
+  });
+
+  HwPlanExplanation {
+    scarcity_weights: scarcity.into_iter().collect(),
+    nodes,
+  }
+}
+
+/// Outcome of [`resolve_hw_description_to_xnames`].
+pub struct HwSelection {
+  /// Post-move target membership, ready to feed to
+  /// `super::pin_unpin::apply_group_updates`.
+  pub target: NodeHwCountVec,
+  /// Post-move parent membership.
+  pub parent: NodeHwCountVec,
+  /// Solver that produced the plan.
+  pub solver: HwSolver,
+  /// Scores of the greedy picks; empty for an exact-solver plan.
+  pub pick_scores: PickScores,
+}
+
 /// Resolve a hardware description pattern into concrete xnames by
 /// running the Pin or Unpin selection algorithm against the supplied
-/// inventories. Returns `(new_target, remaining_parent)` — the
-/// post-move membership lists ready to feed to
-/// `super::pin_unpin::apply_group_updates`.
+/// inventories, optionally with a `locality` objective. Returns the
+/// post-move memberships, the solver that produced them and the
+/// greedy picks' scores as an [`HwSelection`]. A Pin with [`HwSolver::Exact`] runs
+/// [`super::exact::calculate_target_group_pin_exact`] first and falls
+/// back to the greedy selector when it runs out of time.
 ///
 /// # Errors
 ///
@@ -537,13 +813,6 @@ pub fn get_best_candidate_in_target_and_parent_hsm(
 /// [`super::pin_unpin::calculate_target_group_pin`] /
 /// [`super::pin_unpin::calculate_target_group_unpin`] when no valid
 /// selection plan exists.
-//
-// `type_complexity`: the tuple-of-vecs-of-tuples shape is exactly
-// what this function negotiates and renaming the parts behind a
-// `type` alias would just push the same shape one level down. Keeping
-// the structural type at the signature is more honest about the data
-// flow.
-#[allow(clippy::type_complexity)]
 pub fn resolve_hw_description_to_xnames(
   mode: HwClusterMode,
   mut target_group_node_hw_component_count_vec: Vec<(
@@ -555,13 +824,9 @@ pub fn resolve_hw_description_to_xnames(
     HashMap<String, usize>,
   )>,
   user_defined_target_group_hw_component_count_hashmap: &HashMap<String, usize>,
-) -> Result<
-  (
-    Vec<(String, HashMap<String, usize>)>,
-    Vec<(String, HashMap<String, usize>)>,
-  ),
-  Error,
-> {
+  locality: Option<HwLocality>,
+  solver: HwSolver,
+) -> Result<HwSelection, Error> {
   let mut combined_target_parent_group_node_hw_component_count_vec =
     parent_group_node_hw_component_count_vec.clone();
 
@@ -575,6 +840,29 @@ pub fn resolve_hw_description_to_xnames(
     }
   }
 
+  if mode == HwClusterMode::Pin && solver == HwSolver::Exact {
+    if let Some(new_target) = exact::calculate_target_group_pin_exact(
+      user_defined_target_group_hw_component_count_hashmap,
+      &target_group_node_hw_component_count_vec,
+      &combined_target_parent_group_node_hw_component_count_vec,
+      locality,
+      EXACT_SOLVER_TIME_BUDGET,
+    ) {
+      combined_target_parent_group_node_hw_component_count_vec
+        .retain(|(xname, _)| !new_target.iter().any(|(x, _)| x == xname));
+      return Ok(HwSelection {
+        target: new_target,
+        parent: combined_target_parent_group_node_hw_component_count_vec,
+        solver: HwSolver::Exact,
+        pick_scores: PickScores::new(),
+      });
+    }
+    tracing::warn!(
+      "Exact pin solver found no plan within {:?}, falling back to greedy",
+      EXACT_SOLVER_TIME_BUDGET
+    );
+  }
+
   let combined_target_parent_group_hw_component_summary_hashmap =
     calculate_group_hw_component_summary(
       &combined_target_parent_group_node_hw_component_count_vec,
@@ -585,17 +873,13 @@ pub fn resolve_hw_description_to_xnames(
       &combined_target_parent_group_node_hw_component_count_vec,
     );
 
-  let mut final_combined_target_parent_group_hw_component_summary =
-    user_defined_target_group_hw_component_count_hashmap.clone();
-
-  for (hw_component, qty) in
-    combined_target_parent_group_hw_component_summary_hashmap
-  {
-    final_combined_target_parent_group_hw_component_summary
-      .entry(hw_component)
-      .and_modify(|current_qty| *current_qty = qty - *current_qty);
-  }
+  let final_combined_target_parent_group_hw_component_summary =
+    calculate_final_pool_summary(
+      &combined_target_parent_group_hw_component_summary_hashmap,
+      user_defined_target_group_hw_component_count_hashmap,
+    );
 
+  let mut pick_scores = PickScores::new();
   let hw_component_counters_to_move_out_from_combined_hsm = match mode {
     HwClusterMode::Pin => pin_unpin::calculate_target_group_pin(
       &final_combined_target_parent_group_hw_component_summary,
@@ -607,6 +891,8 @@ pub fn resolve_hw_description_to_xnames(
       &mut target_group_node_hw_component_count_vec,
       &mut parent_group_node_hw_component_count_vec,
       &hw_component_scarcity_scores_hashmap,
+      locality,
+      Some(&mut pick_scores),
     )?,
     HwClusterMode::Unpin => pin_unpin::calculate_target_group_unpin(
       &final_combined_target_parent_group_hw_component_summary,
@@ -616,16 +902,17 @@ pub fn resolve_hw_description_to_xnames(
         .collect::<Vec<String>>(),
       &mut combined_target_parent_group_node_hw_component_count_vec,
       &hw_component_scarcity_scores_hashmap,
+      locality,
+      Some(&mut pick_scores),
     )?,
   };
 
-  let new_target_group_node_hw_component_count_vec =
-    hw_component_counters_to_move_out_from_combined_hsm;
-
-  Ok((
-    new_target_group_node_hw_component_count_vec,
-    combined_target_parent_group_node_hw_component_count_vec,
-  ))
+  Ok(HwSelection {
+    target: hw_component_counters_to_move_out_from_combined_hsm,
+    parent: combined_target_parent_group_node_hw_component_count_vec,
+    solver: HwSolver::Greedy,
+    pick_scores,
+  })
 }
 
 /// Parse a hardware pattern string like `"a100:4:epyc:10"` into
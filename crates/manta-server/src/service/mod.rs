@@ -29,13 +29,15 @@
 //! # Module map
 //!
 //! - Cross-cutting: [`authorization`], [`infra_backend`], [`analysis`],
-//!   [`sat_groups`], [`node_ops`], [`ims_ops`].
+//!   [`sat_groups`], [`secrets`], [`node_ops`], [`node_expression`],
+//!   [`ims_ops`], [`git_ref`], [`site`].
 //! - Per-resource: [`auth`], [`boot_parameters`], [`configuration`],
 //!   [`group`], [`hardware`], [`image`], [`kernel_parameters`],
 //!   [`node`], [`node_details`], [`power`], [`redfish`], [`session`],
-//!   [`template`].
+//!   [`session_log`], [`session_log_filter`], [`template`].
 //! - Composite operations: [`cluster`], [`ephemeral_env`], [`migrate`],
-//!   [`hw_cluster`] (the last is a subdirectory module).
+//!   [`reservation`], [`hardware_snapshot`], [`hw_cluster`] (the last
+//!   is a subdirectory module).
 
 pub mod analysis;
 pub mod auth;
@@ -45,8 +47,10 @@ pub mod cluster;
 pub mod configuration;
 pub mod console;
 pub mod ephemeral_env;
+pub mod git_ref;
 pub mod group;
 pub mod hardware;
+pub mod hardware_snapshot;
 pub mod hw_cluster;
 pub mod image;
 pub mod ims_ops;
@@ -55,11 +59,17 @@ pub mod kernel_parameters;
 pub mod migrate;
 pub mod node;
 pub mod node_details;
+pub mod node_expression;
 pub mod node_ops;
 pub mod power;
 pub mod redfish;
+pub mod reservation;
 pub mod runtime_configuration;
 pub mod sat_file;
 pub mod sat_groups;
+pub mod secrets;
 pub mod session;
+pub mod session_log;
+pub mod session_log_filter;
+pub mod site;
 pub mod template;
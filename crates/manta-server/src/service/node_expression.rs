@@ -0,0 +1,611 @@
+//! Set algebra over node expressions: `group:compute & !group:gpu`,
+//! `group:a | x1000c3s0b0n[0-1]`, `nid[001-064] & power=off`.
+//!
+//! An expression combines terms with `|` (union), `&` (intersection)
+//! and `!` (complement against every node the caller can see), with
+//! parentheses for grouping. `!` binds tightest, then `&`, then `|`.
+//! A term is one of:
+//!
+//! - `group:NAME` — members of an HSM group the caller can access.
+//! - `power=STATE` — HSM component state (`on`, `off`, `ready`,
+//!   `standby`, …); `power=on` also matches nodes in `Ready`.
+//! - `cfs=STATUS` — CFS configuration status (`unconfigured`,
+//!   `pending`, `failed`, `configured`).
+//! - `arch=ARCH` — HSM architecture; `aarch64`/`arm64` and
+//!   `x86_64`/`amd64` are accepted as aliases of `arm` and `x86`.
+//! - `role=ROLE` — HSM role (`compute`, `application`, …).
+//! - anything else — a plain hostlist of NIDs or xnames, resolved by
+//!   [`node_ops::from_hosts_expression_to_xname_vec`].
+//!
+//! Plain hostlists never contain these operators, so
+//! [`node_ops::from_user_hosts_expression_to_xname_vec`] only routes
+//! an expression here when [`is_set_expression`] says so. Groups and
+//! CFS statuses are fetched once per distinct term before the
+//! expression is evaluated.
+
+use std::collections::{BTreeSet, HashMap};
+
+use manta_backend_dispatcher::{
+  error::Error,
+  interfaces::{cfs::CfsTrait, hsm::group::GroupTrait},
+  types::Component,
+};
+
+use crate::server::common::app_context::InfraContext;
+use crate::service::{authorization::validate_user_group_access, node_ops};
+
+/// Prefix of a group term.
+const GROUP_PREFIX: &str = "group:";
+
+/// Statuses CFS reports in a component's `configurationStatus`.
+const CFS_STATUSES: [&str; 4] =
+  ["unconfigured", "pending", "failed", "configured"];
+
+/// Node attribute an `attr=value` term filters on.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Attribute {
+  /// HSM component state.
+  Power,
+  /// CFS configuration status.
+  Cfs,
+  /// HSM architecture.
+  Arch,
+  /// HSM role.
+  Role,
+}
+
+impl Attribute {
+  fn from_key(key: &str) -> Option<Self> {
+    match key.to_ascii_lowercase().as_str() {
+      "power" => Some(Self::Power),
+      "cfs" => Some(Self::Cfs),
+      "arch" => Some(Self::Arch),
+      "role" => Some(Self::Role),
+      _ => None,
+    }
+  }
+}
+
+/// Parsed node expression.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum NodeExpr {
+  /// Plain hostlist of NIDs or xnames.
+  Hosts(String),
+  /// Members of an HSM group.
+  Group(String),
+  /// Nodes whose `attribute` equals `value`.
+  Filter {
+    /// Attribute compared.
+    attribute: Attribute,
+    /// Expected value, lowercased.
+    value: String,
+  },
+  /// Every visible node not in the operand.
+  Not(Box<NodeExpr>),
+  /// Nodes in both operands.
+  And(Box<NodeExpr>, Box<NodeExpr>),
+  /// Nodes in either operand.
+  Or(Box<NodeExpr>, Box<NodeExpr>),
+}
+
+impl NodeExpr {
+  fn visit<'a>(&'a self, f: &mut impl FnMut(&'a NodeExpr)) {
+    f(self);
+    match self {
+      Self::Not(inner) => inner.visit(f),
+      Self::And(lhs, rhs) | Self::Or(lhs, rhs) => {
+        lhs.visit(f);
+        rhs.visit(f);
+      }
+      Self::Hosts(_) | Self::Group(_) | Self::Filter { .. } => {}
+    }
+  }
+
+  /// Distinct group names referenced by the expression.
+  pub fn group_names(&self) -> BTreeSet<&str> {
+    let mut names = BTreeSet::new();
+    self.visit(&mut |expr| {
+      if let Self::Group(name) = expr {
+        names.insert(name.as_str());
+      }
+    });
+    names
+  }
+
+  /// Distinct values the expression filters `attribute` on.
+  pub fn filter_values(&self, wanted: Attribute) -> BTreeSet<&str> {
+    let mut values = BTreeSet::new();
+    self.visit(&mut |expr| {
+      if let Self::Filter { attribute, value } = expr
+        && *attribute == wanted
+      {
+        values.insert(value.as_str());
+      }
+    });
+    values
+  }
+}
+
+/// `true` when `expression` uses operators, group terms or attribute
+/// filters, i.e. is more than a plain hostlist.
+pub fn is_set_expression(expression: &str) -> bool {
+  expression.contains(['|', '&', '!', '(', ')', '='])
+    || expression.contains(GROUP_PREFIX)
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Token {
+  Or,
+  And,
+  Not,
+  Open,
+  Close,
+  Term(String),
+}
+
+fn tokenize(expression: &str) -> Vec<Token> {
+  let mut tokens = Vec::new();
+  let mut term = String::new();
+  let flush = |term: &mut String, tokens: &mut Vec<Token>| {
+    let trimmed = term.trim();
+    if !trimmed.is_empty() {
+      tokens.push(Token::Term(trimmed.to_string()));
+    }
+    term.clear();
+  };
+  for c in expression.chars() {
+    let operator = match c {
+      '|' => Token::Or,
+      '&' => Token::And,
+      '!' => Token::Not,
+      '(' => Token::Open,
+      ')' => Token::Close,
+      _ => {
+        term.push(c);
+        continue;
+      }
+    };
+    flush(&mut term, &mut tokens);
+    tokens.push(operator);
+  }
+  flush(&mut term, &mut tokens);
+  tokens
+}
+
+fn bad_expression(expression: &str, reason: &str) -> Error {
+  Error::BadRequest(format!("Invalid node expression '{expression}': {reason}"))
+}
+
+fn parse_term(expression: &str, term: &str) -> Result<NodeExpr, Error> {
+  if let Some(name) = term.strip_prefix(GROUP_PREFIX) {
+    let name = name.trim();
+    if name.is_empty() {
+      return Err(bad_expression(expression, "'group:' needs a group name"));
+    }
+    return Ok(NodeExpr::Group(name.to_string()));
+  }
+  let Some((key, value)) = term.split_once('=') else {
+    return Ok(NodeExpr::Hosts(term.to_string()));
+  };
+  let attribute = Attribute::from_key(key.trim()).ok_or_else(|| {
+    bad_expression(
+      expression,
+      &format!(
+        "unknown attribute '{}' (expected power, cfs, arch or role)",
+        key.trim()
+      ),
+    )
+  })?;
+  let value = value.trim().to_ascii_lowercase();
+  if value.is_empty() {
+    return Err(bad_expression(
+      expression,
+      &format!("'{}=' needs a value", key.trim()),
+    ));
+  }
+  if attribute == Attribute::Cfs && !CFS_STATUSES.contains(&value.as_str()) {
+    return Err(bad_expression(
+      expression,
+      &format!(
+        "unknown CFS status '{value}' (expected one of {})",
+        CFS_STATUSES.join(", ")
+      ),
+    ));
+  }
+  Ok(NodeExpr::Filter { attribute, value })
+}
+
+struct Parser<'a> {
+  expression: &'a str,
+  tokens: Vec<Token>,
+  pos: usize,
+}
+
+impl Parser<'_> {
+  fn eat(&mut self, token: &Token) -> bool {
+    if self.tokens.get(self.pos) == Some(token) {
+      self.pos += 1;
+      true
+    } else {
+      false
+    }
+  }
+
+  fn union(&mut self) -> Result<NodeExpr, Error> {
+    let mut lhs = self.intersection()?;
+    while self.eat(&Token::Or) {
+      lhs = NodeExpr::Or(Box::new(lhs), Box::new(self.intersection()?));
+    }
+    Ok(lhs)
+  }
+
+  fn intersection(&mut self) -> Result<NodeExpr, Error> {
+    let mut lhs = self.complement()?;
+    while self.eat(&Token::And) {
+      lhs = NodeExpr::And(Box::new(lhs), Box::new(self.complement()?));
+    }
+    Ok(lhs)
+  }
+
+  fn complement(&mut self) -> Result<NodeExpr, Error> {
+    if self.eat(&Token::Not) {
+      return Ok(NodeExpr::Not(Box::new(self.complement()?)));
+    }
+    self.primary()
+  }
+
+  fn primary(&mut self) -> Result<NodeExpr, Error> {
+    match self.tokens.get(self.pos).cloned() {
+      Some(Token::Open) => {
+        self.pos += 1;
+        let inner = self.union()?;
+        if !self.eat(&Token::Close) {
+          return Err(bad_expression(self.expression, "missing ')'"));
+        }
+        Ok(inner)
+      }
+      Some(Token::Term(term)) => {
+        self.pos += 1;
+        parse_term(self.expression, &term)
+      }
+      Some(_) => Err(bad_expression(
+        self.expression,
+        "expected a term after an operator",
+      )),
+      None => Err(bad_expression(self.expression, "unexpected end")),
+    }
+  }
+}
+
+/// Parse a node expression (see the module docs for the grammar).
+///
+/// # Errors
+///
+/// [`Error::BadRequest`] on unbalanced parentheses, dangling
+/// operators, unknown attributes or an unknown CFS status.
+pub fn parse(expression: &str) -> Result<NodeExpr, Error> {
+  let mut parser = Parser {
+    expression,
+    tokens: tokenize(expression),
+    pos: 0,
+  };
+  let expr = parser.union()?;
+  if parser.pos != parser.tokens.len() {
+    return Err(bad_expression(expression, "unexpected ')'"));
+  }
+  Ok(expr)
+}
+
+/// `aarch64` → `arm`, `x86_64` → `x86`; other values unchanged.
+fn normalize_arch(arch: &str) -> &str {
+  match arch {
+    "aarch64" | "arm64" => "arm",
+    "x86_64" | "amd64" => "x86",
+    other => other,
+  }
+}
+
+/// Data an expression is evaluated against, fetched up front.
+pub struct Lookup<'a> {
+  /// Every node the caller can see; the universe for `!`.
+  pub components: &'a [Component],
+  /// Group name → member xnames, for every group in the expression.
+  pub groups: HashMap<String, Vec<String>>,
+  /// CFS status → xnames in that status, for every `cfs=` term.
+  pub cfs: HashMap<String, Vec<String>>,
+}
+
+impl Lookup<'_> {
+  fn universe(&self) -> BTreeSet<String> {
+    self
+      .components
+      .iter()
+      .filter_map(|c| c.id.clone())
+      .collect()
+  }
+
+  fn matching(&self, keep: impl Fn(&Component) -> bool) -> BTreeSet<String> {
+    self
+      .components
+      .iter()
+      .filter(|c| keep(c))
+      .filter_map(|c| c.id.clone())
+      .collect()
+  }
+
+  fn visible(&self, xnames: &[String]) -> BTreeSet<String> {
+    let universe = self.universe();
+    xnames
+      .iter()
+      .filter(|xname| universe.contains(*xname))
+      .cloned()
+      .collect()
+  }
+
+  /// Evaluate `expr` to the set of xnames it selects.
+  ///
+  /// # Errors
+  ///
+  /// Errors from resolving a hostlist term, or
+  /// [`Error::BadRequest`] for a group or CFS status missing from the
+  /// lookup.
+  pub fn eval(&self, expr: &NodeExpr) -> Result<BTreeSet<String>, Error> {
+    Ok(match expr {
+      NodeExpr::Hosts(hosts) => node_ops::from_hosts_expression_to_xname_vec(
+        hosts,
+        false,
+        self.components,
+      )?
+      .into_iter()
+      .collect(),
+      NodeExpr::Group(name) => {
+        let members = self.groups.get(name).ok_or_else(|| {
+          Error::BadRequest(format!("Group '{name}' not resolved"))
+        })?;
+        self.visible(members)
+      }
+      NodeExpr::Filter {
+        attribute: Attribute::Cfs,
+        value,
+      } => {
+        let xnames = self.cfs.get(value).ok_or_else(|| {
+          Error::BadRequest(format!("CFS status '{value}' not resolved"))
+        })?;
+        self.visible(xnames)
+      }
+      NodeExpr::Filter {
+        attribute: Attribute::Power,
+        value,
+      } => self.matching(|c| {
+        c.state.as_deref().is_some_and(|state| {
+          state.eq_ignore_ascii_case(value)
+            || (value == "on" && state.eq_ignore_ascii_case("ready"))
+        })
+      }),
+      NodeExpr::Filter {
+        attribute: Attribute::Arch,
+        value,
+      } => {
+        let wanted = normalize_arch(value);
+        self.matching(|c| {
+          c.arch.as_deref().is_some_and(|arch| {
+            normalize_arch(&arch.to_ascii_lowercase()) == wanted
+          })
+        })
+      }
+      NodeExpr::Filter {
+        attribute: Attribute::Role,
+        value,
+      } => self.matching(|c| {
+        c.role
+          .as_deref()
+          .is_some_and(|role| role.eq_ignore_ascii_case(value))
+      }),
+      NodeExpr::Not(inner) => {
+        let excluded = self.eval(inner)?;
+        self
+          .universe()
+          .into_iter()
+          .filter(|xname| !excluded.contains(xname))
+          .collect()
+      }
+      NodeExpr::And(lhs, rhs) => {
+        let rhs = self.eval(rhs)?;
+        self
+          .eval(lhs)?
+          .into_iter()
+          .filter(|xname| rhs.contains(xname))
+          .collect()
+      }
+      NodeExpr::Or(lhs, rhs) => {
+        let mut union = self.eval(lhs)?;
+        union.extend(self.eval(rhs)?);
+        union
+      }
+    })
+  }
+}
+
+/// Resolve a set expression to a sorted xname list, evaluated against
+/// `components` (the caller's available node metadata).
+///
+/// Access to every referenced group is checked before its members
+/// are fetched.
+///
+/// # Errors
+///
+/// - [`Error::BadRequest`] when the expression does not parse, names
+///   a group the caller cannot access, or selects no nodes.
+/// - Backend errors from fetching group members or CFS components.
+pub async fn resolve(
+  infra: &InfraContext<'_>,
+  token: &str,
+  expression: &str,
+  components: &[Component],
+) -> Result<Vec<String>, Error> {
+  let expr = parse(expression)?;
+
+  let mut groups = HashMap::new();
+  for name in expr.group_names() {
+    validate_user_group_access(infra, token, name).await?;
+    let members = infra
+      .backend
+      .get_member_vec_from_group_name_vec(token, &[name.to_string()])
+      .await?;
+    groups.insert(name.to_string(), members);
+  }
+
+  let mut cfs = HashMap::new();
+  for status in expr.filter_values(Attribute::Cfs) {
+    let xnames = infra
+      .backend
+      .get_cfs_components(token, None, None, Some(status))
+      .await?
+      .into_iter()
+      .filter_map(|component| component.id)
+      .collect();
+    cfs.insert(status.to_string(), xnames);
+  }
+
+  let xnames = Lookup {
+    components,
+    groups,
+    cfs,
+  }
+  .eval(&expr)?;
+  if xnames.is_empty() {
+    return Err(Error::BadRequest(format!(
+      "Node expression '{expression}' matches no nodes"
+    )));
+  }
+  Ok(xnames.into_iter().collect())
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn node(id: &str, nid: usize, state: &str, arch: &str) -> Component {
+    Component {
+      id: Some(id.to_string()),
+      r#type: None,
+      state: Some(state.to_string()),
+      flag: None,
+      enabled: None,
+      software_status: None,
+      role: Some("Compute".to_string()),
+      sub_role: None,
+      nid: Some(nid),
+      subtype: None,
+      net_type: None,
+      arch: Some(arch.to_string()),
+      class: None,
+      reservation_disabled: None,
+      locked: None,
+    }
+  }
+
+  fn components() -> Vec<Component> {
+    vec![
+      node("x1000c0s0b0n0", 1, "Ready", "X86"),
+      node("x1000c0s0b0n1", 2, "Off", "X86"),
+      node("x1000c0s1b0n0", 3, "On", "ARM"),
+      node("x1000c0s1b0n1", 4, "Off", "ARM"),
+    ]
+  }
+
+  fn eval(expression: &str) -> Result<Vec<String>, Error> {
+    let components = components();
+    let lookup = Lookup {
+      components: &components,
+      groups: HashMap::from([
+        (
+          "compute".to_string(),
+          vec![
+            "x1000c0s0b0n0".to_string(),
+            "x1000c0s0b0n1".to_string(),
+            "x1000c0s1b0n0".to_string(),
+          ],
+        ),
+        ("gpu".to_string(), vec!["x1000c0s1b0n0".to_string()]),
+      ]),
+      cfs: HashMap::from([(
+        "failed".to_string(),
+        vec!["x1000c0s0b0n1".to_string(), "x9999c0s0b0n0".to_string()],
+      )]),
+    };
+    Ok(lookup.eval(&parse(expression)?)?.into_iter().collect())
+  }
+
+  #[test]
+  fn plain_hostlists_are_not_set_expressions() {
+    assert!(!is_set_expression("x1000c0s0b0n[0-1],nid000003"));
+    assert!(is_set_expression("group:compute"));
+    assert!(is_set_expression("nid[001-004] & power=off"));
+  }
+
+  #[test]
+  fn precedence_is_not_then_and_then_or() {
+    assert_eq!(
+      parse("group:a | group:b & !group:c").unwrap(),
+      NodeExpr::Or(
+        Box::new(NodeExpr::Group("a".to_string())),
+        Box::new(NodeExpr::And(
+          Box::new(NodeExpr::Group("b".to_string())),
+          Box::new(NodeExpr::Not(Box::new(NodeExpr::Group("c".to_string())))),
+        )),
+      )
+    );
+  }
+
+  #[test]
+  fn malformed_expressions_are_rejected() {
+    for bad in [
+      "group:a &",
+      "(group:a",
+      "group:a)",
+      "group:",
+      "colour=red",
+      "cfs=broken",
+      "power=",
+    ] {
+      assert!(
+        matches!(parse(bad), Err(Error::BadRequest(_))),
+        "{bad} should not parse"
+      );
+    }
+  }
+
+  #[test]
+  fn group_difference_and_union_with_hostlist() {
+    assert_eq!(
+      eval("group:compute & !group:gpu").unwrap(),
+      vec!["x1000c0s0b0n0", "x1000c0s0b0n1"]
+    );
+    assert_eq!(
+      eval("group:gpu | x1000c0s0b0n[0-1]").unwrap(),
+      vec!["x1000c0s0b0n0", "x1000c0s0b0n1", "x1000c0s1b0n0"]
+    );
+  }
+
+  #[test]
+  fn attribute_filters_match_hsm_and_cfs_state() {
+    assert_eq!(
+      eval("power=off").unwrap(),
+      vec!["x1000c0s0b0n1", "x1000c0s1b0n1"]
+    );
+    assert_eq!(
+      eval("power=on").unwrap(),
+      vec!["x1000c0s0b0n0", "x1000c0s1b0n0"]
+    );
+    assert_eq!(
+      eval("arch=aarch64 & POWER=Off").unwrap(),
+      vec!["x1000c0s1b0n1"]
+    );
+    assert_eq!(eval("cfs=failed").unwrap(), vec!["x1000c0s0b0n1"]);
+    assert_eq!(
+      eval("nid[000001-000002] & !(role=compute & arch=x86_64)").unwrap(),
+      Vec::<String>::new()
+    );
+  }
+}
@@ -31,9 +31,11 @@
 //!   (`x\d{4}c[0-7]s([0-9]|[1-5][0-9]|6[0-4])b[0-1]n[0-7]`).
 //!   Hostlist notation works here too (`x1000c[0-7]s0b0n0`).
 //!
-//! Group names are **not** accepted by the hosts-expression path —
-//! they go through `resolve_target_nodes`'s `group_name_arg_opt`
-//! branch instead.
+//! Expressions that combine terms with `|`, `&`, `!` and parentheses,
+//! or that use `group:NAME` terms or attribute filters such as
+//! `power=off` and `cfs=failed`, are evaluated by
+//! [`node_expression`] against the same node metadata; see that
+//! module for the set-algebra grammar.
 //!
 //! With `is_include_siblings = true`, every resolved xname is
 //! broadened to its blade prefix (first 10 chars: `xNNNNcSsBb`) and
@@ -58,6 +60,7 @@ static XNAME_RE: LazyLock<Regex> = LazyLock::new(|| {
 });
 
 use crate::server::common::app_context::InfraContext;
+use crate::service::node_expression;
 
 /// Length of a NID string, e.g. "nid000001" = 9 characters.
 const NID_STRING_LENGTH: usize = 9;
@@ -181,12 +184,15 @@ pub fn get_xname_from_xname_hostlist(
 /// [`from_hosts_expression_to_xname_vec`] that recurs in many
 /// command files.
 ///
-/// See the module docs for the supported expression grammar.
+/// See the module docs for the supported expression grammar; set
+/// expressions are handed to [`node_expression::resolve`].
 ///
 /// # Errors
 ///
 /// - [`Error::NetError`] / [`Error::CsmError`] from
 ///   `get_node_metadata_available`.
+/// - Any error produced by [`node_expression::resolve`] for set
+///   expressions.
 /// - Any error produced by
 ///   [`from_hosts_expression_to_xname_vec`]
 ///   (`Error::BadRequest`, `Error::InvalidNodeId`).
@@ -201,11 +207,26 @@ pub async fn from_user_hosts_expression_to_xname_vec(
     .get_node_metadata_available(shasta_token)
     .await?;
 
-  let mut xname_vec = from_hosts_expression_to_xname_vec(
-    hosts_expression,
-    is_include_siblings,
-    &node_metadata_available_vec,
-  )?;
+  let mut xname_vec = if node_expression::is_set_expression(hosts_expression) {
+    let xname_vec = node_expression::resolve(
+      infra,
+      shasta_token,
+      hosts_expression,
+      &node_metadata_available_vec,
+    )
+    .await?;
+    if is_include_siblings {
+      include_siblings(&xname_vec, &node_metadata_available_vec)
+    } else {
+      xname_vec
+    }
+  } else {
+    from_hosts_expression_to_xname_vec(
+      hosts_expression,
+      is_include_siblings,
+      &node_metadata_available_vec,
+    )?
+  };
 
   xname_vec.sort();
   xname_vec.dedup();
@@ -282,34 +303,8 @@ pub fn from_hosts_expression_to_xname_vec(
   }
 
   // Include siblings if requested
-  let xname_vec: Vec<String> = if is_include_siblings {
-    tracing::debug!("Include siblings");
-    let xname_blade_vec: Vec<String> = xname_vec
-      .iter()
-      .map(|xname| {
-        xname
-          .get(0..XNAME_BLADE_PREFIX_LEN)
-          .unwrap_or(xname)
-          .to_string()
-      })
-      .collect();
-
-    tracing::debug!("XNAME blades:\n{:?}", xname_blade_vec);
-
-    // Include siblings: keep any node whose xname shares a blade
-    // prefix with one of the resolved xnames.
-    node_metadata_available_vec
-      .iter()
-      .filter(|node_metadata_available| {
-        node_metadata_available.id.as_ref().is_some_and(|id| {
-          xname_blade_vec
-            .iter()
-            .any(|xname_blade| id.starts_with(xname_blade))
-        })
-      })
-      .filter_map(|node_metadata_available| node_metadata_available.id.as_ref())
-      .cloned()
-      .collect()
+  let xname_vec = if is_include_siblings {
+    include_siblings(&xname_vec, node_metadata_available_vec)
   } else {
     xname_vec
   };
@@ -317,6 +312,42 @@ pub fn from_hosts_expression_to_xname_vec(
   Ok(xname_vec)
 }
 
+/// Widen every xname to its 10-character blade prefix and return
+/// every node in `node_metadata_available_vec` sharing one of those
+/// prefixes — the `--include-siblings` behaviour.
+fn include_siblings(
+  xname_vec: &[String],
+  node_metadata_available_vec: &[Component],
+) -> Vec<String> {
+  tracing::debug!("Include siblings");
+  let xname_blade_vec: Vec<String> = xname_vec
+    .iter()
+    .map(|xname| {
+      xname
+        .get(0..XNAME_BLADE_PREFIX_LEN)
+        .unwrap_or(xname)
+        .to_string()
+    })
+    .collect();
+
+  tracing::debug!("XNAME blades:\n{:?}", xname_blade_vec);
+
+  // Include siblings: keep any node whose xname shares a blade
+  // prefix with one of the resolved xnames.
+  node_metadata_available_vec
+    .iter()
+    .filter(|node_metadata_available| {
+      node_metadata_available.id.as_ref().is_some_and(|id| {
+        xname_blade_vec
+          .iter()
+          .any(|xname_blade| id.starts_with(xname_blade))
+      })
+    })
+    .filter_map(|node_metadata_available| node_metadata_available.id.as_ref())
+    .cloned()
+    .collect()
+}
+
 /// Group the supplied xnames by their parent HSM group.
 ///
 /// Fetches the HSM groups the caller can access, then for each group
@@ -323,3 +323,22 @@ fn validate_xname_format_vec_all_invalid() {
   let xnames = vec!["garbage".to_string(), "not_xname".to_string()];
   assert!(!validate_xname_format_vec(&xnames));
 }
+
+#[test]
+fn compressed_hostlists_parse_back_to_the_same_nodes() {
+  let mut xnames: Vec<String> = (0..4)
+    .flat_map(|c| (0..8).map(move |s| format!("x1000c{c}s{s}b0n0")))
+    .chain(["x1001c0s0b0n1".to_string()])
+    .collect();
+  let mut nids: Vec<String> = [1, 2, 3, 9, 10, 12]
+    .iter()
+    .map(|n| format!("nid{n:06}"))
+    .collect();
+  for names in [&mut xnames, &mut nids] {
+    let compressed = manta_shared::common::hostlist::compress(names.iter());
+    let mut parsed = parse(&compressed).unwrap();
+    parsed.sort();
+    names.sort();
+    assert_eq!(&parsed, names, "{compressed}");
+  }
+}
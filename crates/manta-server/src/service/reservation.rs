@@ -0,0 +1,402 @@
+//! Time-limited hardware reservations — pins that undo themselves.
+//!
+//! A pin request carrying `expires_at` records a [`HwReservation`]
+//! naming the nodes the pin added. Reservations live in a
+//! [`ReservationStore`], a JSON file rewritten on every change so they
+//! survive a server restart. The reaper in
+//! [`crate::server::reservation_reaper`] polls [`due_for_release`] and
+//! hands each expired entry to [`release`], which runs the unpin
+//! algorithm to return that hardware to the parent group as the
+//! configured service account.
+//!
+//! The whole feature is opt-in through `[server.reservations]`; without
+//! it [`crate::server::ServerState::reservations`] is `None` and the
+//! handlers answer `501 Not Implemented`.
+
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use chrono::{DateTime, SecondsFormat, Utc};
+use manta_backend_dispatcher::error::Error;
+use manta_backend_dispatcher::interfaces::hsm::group::GroupTrait;
+use tokio::sync::Mutex;
+
+use crate::server::common::app_context::InfraContext;
+use crate::server::common::jwt_ops;
+use crate::service::authorization;
+use crate::service::hw_cluster;
+use crate::wire_conv;
+
+pub use manta_shared::types::api::hw_cluster::HwReservation;
+
+/// Everything the server needs to track and release reservations,
+/// built at startup from `[server.reservations]`.
+pub struct Reservations {
+  /// Active reservations, persisted to disk.
+  pub store: ReservationStore,
+  /// Credentials the reaper authenticates with.
+  pub service_account: ServiceAccount,
+  /// Delay between two reaper passes.
+  pub check_interval: Duration,
+}
+
+/// Username and password of the account the reaper acts as.
+pub struct ServiceAccount {
+  /// Account username.
+  pub username: String,
+  /// Account password, read from the environment at startup.
+  pub password: String,
+}
+
+impl std::fmt::Debug for ServiceAccount {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    f.debug_struct("ServiceAccount")
+      .field("username", &self.username)
+      .field("password", &"<redacted>")
+      .finish()
+  }
+}
+
+/// Active reservations backed by a JSON file.
+///
+/// Every mutation rewrites the whole file through a sibling temporary
+/// file and a rename, so a crash mid-write leaves the previous
+/// contents intact. The lock is held across the write, serialising
+/// handlers and the reaper.
+pub struct ReservationStore {
+  path: PathBuf,
+  entries: Mutex<Vec<HwReservation>>,
+}
+
+impl ReservationStore {
+  /// Load the store from `path`. A missing file is an empty store; it
+  /// is created on the first insert.
+  ///
+  /// # Errors
+  ///
+  /// [`Error::IoError`] when the file exists but cannot be read, and
+  /// [`Error::SerdeError`] when it is not a JSON list of reservations.
+  pub fn open(path: impl Into<PathBuf>) -> Result<Self, Error> {
+    let path = path.into();
+    let entries = match std::fs::read(&path) {
+      Ok(bytes) => serde_json::from_slice(&bytes)?,
+      Err(e) if e.kind() == std::io::ErrorKind::NotFound => Vec::new(),
+      Err(e) => return Err(e.into()),
+    };
+    Ok(Self {
+      path,
+      entries: Mutex::new(entries),
+    })
+  }
+
+  /// Snapshot of every active reservation.
+  pub async fn list(&self) -> Vec<HwReservation> {
+    self.entries.lock().await.clone()
+  }
+
+  /// Record `reservation` and persist.
+  ///
+  /// # Errors
+  ///
+  /// [`Error::IoError`] / [`Error::SerdeError`] when the file cannot be
+  /// written; the in-memory list is left unchanged.
+  pub async fn insert(&self, reservation: HwReservation) -> Result<(), Error> {
+    let mut entries = self.entries.lock().await;
+    let mut updated = entries.clone();
+    updated.push(reservation);
+    persist(&self.path, &updated).await?;
+    *entries = updated;
+    Ok(())
+  }
+
+  /// Drop the reservation with `id` (if any) and persist.
+  ///
+  /// # Errors
+  ///
+  /// [`Error::IoError`] / [`Error::SerdeError`] when the file cannot be
+  /// written; the in-memory list is left unchanged.
+  pub async fn remove(&self, id: &str) -> Result<(), Error> {
+    let mut entries = self.entries.lock().await;
+    let updated: Vec<HwReservation> =
+      entries.iter().filter(|r| r.id != id).cloned().collect();
+    persist(&self.path, &updated).await?;
+    *entries = updated;
+    Ok(())
+  }
+}
+
+/// Write `entries` to `path` atomically (temporary file + rename).
+/// Shared with [`super::ephemeral_env::EphemeralEnvStore`].
+///
+/// The callers hold the store's lock across the write, so it goes
+/// through `tokio::fs` (a blocking pool) rather than parking the
+/// worker every handler and the reaper contend on.
+pub(crate) async fn persist<T: serde::Serialize>(
+  path: &Path,
+  entries: &[T],
+) -> Result<(), Error> {
+  let tmp = path.with_extension("json.tmp");
+  tokio::fs::write(&tmp, serde_json::to_vec_pretty(entries)?).await?;
+  tokio::fs::rename(&tmp, path).await?;
+  Ok(())
+}
+
+/// Parse a requested expiry and check it lies in the future.
+///
+/// # Errors
+///
+/// [`Error::BadRequest`] when `raw` is not RFC 3339 or is not after
+/// `now`.
+pub fn parse_expiry(
+  raw: &str,
+  now: DateTime<Utc>,
+) -> Result<DateTime<Utc>, Error> {
+  let expires_at = DateTime::parse_from_rfc3339(raw)
+    .map_err(|e| {
+      Error::BadRequest(format!(
+        "Invalid 'expires_at' '{raw}': {e} (expected RFC 3339, e.g. \
+         2026-11-01T08:00:00Z)"
+      ))
+    })?
+    .with_timezone(&Utc);
+  if expires_at <= now {
+    return Err(Error::BadRequest(format!(
+      "'expires_at' {raw} is not in the future"
+    )));
+  }
+  Ok(expires_at)
+}
+
+/// Build the reservation for a pin that added `nodes` to `target`.
+#[allow(clippy::too_many_arguments)]
+pub fn new_reservation(
+  site: &str,
+  target: &str,
+  parent: &str,
+  pattern: &str,
+  nodes: Vec<String>,
+  owner: &str,
+  now: DateTime<Utc>,
+  expires_at: DateTime<Utc>,
+) -> HwReservation {
+  HwReservation {
+    id: uuid::Uuid::new_v4().to_string(),
+    site: site.to_string(),
+    target_cluster: target.to_string(),
+    parent_cluster: parent.to_string(),
+    pattern: pattern.to_string(),
+    nodes,
+    owner: owner.to_string(),
+    created_at: now.to_rfc3339_opts(SecondsFormat::Secs, true),
+    expires_at: expires_at.to_rfc3339_opts(SecondsFormat::Secs, true),
+  }
+}
+
+/// Owner recorded on a new reservation: the token's
+/// `preferred_username`.
+///
+/// # Errors
+///
+/// [`Error::JwtMalformed`] (via `wire_conv::to_backend`) when the
+/// claim is missing.
+pub fn owner_from_token(token: &str) -> Result<String, Error> {
+  jwt_ops::get_preferred_username(token).map_err(wire_conv::to_backend)
+}
+
+/// Reservations whose expiry is at or before `now`.
+///
+/// An `expires_at` that does not parse counts as due: the server wrote
+/// it, so a bad value means a hand-edited file, and releasing early is
+/// safer than holding nodes forever.
+pub fn due_for_release(
+  entries: &[HwReservation],
+  now: DateTime<Utc>,
+) -> Vec<HwReservation> {
+  entries
+    .iter()
+    .filter(|r| {
+      DateTime::parse_from_rfc3339(&r.expires_at)
+        .map_or(true, |expires_at| expires_at <= now)
+    })
+    .cloned()
+    .collect()
+}
+
+/// Active reservations on the caller's site, soonest expiry first.
+///
+/// Admins see every reservation on the site; other users see those
+/// whose target group they can access.
+///
+/// # Errors
+///
+/// Whatever `get_group_name_available` returns for a non-admin token.
+pub async fn list_reservations(
+  infra: &InfraContext<'_>,
+  token: &str,
+  store: &ReservationStore,
+) -> Result<Vec<HwReservation>, Error> {
+  let mut reservations: Vec<HwReservation> = store
+    .list()
+    .await
+    .into_iter()
+    .filter(|r| r.site == infra.site_name)
+    .collect();
+  if !authorization::is_admin(token) {
+    let available = infra.backend.get_group_name_available(token).await?;
+    reservations.retain(|r| available.contains(&r.target_cluster));
+  }
+  reservations.sort_by(|a, b| a.expires_at.cmp(&b.expires_at));
+  Ok(reservations)
+}
+
+/// Hand an expired reservation's hardware back to its parent group,
+/// choosing the nodes with the unpin algorithm over the target's
+/// current members. Returns the xnames moved; see
+/// [`hw_cluster::release_nodes`].
+///
+/// # Errors
+///
+/// Propagates [`hw_cluster::release_nodes`] errors.
+pub async fn release(
+  infra: &InfraContext<'_>,
+  token: &str,
+  reservation: &HwReservation,
+) -> Result<Vec<String>, Error> {
+  hw_cluster::release_nodes(
+    infra,
+    token,
+    &reservation.target_cluster,
+    &reservation.parent_cluster,
+    &reservation.pattern,
+    &reservation.nodes,
+  )
+  .await
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn at(raw: &str) -> DateTime<Utc> {
+    DateTime::parse_from_rfc3339(raw)
+      .unwrap()
+      .with_timezone(&Utc)
+  }
+
+  fn reservation(id: &str, expires_at: &str) -> HwReservation {
+    HwReservation {
+      id: id.to_string(),
+      site: "alps".to_string(),
+      target_cluster: "tenant-a".to_string(),
+      parent_cluster: "nodes_free".to_string(),
+      pattern: "a100>=8".to_string(),
+      nodes: vec!["x1000c0s0b0n0".to_string()],
+      owner: "alice".to_string(),
+      created_at: "2026-10-01T08:00:00Z".to_string(),
+      expires_at: expires_at.to_string(),
+    }
+  }
+
+  #[test]
+  fn parse_expiry_accepts_future_rfc3339_with_offset() {
+    let now = at("2026-10-01T08:00:00Z");
+    let expires_at = parse_expiry("2026-11-01T09:00:00+01:00", now).unwrap();
+    assert_eq!(expires_at, at("2026-11-01T08:00:00Z"));
+  }
+
+  #[test]
+  fn parse_expiry_rejects_past_and_malformed() {
+    let now = at("2026-10-01T08:00:00Z");
+    assert!(matches!(
+      parse_expiry("2026-09-30T08:00:00Z", now),
+      Err(Error::BadRequest(_))
+    ));
+    assert!(matches!(
+      parse_expiry("2026-10-01T08:00:00Z", now),
+      Err(Error::BadRequest(_))
+    ));
+    assert!(matches!(
+      parse_expiry("next tuesday", now),
+      Err(Error::BadRequest(_))
+    ));
+  }
+
+  #[test]
+  fn new_reservation_formats_instants_in_utc() {
+    let r = new_reservation(
+      "alps",
+      "tenant-a",
+      "nodes_free",
+      "a100>=8",
+      vec!["x1000c0s0b0n0".to_string()],
+      "alice",
+      at("2026-10-01T08:00:00.250Z"),
+      at("2026-10-04T10:00:00+02:00"),
+    );
+    assert_eq!(r.created_at, "2026-10-01T08:00:00Z");
+    assert_eq!(r.expires_at, "2026-10-04T08:00:00Z");
+    assert!(uuid::Uuid::parse_str(&r.id).is_ok());
+  }
+
+  #[test]
+  fn due_for_release_picks_expired_and_unparseable() {
+    let entries = vec![
+      reservation("past", "2026-10-01T07:59:59Z"),
+      reservation("now", "2026-10-01T08:00:00Z"),
+      reservation("future", "2026-10-01T08:00:01Z"),
+      reservation("garbled", "soon"),
+    ];
+    let due: Vec<String> =
+      due_for_release(&entries, at("2026-10-01T08:00:00Z"))
+        .into_iter()
+        .map(|r| r.id)
+        .collect();
+    assert_eq!(due, ["past", "now", "garbled"]);
+  }
+
+  #[tokio::test]
+  async fn store_persists_across_reopen() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("reservations.json");
+
+    let store = ReservationStore::open(&path).unwrap();
+    assert!(store.list().await.is_empty());
+    store
+      .insert(reservation("a", "2026-11-01T08:00:00Z"))
+      .await
+      .unwrap();
+    store
+      .insert(reservation("b", "2026-11-02T08:00:00Z"))
+      .await
+      .unwrap();
+    store.remove("a").await.unwrap();
+
+    let reopened = ReservationStore::open(&path).unwrap();
+    let ids: Vec<String> =
+      reopened.list().await.into_iter().map(|r| r.id).collect();
+    assert_eq!(ids, ["b"]);
+    assert!(!path.with_extension("json.tmp").exists());
+  }
+
+  #[test]
+  fn store_open_rejects_corrupt_file() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("reservations.json");
+    std::fs::write(&path, "not json").unwrap();
+    assert!(matches!(
+      ReservationStore::open(&path),
+      Err(Error::SerdeError(_))
+    ));
+  }
+
+  #[test]
+  fn service_account_debug_redacts_password() {
+    let account = ServiceAccount {
+      username: "manta-reaper".to_string(),
+      password: "hunter2".to_string(),
+    };
+    let rendered = format!("{account:?}");
+    assert!(rendered.contains("manta-reaper"));
+    assert!(!rendered.contains("hunter2"));
+  }
+}
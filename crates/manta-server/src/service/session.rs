@@ -11,10 +11,23 @@
 //! lets the CLI render a confirmation prompt with the full blast
 //! radius before any backend write.
 //!
+//! Bulk deletion has the same two steps: [`prepare_sessions_deletion`]
+//! gathers every session matching a set of filters, and
+//! [`SessionsDeletionContext::plan`] sums up their blast radius;
+//! [`execute_sessions_deletion`] then deletes them a few at a time,
+//! reporting a result per session instead of stopping at the first
+//! failure.
+//!
+//! [`rerun_session`] creates a fresh session from an existing one's
+//! configuration, target and Ansible settings, so a session that
+//! failed from a transient error can be retried without rebuilding
+//! its parameters.
+//!
 //! [`validate_session_access`] and [`validate_console_session`] are
 //! standalone pre-checks used by handlers that need to fail-fast
 //! before doing anything else (typically a console attach).
 
+use futures::StreamExt;
 use manta_backend_dispatcher::error::Error;
 use manta_backend_dispatcher::interfaces::apply_session::ApplySessionTrait;
 use manta_backend_dispatcher::interfaces::bss::BootParametersTrait;
@@ -23,12 +36,29 @@ use manta_backend_dispatcher::interfaces::hsm::group::GroupTrait;
 use manta_backend_dispatcher::types::Group;
 use manta_backend_dispatcher::types::bss::BootParameters;
 use manta_backend_dispatcher::types::cfs::component::Component;
-use manta_backend_dispatcher::types::cfs::session::CfsSessionGetResponse;
+use manta_backend_dispatcher::types::cfs::session::{
+  CfsSessionGetResponse, CfsSessionPostRequest,
+};
 
 use crate::server::common::app_context::InfraContext;
-use crate::service::authorization::validate_user_group_members_access;
+use crate::service::authorization::{self, validate_user_group_members_access};
 use crate::service::node_ops;
-pub use manta_shared::types::api::session::GetSessionParams;
+pub use manta_shared::types::api::session::{
+  BootImageInUse, GetSessionParams, RerunSessionRequest, RerunSessionResponse,
+  SessionDeletionItem, SessionDeletionResult, SessionsDeletionPlan,
+};
+
+/// CFS rejects session names longer than this.
+const MAX_SESSION_NAME_LEN: usize = 45;
+
+/// Tag recording which session a re-run repeats.
+const RERUN_OF_TAG: &str = "manta.rerun_of";
+
+/// Highest Ansible verbosity CFS accepts.
+const MAX_ANSIBLE_VERBOSITY: u8 = 4;
+
+/// Sessions [`execute_sessions_deletion`] deletes at the same time.
+const SESSION_DELETION_CONCURRENCY_LIMIT: usize = 4;
 
 /// List CFS sessions visible to the caller, applying every filter on
 /// `params`.
@@ -196,6 +226,172 @@ pub async fn execute_session_deletion(
     .await
 }
 
+/// Data needed to delete several sessions at once.
+pub struct SessionsDeletionContext {
+  /// The sessions to delete, oldest first.
+  pub sessions: Vec<CfsSessionGetResponse>,
+  /// All HSM groups the token has access to.
+  pub group_available_vec: Vec<Group>,
+  /// CFS component states.
+  pub cfs_component_vec: Vec<Component>,
+  /// BSS boot parameters.
+  pub bss_bootparameters_vec: Vec<BootParameters>,
+}
+
+impl SessionsDeletionContext {
+  /// The combined blast radius of deleting every session: the images
+  /// the image sessions built (minus those nodes boot from, which the
+  /// backend keeps) and the components the runtime sessions target.
+  pub fn plan(&self) -> SessionsDeletionPlan {
+    let mut plan = SessionsDeletionPlan::default();
+    let mut components = std::collections::BTreeSet::new();
+    for session in &self.sessions {
+      plan.sessions.push(SessionDeletionItem {
+        name: session.name.clone(),
+        target_definition: session.get_target_def(),
+        status: session.status(),
+        start_time: session.get_start_time(),
+      });
+      if session.is_target_def_image() {
+        for image_id in session.get_result_id_vec() {
+          let hosts: Vec<String> = self
+            .bss_bootparameters_vec
+            .iter()
+            .filter(|bp| {
+              bp.try_get_boot_image_id().as_deref() == Some(image_id.as_str())
+            })
+            .flat_map(|bp| bp.hosts.iter().cloned())
+            .collect();
+          if hosts.is_empty() {
+            plan.image_ids.push(image_id);
+          } else {
+            plan
+              .boot_images_kept
+              .push(BootImageInUse { image_id, hosts });
+          }
+        }
+      } else {
+        let groups = session.get_target_hsm().unwrap_or_default();
+        components.extend(session.get_target_xname().unwrap_or_default());
+        components.extend(
+          self
+            .group_available_vec
+            .iter()
+            .filter(|group| groups.contains(&group.label))
+            .flat_map(Group::get_members),
+        );
+      }
+    }
+    // Only existing CFS components get their error count changed.
+    plan.components = self
+      .cfs_component_vec
+      .iter()
+      .filter_map(|component| component.id.clone())
+      .filter(|id| components.contains(id))
+      .collect();
+    plan.components.sort();
+    plan.components.dedup();
+    plan
+  }
+}
+
+/// Collect the sessions matching `params` (group, status, minimum age,
+/// type; other fields are ignored), narrowed to `names` when not
+/// empty, plus everything deleting them will need. Nothing is
+/// modified.
+///
+/// # Errors
+///
+/// - [`Error::BadRequest`] when the caller can't access
+///   `params.group`.
+/// - Backend errors fetching groups, sessions, CFS components or BSS
+///   boot parameters.
+pub async fn prepare_sessions_deletion(
+  infra: &InfraContext<'_>,
+  token: &str,
+  params: &GetSessionParams,
+  names: &[String],
+) -> Result<SessionsDeletionContext, Error> {
+  let (group_available_vec, target_group_vec) =
+    crate::service::group::resolve_target_and_available_groups(
+      infra,
+      token,
+      params.group.as_deref(),
+    )
+    .await?;
+
+  let (cfs_session_vec, cfs_component_vec, bss_bootparameters_vec) = tokio::try_join!(
+    infra.backend.get_and_filter_sessions(
+      token,
+      target_group_vec,
+      Vec::new(),
+      params.min_age.as_ref(),
+      None,
+      params.session_type.as_ref(),
+      params.status.as_ref(),
+      None,
+      None,
+      None,
+    ),
+    infra.backend.get_cfs_components(token, None, None, None),
+    infra.backend.get_all_bootparameters(token),
+  )?;
+
+  let sessions = cfs_session_vec
+    .into_iter()
+    .filter(|session| names.is_empty() || names.contains(&session.name))
+    .collect();
+
+  Ok(SessionsDeletionContext {
+    sessions,
+    group_available_vec,
+    cfs_component_vec,
+    bss_bootparameters_vec,
+  })
+}
+
+/// Delete every session of `deletion_ctx`, at most
+/// [`SESSION_DELETION_CONCURRENCY_LIMIT`] at a time. A failed deletion
+/// doesn't stop the others; each session gets a result, in the order
+/// of `deletion_ctx.sessions`.
+pub async fn execute_sessions_deletion(
+  infra: &InfraContext<'_>,
+  token: &str,
+  deletion_ctx: &SessionsDeletionContext,
+) -> Vec<SessionDeletionResult> {
+  // Built up front: a stream `map` closure over borrowed sessions
+  // makes the handler future fail axum's `Send` bound.
+  let deletions: Vec<_> = deletion_ctx
+    .sessions
+    .iter()
+    .map(|session| async move {
+      let outcome = infra
+        .backend
+        .delete_and_cancel_session(
+          token,
+          &deletion_ctx.group_available_vec,
+          session,
+          &deletion_ctx.cfs_component_vec,
+          &deletion_ctx.bss_bootparameters_vec,
+          false,
+        )
+        .await;
+      if let Err(e) = &outcome {
+        tracing::warn!("Deleting CFS session '{}' failed: {e}", session.name);
+      }
+      SessionDeletionResult {
+        session_name: session.name.clone(),
+        deleted: outcome.is_ok(),
+        error: outcome.err().map(|e| e.to_string()),
+      }
+    })
+    .collect();
+  futures::stream::iter(deletions)
+    .buffered(SESSION_DELETION_CONCURRENCY_LIMIT)
+    .collect()
+    .await
+}
+
 /// Parameters for [`create_cfs_session`]. Bundled to keep the
 /// service entry point readable at the call site (the handler-level
 /// `CreateSessionRequest` body folds 1:1 into this).
@@ -267,10 +463,180 @@ pub async fn create_cfs_session(
     .await
 }
 
+/// Default name of a re-run of `original`:
+/// `<original>-rerun-<yymmddHHMMSS>`, the original shortened (and any
+/// earlier `-rerun-…` suffix dropped) to fit CFS's name limit.
+fn rerun_session_name(
+  original: &str,
+  now: chrono::DateTime<chrono::Utc>,
+) -> String {
+  let suffix = now.format("-rerun-%y%m%d%H%M%S").to_string();
+  let base = original
+    .rsplit_once("-rerun-")
+    .map_or(original, |(base, _)| base);
+  let base =
+    &base[..base.floor_char_boundary(MAX_SESSION_NAME_LEN - suffix.len())];
+  format!("{}{suffix}", base.trim_end_matches(['-', '.']))
+}
+
+/// The CFS request re-running `original` as `session_name`: same
+/// configuration (and configuration limit), target, Ansible settings,
+/// tags and debug flag, with `ansible_limit` / `ansible_verbosity`
+/// replaced when given.
+fn build_rerun_request(
+  original: &CfsSessionGetResponse,
+  session_name: String,
+  ansible_limit: Option<String>,
+  ansible_verbosity: Option<u8>,
+) -> Result<CfsSessionPostRequest, Error> {
+  let configuration = original.configuration.as_ref();
+  let configuration_name =
+    configuration.and_then(|c| c.name.clone()).ok_or_else(|| {
+      Error::BadRequest(format!(
+        "CFS session '{}' has no configuration to re-run",
+        original.name
+      ))
+    })?;
+  let ansible = original.ansible.as_ref();
+  let mut tags = original.tags.clone().unwrap_or_default();
+  tags.insert(RERUN_OF_TAG.to_string(), original.name.clone());
+  let mut target = original.target.clone().unwrap_or_default();
+  target
+    .definition
+    .get_or_insert_with(|| "dynamic".to_string());
+  Ok(CfsSessionPostRequest {
+    name: session_name,
+    configuration_name,
+    configuration_limit: configuration.and_then(|c| c.limit.clone()),
+    ansible_limit: ansible_limit
+      .or_else(|| ansible.and_then(|a| a.limit.clone())),
+    ansible_config: ansible.and_then(|a| a.config.clone()),
+    ansible_verbosity: ansible_verbosity
+      .or_else(|| ansible.and_then(|a| a.verbosity)),
+    ansible_passthrough: ansible.and_then(|a| a.passthrough.clone()),
+    target,
+    tags: Some(tags),
+    debug_on_failure: original.debug_on_failure,
+  })
+}
+
+/// Refuse a runtime re-run that names neither target groups nor an
+/// Ansible limit unless the caller is an admin: such a session runs on
+/// every node in the inventory, which no group access check covers.
+/// Image sessions configure an image, not nodes, and always pass.
+fn require_rerun_scope(
+  request: &CfsSessionPostRequest,
+  is_admin: bool,
+) -> Result<(), Error> {
+  let has_groups = request
+    .target
+    .groups
+    .as_ref()
+    .is_some_and(|groups| !groups.is_empty());
+  let is_image = request.target.definition.as_deref() == Some("image");
+  if is_image || has_groups || request.ansible_limit.is_some() || is_admin {
+    return Ok(());
+  }
+  Err(Error::BadRequest(format!(
+    "CFS session '{}' has no target groups and no Ansible limit, so a \
+     re-run would reach every node; only admins may re-run it as is. \
+     Give an 'ansible_limit' of nodes you can access.",
+    request
+      .tags
+      .as_ref()
+      .and_then(|t| t.get(RERUN_OF_TAG))
+      .map_or(request.name.as_str(), String::as_str)
+  )))
+}
+
+/// Create a new CFS session repeating session `original_name`, or on
+/// `req.dry_run` only describe it.
+///
+/// The caller needs access to the original session's target groups
+/// and to every node of the (original or replacement) Ansible limit,
+/// the same checks `POST /sessions` applies. A session with neither
+/// target groups nor a limit reaches every node, so only admins may
+/// re-run it without giving a limit.
+///
+/// # Errors
+///
+/// - [`Error::NotFound`] when the original session does not exist.
+/// - [`Error::BadRequest`] when the caller may not access its targets
+///   or the new limit, when a non-admin re-runs an untargeted,
+///   unlimited session, when the verbosity is above 4, or when the
+///   original has no configuration.
+/// - Backend errors creating the session (e.g. the name is taken).
+pub async fn rerun_session(
+  infra: &InfraContext<'_>,
+  token: &str,
+  original_name: &str,
+  req: &RerunSessionRequest,
+) -> Result<RerunSessionResponse, Error> {
+  if req
+    .ansible_verbosity
+    .is_some_and(|v| v > MAX_ANSIBLE_VERBOSITY)
+  {
+    return Err(Error::BadRequest(format!(
+      "ansible_verbosity must be between 0 and {MAX_ANSIBLE_VERBOSITY}"
+    )));
+  }
+  let original = validate_session_access(infra, token, original_name).await?;
+
+  let ansible_limit = match req.ansible_limit.as_deref() {
+    Some(expression) => Some(
+      node_ops::from_user_hosts_expression_to_xname_vec(
+        infra, token, expression, false,
+      )
+      .await?
+      .join(","),
+    ),
+    None => None,
+  };
+  let session_name = req
+    .session_name
+    .clone()
+    .unwrap_or_else(|| rerun_session_name(original_name, chrono::Utc::now()));
+  let request = build_rerun_request(
+    &original,
+    session_name,
+    ansible_limit,
+    req.ansible_verbosity,
+  )?;
+  require_rerun_scope(&request, authorization::is_admin(token))?;
+  if let Some(limit) = &request.ansible_limit {
+    authorization::validate_ansible_limit_membership_access(
+      infra, token, limit,
+    )
+    .await?;
+  }
+
+  let created_name = if req.dry_run {
+    request.name.clone()
+  } else {
+    infra.backend.post_session(token, &request).await?.name
+  };
+  Ok(RerunSessionResponse {
+    session_name: created_name,
+    rerun_of: original.name,
+    configuration_name: request.configuration_name,
+    target_definition: request.target.definition.unwrap_or_default(),
+    target_groups: request
+      .target
+      .groups
+      .unwrap_or_default()
+      .into_iter()
+      .map(|group| group.name)
+      .collect(),
+    ansible_limit: request.ansible_limit,
+    ansible_verbosity: request.ansible_verbosity,
+    dry_run: req.dry_run,
+  })
+}
+
 /// Fetch a single CFS session by name.
 ///
 /// Returns `NotFound` when no session with that name exists.
-async fn fetch_session_by_name(
+pub(crate) async fn fetch_session_by_name(
   infra: &InfraContext<'_>,
   token: &str,
   name: &str,
@@ -430,10 +796,17 @@ mod tests {
   //! `get_sessions`, etc.) are exercised through integration tests
   //! against `router()` — see `crates/manta-server/tests/`.
 
-  use super::{Error, require_result_image};
+  use super::{
+    Error, RERUN_OF_TAG, SessionsDeletionContext, build_rerun_request,
+    require_rerun_scope, require_result_image, rerun_session_name,
+  };
+  use manta_backend_dispatcher::types::bss::BootParameters;
+  use manta_backend_dispatcher::types::cfs::component::Component;
   use manta_backend_dispatcher::types::cfs::session::{
-    Artifact, CfsSessionGetResponse, Status,
+    Ansible, Artifact, CfsSessionGetResponse, Configuration, Group, Status,
+    Target,
   };
+  use manta_backend_dispatcher::types::{Group as HsmGroup, Member};
 
   fn session_with_result_id(
     name: &str,
@@ -491,4 +864,222 @@ mod tests {
     let err = require_result_image(&session).unwrap_err();
     assert!(matches!(err, Error::BadRequest(_)));
   }
+
+  fn failed_runtime_session() -> CfsSessionGetResponse {
+    CfsSessionGetResponse {
+      name: "batcher-1".to_string(),
+      configuration: Some(Configuration {
+        name: Some("compute-config".to_string()),
+        limit: Some("0,2".to_string()),
+      }),
+      ansible: Some(Ansible {
+        config: Some("cfs-default-ansible-cfg".to_string()),
+        limit: Some("x1000c0s0b0n0,x1000c0s0b0n1".to_string()),
+        verbosity: Some(1),
+        passthrough: Some("--tags=update".to_string()),
+      }),
+      target: Some(Target {
+        definition: Some("dynamic".to_string()),
+        groups: Some(vec![Group {
+          name: "compute".to_string(),
+          members: Vec::new(),
+        }]),
+        image_map: None,
+      }),
+      status: None,
+      tags: None,
+      debug_on_failure: true,
+      logs: None,
+    }
+  }
+
+  #[test]
+  fn rerun_copies_the_original_parameters() {
+    let original = failed_runtime_session();
+    let req =
+      build_rerun_request(&original, "again".to_string(), None, None).unwrap();
+    assert_eq!(req.name, "again");
+    assert_eq!(req.configuration_name, "compute-config");
+    assert_eq!(req.configuration_limit.as_deref(), Some("0,2"));
+    assert_eq!(
+      req.ansible_limit.as_deref(),
+      Some("x1000c0s0b0n0,x1000c0s0b0n1")
+    );
+    assert_eq!(req.ansible_verbosity, Some(1));
+    assert_eq!(req.ansible_passthrough.as_deref(), Some("--tags=update"));
+    assert_eq!(req.target.definition.as_deref(), Some("dynamic"));
+    assert_eq!(req.target.groups.unwrap()[0].name, "compute");
+    assert_eq!(
+      req.tags.unwrap().get(RERUN_OF_TAG).map(String::as_str),
+      Some("batcher-1")
+    );
+    assert!(req.debug_on_failure);
+  }
+
+  #[test]
+  fn rerun_overrides_limit_and_verbosity() {
+    let req = build_rerun_request(
+      &failed_runtime_session(),
+      "again".to_string(),
+      Some("x1000c0s0b0n1".to_string()),
+      Some(3),
+    )
+    .unwrap();
+    assert_eq!(req.ansible_limit.as_deref(), Some("x1000c0s0b0n1"));
+    assert_eq!(req.ansible_verbosity, Some(3));
+  }
+
+  #[test]
+  fn rerun_needs_a_configuration() {
+    let mut original = failed_runtime_session();
+    original.configuration = None;
+    assert!(matches!(
+      build_rerun_request(&original, "again".to_string(), None, None),
+      Err(Error::BadRequest(_))
+    ));
+  }
+
+  #[test]
+  fn untargeted_unlimited_rerun_needs_an_admin() {
+    let mut original = failed_runtime_session();
+    original.target = None;
+    original.ansible = None;
+    let req =
+      build_rerun_request(&original, "again".to_string(), None, None).unwrap();
+    assert!(matches!(
+      require_rerun_scope(&req, false),
+      Err(Error::BadRequest(msg)) if msg.contains("batcher-1")
+    ));
+    assert!(require_rerun_scope(&req, true).is_ok());
+
+    let limited = build_rerun_request(
+      &original,
+      "again".to_string(),
+      Some("x1000c0s0b0n1".to_string()),
+      None,
+    )
+    .unwrap();
+    assert!(require_rerun_scope(&limited, false).is_ok());
+    let targeted = build_rerun_request(
+      &failed_runtime_session(),
+      "again".to_string(),
+      None,
+      None,
+    )
+    .unwrap();
+    assert!(require_rerun_scope(&targeted, false).is_ok());
+  }
+
+  #[test]
+  fn rerun_names_fit_cfs_and_do_not_stack() {
+    let now = chrono::DateTime::parse_from_rfc3339("2026-10-19T08:30:00Z")
+      .unwrap()
+      .with_timezone(&chrono::Utc);
+    assert_eq!(
+      rerun_session_name("batcher-1", now),
+      "batcher-1-rerun-261019083000"
+    );
+    let long =
+      rerun_session_name("batcher-64d35a81-d0e1-496d-9eda-0010e502f2a3", now);
+    assert!(long.len() <= 45, "{long}");
+    assert!(long.starts_with("batcher-64d35a81-d0e1-496"), "{long}");
+    assert_eq!(
+      rerun_session_name("batcher-1-rerun-261018000000", now),
+      "batcher-1-rerun-261019083000"
+    );
+    // Truncation lands on a char boundary: 'é' spans bytes 25 and 26.
+    let accented =
+      rerun_session_name(&format!("{}é-batcher", "b".repeat(25)), now);
+    assert!(accented.len() <= 45, "{accented}");
+    assert!(accented.starts_with(&"b".repeat(25)), "{accented}");
+  }
+
+  fn image_session(name: &str, result_ids: &[&str]) -> CfsSessionGetResponse {
+    CfsSessionGetResponse {
+      target: Some(Target {
+        definition: Some("image".to_string()),
+        groups: None,
+        image_map: None,
+      }),
+      status: Some(Status {
+        artifacts: Some(
+          result_ids
+            .iter()
+            .map(|id| Artifact {
+              image_id: None,
+              result_id: Some(id.to_string()),
+              r#type: None,
+            })
+            .collect(),
+        ),
+        session: None,
+      }),
+      ..session_with_result_id(name, None)
+    }
+  }
+
+  fn cfs_component(id: &str) -> Component {
+    Component {
+      id: Some(id.to_string()),
+      state: None,
+      desired_config: None,
+      error_count: None,
+      retry_policy: None,
+      enabled: None,
+      configuration_status: None,
+      tags: None,
+      logs: None,
+    }
+  }
+
+  #[test]
+  fn deletion_plan_sums_up_every_session() {
+    let deletion_ctx = SessionsDeletionContext {
+      sessions: vec![
+        image_session("img-1", &["ims-a", "ims-b"]),
+        failed_runtime_session(),
+        image_session("img-2", &["ims-c"]),
+      ],
+      group_available_vec: vec![HsmGroup {
+        label: "compute".to_string(),
+        description: None,
+        tags: None,
+        members: Some(Member {
+          ids: Some(vec![
+            "x1000c0s0b0n1".to_string(),
+            "x1000c0s0b0n2".to_string(),
+          ]),
+        }),
+        exclusive_group: None,
+      }],
+      // x1000c0s0b0n2 is in the group but has no CFS component.
+      cfs_component_vec: vec![
+        cfs_component("x1000c0s0b0n1"),
+        cfs_component("x1000c0s0b0n0"),
+        cfs_component("x9000c0s0b0n0"),
+      ],
+      bss_bootparameters_vec: vec![BootParameters {
+        hosts: vec!["x3000c0s1b0n0".to_string()],
+        macs: None,
+        nids: None,
+        params: "root=s3://boot-images/ims-b/rootfs".to_string(),
+        kernel: "s3://boot-images/ims-b/kernel".to_string(),
+        initrd: "s3://boot-images/ims-b/initrd".to_string(),
+        cloud_init: None,
+      }],
+    };
+
+    let plan = deletion_ctx.plan();
+    let names: Vec<&str> = plan
+      .sessions
+      .iter()
+      .map(|item| item.name.as_str())
+      .collect();
+    assert_eq!(names, ["img-1", "batcher-1", "img-2"]);
+    assert_eq!(plan.image_ids, ["ims-a", "ims-c"]);
+    assert_eq!(plan.boot_images_kept.len(), 1);
+    assert_eq!(plan.boot_images_kept[0].image_id, "ims-b");
+    assert_eq!(plan.boot_images_kept[0].hosts, ["x3000c0s1b0n0"]);
+    assert_eq!(plan.components, ["x1000c0s0b0n0", "x1000c0s0b0n1"]);
+  }
 }
@@ -0,0 +1,459 @@
+//! Archived CFS session logs — the full log of a session kept on the
+//! server so it can still be read once CFS has garbage-collected the
+//! session's pod (or the session itself).
+//!
+//! Logs live in a [`SessionLogStore`]: per site, `<session>.log` holds
+//! the lines as the pod printed them (always with Kubernetes
+//! timestamps) and `<session>.json` the [`ArchiveInfo`] used to
+//! authorize readers after the CFS session is gone. Archives are
+//! written by [`crate::server::session_log_archiver`], which also
+//! prunes them by age and count on a timer.
+//!
+//! The whole feature is opt-in through `[server.session_logs]`;
+//! without it [`crate::server::ServerState::session_logs`] is `None`
+//! and the archive endpoint answers `501 Not Implemented`.
+
+use std::collections::HashSet;
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::Duration;
+
+use chrono::{DateTime, SecondsFormat, Utc};
+use manta_backend_dispatcher::error::Error;
+use manta_backend_dispatcher::interfaces::hsm::group::GroupTrait;
+use serde::{Deserialize, Serialize};
+
+use crate::server::common::app_context::InfraContext;
+use crate::service::authorization;
+use crate::service::reservation::ServiceAccount;
+
+pub use manta_shared::types::api::session::ArchivedSessionLog;
+
+/// Everything the server needs to archive session logs, built at
+/// startup from `[server.session_logs]`.
+pub struct SessionLogs {
+  /// Archived logs.
+  pub store: SessionLogStore,
+  /// Credentials the archiver reads logs with.
+  pub service_account: ServiceAccount,
+}
+
+/// Metadata stored next to each archived log.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ArchiveInfo {
+  /// CFS session name.
+  pub session_name: String,
+  /// Site the session ran on.
+  pub site: String,
+  /// HSM groups the session targeted.
+  pub groups: Vec<String>,
+  /// RFC 3339 instant the archive was written (UTC).
+  pub archived_at: String,
+  /// Whether the log stream ran to its end.
+  pub complete: bool,
+}
+
+/// Archived logs stored as `<site>/<session>.{log,json}` under one
+/// directory.
+///
+/// At most one [`ArchiveWriter`] exists per session at a time, so two
+/// clients streaming the same session produce one archive.
+pub struct SessionLogStore {
+  dir: PathBuf,
+  retention: Option<Duration>,
+  max_archives: Option<usize>,
+  in_progress: Mutex<HashSet<(String, String)>>,
+}
+
+/// Session and site names become file names, so only plain names are
+/// accepted (CFS session names are lowercase DNS labels anyway).
+fn is_plain_name(name: &str) -> bool {
+  !name.is_empty()
+    && !name.starts_with('.')
+    && name
+      .chars()
+      .all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.'))
+}
+
+impl SessionLogStore {
+  /// Open the store in `dir`, creating the directory if needed, and
+  /// drop archives already past retention. `retention` of `None` keeps
+  /// archives forever; `max_archives` of `None` keeps any number.
+  ///
+  /// # Errors
+  ///
+  /// [`Error::IoError`] when the directory cannot be created.
+  pub fn open(
+    dir: impl Into<PathBuf>,
+    retention: Option<Duration>,
+    max_archives: Option<usize>,
+  ) -> Result<Self, Error> {
+    let dir = dir.into();
+    std::fs::create_dir_all(&dir)?;
+    let store = Self {
+      dir,
+      retention,
+      max_archives,
+      in_progress: Mutex::new(HashSet::new()),
+    };
+    store.prune(Utc::now())?;
+    Ok(store)
+  }
+
+  fn paths(&self, site: &str, session: &str) -> Option<(PathBuf, PathBuf)> {
+    if !is_plain_name(site) || !is_plain_name(session) {
+      return None;
+    }
+    let site_dir = self.dir.join(site);
+    Some((
+      site_dir.join(format!("{session}.log")),
+      site_dir.join(format!("{session}.json")),
+    ))
+  }
+
+  /// Start archiving `session` on `site`. `None` when the session
+  /// already has a complete archive, is being archived right now, or
+  /// its name cannot be stored.
+  ///
+  /// # Errors
+  ///
+  /// [`Error::IoError`] when the partial log file cannot be created.
+  pub fn begin(
+    &self,
+    site: &str,
+    session: &str,
+  ) -> Result<Option<ArchiveWriter<'_>>, Error> {
+    let Some((log_path, info_path)) = self.paths(site, session) else {
+      return Ok(None);
+    };
+    if read_info(&info_path)?.is_some_and(|info| info.complete) {
+      return Ok(None);
+    }
+    let key = (site.to_string(), session.to_string());
+    if !self
+      .in_progress
+      .lock()
+      .unwrap_or_else(std::sync::PoisonError::into_inner)
+      .insert(key.clone())
+    {
+      return Ok(None);
+    }
+    let mut writer = ArchiveWriter {
+      store: self,
+      key,
+      partial_path: log_path.with_extension("log.partial"),
+      log_path,
+      info_path,
+      file: None,
+    };
+    if let Some(parent) = writer.log_path.parent() {
+      std::fs::create_dir_all(parent)?;
+    }
+    writer.file = Some(BufWriter::new(File::create(&writer.partial_path)?));
+    Ok(Some(writer))
+  }
+
+  /// The archive of `session` on `site`; `None` when there is none.
+  ///
+  /// # Errors
+  ///
+  /// [`Error::IoError`] / [`Error::SerdeError`] when the archive exists
+  /// but cannot be read.
+  pub fn load(
+    &self,
+    site: &str,
+    session: &str,
+  ) -> Result<Option<(ArchiveInfo, String)>, Error> {
+    let Some((log_path, info_path)) = self.paths(site, session) else {
+      return Ok(None);
+    };
+    let Some(info) = read_info(&info_path)? else {
+      return Ok(None);
+    };
+    match std::fs::read_to_string(&log_path) {
+      Ok(log) => Ok(Some((info, log))),
+      Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(None),
+      Err(e) => Err(e.into()),
+    }
+  }
+
+  /// Delete archives older than the retention period, then the oldest
+  /// ones beyond `max_archives`. Returns how many were deleted.
+  ///
+  /// # Errors
+  ///
+  /// [`Error::IoError`] when the store directory cannot be listed.
+  pub fn prune(&self, now: DateTime<Utc>) -> Result<usize, Error> {
+    let mut archives: Vec<(DateTime<Utc>, PathBuf)> = Vec::new();
+    for site in std::fs::read_dir(&self.dir)? {
+      let site = site?.path();
+      if !site.is_dir() {
+        continue;
+      }
+      for entry in std::fs::read_dir(&site)? {
+        let path = entry?.path();
+        if path.extension().is_none_or(|ext| ext != "json") {
+          continue;
+        }
+        match read_info(&path) {
+          Ok(Some(info)) => {
+            let archived_at = DateTime::parse_from_rfc3339(&info.archived_at)
+              .map_or(DateTime::<Utc>::MIN_UTC, |t| t.with_timezone(&Utc));
+            archives.push((archived_at, path));
+          }
+          Ok(None) => {}
+          Err(e) => tracing::warn!(
+            "skipping unreadable session log archive {}: {e}",
+            path.display()
+          ),
+        }
+      }
+    }
+    archives.sort_by(|a, b| b.0.cmp(&a.0));
+
+    let mut deleted = 0;
+    for (i, (archived_at, info_path)) in archives.iter().enumerate() {
+      let expired = self.retention.is_some_and(|retention| {
+        now
+          .signed_duration_since(*archived_at)
+          .to_std()
+          .is_ok_and(|age| age > retention)
+      });
+      let over_limit = self.max_archives.is_some_and(|max| i >= max);
+      if expired || over_limit {
+        remove_archive(info_path);
+        deleted += 1;
+      }
+    }
+    Ok(deleted)
+  }
+}
+
+fn read_info(path: &Path) -> Result<Option<ArchiveInfo>, Error> {
+  match std::fs::read(path) {
+    Ok(bytes) => Ok(Some(serde_json::from_slice(&bytes)?)),
+    Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(None),
+    Err(e) => Err(e.into()),
+  }
+}
+
+fn remove_archive(info_path: &Path) {
+  for path in [info_path.with_extension("log"), info_path.to_path_buf()] {
+    if let Err(e) = std::fs::remove_file(&path)
+      && e.kind() != std::io::ErrorKind::NotFound
+    {
+      tracing::warn!(
+        "could not delete session log archive {}: {e}",
+        path.display()
+      );
+    }
+  }
+}
+
+/// An archive being written. The log goes to `<session>.log.partial`
+/// and only replaces `<session>.log` on [`Self::finish`]; a writer
+/// dropped without finishing leaves any earlier archive untouched.
+pub struct ArchiveWriter<'a> {
+  store: &'a SessionLogStore,
+  key: (String, String),
+  log_path: PathBuf,
+  partial_path: PathBuf,
+  info_path: PathBuf,
+  file: Option<BufWriter<File>>,
+}
+
+impl ArchiveWriter<'_> {
+  /// Append one log line.
+  ///
+  /// # Errors
+  ///
+  /// [`Error::IoError`] when the line cannot be written.
+  pub fn write_line(&mut self, line: &str) -> Result<(), Error> {
+    if let Some(file) = self.file.as_mut() {
+      writeln!(file, "{line}")?;
+    }
+    Ok(())
+  }
+
+  /// Publish the archive with its metadata. `complete` records whether
+  /// the log stream ran to its end.
+  ///
+  /// # Errors
+  ///
+  /// [`Error::IoError`] / [`Error::SerdeError`] when the archive cannot
+  /// be written.
+  pub fn finish(
+    mut self,
+    groups: Vec<String>,
+    complete: bool,
+  ) -> Result<(), Error> {
+    if let Some(mut file) = self.file.take() {
+      file.flush()?;
+    }
+    std::fs::rename(&self.partial_path, &self.log_path)?;
+    let now = Utc::now();
+    let info = ArchiveInfo {
+      session_name: self.key.1.clone(),
+      site: self.key.0.clone(),
+      groups,
+      archived_at: now.to_rfc3339_opts(SecondsFormat::Secs, true),
+      complete,
+    };
+    let tmp = self.info_path.with_extension("json.tmp");
+    std::fs::write(&tmp, serde_json::to_vec_pretty(&info)?)?;
+    std::fs::rename(&tmp, &self.info_path)?;
+    Ok(())
+  }
+}
+
+impl Drop for ArchiveWriter<'_> {
+  fn drop(&mut self) {
+    if self.file.take().is_some() {
+      let _ = std::fs::remove_file(&self.partial_path);
+    }
+    self
+      .store
+      .in_progress
+      .lock()
+      .unwrap_or_else(std::sync::PoisonError::into_inner)
+      .remove(&self.key);
+  }
+}
+
+/// Drop the Kubernetes timestamp (`2024-05-01T10:00:00.123456789Z `)
+/// that archived lines start with.
+pub(crate) fn strip_timestamp(line: &str) -> &str {
+  match line.split_once(' ') {
+    Some((stamp, rest)) if DateTime::parse_from_rfc3339(stamp).is_ok() => rest,
+    _ => line,
+  }
+}
+
+/// Read the archived log of `session_name` on the caller's site.
+/// `timestamps` keeps the Kubernetes timestamp on each line.
+///
+/// # Errors
+///
+/// [`Error::NotFound`] when there is no archive for the session or the
+/// caller cannot access every group it targeted (the two are not told
+/// apart); store read errors otherwise.
+pub async fn get_archived_log(
+  infra: &InfraContext<'_>,
+  token: &str,
+  store: &SessionLogStore,
+  session_name: &str,
+  timestamps: bool,
+) -> Result<ArchivedSessionLog, Error> {
+  let not_found = || {
+    Error::NotFound(format!("no archived log for CFS session '{session_name}'"))
+  };
+  let (info, log) = store
+    .load(infra.site_name, session_name)?
+    .ok_or_else(not_found)?;
+  if !authorization::is_admin(token) && !info.groups.is_empty() {
+    let available = infra.backend.get_group_name_available(token).await?;
+    if !info.groups.iter().all(|g| available.contains(g)) {
+      return Err(not_found());
+    }
+  }
+  Ok(ArchivedSessionLog {
+    session_name: info.session_name,
+    site: info.site,
+    groups: info.groups,
+    archived_at: info.archived_at,
+    complete: info.complete,
+    lines: log
+      .lines()
+      .map(|line| {
+        if timestamps {
+          line
+        } else {
+          strip_timestamp(line)
+        }
+        .to_string()
+      })
+      .collect(),
+  })
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn archive(store: &SessionLogStore, session: &str, lines: &[&str]) {
+    let mut writer = store.begin("alps", session).unwrap().unwrap();
+    for line in lines {
+      writer.write_line(line).unwrap();
+    }
+    writer.finish(vec!["compute".to_string()], true).unwrap();
+  }
+
+  #[test]
+  fn archives_round_trip_and_are_written_once() {
+    let dir = tempfile::tempdir().unwrap();
+    let store = SessionLogStore::open(dir.path(), None, None).unwrap();
+    archive(
+      &store,
+      "batcher-1",
+      &["2024-05-01T10:00:00.5Z hello", "bye"],
+    );
+
+    let (info, log) = store.load("alps", "batcher-1").unwrap().unwrap();
+    assert_eq!(info.groups, vec!["compute".to_string()]);
+    assert!(info.complete);
+    assert_eq!(log, "2024-05-01T10:00:00.5Z hello\nbye\n");
+    assert!(store.begin("alps", "batcher-1").unwrap().is_none());
+    assert!(store.load("other", "batcher-1").unwrap().is_none());
+  }
+
+  #[test]
+  fn concurrent_writers_and_unsafe_names_are_refused() {
+    let dir = tempfile::tempdir().unwrap();
+    let store = SessionLogStore::open(dir.path(), None, None).unwrap();
+    let first = store.begin("alps", "batcher-2").unwrap();
+    assert!(first.is_some());
+    assert!(store.begin("alps", "batcher-2").unwrap().is_none());
+    drop(first);
+    assert!(store.load("alps", "batcher-2").unwrap().is_none());
+    assert!(store.begin("alps", "batcher-2").unwrap().is_some());
+
+    assert!(store.begin("alps", "../etc/passwd").unwrap().is_none());
+    assert!(store.begin("..", "batcher-3").unwrap().is_none());
+  }
+
+  #[test]
+  fn prune_applies_retention_then_count() {
+    let dir = tempfile::tempdir().unwrap();
+    let store = SessionLogStore::open(
+      dir.path(),
+      Some(Duration::from_secs(3600)),
+      Some(2),
+    )
+    .unwrap();
+    for session in ["a", "b", "c"] {
+      archive(&store, session, &["line"]);
+    }
+    // Archiving leaves pruning to the archiver's timer.
+    assert_eq!(store.prune(Utc::now()).unwrap(), 1);
+    let kept = ["a", "b", "c"]
+      .iter()
+      .filter(|s| store.load("alps", s).unwrap().is_some())
+      .count();
+    assert_eq!(kept, 2);
+
+    let later = Utc::now() + chrono::Duration::hours(2);
+    assert_eq!(store.prune(later).unwrap(), 2);
+    assert!(store.load("alps", "c").unwrap().is_none());
+  }
+
+  #[test]
+  fn timestamps_are_stripped_only_when_present() {
+    assert_eq!(strip_timestamp("2024-05-01T10:00:00.123456789Z ok"), "ok");
+    assert_eq!(
+      strip_timestamp("TASK [common : x] ***"),
+      "TASK [common : x] ***"
+    );
+  }
+}
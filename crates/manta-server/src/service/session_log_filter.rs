@@ -0,0 +1,227 @@
+//! Server-side filtering of CFS session log lines (`?layer=` and
+//! `?grep=` on the session log endpoints), so only the lines the
+//! caller asked for cross the wire.
+//!
+//! CFS runs every configuration layer in the session pod's single
+//! `ansible` container, one after the other, announcing each with a
+//! `Running <playbook> from repo <url>` line
+//! ([`manta_shared::types::ansible_summary::layer_start`]). A layer
+//! filter therefore keeps the lines from the start of the selected
+//! layer up to the start of the next one; the `git-clone` and
+//! `inventory` output before the first layer belongs to none.
+
+use manta_backend_dispatcher::error::Error;
+use manta_backend_dispatcher::interfaces::cfs::CfsTrait;
+use manta_backend_dispatcher::types::cfs::session::CfsSessionGetResponse;
+use manta_shared::types::ansible_summary::{layer_start, repo_name};
+use regex::Regex;
+
+use crate::server::common::app_context::InfraContext;
+use crate::service::session_log::strip_timestamp;
+
+/// Which configuration layer to keep.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum LayerSelector {
+  /// The layer at this 0-based position in the configuration.
+  Index(usize),
+  /// The layer whose repository name, playbook, or
+  /// `repository/playbook` is this.
+  Name(String),
+}
+
+impl LayerSelector {
+  /// A number selects by position, anything else by name.
+  pub fn parse(spec: &str) -> Self {
+    spec
+      .trim()
+      .parse()
+      .map_or_else(|_| Self::Name(spec.trim().to_string()), Self::Index)
+  }
+
+  fn matches(&self, index: usize, playbook: &str, repo_url: &str) -> bool {
+    match self {
+      Self::Index(wanted) => *wanted == index,
+      Self::Name(name) => {
+        let repo = repo_name(repo_url);
+        name == repo
+          || name == playbook
+          || name.split_once('/') == Some((repo, playbook))
+      }
+    }
+  }
+}
+
+/// Resolve the `?layer=` value of a request against the configuration
+/// `session` applies: a layer name of that configuration becomes its
+/// position. Anything else (or no session, e.g. for an archived log
+/// whose session is gone) is kept for matching against the log's own
+/// repository and playbook names.
+///
+/// # Errors
+///
+/// Propagates backend errors fetching the configuration.
+pub async fn resolve_layer(
+  infra: &InfraContext<'_>,
+  token: &str,
+  session: Option<&CfsSessionGetResponse>,
+  spec: &str,
+) -> Result<LayerSelector, Error> {
+  let selector = LayerSelector::parse(spec);
+  let LayerSelector::Name(name) = &selector else {
+    return Ok(selector);
+  };
+  let Some(configuration_name) =
+    session.and_then(CfsSessionGetResponse::get_configuration_name)
+  else {
+    return Ok(selector);
+  };
+  let configurations = infra
+    .backend
+    .get_configuration(token, Some(&configuration_name))
+    .await?;
+  let position = configurations.first().and_then(|configuration| {
+    configuration
+      .layers
+      .iter()
+      .position(|layer| layer.name.as_deref() == Some(name.as_str()))
+  });
+  Ok(position.map_or(selector, LayerSelector::Index))
+}
+
+/// The filter for a request's `?layer=` and `?grep=` values, with the
+/// layer resolved by [`resolve_layer`].
+///
+/// # Errors
+///
+/// [`Error::BadRequest`] for an invalid `grep`; backend errors from
+/// [`resolve_layer`].
+pub async fn for_request(
+  infra: &InfraContext<'_>,
+  token: &str,
+  session: Option<&CfsSessionGetResponse>,
+  layer: Option<&str>,
+  grep: Option<&str>,
+) -> Result<LogLineFilter, Error> {
+  let layer = match layer {
+    Some(spec) => Some(resolve_layer(infra, token, session, spec).await?),
+    None => None,
+  };
+  LogLineFilter::new(layer, grep)
+}
+
+/// Stateful line filter; feed it every line of a log in order.
+#[derive(Debug, Default)]
+pub struct LogLineFilter {
+  layer: Option<LayerSelector>,
+  grep: Option<Regex>,
+  /// Layers started so far.
+  layers_seen: usize,
+  in_layer: bool,
+}
+
+impl LogLineFilter {
+  /// A filter keeping the lines of `layer` that match `grep`.
+  ///
+  /// # Errors
+  ///
+  /// [`Error::BadRequest`] when `grep` is not a valid regular
+  /// expression.
+  pub fn new(
+    layer: Option<LayerSelector>,
+    grep: Option<&str>,
+  ) -> Result<Self, Error> {
+    let grep = grep
+      .map(Regex::new)
+      .transpose()
+      .map_err(|e| Error::BadRequest(format!("Invalid grep pattern: {e}")))?;
+    Ok(Self {
+      layer,
+      grep,
+      ..Self::default()
+    })
+  }
+
+  /// Whether `line` should be passed on. `grep` is matched against
+  /// the line without its Kubernetes timestamp, so anchored patterns
+  /// work whether or not timestamps were requested.
+  pub fn keep(&mut self, line: &str) -> bool {
+    if let Some(layer) = &self.layer {
+      if let Some((playbook, repo)) = layer_start(line) {
+        self.in_layer = layer.matches(self.layers_seen, playbook, repo);
+        self.layers_seen += 1;
+      }
+      if !self.in_layer {
+        return false;
+      }
+    }
+    self
+      .grep
+      .as_ref()
+      .is_none_or(|grep| grep.is_match(strip_timestamp(line)))
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  const LOG: &str = "\
+Cloning into 'cfg'...
+Running site.yml from repo https://vcs/cray/cos-config-management.git
+TASK [a] ***
+ok: [x1]
+Running ncn.yml from repo https://vcs/cray/csm-config-management.git
+TASK [b] ***
+fatal: [x1]: FAILED! => {\"msg\": \"boom\"}";
+
+  fn kept(mut filter: LogLineFilter) -> Vec<&'static str> {
+    LOG.lines().filter(|line| filter.keep(line)).collect()
+  }
+
+  #[test]
+  fn layer_by_index_keeps_that_layer_only() {
+    let filter =
+      LogLineFilter::new(Some(LayerSelector::Index(1)), None).unwrap();
+    let lines = kept(filter);
+    assert_eq!(lines.len(), 3);
+    assert!(lines[0].starts_with("Running ncn.yml"));
+  }
+
+  #[test]
+  fn layer_by_repo_or_playbook_name() {
+    for name in [
+      "cos-config-management",
+      "site.yml",
+      "cos-config-management/site.yml",
+    ] {
+      let filter =
+        LogLineFilter::new(Some(LayerSelector::parse(name)), None).unwrap();
+      assert_eq!(kept(filter).len(), 3, "{name}");
+    }
+  }
+
+  #[test]
+  fn grep_combines_with_layer() {
+    let filter =
+      LogLineFilter::new(Some(LayerSelector::Index(1)), Some("FAILED|TASK"))
+        .unwrap();
+    assert_eq!(
+      kept(filter),
+      vec![
+        "TASK [b] ***",
+        "fatal: [x1]: FAILED! => {\"msg\": \"boom\"}",
+      ]
+    );
+    let mut filter = LogLineFilter::new(None, Some("^ok:")).unwrap();
+    assert!(filter.keep("2024-05-01T10:00:00.1Z ok: [x1]"));
+    assert!(!filter.keep("2024-05-01T10:00:00.1Z TASK [ok:]"));
+  }
+
+  #[test]
+  fn invalid_grep_is_a_bad_request() {
+    assert!(matches!(
+      LogLineFilter::new(None, Some("(")),
+      Err(Error::BadRequest(_))
+    ));
+  }
+}
@@ -0,0 +1,209 @@
+//! Per-site capability report (`GET /v2/sites/{name}/capabilities`).
+//!
+//! Derived without any backend call from what the server already
+//! knows about the site: the backend kind (OpenCHAMI leaves the CFS,
+//! BOS, IMS, SAT, console, migrate and hardware-pin traits at their
+//! "not implemented" defaults, and has no [`EphemeralEnvTrait`]
+//! implementation), whether `[sites.X]` sets `vault_base_url` /
+//! `k8s_api_url`, and whether the server has a session log archive;
+//! the corresponding handlers require each or answer 501.
+//!
+//! [`EphemeralEnvTrait`]: crate::backend_dispatcher::EphemeralEnvTrait
+
+use manta_shared::types::api::site::{SiteCapabilities, SiteFeature, feature};
+
+use crate::server::common::app_context::InfraContext;
+
+/// What a feature needs from the site.
+struct Needs {
+  /// Only the CSM backend implements it.
+  csm: bool,
+  /// The handler requires `vault_base_url`.
+  vault: bool,
+  /// The handler requires `k8s_api_url`.
+  k8s: bool,
+  /// The handler requires the server's `[server.session_logs]`.
+  session_logs: bool,
+}
+
+/// Backend features: CSM only.
+const CSM: Needs = Needs {
+  csm: true,
+  vault: false,
+  k8s: false,
+  session_logs: false,
+};
+/// CSM plus Vault.
+const CSM_VAULT: Needs = Needs {
+  csm: true,
+  vault: true,
+  k8s: false,
+  session_logs: false,
+};
+/// CSM plus Vault and Kubernetes.
+const CSM_VAULT_K8S: Needs = Needs {
+  csm: true,
+  vault: true,
+  k8s: true,
+  session_logs: false,
+};
+/// The server's session log archive, on any backend.
+const SESSION_LOG_STORE: Needs = Needs {
+  csm: false,
+  vault: false,
+  k8s: false,
+  session_logs: true,
+};
+
+/// Every feature whose availability depends on the site, in report
+/// order.
+const FEATURES: &[(&str, Needs)] = &[
+  (feature::CFS, CSM),
+  (feature::SESSION_CREATE, CSM_VAULT),
+  (feature::SESSION_RERUN, CSM),
+  (feature::SESSION_LOGS, CSM_VAULT_K8S),
+  (feature::SESSION_LOG_ARCHIVE, SESSION_LOG_STORE),
+  (feature::CONSOLE, CSM_VAULT_K8S),
+  (feature::SAT_FILE, CSM_VAULT_K8S),
+  (feature::IMAGES, CSM),
+  (feature::TEMPLATES, CSM),
+  (feature::MIGRATE, CSM),
+  (feature::HARDWARE_PIN, CSM),
+  (feature::EPHEMERAL_ENV, CSM),
+];
+
+/// The capability report of the site behind `infra`; `session_logs`
+/// tells whether the server archives session logs.
+pub fn capabilities(
+  infra: &InfraContext<'_>,
+  session_logs: bool,
+) -> SiteCapabilities {
+  let backend = infra.backend_kind();
+  let vault = infra.vault_base_url.is_some();
+  let k8s = infra.k8s_api_url.is_some();
+
+  let features = FEATURES
+    .iter()
+    .map(|(name, needs)| {
+      let mut missing = Vec::new();
+      if needs.csm && backend != "csm" {
+        missing.push(format!("not implemented for the {backend} backend"));
+      }
+      if needs.vault && !vault {
+        missing.push("no vault_base_url configured for the site".to_string());
+      }
+      if needs.k8s && !k8s {
+        missing.push("no k8s_api_url configured for the site".to_string());
+      }
+      if needs.session_logs && !session_logs {
+        missing.push(
+          "no [server.session_logs] configured on the server".to_string(),
+        );
+      }
+      SiteFeature {
+        name: (*name).to_string(),
+        supported: missing.is_empty(),
+        reason: (!missing.is_empty()).then(|| missing.join("; ")),
+      }
+    })
+    .collect();
+
+  SiteCapabilities {
+    site: infra.site_name.to_string(),
+    backend: backend.to_string(),
+    vault,
+    k8s,
+    features,
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::dispatcher::StaticBackendDispatcher;
+
+  fn report(
+    backend_type: &str,
+    vault_base_url: Option<&str>,
+    k8s_api_url: Option<&str>,
+    session_logs: bool,
+  ) -> SiteCapabilities {
+    let backend =
+      StaticBackendDispatcher::new(backend_type, "http://stub.invalid", b"")
+        .unwrap();
+    capabilities(
+      &InfraContext {
+        backend: &backend,
+        site_name: "alps",
+        shasta_base_url: "http://stub.invalid",
+        shasta_root_cert: b"",
+        vault_base_url,
+        gitea_base_url: "http://stub.invalid",
+        k8s_api_url,
+      },
+      session_logs,
+    )
+  }
+
+  fn supported(caps: &SiteCapabilities, name: &str) -> bool {
+    caps
+      .features
+      .iter()
+      .find(|f| f.name == name)
+      .is_some_and(|f| f.supported)
+  }
+
+  #[test]
+  fn csm_site_with_vault_and_k8s_supports_everything() {
+    let caps = report("csm", Some("http://vault"), Some("http://k8s"), true);
+    assert_eq!(caps.backend, "csm");
+    assert!(
+      caps
+        .features
+        .iter()
+        .all(|f| f.supported && f.reason.is_none())
+    );
+  }
+
+  #[test]
+  fn csm_site_without_k8s_loses_only_k8s_features() {
+    let caps = report("csm", Some("http://vault"), None, true);
+    assert!(supported(&caps, feature::SESSION_CREATE));
+    assert!(supported(&caps, feature::SESSION_LOG_ARCHIVE));
+    assert!(!supported(&caps, feature::SESSION_LOGS));
+    assert!(supported(&caps, feature::EPHEMERAL_ENV));
+    let console = caps
+      .features
+      .iter()
+      .find(|f| f.name == feature::CONSOLE)
+      .unwrap();
+    assert!(!console.supported);
+    assert_eq!(
+      console.reason.as_deref(),
+      Some("no k8s_api_url configured for the site")
+    );
+  }
+
+  #[test]
+  fn reruns_and_archived_logs_need_no_vault() {
+    let caps = report("csm", None, None, true);
+    assert!(!supported(&caps, feature::SESSION_CREATE));
+    assert!(supported(&caps, feature::SESSION_RERUN));
+    assert!(supported(&caps, feature::SESSION_LOG_ARCHIVE));
+    let caps = report("csm", None, None, false);
+    assert!(!supported(&caps, feature::SESSION_LOG_ARCHIVE));
+  }
+
+  #[test]
+  fn ochami_site_supports_none_of_the_csm_features() {
+    let caps = report("ochami", Some("http://vault"), Some("http://k8s"), true);
+    assert_eq!(caps.backend, "ochami");
+    assert!(!supported(&caps, feature::EPHEMERAL_ENV));
+    assert!(
+      caps
+        .features
+        .iter()
+        .all(|f| f.supported == (f.name == feature::SESSION_LOG_ARCHIVE))
+    );
+  }
+}
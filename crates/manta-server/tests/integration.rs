@@ -135,6 +135,10 @@ impl TestFixture {
       request_timeout: Duration::from_secs(60),
       shutdown_grace_period: Duration::from_secs(30),
       migrate_backup_root: None,
+      reservations: None,
+      hardware_snapshots: None,
+      session_logs: None,
+      ephemeral_envs: None,
     });
 
     let router = build_router(state);
@@ -32,8 +32,14 @@ use manta_server::server::{ServerState, SiteBackend, routes::build_router};
 /// Backend methods are never called because all non-health tests
 /// either fail at auth or at Axum's request extraction layer first.
 fn router() -> axum::Router {
+  router_for_backend("csm")
+}
+
+/// Same as `router()` with a `backend_type` (`csm` / `ochami`) stub.
+fn router_for_backend(backend_type: &str) -> axum::Router {
   let backend =
-    StaticBackendDispatcher::new("csm", "http://stub.invalid", b"").unwrap();
+    StaticBackendDispatcher::new(backend_type, "http://stub.invalid", b"")
+      .unwrap();
   let mut sites = std::collections::HashMap::new();
   sites.insert(
     "test".to_string(),
@@ -54,6 +60,10 @@ fn router() -> axum::Router {
     request_timeout: std::time::Duration::from_secs(60),
     shutdown_grace_period: std::time::Duration::from_secs(30),
     migrate_backup_root: None,
+    reservations: None,
+    hardware_snapshots: None,
+    session_logs: None,
+    ephemeral_envs: None,
   });
   build_router(state)
 }
@@ -83,6 +93,10 @@ fn router_with_vault() -> axum::Router {
     request_timeout: std::time::Duration::from_secs(60),
     shutdown_grace_period: std::time::Duration::from_secs(30),
     migrate_backup_root: None,
+    reservations: None,
+    hardware_snapshots: None,
+    session_logs: None,
+    ephemeral_envs: None,
   });
   build_router(state)
 }
@@ -247,6 +261,7 @@ async fn delete_health_returns_405() {
 async fn get_routes_reject_missing_bearer_token() {
   let routes = [
     "/v2/sessions",
+    "/v2/sessions/my-session/logs/archive",
     "/v2/configurations",
     "/v2/groups",
     "/v2/images",
@@ -256,6 +271,9 @@ async fn get_routes_reject_missing_bearer_token() {
     "/v2/redfish-endpoints",
     "/v2/clusters",
     "/v2/hardware-clusters",
+    "/v2/groups/prometheus-sd",
+    "/v2/ephemeral-env",
+    "/v2/sites/test/capabilities",
   ];
   for uri in routes {
     let resp = router().oneshot(get(uri)).await.unwrap();
@@ -273,7 +291,9 @@ async fn delete_routes_without_body_reject_missing_bearer_token() {
     "/v2/nodes/x3000c0s1b0n0",
     "/v2/groups/my-group",
     "/v2/sessions/my-session",
+    "/v2/sessions?status=complete",
     "/v2/redfish-endpoints/x3000c0s1b0",
+    "/v2/ephemeral-env/job-1",
   ];
   for uri in routes {
     let resp = router()
@@ -379,6 +399,11 @@ async fn post_routes_reject_invalid_bodies() {
       r#"{"host_expression":"x3000c0s1b0n0","target_type":"nodes"}"#,
     ),
     ("/v2/templates/my-template/sessions", r#"{"dry_run":false}"#),
+    // `ansible_verbosity` is a u8.
+    (
+      "/v2/sessions/my-session/rerun",
+      r#"{"ansible_verbosity":"high"}"#,
+    ),
     ("/v2/sat-file/configurations", "{}"),
     ("/v2/sat-file/images/cfs-session", "{}"),
     ("/v2/sat-file/images/stamp", "{}"),
@@ -419,6 +444,23 @@ async fn get_hardware_nodes_list_without_xnames_returns_400() {
   assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
 }
 
+#[tokio::test]
+async fn delete_sessions_without_filter_returns_400() {
+  let resp = router()
+    .oneshot(
+      Request::builder()
+        .method(Method::DELETE)
+        .uri("/v2/sessions?hsm_group=compute")
+        .header(header::AUTHORIZATION, "Bearer test-token")
+        .header("X-Manta-Site", "test")
+        .body(Body::empty())
+        .unwrap(),
+    )
+    .await
+    .unwrap();
+  assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
+}
+
 // ---------------------------------------------------------------------------
 // Optional-config endpoints return 501 when vault/k8s not configured
 // ---------------------------------------------------------------------------
@@ -432,6 +474,105 @@ async fn get_session_logs_without_k8s_config_returns_501() {
   assert_eq!(resp.status(), StatusCode::NOT_IMPLEMENTED);
 }
 
+#[tokio::test]
+async fn get_session_log_archive_without_config_returns_501() {
+  let resp = router()
+    .oneshot(get_auth("/v2/sessions/my-session/logs/archive"))
+    .await
+    .unwrap();
+  assert_eq!(resp.status(), StatusCode::NOT_IMPLEMENTED);
+}
+
+#[tokio::test]
+async fn get_reservations_without_reservations_config_returns_501() {
+  let resp = router()
+    .oneshot(get_auth("/v2/reservations"))
+    .await
+    .unwrap();
+  assert_eq!(resp.status(), StatusCode::NOT_IMPLEMENTED);
+}
+
+#[tokio::test]
+async fn hardware_snapshots_without_config_return_501() {
+  for req in [
+    get_auth("/v2/hardware-snapshots"),
+    get_auth("/v2/hardware-snapshots/3f2a"),
+    post_json("/v2/hardware-snapshots", r#"{"group":"compute"}"#),
+  ] {
+    let resp = router().oneshot(req).await.unwrap();
+    assert_eq!(resp.status(), StatusCode::NOT_IMPLEMENTED);
+  }
+}
+
+/// OpenCHAMI has no ephemeral environments: the backend refusal
+/// surfaces as 501, not the 400 `UnsupportedBackend` maps to elsewhere.
+#[tokio::test]
+async fn ephemeral_envs_on_ochami_return_501() {
+  use base64::prelude::*;
+  let header = BASE64_URL_SAFE_NO_PAD.encode(r#"{"alg":"none","typ":"JWT"}"#);
+  let payload = BASE64_URL_SAFE_NO_PAD
+    .encode(serde_json::json!({ "preferred_username": "jdoe" }).to_string());
+  let token = format!("{header}.{payload}.sig");
+  for (method, uri, body) in [
+    (Method::GET, "/v2/ephemeral-env", ""),
+    (Method::DELETE, "/v2/ephemeral-env/job-1", ""),
+    (Method::POST, "/v2/ephemeral-env", r#"{"image_id":"img-1"}"#),
+  ] {
+    let req = Request::builder()
+      .method(method)
+      .uri(uri)
+      .header(header::CONTENT_TYPE, "application/json")
+      .header(header::AUTHORIZATION, format!("Bearer {token}"))
+      .header("X-Manta-Site", "test")
+      .body(Body::from(body))
+      .unwrap();
+    let resp = router_for_backend("ochami").oneshot(req).await.unwrap();
+    assert_eq!(resp.status(), StatusCode::NOT_IMPLEMENTED, "{uri}");
+  }
+}
+
+/// The capability report reflects the backend kind, the site's
+/// optional Vault / k8s URLs and the server's session log archive
+/// without calling the backend.
+#[tokio::test]
+async fn site_capabilities_report_backend_and_config() {
+  let resp = router_for_backend("ochami")
+    .oneshot(get_auth("/v2/sites/test/capabilities"))
+    .await
+    .unwrap();
+  assert_eq!(resp.status(), StatusCode::OK);
+  let caps: serde_json::Value =
+    serde_json::from_str(&body_string(resp.into_body()).await).unwrap();
+  assert_eq!(caps["site"], "test");
+  assert_eq!(caps["backend"], "ochami");
+  assert_eq!(caps["vault"], false);
+  let features = caps["features"].as_array().unwrap();
+  assert!(!features.is_empty());
+  assert!(features.iter().all(|f| f["supported"] == false));
+
+  let resp = router_with_vault()
+    .oneshot(get_auth("/v2/sites/test/capabilities"))
+    .await
+    .unwrap();
+  let caps: serde_json::Value =
+    serde_json::from_str(&body_string(resp.into_body()).await).unwrap();
+  let features = caps["features"].as_array().unwrap();
+  // No `[server.session_logs]` in the test config.
+  for f in features {
+    let archive = f["name"] == "session_log_archive";
+    assert_eq!(f["supported"], !archive, "{f}");
+  }
+}
+
+#[tokio::test]
+async fn site_capabilities_for_unknown_site_return_404() {
+  let resp = router()
+    .oneshot(get_auth("/v2/sites/nope/capabilities"))
+    .await
+    .unwrap();
+  assert_eq!(resp.status(), StatusCode::NOT_FOUND);
+}
+
 // ---------------------------------------------------------------------------
 // Route existence — authenticated requests must not return 404 or 405
 // ---------------------------------------------------------------------------
@@ -468,6 +609,7 @@ async fn all_get_routes_are_registered() {
     "/v2/groups/available",
     "/v2/groups/nodes",
     "/v2/groups/hardware",
+    "/v2/groups/prometheus-sd",
     "/v2/images",
     "/v2/templates",
     "/v2/boot-parameters",
@@ -478,9 +620,12 @@ async fn all_get_routes_are_registered() {
     "/v2/hardware-nodes-list",
     "/v2/power/transitions/abcd-1234",
     "/v2/sessions/my-session/logs",
+    "/v2/sessions/my-session/logs/archive",
     "/v2/health",
     "/v2/nodes/x3000c0s1b0n0/console",
     "/v2/sessions/my-session/console",
+    "/v2/ephemeral-env",
+    "/v2/sites/test/capabilities",
   ] {
     assert_route_exists(Method::GET, uri).await;
   }
@@ -490,6 +635,7 @@ async fn all_get_routes_are_registered() {
 async fn all_post_routes_are_registered() {
   for uri in &[
     "/v2/sessions",
+    "/v2/sessions/my-session/rerun",
     "/v2/nodes",
     "/v2/groups",
     "/v2/groups/test/members",
@@ -527,6 +673,8 @@ async fn all_delete_routes_are_registered() {
     "/v2/kernel-parameters",
     "/v2/redfish-endpoints/x3000c0s1b0",
     "/v2/sessions/my-session",
+    "/v2/sessions",
+    "/v2/ephemeral-env/job-1",
     "/v2/images",
     "/v2/configurations",
     "/v2/hardware-clusters/my-cluster/members",
@@ -0,0 +1,278 @@
+//! Hostlist compression: the inverse of the hostlist expressions the
+//! server parses.
+//!
+//! [`compress`] folds a set of node names into the shortest
+//! bracket notation this simple scheme finds, dimension by dimension:
+//! 64 blade xnames become `x1000c[0-3]s[0-7]b0n[0-1]` and 128 NIDs
+//! become `nid[000001-000128]`. Zero padding is kept (`nid000007`
+//! stays a 6-digit number), and names that cannot be folded are passed
+//! through unchanged after the folded terms.
+//!
+//! The CLI uses it for node lists in tables and confirmation prompts;
+//! `--expand` turns it off there.
+
+use std::collections::{BTreeMap, BTreeSet};
+
+/// One numeric field of a node name. Ordered by padding first so a
+/// range never mixes `07` and `7`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+struct Index {
+  /// Zero-padded width, or 0 for a number written without leading
+  /// zeros.
+  pad: usize,
+  value: u64,
+}
+
+impl Index {
+  fn parse(digits: &str) -> Option<Self> {
+    let value = digits.parse().ok()?;
+    let pad = if digits.len() > 1 && digits.starts_with('0') {
+      digits.len()
+    } else {
+      0
+    };
+    Some(Self { pad, value })
+  }
+
+  /// `self` followed directly by `next` in one range.
+  fn precedes(self, next: Self) -> bool {
+    self.pad == next.pad && self.value.checked_add(1) == Some(next.value)
+  }
+
+  fn render(self) -> String {
+    format!("{:0pad$}", self.value, pad = self.pad)
+  }
+}
+
+/// A name split into its text fields and the numbers between them,
+/// e.g. `x1000c0s7b0n1` → `["x", "c", "s", "b", "n", ""]` and
+/// `[1000, 0, 7, 0, 1]`. `None` for names without a number, holding
+/// hostlist syntax, or with numbers too long to fold.
+fn split_name(name: &str) -> Option<(Vec<String>, Vec<Index>)> {
+  if name.is_empty()
+    || !name
+      .chars()
+      .all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.'))
+  {
+    return None;
+  }
+  let mut texts = vec![String::new()];
+  let mut indexes = Vec::new();
+  let mut digits = String::new();
+  for c in name.chars() {
+    if c.is_ascii_digit() {
+      digits.push(c);
+      continue;
+    }
+    if !digits.is_empty() {
+      indexes.push(Index::parse(&digits)?);
+      digits.clear();
+      texts.push(String::new());
+    }
+    texts.last_mut()?.push(c);
+  }
+  if !digits.is_empty() {
+    indexes.push(Index::parse(&digits)?);
+    texts.push(String::new());
+  }
+  (!indexes.is_empty()).then_some((texts, indexes))
+}
+
+/// `[0-3,7]`, or the bare number for a single index.
+fn render_set(set: &BTreeSet<Index>) -> String {
+  let indexes: Vec<Index> = set.iter().copied().collect();
+  if let [only] = indexes.as_slice() {
+    return only.render();
+  }
+  let mut ranges: Vec<String> = Vec::new();
+  let mut start = 0;
+  for i in 1..=indexes.len() {
+    if i < indexes.len() && indexes[i - 1].precedes(indexes[i]) {
+      continue;
+    }
+    ranges.push(if start == i - 1 {
+      indexes[start].render()
+    } else {
+      format!("{}-{}", indexes[start].render(), indexes[i - 1].render())
+    });
+    start = i;
+  }
+  format!("[{}]", ranges.join(","))
+}
+
+/// Fold points sharing a text pattern into terms, one dimension at a
+/// time starting from the last: points that agree on every other
+/// dimension merge their values in this one.
+fn fold(points: BTreeSet<Vec<Index>>) -> Vec<Vec<BTreeSet<Index>>> {
+  let dims = points.first().map_or(0, Vec::len);
+  let mut terms: Vec<Vec<BTreeSet<Index>>> = points
+    .into_iter()
+    .map(|point| point.into_iter().map(|i| BTreeSet::from([i])).collect())
+    .collect();
+  for dim in (0..dims).rev() {
+    let mut merged: BTreeMap<Vec<BTreeSet<Index>>, BTreeSet<Index>> =
+      BTreeMap::new();
+    for mut term in terms {
+      let values = term.remove(dim);
+      merged.entry(term).or_default().extend(values);
+    }
+    terms = merged
+      .into_iter()
+      .map(|(mut term, values)| {
+        term.insert(dim, values);
+        term
+      })
+      .collect();
+  }
+  terms.sort_by_key(|term| {
+    term
+      .iter()
+      .filter_map(|set| set.first().copied())
+      .collect::<Vec<_>>()
+  });
+  terms
+}
+
+/// Compress node names into a comma-separated hostlist expression.
+/// Duplicates collapse; the result lists folded terms in name order,
+/// then any name that could not be folded.
+///
+/// ```
+/// use manta_shared::common::hostlist::compress;
+///
+/// let nids: Vec<String> = (1..=128).map(|n| format!("nid{n:06}")).collect();
+/// assert_eq!(compress(&nids), "nid[000001-000128]");
+/// assert_eq!(
+///   compress(["x1000c0s0b0n0", "x1000c0s0b0n1", "x1000c0s1b0n0"]),
+///   "x1000c0s0b0n[0-1],x1000c0s1b0n0"
+/// );
+/// ```
+pub fn compress<I, S>(names: I) -> String
+where
+  I: IntoIterator<Item = S>,
+  S: AsRef<str>,
+{
+  let mut patterns: BTreeMap<Vec<String>, BTreeSet<Vec<Index>>> =
+    BTreeMap::new();
+  let mut literals: BTreeSet<String> = BTreeSet::new();
+  for name in names {
+    let name = name.as_ref().trim();
+    match split_name(name) {
+      Some((texts, indexes)) => {
+        patterns.entry(texts).or_default().insert(indexes);
+      }
+      None if !name.is_empty() => {
+        literals.insert(name.to_string());
+      }
+      None => {}
+    }
+  }
+
+  let mut out: Vec<String> = Vec::new();
+  for (texts, points) in patterns {
+    for term in fold(points) {
+      let mut rendered = texts[0].clone();
+      for (set, text) in term.iter().zip(&texts[1..]) {
+        rendered.push_str(&render_set(set));
+        rendered.push_str(text);
+      }
+      out.push(rendered);
+    }
+  }
+  out.extend(literals);
+  out.join(",")
+}
+
+/// Split a hostlist expression on the commas outside brackets, so
+/// `nid[001,003],x1000c0s0b0n0` yields two terms.
+fn split_terms(expression: &str) -> Vec<&str> {
+  let mut terms = Vec::new();
+  let mut depth = 0usize;
+  let mut start = 0;
+  for (i, c) in expression.char_indices() {
+    match c {
+      '[' => depth += 1,
+      ']' => depth = depth.saturating_sub(1),
+      ',' if depth == 0 => {
+        terms.push(&expression[start..i]);
+        start = i + 1;
+      }
+      _ => {}
+    }
+  }
+  terms.push(&expression[start..]);
+  terms
+}
+
+/// Compress a user-supplied hosts expression for display: its plain
+/// names are folded with [`compress`], while terms already in bracket
+/// notation (or any other syntax) are kept as written.
+pub fn compress_expression(expression: &str) -> String {
+  compress(split_terms(expression))
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn blade_xnames() -> Vec<String> {
+    let mut names = Vec::new();
+    for c in 0..4 {
+      for s in 0..8 {
+        for n in 0..2 {
+          names.push(format!("x1000c{c}s{s}b0n{n}"));
+        }
+      }
+    }
+    names
+  }
+
+  #[test]
+  fn full_cabinet_folds_into_one_term() {
+    assert_eq!(compress(blade_xnames()), "x1000c[0-3]s[0-7]b0n[0-1]");
+  }
+
+  #[test]
+  fn padded_nids_keep_their_width() {
+    let nids: Vec<String> = (1..=128).map(|n| format!("nid{n:06}")).collect();
+    assert_eq!(compress(&nids), "nid[000001-000128]");
+    assert_eq!(
+      compress(["nid000009", "nid000010", "nid000012"]),
+      "nid[000009-000010,000012]"
+    );
+  }
+
+  #[test]
+  fn unpadded_numbers_range_across_digit_counts() {
+    assert_eq!(compress(["s9", "s10", "s11"]), "s[9-11]");
+    assert_eq!(compress(["n07", "n7"]), "n[7,07]");
+  }
+
+  #[test]
+  fn ragged_sets_split_into_several_terms() {
+    let mut names = blade_xnames();
+    names.retain(|n| n != "x1000c3s7b0n1");
+    let compressed = compress(&names);
+    assert!(compressed.contains("x1000c3s7b0n0"), "{compressed}");
+    assert!(!compressed.contains("x1000c3s7b0n1"), "{compressed}");
+    assert!(compressed.split(',').count() < 10, "{compressed}");
+  }
+
+  #[test]
+  fn duplicates_and_unfoldable_names_are_kept_once() {
+    assert_eq!(
+      compress(["nid000002", "login", "nid000001", "nid000002", "login"]),
+      "nid[000001-000002],login"
+    );
+    assert_eq!(compress(Vec::<String>::new()), "");
+  }
+
+  #[test]
+  fn expressions_keep_bracket_terms() {
+    assert_eq!(
+      compress_expression("x1000c0s0b0n0, x1000c0s0b0n1,nid[001,003]"),
+      "x1000c0s0b0n[0-1],nid[001,003]"
+    );
+    assert_eq!(compress_expression("x1000c0s0b0n0"), "x1000c0s0b0n0");
+  }
+}
@@ -1,11 +1,13 @@
 //! Behavioural helpers shared by `manta-cli` and `manta-server`.
 //!
-//! The three submodules are intentionally narrow:
+//! The submodules are intentionally narrow:
 //!
 //! - [`config`] — locates and parses `cli.toml` / `server.toml`,
 //!   honouring `MANTA_CLI_CONFIG` / `MANTA_SERVER_CONFIG` and merging
 //!   `MANTA_*`-prefixed environment overrides. Returns an untyped
 //!   `::config::Config` so each binary owns its own typed schema.
+//! - [`hostlist`] — [`hostlist::compress`] folds node names into
+//!   bracket notation for display.
 //! - [`error`] — the [`error::MantaError`] enum returned by every
 //!   fallible helper in this crate; the server bridges it to its
 //!   `BackendError` at call sites.
@@ -58,5 +60,6 @@ pub fn parse_ims_timestamp(raw: &str) -> Option<chrono::NaiveDateTime> {
 
 pub mod config;
 pub mod error;
+pub mod hostlist;
 pub mod jwt_ops;
 pub mod log_ops;
@@ -0,0 +1,479 @@
+//! Summaries of the Ansible output in a CFS session log: which hosts
+//! failed which task in which layer, and the per-host `PLAY RECAP`
+//! counts.
+//!
+//! [`AnsibleLogParser`] is fed the log one line at a time, so it works
+//! on a live SSE stream as well as on an archived log, and tolerates
+//! Kubernetes timestamps at the start of each line. A new layer starts
+//! at each `Running <playbook> from repo <url>` line the CFS Ansible
+//! container prints, or — for logs without those lines — at the first
+//! `PLAY` after a `PLAY RECAP`.
+//!
+//! The CLI renders the result (`manta log --summary`,
+//! `manta get sessions --failures`); its JSON form is the
+//! [`SessionLogSummary`] serialisation.
+
+use chrono::DateTime;
+use serde::{Deserialize, Serialize};
+
+/// Lines a multi-line (pretty-printed) task result may span before
+/// the parser gives up on it and keeps the raw text.
+const MAX_RESULT_LINES: usize = 1000;
+
+/// Ansible summary of one CFS session log.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SessionLogSummary {
+  /// CFS session name.
+  pub session_name: String,
+  /// Layers in log order.
+  pub layers: Vec<LayerSummary>,
+}
+
+/// One Ansible run (one CFS configuration layer).
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LayerSummary {
+  /// Position of the layer in the log, from 0.
+  pub index: usize,
+  /// Playbook run, when the log names it.
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub playbook: Option<String>,
+  /// Repository clone URL, when the log names it.
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub repo: Option<String>,
+  /// Plays in run order.
+  pub plays: Vec<String>,
+  /// `PLAY RECAP` rows; empty when the run did not reach its recap.
+  pub recap: Vec<HostRecap>,
+  /// Failed or unreachable task results, in log order.
+  pub failures: Vec<TaskFailure>,
+}
+
+/// One `PLAY RECAP` row.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct HostRecap {
+  /// Inventory host (an xname for node sessions).
+  pub host: String,
+  /// Tasks that ran without change.
+  pub ok: u32,
+  /// Tasks that changed the host.
+  pub changed: u32,
+  /// Tasks that could not reach the host.
+  pub unreachable: u32,
+  /// Tasks that failed.
+  pub failed: u32,
+  /// Tasks skipped by a condition.
+  pub skipped: u32,
+  /// Failures recovered by a `rescue` block.
+  pub rescued: u32,
+  /// Failures ignored by `ignore_errors`.
+  pub ignored: u32,
+}
+
+/// A task that failed on, or could not reach, one host.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TaskFailure {
+  /// Inventory host.
+  pub host: String,
+  /// Play the task belongs to.
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub play: Option<String>,
+  /// Task name as Ansible printed it (`role : task`).
+  pub task: String,
+  /// Loop item, for failures inside a loop.
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub item: Option<String>,
+  /// `true` for `UNREACHABLE!` results.
+  pub unreachable: bool,
+  /// `true` when the task had `ignore_errors` (`...ignoring`).
+  pub ignored: bool,
+  /// Error message from the result's `msg` (or `stderr`, `reason`).
+  pub message: String,
+}
+
+impl LayerSummary {
+  /// `repo/playbook` (repository name without `.git`), `playbook`, or
+  /// `layer N` when the log does not name it.
+  pub fn label(&self) -> String {
+    let repo = self.repo.as_deref().map(|url| repo_name(url).to_string());
+    match (repo, self.playbook.as_deref()) {
+      (Some(repo), Some(playbook)) => format!("{repo}/{playbook}"),
+      (None, Some(playbook)) => playbook.to_string(),
+      (Some(repo), None) => repo,
+      (None, None) => format!("layer {}", self.index),
+    }
+  }
+}
+
+impl SessionLogSummary {
+  /// Failures that were not ignored, with their layer.
+  pub fn failures(
+    &self,
+  ) -> impl Iterator<Item = (&LayerSummary, &TaskFailure)> {
+    self.layers.iter().flat_map(|layer| {
+      layer
+        .failures
+        .iter()
+        .filter(|failure| !failure.ignored)
+        .map(move |failure| (layer, failure))
+    })
+  }
+
+  /// `true` when some task failed (not ignored) or a host was
+  /// unreachable.
+  pub fn has_failures(&self) -> bool {
+    self.failures().next().is_some()
+  }
+}
+
+/// A failure whose result spans several lines.
+struct PendingResult {
+  failure: TaskFailure,
+  text: String,
+  lines: usize,
+}
+
+/// Incremental parser over a CFS session log.
+#[derive(Default)]
+pub struct AnsibleLogParser {
+  layers: Vec<LayerSummary>,
+  play: Option<String>,
+  task: Option<String>,
+  in_recap: bool,
+  pending: Option<PendingResult>,
+}
+
+/// Drop the Kubernetes timestamp a line may start with.
+fn strip_timestamp(line: &str) -> &str {
+  match line.split_once(' ') {
+    Some((stamp, rest)) if DateTime::parse_from_rfc3339(stamp).is_ok() => rest,
+    _ => line,
+  }
+}
+
+/// `(playbook, repo URL)` when `line` is the `Running <playbook> from
+/// repo <url>` line that starts a configuration layer. `line` may carry
+/// a Kubernetes timestamp.
+pub fn layer_start(line: &str) -> Option<(&str, &str)> {
+  let rest = strip_timestamp(line).trim().strip_prefix("Running ")?;
+  let (playbook, repo) = rest.split_once(" from repo ")?;
+  Some((playbook.trim(), repo.trim()))
+}
+
+/// Name of the repository at `url`, without `.git`.
+pub fn repo_name(url: &str) -> &str {
+  let name = url.trim_end_matches('/').rsplit('/').next().unwrap_or(url);
+  name.strip_suffix(".git").unwrap_or(name)
+}
+
+/// Text between `prefix[` and the last `]` of a header line, e.g. the
+/// task name of `TASK [role : name] ****`.
+fn bracketed<'a>(line: &'a str, prefix: &str) -> Option<&'a str> {
+  let rest = line.strip_prefix(prefix)?.strip_prefix('[')?;
+  let end = rest.rfind(']')?;
+  Some(&rest[..end])
+}
+
+/// The message of a task result: `msg`, else `stderr`, else `reason`,
+/// else the whole result. Non-JSON results are returned trimmed.
+fn result_message(text: &str) -> String {
+  let Ok(value) = serde_json::from_str::<serde_json::Value>(text) else {
+    return text.trim().to_string();
+  };
+  for key in ["msg", "stderr", "reason"] {
+    match value.get(key) {
+      Some(serde_json::Value::String(s)) if !s.is_empty() => {
+        return s.trim().to_string();
+      }
+      Some(serde_json::Value::Null | serde_json::Value::String(_)) | None => {}
+      Some(other) => return other.to_string(),
+    }
+  }
+  value.to_string()
+}
+
+/// `host : ok=1 changed=0 …` → a [`HostRecap`].
+fn parse_recap_row(line: &str) -> Option<HostRecap> {
+  let (host, counts) = line.split_once(" : ")?;
+  let mut recap = HostRecap {
+    host: host.trim().to_string(),
+    ..HostRecap::default()
+  };
+  let mut seen = false;
+  for field in counts.split_whitespace() {
+    let (key, value) = field.split_once('=')?;
+    let value: u32 = value.parse().ok()?;
+    let slot = match key {
+      "ok" => &mut recap.ok,
+      "changed" => &mut recap.changed,
+      "unreachable" => &mut recap.unreachable,
+      "failed" => &mut recap.failed,
+      "skipped" => &mut recap.skipped,
+      "rescued" => &mut recap.rescued,
+      "ignored" => &mut recap.ignored,
+      _ => continue,
+    };
+    *slot = value;
+    seen = true;
+  }
+  seen.then_some(recap)
+}
+
+impl AnsibleLogParser {
+  /// An empty parser.
+  pub fn new() -> Self {
+    Self::default()
+  }
+
+  fn start_layer(&mut self, playbook: Option<String>, repo: Option<String>) {
+    self.layers.push(LayerSummary {
+      index: self.layers.len(),
+      playbook,
+      repo,
+      ..LayerSummary::default()
+    });
+    self.play = None;
+    self.task = None;
+    self.in_recap = false;
+  }
+
+  fn layer(&mut self) -> &mut LayerSummary {
+    if self.layers.is_empty() {
+      self.start_layer(None, None);
+    }
+    self.layers.last_mut().expect("layer just pushed")
+  }
+
+  fn record(&mut self, failure: TaskFailure) {
+    self.layer().failures.push(failure);
+  }
+
+  /// `fatal: [host]: FAILED! => {…}`, `fatal: [host]: UNREACHABLE! =>
+  /// {…}` or `failed: [host] (item=x) => {…}`.
+  fn failure_line(&mut self, line: &str) -> bool {
+    let Some(rest) = line
+      .strip_prefix("fatal: [")
+      .or_else(|| line.strip_prefix("failed: ["))
+    else {
+      return false;
+    };
+    let Some((host, rest)) = rest.split_once(']') else {
+      return false;
+    };
+    let (head, result) = rest.split_once("=>").unwrap_or((rest, ""));
+    let item = head
+      .split_once("(item=")
+      .and_then(|(_, item)| item.rsplit_once(')'))
+      .map(|(item, _)| item.to_string());
+    let failure = TaskFailure {
+      host: host.to_string(),
+      play: self.play.clone(),
+      task: self.task.clone().unwrap_or_default(),
+      item,
+      unreachable: head.contains("UNREACHABLE!"),
+      ignored: false,
+      message: String::new(),
+    };
+    let result = result.trim();
+    if result.starts_with('{')
+      && serde_json::from_str::<serde_json::Value>(result).is_err()
+    {
+      self.pending = Some(PendingResult {
+        failure,
+        text: result.to_string(),
+        lines: 1,
+      });
+    } else {
+      self.record(TaskFailure {
+        message: result_message(result),
+        ..failure
+      });
+    }
+    true
+  }
+
+  /// Continue a multi-line result; `true` while the line belonged to
+  /// it.
+  fn continue_pending(&mut self, line: &str) -> bool {
+    let Some(mut pending) = self.pending.take() else {
+      return false;
+    };
+    pending.text.push('\n');
+    pending.text.push_str(line);
+    pending.lines += 1;
+    let closes = line.starts_with('}')
+      && serde_json::from_str::<serde_json::Value>(&pending.text).is_ok();
+    if closes || pending.lines >= MAX_RESULT_LINES {
+      let message = result_message(&pending.text);
+      self.record(TaskFailure {
+        message,
+        ..pending.failure
+      });
+    } else {
+      self.pending = Some(pending);
+    }
+    true
+  }
+
+  /// Feed one log line.
+  pub fn push_line(&mut self, line: &str) {
+    let line = strip_timestamp(line.trim_end_matches(['\r', '\n']));
+    if self.continue_pending(line) {
+      return;
+    }
+    let trimmed = line.trim();
+    if self.failure_line(trimmed) {
+      return;
+    }
+
+    if let Some((playbook, repo)) = layer_start(trimmed) {
+      self.start_layer(Some(playbook.to_string()), Some(repo.to_string()));
+    } else if trimmed.starts_with("PLAY RECAP") {
+      self.layer();
+      self.in_recap = true;
+    } else if let Some(play) = bracketed(trimmed, "PLAY ") {
+      if self.in_recap {
+        self.start_layer(None, None);
+      }
+      let play = play.to_string();
+      self.layer().plays.push(play.clone());
+      self.play = Some(play);
+      self.task = None;
+    } else if let Some(task) = bracketed(trimmed, "TASK ")
+      .or_else(|| bracketed(trimmed, "RUNNING HANDLER "))
+    {
+      self.task = Some(task.to_string());
+      self.in_recap = false;
+    } else if trimmed == "...ignoring" {
+      if let Some(last) = self.layer().failures.last_mut() {
+        last.ignored = true;
+      }
+    } else if self.in_recap
+      && let Some(recap) = parse_recap_row(trimmed)
+    {
+      self.layer().recap.push(recap);
+    }
+  }
+
+  /// Finish parsing and return the summary of `session_name`.
+  pub fn finish(mut self, session_name: &str) -> SessionLogSummary {
+    if let Some(pending) = self.pending.take() {
+      let message = result_message(&pending.text);
+      self.record(TaskFailure {
+        message,
+        ..pending.failure
+      });
+    }
+    SessionLogSummary {
+      session_name: session_name.to_string(),
+      layers: self.layers,
+    }
+  }
+}
+
+/// Summarise a complete log.
+pub fn summarize<'a>(
+  session_name: &str,
+  lines: impl IntoIterator<Item = &'a str>,
+) -> SessionLogSummary {
+  let mut parser = AnsibleLogParser::new();
+  for line in lines {
+    parser.push_line(line);
+  }
+  parser.finish(session_name)
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  const LOG: &str = r#"Running site.yml from repo https://api-gw-service-nmn.local/vcs/cray/csm-config-management.git
+
+PLAY [Compute nodes] ***********************************************************
+
+TASK [Gathering Facts] *********************************************************
+ok: [x1000c0s0b0n0]
+fatal: [x1000c0s0b0n1]: UNREACHABLE! => {"changed": false, "msg": "Failed to connect to the host via ssh", "unreachable": true}
+
+TASK [csm.packages : Install packages] *****************************************
+failed: [x1000c0s0b0n0] (item=vim) => {"ansible_loop_var": "item", "changed": false, "item": "vim", "msg": "No package matching 'vim' found available"}
+fatal: [x1000c0s0b0n0]: FAILED! => {"changed": false, "msg": "One or more items failed"}
+
+PLAY RECAP *********************************************************************
+x1000c0s0b0n0              : ok=1    changed=0    unreachable=0    failed=1    skipped=0    rescued=0    ignored=0
+x1000c0s0b0n1              : ok=0    changed=0    unreachable=1    failed=0    skipped=0    rescued=0    ignored=0
+
+Running cos.yml from repo https://api-gw-service-nmn.local/vcs/cray/cos-config-management.git
+
+PLAY [Compute nodes] ***********************************************************
+
+TASK [cos : Optional check] ****************************************************
+2024-05-01T10:00:00.123456789Z fatal: [x1000c0s0b0n0]: FAILED! => {"changed": false, "msg": "not needed"}
+...ignoring
+
+TASK [cos : Configure] *********************************************************
+fatal: [x1000c0s0b0n0]: FAILED! => {
+    "changed": false,
+    "rc": 1,
+    "stderr": "configure: error: missing header\n"
+}
+"#;
+
+  #[test]
+  fn layers_failures_and_recap_are_tracked() {
+    let summary = summarize("batcher-1", LOG.lines());
+    assert_eq!(summary.layers.len(), 2);
+
+    let first = &summary.layers[0];
+    assert_eq!(first.label(), "csm-config-management/site.yml");
+    assert_eq!(first.plays, vec!["Compute nodes".to_string()]);
+    assert_eq!(first.recap.len(), 2);
+    assert_eq!(first.recap[1].unreachable, 1);
+    assert_eq!(first.failures.len(), 3);
+    assert!(first.failures[0].unreachable);
+    assert_eq!(first.failures[0].task, "Gathering Facts");
+    assert_eq!(first.failures[1].item.as_deref(), Some("vim"));
+    assert_eq!(
+      first.failures[1].message,
+      "No package matching 'vim' found available"
+    );
+
+    let second = &summary.layers[1];
+    assert!(second.failures[0].ignored);
+    assert_eq!(second.failures[1].task, "cos : Configure");
+    assert_eq!(
+      second.failures[1].message,
+      "configure: error: missing header"
+    );
+    assert!(second.recap.is_empty());
+  }
+
+  #[test]
+  fn ignored_failures_do_not_count() {
+    let summary = summarize("batcher-1", LOG.lines());
+    let failures: Vec<_> = summary.failures().collect();
+    assert_eq!(failures.len(), 4);
+    assert!(failures.iter().all(|(_, f)| !f.ignored));
+    assert!(summary.has_failures());
+  }
+
+  #[test]
+  fn logs_without_layer_markers_split_on_recap() {
+    let log = "PLAY [a] ***\nTASK [t] ***\nok: [h]\nPLAY RECAP ***\n\
+               h : ok=1 changed=0 unreachable=0 failed=0\n\
+               PLAY [b] ***\nTASK [u] ***\nok: [h]\n";
+    let summary = summarize("s", log.lines());
+    assert_eq!(summary.layers.len(), 2);
+    assert_eq!(summary.layers[0].label(), "layer 0");
+    assert_eq!(summary.layers[1].plays, vec!["b".to_string()]);
+    assert!(!summary.has_failures());
+  }
+
+  #[test]
+  fn non_json_results_keep_their_text() {
+    let summary = summarize(
+      "s",
+      ["TASK [t] ***", "fatal: [h]: FAILED! => not json at all"],
+    );
+    assert_eq!(summary.layers[0].failures[0].message, "not json at all");
+  }
+}
@@ -1,10 +1,22 @@
 //! HTTP request/response bodies and CLI-built parameter structs for
 //! the HSM group endpoints (`/v2/groups`,
-//! `/v2/groups/{name}/members`).
+//! `/v2/groups/{name}/members`, `/v2/groups/prometheus-sd`).
+
+use std::collections::BTreeMap;
 
 use serde::{Deserialize, Serialize};
 use utoipa::ToSchema;
 
+/// One entry of `GET /v2/groups/prometheus-sd`, in the shape
+/// Prometheus' `http_sd_config` expects.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+pub struct PrometheusTargetGroup {
+  /// `host:port` scrape targets sharing `labels`.
+  pub targets: Vec<String>,
+  /// Labels attached to every target in the entry.
+  pub labels: BTreeMap<String, String>,
+}
+
 /// Request body for `POST /v2/groups/{name}/members`.
 ///
 /// Paired with [`AddNodesToGroupResponse`] on success.
@@ -1,5 +1,11 @@
 //! Parameters for `GET /groups/hardware` (and the deprecated
-//! `/hardware-clusters` alias) and `GET /hardware-nodes-list`.
+//! `/hardware-clusters` alias) and `GET /hardware-nodes-list`, plus
+//! the hardware snapshot bodies for `/v2/hardware-snapshots`.
+
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
 
 /// Typed parameters for fetching cluster hardware inventory.
 pub struct GetHardwareClusterParams {
@@ -29,3 +35,83 @@ pub struct GetHardwareNodesListParams {
   /// Hosts expression (xnames, NIDs, or hostlist notation).
   pub host_expression: String,
 }
+
+/// Request body for `POST /v2/hardware-snapshots`.
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct TakeHardwareSnapshotRequest {
+  /// Group whose members are inventoried.
+  pub group: String,
+}
+
+/// Normalised hardware of one node, as recorded in a
+/// [`HardwareSnapshot`]. Built from a `NodeSummary` by
+/// [`crate::types::hardware_diff::node_hardware`].
+#[derive(
+  Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize, ToSchema,
+)]
+pub struct NodeHardware {
+  /// Processor count per model string.
+  pub processors: BTreeMap<String, usize>,
+  /// Accelerator count per model string.
+  pub accelerators: BTreeMap<String, usize>,
+  /// Total memory capacity over every DIMM, in MiB.
+  pub memory_mib: usize,
+  /// Number of DIMMs.
+  pub dimms: usize,
+  /// Number of high-speed network NICs.
+  pub hsn_nics: usize,
+}
+
+/// Per-node hardware inventory of a group at one point in time,
+/// stored by `POST /v2/hardware-snapshots` or written locally by
+/// `manta snapshot hardware --local`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+pub struct HardwareSnapshot {
+  /// Server-assigned identifier (`local` for a CLI-side snapshot).
+  pub id: String,
+  /// Site the inventory was read from.
+  pub site: String,
+  /// Group whose members were inventoried.
+  pub group: String,
+  /// RFC 3339 instant the snapshot was taken (UTC).
+  pub taken_at: String,
+  /// User who took the snapshot, or the watcher's service account.
+  pub taken_by: String,
+  /// Hardware per node xname.
+  pub nodes: BTreeMap<String, NodeHardware>,
+  /// Members whose inventory could not be read; left out of `nodes`
+  /// so a failed read is not mistaken for removed hardware.
+  #[serde(default, skip_serializing_if = "Vec::is_empty")]
+  pub unreadable: Vec<String>,
+}
+
+/// Listing entry for a stored snapshot (`GET /v2/hardware-snapshots`);
+/// the node inventory is fetched by id.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+pub struct HardwareSnapshotInfo {
+  /// Server-assigned identifier.
+  pub id: String,
+  /// Site the inventory was read from.
+  pub site: String,
+  /// Group whose members were inventoried.
+  pub group: String,
+  /// RFC 3339 instant the snapshot was taken (UTC).
+  pub taken_at: String,
+  /// User who took the snapshot, or the watcher's service account.
+  pub taken_by: String,
+  /// Number of nodes with a readable inventory.
+  pub node_count: usize,
+}
+
+impl From<&HardwareSnapshot> for HardwareSnapshotInfo {
+  fn from(snapshot: &HardwareSnapshot) -> Self {
+    Self {
+      id: snapshot.id.clone(),
+      site: snapshot.site.clone(),
+      group: snapshot.group.clone(),
+      taken_at: snapshot.taken_at.clone(),
+      taken_by: snapshot.taken_by.clone(),
+      node_count: snapshot.nodes.len(),
+    }
+  }
+}
@@ -2,6 +2,8 @@
 //! `POST /v2/hardware-clusters/{target}/*` and
 //! `DELETE /v2/hardware-clusters/{target}/members` endpoints.
 
+use std::collections::BTreeMap;
+
 use serde::{Deserialize, Serialize};
 use utoipa::ToSchema;
 
@@ -72,6 +74,24 @@ pub struct ApplyHwConfigurationRequest {
   /// membership.
   #[serde(default)]
   pub dry_run: bool,
+  /// Optional locality objective: prefer filling whole blades,
+  /// chassis or cabinets (from the xname hierarchy) over scattering
+  /// the selection. Absent means scarcity scoring alone.
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub locality: Option<HwLocality>,
+  /// Pin selection algorithm. Defaults to `Greedy`; ignored when
+  /// unpinning.
+  #[serde(default)]
+  pub solver: HwSolver,
+  /// Return an `explanation` ([`HwPlanExplanation`]) alongside the
+  /// memberships.
+  #[serde(default)]
+  pub explain: bool,
+  /// RFC 3339 instant at which the pin expires. Pin mode only: the
+  /// server records a [`HwReservation`] for the nodes this pin adds
+  /// and moves them back to the parent once the instant passes.
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub expires_at: Option<String>,
 }
 
 fn default_true() -> bool {
@@ -93,3 +113,119 @@ pub enum HwClusterMode {
   /// back to the parent cluster.
   Unpin,
 }
+
+/// Algorithm that picks the nodes for a pin.
+#[derive(
+  Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize, ToSchema,
+)]
+#[serde(rename_all = "lowercase")]
+pub enum HwSolver {
+  /// Scarcity-weighted best-candidate loop: fast, keeps existing
+  /// target members first, may take more nodes than needed.
+  #[default]
+  Greedy,
+  /// Branch-and-bound search for the plan that moves the fewest
+  /// nodes. Falls back to `Greedy` when its time budget runs out; the
+  /// response's `solver` field says which one produced the plan.
+  Exact,
+}
+
+/// Level of the xname hierarchy (`x<cabinet>c<chassis>s<slot>b<bmc>n<node>`)
+/// the pin/unpin selector tries to keep whole.
+#[derive(
+  Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize, ToSchema,
+)]
+#[serde(rename_all = "lowercase")]
+pub enum HwLocality {
+  /// Nodes sharing a compute blade (`x1000c0s1`).
+  Blade,
+  /// Nodes sharing a chassis (`x1000c0`).
+  Chassis,
+  /// Nodes sharing a cabinet (`x1000`).
+  Cabinet,
+}
+
+/// Why a pin/unpin plan placed each node where it did. Returned under
+/// `explanation` by `POST /v2/hardware-clusters/{target}/configuration`
+/// when the request sets `explain`.
+///
+/// A node the greedy selector picked carries the score that decided
+/// the pick: computed against the pool as it stood at that round, with
+/// any locality bonus as a `locality` contribution. Released nodes, and
+/// every node of an exact-solver plan, are scored against the combined
+/// target + parent pool before any node moves.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, ToSchema)]
+pub struct HwPlanExplanation {
+  /// Scarcity weight per component (`total components / supply of
+  /// this one`); rarer components weigh more.
+  pub scarcity_weights: BTreeMap<String, f64>,
+  /// Every node added to, kept in, or released from the target.
+  pub nodes: Vec<HwNodeExplanation>,
+}
+
+/// One node's line in a [`HwPlanExplanation`].
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct HwNodeExplanation {
+  /// Node xname.
+  pub xname: String,
+  /// What the plan did with the node.
+  pub decision: HwNodeDecision,
+  /// Component counts (memory in 16 GiB units).
+  pub components: BTreeMap<String, usize>,
+  /// Signed score contribution of each component, plus `locality`
+  /// when a locality bonus helped pick the node.
+  pub contributions: BTreeMap<String, f64>,
+  /// Sum of `contributions`; higher is more useful to the target.
+  pub score: f64,
+  /// Pattern terms the node satisfies or contributes to, e.g.
+  /// `a100>=8`, `node.a100>=4`, `!mi250`.
+  pub satisfies: Vec<String>,
+  /// Per-node requirements or exclusions the node fails; such nodes
+  /// are never picked.
+  #[serde(default, skip_serializing_if = "Vec::is_empty")]
+  pub fails: Vec<String>,
+}
+
+/// What a pin/unpin plan did with a node.
+#[derive(
+  Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema,
+)]
+#[serde(rename_all = "lowercase")]
+pub enum HwNodeDecision {
+  /// Moved from the parent into the target.
+  Added,
+  /// Already in the target and stays there.
+  Kept,
+  /// Moved from the target back to the parent.
+  Released,
+}
+
+/// A time-limited pin tracked by manta-server. Created by
+/// `POST /v2/hardware-clusters/{target}/configuration` when the
+/// request sets `expires_at`; listed by `GET /v2/reservations`.
+///
+/// Only the nodes the pin added are recorded. On expiry the server
+/// moves those still in the target back to the parent; nodes the
+/// target held before the pin stay where they are.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+pub struct HwReservation {
+  /// Server-assigned identifier.
+  pub id: String,
+  /// Site the reservation was made on.
+  pub site: String,
+  /// Group the nodes were pinned into.
+  pub target_cluster: String,
+  /// Group the nodes return to on expiry.
+  pub parent_cluster: String,
+  /// Hardware pattern of the pin.
+  pub pattern: String,
+  /// Xnames the pin moved into the target.
+  pub nodes: Vec<String>,
+  /// User who made the reservation (from the token's
+  /// `preferred_username` claim).
+  pub owner: String,
+  /// RFC 3339 creation instant (UTC).
+  pub created_at: String,
+  /// RFC 3339 expiry instant (UTC).
+  pub expires_at: String,
+}
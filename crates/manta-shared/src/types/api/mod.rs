@@ -40,7 +40,8 @@ pub mod configuration_analysis;
 /// `/v2/groups/{name}/members`).
 pub mod group;
 /// CLI-built params for `GET /groups/hardware` and the
-/// `/hardware-nodes-list` family.
+/// `/hardware-nodes-list` family, and hardware snapshot bodies
+/// (`/v2/hardware-snapshots`).
 pub mod hardware;
 /// Wire types for the `POST/DELETE /v2/hardware-clusters/{target}/*`
 /// endpoints.
@@ -73,6 +74,8 @@ pub mod runtime_configuration;
 pub mod sat_file;
 /// CFS session request/response bodies (`/v2/sessions`).
 pub mod session;
+/// Per-site capability report (`/v2/sites/{name}/capabilities`).
+pub mod site;
 /// BOS session-template request/response bodies (`/v2/templates`,
 /// `/v2/templates/{name}/sessions`).
 pub mod template;
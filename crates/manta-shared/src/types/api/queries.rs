@@ -42,6 +42,12 @@ pub struct SessionLogsQuery {
   /// When true, prefix each log line with its timestamp.
   #[serde(default)]
   pub timestamps: bool,
+  /// Only the lines of this configuration layer: its 0-based index,
+  /// the layer name in the session's configuration, or the repository
+  /// name or playbook the layer runs.
+  pub layer: Option<String>,
+  /// Only the lines matching this regular expression.
+  pub grep: Option<String>,
 }
 
 /// Query parameters for `DELETE /v2/sessions/{name}`.
@@ -53,6 +59,31 @@ pub struct DeleteSessionQuery {
   pub dry_run: bool,
 }
 
+/// Query parameters for `DELETE /v2/sessions` (bulk deletion). At
+/// least one of `status`, `min_age` or `names` is required, so an
+/// empty query can't select every session.
+#[derive(Debug, Default, Serialize, Deserialize, IntoParams)]
+pub struct DeleteSessionsQuery {
+  /// Only sessions targeting this HSM group (default: every group the
+  /// caller can access).
+  pub hsm_group: Option<String>,
+  /// Only sessions in this status: `"pending"`, `"running"` or
+  /// `"complete"`.
+  pub status: Option<String>,
+  /// Only sessions older than this duration (e.g. `"30d"`, `"12h"`).
+  pub min_age: Option<String>,
+  /// Only sessions of this type: `"image"` or `"runtime"`.
+  pub session_type: Option<String>,
+  /// Only these comma-separated session names; the CLI passes the
+  /// names it showed for confirmation, so sessions matching the
+  /// filters since then are left alone.
+  pub names: Option<String>,
+  /// When true, return the deletion plan without deleting anything
+  /// (default: `false`).
+  #[serde(default)]
+  pub dry_run: bool,
+}
+
 /// Query parameters for `GET /v2/configurations`.
 #[derive(Debug, Serialize, Deserialize, IntoParams)]
 pub struct ConfigurationQuery {
@@ -101,6 +132,16 @@ pub struct GroupQuery {
   pub name: Option<String>,
 }
 
+/// Query parameters for `GET /v2/groups/prometheus-sd`.
+#[derive(Debug, Serialize, Deserialize, IntoParams)]
+pub struct PrometheusSdQuery {
+  /// Group to export; every group the bearer token can access when
+  /// omitted.
+  pub group: Option<String>,
+  /// Port appended to each target (default: 9100, `node_exporter`).
+  pub port: Option<u16>,
+}
+
 /// Query parameters for `DELETE /v2/groups/{label}`.
 #[derive(Debug, Serialize, Deserialize, IntoParams)]
 pub struct DeleteGroupQuery {
@@ -118,6 +159,13 @@ pub struct HardwareClusterQuery {
   pub hsm_group: Option<String>,
 }
 
+/// Query parameters for `GET /v2/hardware-snapshots`.
+#[derive(Debug, Serialize, Deserialize, IntoParams)]
+pub struct HardwareSnapshotsQuery {
+  /// Only list snapshots of this group.
+  pub group: Option<String>,
+}
+
 /// Query parameters for `GET /v2/hardware-nodes-list`.
 #[derive(Debug, Serialize, Deserialize, IntoParams)]
 pub struct HardwareNodesListQuery {
@@ -57,8 +57,33 @@ pub struct CreateSessionResponse {
 /// ephemeral host.
 #[derive(Debug, Serialize, Deserialize, ToSchema)]
 pub struct EphemeralEnvResponse {
+  /// IMS customize job backing the environment; pass it to
+  /// `DELETE /v2/ephemeral-env/{id}`.
+  pub id: String,
   /// Hostname of the ephemeral environment.
   pub hostname: String,
+  /// When the server deletes the environment (RFC 3339); `None` when
+  /// it lives until deleted.
+  pub expires_at: Option<String>,
+}
+
+/// One of the caller's ephemeral environments, as listed by
+/// `GET /v2/ephemeral-env`.
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct EphemeralEnv {
+  /// IMS customize job id.
+  pub id: String,
+  /// IMS image the environment was booted from.
+  pub image_id: String,
+  /// IMS job status (e.g. `waiting_on_user`, `error`).
+  pub status: Option<String>,
+  /// SSH hostname, once IMS has assigned one.
+  pub hostname: Option<String>,
+  /// When the environment was created.
+  pub created: Option<String>,
+  /// When the server deletes the environment (RFC 3339); `None` when
+  /// it lives until deleted.
+  pub expires_at: Option<String>,
 }
 
 /// Response for endpoints that simply confirm a backup / restore /
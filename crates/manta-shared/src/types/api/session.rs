@@ -12,6 +12,10 @@ use utoipa::ToSchema;
 /// `repo_last_commit_ids[i]` is the commit SHA for `repo_names[i]`.
 /// The two vectors must therefore have the same length.
 ///
+/// Instead of repository names and commits, `repo_refs` may name the
+/// layers by repository URL and Git ref; the server resolves each ref
+/// to a commit against the site's Gitea. The two forms can't be mixed.
+///
 /// Paired with [`super::responses::CreateSessionResponse`].
 #[derive(Debug, Serialize, Deserialize, ToSchema)]
 pub struct CreateSessionRequest {
@@ -24,9 +28,15 @@ pub struct CreateSessionRequest {
   pub hsm_group: Option<String>,
   /// Git repository names (parallel-indexed with
   /// `repo_last_commit_ids`).
+  #[serde(default)]
   pub repo_names: Vec<String>,
   /// Git commit SHAs matching each entry in `repo_names`.
+  #[serde(default)]
   pub repo_last_commit_ids: Vec<String>,
+  /// Layers given as a repository URL and a branch, tag or commit,
+  /// in layer order. Used when `repo_names` is empty.
+  #[serde(default)]
+  pub repo_refs: Vec<RemoteRepoRef>,
   /// Ansible `--limit` expression restricting which xnames are
   /// targeted (the service-layer authz check rejects group names —
   /// pre-resolve them client-side).
@@ -37,6 +47,18 @@ pub struct CreateSessionRequest {
   pub ansible_passthrough: Option<String>,
 }
 
+/// A configuration layer named by repository and Git ref, for
+/// [`CreateSessionRequest::repo_refs`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+pub struct RemoteRepoRef {
+  /// Repository in the site's Gitea: its clone URL
+  /// (`https://<host>/vcs/cray/<repo>.git`), `cray/<repo>`, or just
+  /// `<repo>`.
+  pub repo_url: String,
+  /// Branch, tag, or (possibly abbreviated) commit SHA.
+  pub git_ref: String,
+}
+
 /// Typed parameters for fetching CFS sessions.
 pub struct GetSessionParams {
   /// Group whose sessions should be returned.
@@ -59,3 +81,125 @@ pub struct GetSessionParams {
   /// oldest first, newest last).
   pub limit: Option<u8>,
 }
+
+/// A CFS session log kept by the server after the session's pod is
+/// gone (`GET /v2/sessions/{name}/logs/archive`).
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+pub struct ArchivedSessionLog {
+  /// CFS session name.
+  pub session_name: String,
+  /// Site the session ran on.
+  pub site: String,
+  /// HSM groups the session targeted; callers need access to all of
+  /// them to read the log.
+  pub groups: Vec<String>,
+  /// RFC 3339 instant the archive was written (UTC).
+  pub archived_at: String,
+  /// `false` when the log stream broke off before the session ended,
+  /// so the tail of the log is missing.
+  pub complete: bool,
+  /// Log lines, oldest first.
+  pub lines: Vec<String>,
+}
+
+/// Request body for `POST /v2/sessions/{name}/rerun`.
+///
+/// Everything not given here — configuration, target, Ansible limit,
+/// verbosity, passthrough, tags — is copied from session `{name}`.
+/// Paired with [`RerunSessionResponse`].
+#[derive(Debug, Default, Serialize, Deserialize, ToSchema)]
+pub struct RerunSessionRequest {
+  /// Name for the new session; derived from the original's when
+  /// absent.
+  pub session_name: Option<String>,
+  /// Replace the original Ansible `--limit` (hosts expression: xnames,
+  /// NIDs, hostlist ranges).
+  pub ansible_limit: Option<String>,
+  /// Replace the original Ansible verbosity (0–4).
+  pub ansible_verbosity: Option<u8>,
+  /// When true, return the would-be session without creating it.
+  #[serde(default)]
+  pub dry_run: bool,
+}
+
+/// Response for `POST /v2/sessions/{name}/rerun`: the session created
+/// (or, on a dry run, the one that would be).
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct RerunSessionResponse {
+  /// Name of the new session.
+  pub session_name: String,
+  /// Session the new one re-runs.
+  pub rerun_of: String,
+  /// CFS configuration applied.
+  pub configuration_name: String,
+  /// `dynamic` (runtime) or `image`.
+  pub target_definition: String,
+  /// HSM groups targeted.
+  pub target_groups: Vec<String>,
+  /// Ansible `--limit`, comma-separated xnames.
+  pub ansible_limit: Option<String>,
+  /// Ansible verbosity.
+  pub ansible_verbosity: Option<u8>,
+  /// `true` when nothing was created.
+  pub dry_run: bool,
+}
+
+/// What a bulk session deletion (`DELETE /v2/sessions`) touches, summed
+/// over every matching session.
+#[derive(Debug, Default, Clone, Serialize, Deserialize, ToSchema)]
+pub struct SessionsDeletionPlan {
+  /// Sessions to delete, oldest first.
+  pub sessions: Vec<SessionDeletionItem>,
+  /// IMS images built by the image sessions, deleted with them.
+  pub image_ids: Vec<String>,
+  /// Images built by the image sessions that nodes boot from: kept,
+  /// with the nodes booting them.
+  pub boot_images_kept: Vec<BootImageInUse>,
+  /// Xnames targeted by the runtime sessions, whose CFS component
+  /// error count is raised to the retry limit to cancel them.
+  pub components: Vec<String>,
+}
+
+/// One session in a [`SessionsDeletionPlan`].
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct SessionDeletionItem {
+  /// CFS session name.
+  pub name: String,
+  /// `dynamic` (runtime) or `image`.
+  pub target_definition: Option<String>,
+  /// `pending`, `running` or `complete`.
+  pub status: Option<String>,
+  /// When the session started.
+  pub start_time: Option<String>,
+}
+
+/// An image kept by a session deletion because nodes boot from it.
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct BootImageInUse {
+  /// IMS image id.
+  pub image_id: String,
+  /// Nodes whose boot parameters reference the image.
+  pub hosts: Vec<String>,
+}
+
+/// Outcome of deleting one session of a bulk deletion.
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct SessionDeletionResult {
+  /// CFS session name.
+  pub session_name: String,
+  /// Whether the session (and what it produced) was deleted.
+  pub deleted: bool,
+  /// Why the deletion failed.
+  pub error: Option<String>,
+}
+
+/// Response for `DELETE /v2/sessions`.
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct DeleteSessionsResponse {
+  /// What the deletion covers.
+  pub plan: SessionsDeletionPlan,
+  /// `true` when nothing was deleted.
+  pub dry_run: bool,
+  /// One entry per session, in plan order; empty on a dry run.
+  pub results: Vec<SessionDeletionResult>,
+}
@@ -0,0 +1,74 @@
+//! Per-site capability report returned by
+//! `GET /v2/sites/{name}/capabilities`.
+//!
+//! Which features a site serves depends on its backend (OpenCHAMI has
+//! no CFS, BOS or IMS), on the optional `vault_base_url` /
+//! `k8s_api_url` of its `[sites.X]` block and, for archived session
+//! logs, on the server's `[server.session_logs]`. The CLI caches the report
+//! to hide commands the active site can't serve and to warn before
+//! running one.
+//!
+//! Feature names are plain strings so an older CLI keeps working
+//! against a server that reports more features; the ones known today
+//! are listed in [`feature`].
+
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+/// Names of the features a [`SiteCapabilities`] reports on.
+pub mod feature {
+  /// CFS configurations and sessions: listing and deleting.
+  pub const CFS: &str = "cfs";
+  /// Creating CFS sessions (`POST /sessions`); needs Vault for the
+  /// Gitea credentials.
+  pub const SESSION_CREATE: &str = "session_create";
+  /// Re-running a CFS session (`POST /sessions/{name}/rerun`); needs
+  /// no Vault, the configuration already exists.
+  pub const SESSION_RERUN: &str = "session_rerun";
+  /// Streaming live CFS session logs; needs Vault and Kubernetes.
+  pub const SESSION_LOGS: &str = "session_logs";
+  /// Reading archived CFS session logs; needs only the server's
+  /// `[server.session_logs]`.
+  pub const SESSION_LOG_ARCHIVE: &str = "session_log_archive";
+  /// Node and CFS session consoles; needs Vault and Kubernetes.
+  pub const CONSOLE: &str = "console";
+  /// Applying SAT files; needs Vault and Kubernetes.
+  pub const SAT_FILE: &str = "sat_file";
+  /// IMS images.
+  pub const IMAGES: &str = "images";
+  /// BOS session templates.
+  pub const TEMPLATES: &str = "templates";
+  /// Backing up and restoring virtual clusters.
+  pub const MIGRATE: &str = "migrate";
+  /// Pattern-driven hardware pins on groups.
+  pub const HARDWARE_PIN: &str = "hardware_pin";
+  /// Ephemeral SSH environments.
+  pub const EPHEMERAL_ENV: &str = "ephemeral_env";
+}
+
+/// Whether a site serves one feature.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+pub struct SiteFeature {
+  /// Feature name, one of [`feature`].
+  pub name: String,
+  /// Whether the site serves it.
+  pub supported: bool,
+  /// Why the site doesn't serve it; `None` when supported.
+  pub reason: Option<String>,
+}
+
+/// What a site supports, as reported by
+/// `GET /v2/sites/{name}/capabilities`.
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct SiteCapabilities {
+  /// Site name.
+  pub site: String,
+  /// Backend kind: `csm` or `ochami`.
+  pub backend: String,
+  /// Whether the site has a Vault configured.
+  pub vault: bool,
+  /// Whether the site has a Kubernetes API configured.
+  pub k8s: bool,
+  /// Every feature whose availability depends on the site.
+  pub features: Vec<SiteFeature>,
+}
@@ -0,0 +1,347 @@
+//! Pure helpers for hardware snapshots: normalising a `NodeSummary`
+//! into a [`NodeHardware`] and comparing two [`HardwareSnapshot`]s.
+//!
+//! The CLI (`manta diff hardware`) and the server's snapshot watcher
+//! (`server::hardware_watch`) both call [`diff_snapshots`], so a change
+//! reported on the terminal and one raised as an audit event are
+//! computed the same way.
+
+use std::collections::{BTreeMap, BTreeSet};
+
+use manta_backend_dispatcher::types::NodeSummary;
+use serde::{Deserialize, Serialize};
+
+use crate::types::api::hardware::{HardwareSnapshot, NodeHardware};
+
+/// Normalise one node's inventory. `None` when the summary carries no
+/// processor, memory, accelerator or NIC at all — the server degrades
+/// a failed inventory read to such an empty row, and recording it as
+/// "no hardware" would report every component as removed.
+pub fn node_hardware(summary: &NodeSummary) -> Option<NodeHardware> {
+  if summary.processors.is_empty()
+    && summary.memory.is_empty()
+    && summary.node_accels.is_empty()
+    && summary.node_hsn_nics.is_empty()
+  {
+    return None;
+  }
+  let mut hw = NodeHardware {
+    dimms: summary.memory.len(),
+    hsn_nics: summary.node_hsn_nics.len(),
+    ..NodeHardware::default()
+  };
+  for model in summary.processors.iter().filter_map(|p| p.info.as_ref()) {
+    *hw.processors.entry(model.clone()).or_default() += 1;
+  }
+  for model in summary.node_accels.iter().filter_map(|a| a.info.as_ref()) {
+    *hw.accelerators.entry(model.clone()).or_default() += 1;
+  }
+  // DIMM capacities are reported as "<n> MiB"; anything else counts 0.
+  hw.memory_mib = summary
+    .memory
+    .iter()
+    .filter_map(|m| m.info.as_deref()?.split(' ').next()?.parse::<usize>().ok())
+    .sum();
+  Some(hw)
+}
+
+/// Split a group's summaries into the `nodes` and `unreadable` fields
+/// of a [`HardwareSnapshot`].
+pub fn snapshot_nodes(
+  summaries: &[NodeSummary],
+) -> (BTreeMap<String, NodeHardware>, Vec<String>) {
+  let mut nodes = BTreeMap::new();
+  let mut unreadable = Vec::new();
+  for summary in summaries {
+    match node_hardware(summary) {
+      Some(hw) => {
+        nodes.insert(summary.xname.clone(), hw);
+      }
+      None => unreadable.push(summary.xname.clone()),
+    }
+  }
+  unreadable.sort();
+  (nodes, unreadable)
+}
+
+/// What happened to a node between two snapshots.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum NodeChangeKind {
+  /// Only in the later snapshot.
+  Added,
+  /// Only in the earlier snapshot.
+  Removed,
+  /// In both, with different hardware.
+  Changed,
+}
+
+/// One component whose count differs between two snapshots.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ComponentChange {
+  /// `processor: <model>`, `accelerator: <model>`, `memory_mib`,
+  /// `dimms` or `hsn_nics`.
+  pub component: String,
+  /// Count (or MiB) in the earlier snapshot.
+  pub before: usize,
+  /// Count (or MiB) in the later snapshot.
+  pub after: usize,
+}
+
+/// A node that was added, removed or whose hardware changed.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct NodeHardwareChange {
+  /// Node xname.
+  pub xname: String,
+  /// Kind of change.
+  pub change: NodeChangeKind,
+  /// Components that differ; for an added or removed node, every
+  /// component it has.
+  pub components: Vec<ComponentChange>,
+}
+
+/// Result of [`diff_snapshots`].
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct HardwareDiff {
+  /// Changed nodes, by xname.
+  pub changes: Vec<NodeHardwareChange>,
+  /// Nodes left out of the comparison because their inventory could
+  /// not be read in one of the snapshots.
+  #[serde(default, skip_serializing_if = "Vec::is_empty")]
+  pub unreadable: Vec<String>,
+}
+
+impl HardwareDiff {
+  /// `true` when no node changed.
+  pub fn is_empty(&self) -> bool {
+    self.changes.is_empty()
+  }
+}
+
+/// Flatten a node's hardware into `component → count`, using the
+/// names documented on [`ComponentChange::component`].
+fn component_counts(hw: &NodeHardware) -> BTreeMap<String, usize> {
+  let mut counts: BTreeMap<String, usize> = hw
+    .processors
+    .iter()
+    .map(|(model, qty)| (format!("processor: {model}"), *qty))
+    .chain(
+      hw.accelerators
+        .iter()
+        .map(|(model, qty)| (format!("accelerator: {model}"), *qty)),
+    )
+    .collect();
+  counts.insert("memory_mib".to_string(), hw.memory_mib);
+  counts.insert("dimms".to_string(), hw.dimms);
+  counts.insert("hsn_nics".to_string(), hw.hsn_nics);
+  counts
+}
+
+fn component_changes(
+  before: &BTreeMap<String, usize>,
+  after: &BTreeMap<String, usize>,
+) -> Vec<ComponentChange> {
+  before
+    .keys()
+    .chain(after.keys())
+    .collect::<BTreeSet<_>>()
+    .into_iter()
+    .filter_map(|component| {
+      let was = before.get(component).copied().unwrap_or(0);
+      let now = after.get(component).copied().unwrap_or(0);
+      (was != now).then(|| ComponentChange {
+        component: component.clone(),
+        before: was,
+        after: now,
+      })
+    })
+    .collect()
+}
+
+/// Compare two snapshots of the same group. Nodes unreadable in either
+/// snapshot are listed in [`HardwareDiff::unreadable`] rather than
+/// reported as added or removed.
+pub fn diff_snapshots(
+  before: &HardwareSnapshot,
+  after: &HardwareSnapshot,
+) -> HardwareDiff {
+  let unreadable: BTreeSet<&String> =
+    before.unreadable.iter().chain(&after.unreadable).collect();
+  let empty = BTreeMap::new();
+
+  let changes = before
+    .nodes
+    .keys()
+    .chain(after.nodes.keys())
+    .collect::<BTreeSet<_>>()
+    .into_iter()
+    .filter(|xname| !unreadable.contains(xname))
+    .filter_map(|xname| {
+      let was = before.nodes.get(xname).map(component_counts);
+      let now = after.nodes.get(xname).map(component_counts);
+      let change = match (&was, &now) {
+        (Some(_), None) => NodeChangeKind::Removed,
+        (None, Some(_)) => NodeChangeKind::Added,
+        _ => NodeChangeKind::Changed,
+      };
+      let components = component_changes(
+        was.as_ref().unwrap_or(&empty),
+        now.as_ref().unwrap_or(&empty),
+      );
+      (!components.is_empty()).then(|| NodeHardwareChange {
+        xname: xname.clone(),
+        change,
+        components,
+      })
+    })
+    .collect();
+
+  HardwareDiff {
+    changes,
+    unreadable: unreadable.into_iter().cloned().collect(),
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use manta_backend_dispatcher::types::{ArtifactSummary, ArtifactType};
+
+  fn artifact(r#type: ArtifactType, info: &str) -> ArtifactSummary {
+    ArtifactSummary {
+      xname: String::new(),
+      r#type,
+      info: Some(info.to_string()),
+    }
+  }
+
+  fn summary(xname: &str, gpus: usize, dimm_mib: &[&str]) -> NodeSummary {
+    NodeSummary {
+      xname: xname.to_string(),
+      r#type: "Node".to_string(),
+      processors: vec![artifact(ArtifactType::Processor, "AMD EPYC 7763"); 2],
+      memory: dimm_mib
+        .iter()
+        .map(|mib| artifact(ArtifactType::Memory, &format!("{mib} MiB")))
+        .collect(),
+      node_accels: vec![artifact(ArtifactType::NodeAccel, "NVIDIA A100"); gpus],
+      node_hsn_nics: vec![artifact(ArtifactType::NodeHsnNic, "HSN")],
+    }
+  }
+
+  fn snapshot(summaries: &[NodeSummary]) -> HardwareSnapshot {
+    let (nodes, unreadable) = snapshot_nodes(summaries);
+    HardwareSnapshot {
+      id: "s".to_string(),
+      site: "alps".to_string(),
+      group: "compute".to_string(),
+      taken_at: "2026-10-01T08:00:00Z".to_string(),
+      taken_by: "alice".to_string(),
+      nodes,
+      unreadable,
+    }
+  }
+
+  fn empty(xname: &str) -> NodeSummary {
+    NodeSummary {
+      xname: xname.to_string(),
+      ..NodeSummary::default()
+    }
+  }
+
+  #[test]
+  fn node_hardware_counts_models_and_sums_memory() {
+    let hw = node_hardware(&summary("x0", 4, &["262144", "262144"])).unwrap();
+    assert_eq!(hw.processors["AMD EPYC 7763"], 2);
+    assert_eq!(hw.accelerators["NVIDIA A100"], 4);
+    assert_eq!(hw.memory_mib, 524_288);
+    assert_eq!(hw.dimms, 2);
+    assert_eq!(hw.hsn_nics, 1);
+    assert!(node_hardware(&empty("x0")).is_none());
+  }
+
+  #[test]
+  fn identical_snapshots_have_no_changes() {
+    let nodes = [summary("x0", 4, &["1024"]), summary("x1", 4, &["1024"])];
+    assert!(diff_snapshots(&snapshot(&nodes), &snapshot(&nodes)).is_empty());
+  }
+
+  #[test]
+  fn lost_gpu_and_dimm_are_reported_per_component() {
+    let before = snapshot(&[summary("x0", 4, &["1024", "1024"])]);
+    let after = snapshot(&[summary("x0", 3, &["1024"])]);
+    let diff = diff_snapshots(&before, &after);
+    assert_eq!(
+      diff.changes,
+      vec![NodeHardwareChange {
+        xname: "x0".to_string(),
+        change: NodeChangeKind::Changed,
+        components: vec![
+          ComponentChange {
+            component: "accelerator: NVIDIA A100".to_string(),
+            before: 4,
+            after: 3,
+          },
+          ComponentChange {
+            component: "dimms".to_string(),
+            before: 2,
+            after: 1,
+          },
+          ComponentChange {
+            component: "memory_mib".to_string(),
+            before: 2048,
+            after: 1024,
+          },
+        ],
+      }]
+    );
+  }
+
+  #[test]
+  fn swapped_accelerator_model_shows_both_models() {
+    let before = snapshot(&[summary("x0", 1, &["1024"])]);
+    let mut after = before.clone();
+    let hw = after.nodes.get_mut("x0").unwrap();
+    hw.accelerators = BTreeMap::from([("NVIDIA H100".to_string(), 1)]);
+    let components: Vec<(String, usize, usize)> =
+      diff_snapshots(&before, &after).changes[0]
+        .components
+        .iter()
+        .map(|c| (c.component.clone(), c.before, c.after))
+        .collect();
+    assert_eq!(
+      components,
+      vec![
+        ("accelerator: NVIDIA A100".to_string(), 1, 0),
+        ("accelerator: NVIDIA H100".to_string(), 0, 1),
+      ]
+    );
+  }
+
+  #[test]
+  fn membership_changes_are_added_and_removed() {
+    let before = snapshot(&[summary("x0", 0, &["1024"])]);
+    let after = snapshot(&[summary("x1", 0, &["1024"])]);
+    let kinds: Vec<(String, NodeChangeKind)> = diff_snapshots(&before, &after)
+      .changes
+      .into_iter()
+      .map(|c| (c.xname, c.change))
+      .collect();
+    assert_eq!(
+      kinds,
+      vec![
+        ("x0".to_string(), NodeChangeKind::Removed),
+        ("x1".to_string(), NodeChangeKind::Added),
+      ]
+    );
+  }
+
+  #[test]
+  fn unreadable_nodes_are_not_reported_as_removed() {
+    let before = snapshot(&[summary("x0", 4, &["1024"])]);
+    let after = snapshot(&[empty("x0")]);
+    let diff = diff_snapshots(&before, &after);
+    assert!(diff.is_empty());
+    assert_eq!(diff.unreadable, vec!["x0".to_string()]);
+  }
+}
@@ -0,0 +1,303 @@
+//! Pure helpers that turn group membership ([`NodeDetails`] rows, as
+//! returned by `GET /v2/groups/nodes`) into inventories for other
+//! tools: an Ansible inventory ([`AnsibleInventory`]) and Prometheus
+//! HTTP service-discovery targets ([`prometheus_targets`]).
+//!
+//! Hosts are named by xname in both, matching the inventory CFS hands
+//! to its own Ansible runs. The CLI renders both formats
+//! (`manta get groups -o ansible-inventory|prometheus-sd`); the server
+//! serves the Prometheus one from `GET /v2/groups/prometheus-sd`.
+
+use std::collections::{BTreeMap, BTreeSet};
+
+use serde::Serialize;
+
+use crate::types::api::group::PrometheusTargetGroup;
+use crate::types::dto::NodeDetails;
+
+/// Default scrape port (`node_exporter`).
+pub const DEFAULT_PROMETHEUS_PORT: u16 = 9100;
+
+/// Per-host variables exported with each inventory host.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct HostVars {
+  /// Node xname (also the host name).
+  pub xname: String,
+  /// Numeric node id; absent when HSM does not report one.
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub nid: Option<u32>,
+  /// IMS image id the node boots; absent when BSS has none.
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub boot_image: Option<String>,
+}
+
+/// An Ansible inventory: one Ansible group per HSM group.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct AnsibleInventory {
+  /// Ansible group name → host name → host variables.
+  pub groups: BTreeMap<String, BTreeMap<String, HostVars>>,
+}
+
+/// `nid001313` → `1313`; `None` for the `"Not found"` placeholder.
+fn parse_nid(nid: &str) -> Option<u32> {
+  nid.strip_prefix("nid")?.parse().ok()
+}
+
+/// Group labels of `node` restricted to `groups`, in `groups` order.
+fn member_groups<'a>(node: &NodeDetails, groups: &'a [String]) -> Vec<&'a str> {
+  let labels: BTreeSet<&str> = node.hsm.split(',').map(str::trim).collect();
+  groups
+    .iter()
+    .map(String::as_str)
+    .filter(|group| labels.contains(group))
+    .collect()
+}
+
+/// Ansible group names may only hold letters, digits and underscores
+/// (and not start with a digit); anything else becomes `_`.
+pub fn ansible_group_name(label: &str) -> String {
+  let mut name: String = label
+    .chars()
+    .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+    .collect();
+  if name.starts_with(|c: char| c.is_ascii_digit()) {
+    name.insert(0, '_');
+  }
+  name
+}
+
+impl AnsibleInventory {
+  /// Build the inventory of `groups` from their members' details.
+  /// Membership comes from each node's `hsm` field, so a node in two
+  /// exported groups appears in both.
+  ///
+  /// # Errors
+  ///
+  /// Returns a message naming both labels when two groups sanitise to
+  /// the same Ansible group name (e.g. `compute-a` and `compute_a`),
+  /// rather than merging their hosts into one group.
+  pub fn build(
+    groups: &[String],
+    nodes: &[NodeDetails],
+  ) -> Result<Self, String> {
+    let mut inventory = Self::default();
+    let mut labels_by_name: BTreeMap<String, &str> = BTreeMap::new();
+    for group in groups {
+      let name = ansible_group_name(group);
+      if let Some(other) = labels_by_name.get(&name)
+        && *other != group.as_str()
+      {
+        return Err(format!(
+          "groups '{other}' and '{group}' would both become Ansible group \
+           '{name}'; export them separately"
+        ));
+      }
+      labels_by_name.insert(name.clone(), group);
+      inventory.groups.entry(name).or_default();
+    }
+    for node in nodes {
+      let vars = HostVars {
+        xname: node.xname.clone(),
+        nid: parse_nid(&node.nid),
+        boot_image: Some(node.boot_image_id.clone())
+          .filter(|id| !id.is_empty() && id != "Not found"),
+      };
+      for group in member_groups(node, groups) {
+        inventory
+          .groups
+          .entry(ansible_group_name(group))
+          .or_default()
+          .insert(node.xname.clone(), vars.clone());
+      }
+    }
+    Ok(inventory)
+  }
+
+  /// INI rendering: one `[group]` section per group, one
+  /// `host key=value…` line per member.
+  pub fn to_ini(&self) -> String {
+    let mut out = String::new();
+    for (group, hosts) in &self.groups {
+      if !out.is_empty() {
+        out.push('\n');
+      }
+      out.push_str(&format!("[{group}]\n"));
+      for (host, vars) in hosts {
+        out.push_str(host);
+        out.push_str(&format!(" xname={}", vars.xname));
+        if let Some(nid) = vars.nid {
+          out.push_str(&format!(" nid={nid}"));
+        }
+        if let Some(image) = &vars.boot_image {
+          out.push_str(&format!(" boot_image={image}"));
+        }
+        out.push('\n');
+      }
+    }
+    out
+  }
+
+  /// YAML rendering: `all.children.<group>.hosts.<host>: <vars>`.
+  ///
+  /// # Errors
+  ///
+  /// Propagates `serde_yaml` serialisation errors.
+  pub fn to_yaml(&self) -> Result<String, serde_yaml::Error> {
+    #[derive(Serialize)]
+    struct Group<'a> {
+      hosts: &'a BTreeMap<String, HostVars>,
+    }
+    let children: BTreeMap<&str, Group> = self
+      .groups
+      .iter()
+      .map(|(name, hosts)| (name.as_str(), Group { hosts }))
+      .collect();
+    serde_yaml::to_string(&BTreeMap::from([(
+      "all",
+      BTreeMap::from([("children", children)]),
+    )]))
+  }
+}
+
+/// Prometheus HTTP SD targets for the members of `groups` on `site`,
+/// one entry per node so a node in several groups is scraped once.
+///
+/// Each entry carries `manta_site`, `manta_xname`, `manta_nid` (when
+/// known) and `manta_groups`: the node's exported groups joined with
+/// commas and wrapped in commas (`,compute,gpu,`), so a relabel rule
+/// can match one group with the regex `.*,compute,.*`.
+pub fn prometheus_targets(
+  site: &str,
+  groups: &[String],
+  nodes: &[NodeDetails],
+  port: u16,
+) -> Vec<PrometheusTargetGroup> {
+  nodes
+    .iter()
+    .filter_map(|node| {
+      let member_of = member_groups(node, groups);
+      if member_of.is_empty() {
+        return None;
+      }
+      let mut labels = BTreeMap::from([
+        ("manta_site".to_string(), site.to_string()),
+        ("manta_xname".to_string(), node.xname.clone()),
+        (
+          "manta_groups".to_string(),
+          format!(",{},", member_of.join(",")),
+        ),
+      ]);
+      if let Some(nid) = parse_nid(&node.nid) {
+        labels.insert("manta_nid".to_string(), nid.to_string());
+      }
+      Some(PrometheusTargetGroup {
+        targets: vec![format!("{}:{port}", node.xname)],
+        labels,
+      })
+    })
+    .collect()
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn node(xname: &str, nid: &str, hsm: &str, image: &str) -> NodeDetails {
+    NodeDetails {
+      xname: xname.to_string(),
+      nid: nid.to_string(),
+      hsm: hsm.to_string(),
+      power_status: "ON".to_string(),
+      desired_configuration: String::new(),
+      configuration_status: String::new(),
+      enabled: "true".to_string(),
+      error_count: "0".to_string(),
+      boot_image_id: image.to_string(),
+      boot_configuration: String::new(),
+      kernel_params: String::new(),
+    }
+  }
+
+  fn nodes() -> Vec<NodeDetails> {
+    vec![
+      node("x1000c0s0b0n0", "nid001000", "compute, gpu-a100", "img-1"),
+      node("x1000c0s0b0n1", "Not found", "compute", "Not found"),
+    ]
+  }
+
+  fn groups() -> Vec<String> {
+    vec!["compute".to_string(), "gpu-a100".to_string()]
+  }
+
+  #[test]
+  fn ini_lists_hosts_per_group_with_hostvars() {
+    let ini = AnsibleInventory::build(&groups(), &nodes())
+      .unwrap()
+      .to_ini();
+    assert_eq!(
+      ini,
+      "[compute]\n\
+       x1000c0s0b0n0 xname=x1000c0s0b0n0 nid=1000 boot_image=img-1\n\
+       x1000c0s0b0n1 xname=x1000c0s0b0n1\n\
+       \n\
+       [gpu_a100]\n\
+       x1000c0s0b0n0 xname=x1000c0s0b0n0 nid=1000 boot_image=img-1\n"
+    );
+  }
+
+  #[test]
+  fn yaml_nests_hosts_under_all_children() {
+    let yaml = AnsibleInventory::build(&groups(), &nodes())
+      .unwrap()
+      .to_yaml()
+      .unwrap();
+    let parsed: serde_yaml::Value = serde_yaml::from_str(&yaml).unwrap();
+    let host = &parsed["all"]["children"]["gpu_a100"]["hosts"]["x1000c0s0b0n0"];
+    assert_eq!(host["nid"], serde_yaml::Value::from(1000));
+    assert_eq!(host["boot_image"], serde_yaml::Value::from("img-1"));
+    assert!(
+      parsed["all"]["children"]["compute"]["hosts"]["x1000c0s0b0n1"]["nid"]
+        .is_null()
+    );
+  }
+
+  #[test]
+  fn group_names_are_sanitised_for_ansible() {
+    assert_eq!(ansible_group_name("gpu-a100"), "gpu_a100");
+    assert_eq!(ansible_group_name("42nodes"), "_42nodes");
+    assert_eq!(ansible_group_name("compute"), "compute");
+  }
+
+  #[test]
+  fn labels_sanitising_to_one_name_are_refused() {
+    let groups = vec!["compute-a".to_string(), "compute_a".to_string()];
+    let nodes = vec![
+      node("x1000c0s0b0n0", "nid001000", "compute-a", "img-1"),
+      node("x1000c0s0b0n1", "nid001001", "compute_a", "img-1"),
+    ];
+    let err = AnsibleInventory::build(&groups, &nodes).unwrap_err();
+    assert!(
+      err.contains("'compute-a'") && err.contains("'compute_a'"),
+      "{err}"
+    );
+    // The same label twice is not a collision.
+    let twice = vec!["compute".to_string(), "compute".to_string()];
+    assert!(AnsibleInventory::build(&twice, &nodes).is_ok());
+  }
+
+  #[test]
+  fn prometheus_targets_one_entry_per_node_with_group_labels() {
+    let targets = prometheus_targets("alps", &groups(), &nodes(), 9100);
+    assert_eq!(targets.len(), 2);
+    assert_eq!(targets[0].targets, vec!["x1000c0s0b0n0:9100".to_string()]);
+    assert_eq!(targets[0].labels["manta_groups"], ",compute,gpu-a100,");
+    assert_eq!(targets[0].labels["manta_nid"], "1000");
+    assert_eq!(targets[0].labels["manta_site"], "alps");
+    assert!(!targets[1].labels.contains_key("manta_nid"));
+
+    let only_gpu =
+      prometheus_targets("alps", &["gpu-a100".to_string()], &nodes(), 9100);
+    assert_eq!(only_gpu.len(), 1);
+    assert_eq!(only_gpu[0].labels["manta_groups"], ",gpu-a100,");
+  }
+}
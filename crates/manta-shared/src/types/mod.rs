@@ -5,13 +5,22 @@
 //! bodies, query-string structs, and CLI-built parameter structs
 //! (`api/`), response DTOs re-exported from upstream crates (`dto`),
 //! auth wire shapes (`auth`), plus pure helpers that operate on those
-//! types (`cluster_status`). There is no business logic and no I/O;
-//! this module depends only on `serde`, `utoipa`, and
+//! types (`cluster_status`, `hardware_diff`, `inventory_export`) and
+//! over CFS session logs (`ansible_summary`), the typed SAT-file schema used for
+//! offline validation (`sat_schema`), and the Vault secret-reference
+//! syntax the CLI renders and the server resolves (`secret_ref`).
+//! There is no business logic and no I/O; this module depends only on
+//! `serde`, `serde_json`, `serde_yaml`, `utoipa`, and
 //! `manta-backend-dispatcher` type re-exports. Anything that performs
 //! work — config loading, tracing init, error conversion — lives in
 //! [`super::common`].
 
+pub mod ansible_summary;
 pub mod api;
 pub mod auth;
 pub mod cluster_status;
 pub mod dto;
+pub mod hardware_diff;
+pub mod inventory_export;
+pub mod sat_schema;
+pub mod secret_ref;
@@ -0,0 +1,950 @@
+//! Typed SAT-file schema and offline validation.
+//!
+//! The apply path still carries SAT entries as `serde_json::Value` —
+//! csm-rs owns the canonical shapes and the server forwards entries
+//! verbatim. This module mirrors those shapes strictly enough to catch
+//! mistakes before anything is sent: every struct rejects unknown
+//! fields (csm-rs would silently drop them), required fields and enum
+//! values are checked by serde, and the "exactly one of" rules csm-rs
+//! encodes as untagged enums are checked afterwards with a readable
+//! message instead of "data did not match any variant".
+//!
+//! Errors carry the 1-based line and column in the *rendered* YAML
+//! (after Jinja2), since that is the document being validated.
+//!
+//! ```
+//! use manta_shared::types::sat_schema::validate_sat_file;
+//!
+//! let errors = validate_sat_file(
+//!   "configurations:\n- name: cfg\n  layers:\n  - git:\n      url: https://x\n      brnach: main\n",
+//! )
+//! .unwrap_err();
+//! assert_eq!(errors[0].line, Some(6));
+//! assert!(errors[0].message.contains("unknown field `brnach`"));
+//! ```
+
+use std::collections::BTreeMap;
+use std::fmt;
+
+use serde::Deserialize;
+use serde::de::{
+  self, DeserializeSeed, IgnoredAny, MapAccess, SeqAccess, Visitor,
+};
+
+/// A whole SAT file: up to four top-level sections.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct SatFile {
+  /// SAT schema version marker; accepted and ignored.
+  pub schema_version: Option<String>,
+  /// HSM group hardware patterns.
+  pub hardware: Option<Vec<HardwarePattern>>,
+  /// CFS configurations to create.
+  pub configurations: Option<Vec<Configuration>>,
+  /// IMS images to build.
+  pub images: Option<Vec<Image>>,
+  /// BOS session templates to create.
+  pub session_templates: Option<Vec<SessionTemplate>>,
+}
+
+/// One `hardware[]` entry. Exactly one of `pattern` / `nodespattern`.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct HardwarePattern {
+  /// HSM group to pin nodes into.
+  pub target: String,
+  /// HSM group nodes are drawn from.
+  pub parent: String,
+  /// Hardware component pattern (e.g. `a100:4:epyc:8`).
+  pub pattern: Option<String>,
+  /// Explicit comma-separated xname list.
+  pub nodespattern: Option<String>,
+}
+
+/// One `configurations[]` entry.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Configuration {
+  /// CFS configuration name.
+  pub name: String,
+  /// Free-text description.
+  pub description: Option<String>,
+  /// Ordered CFS layers.
+  pub layers: Vec<Layer>,
+  /// Extra Ansible inventory repository.
+  pub additional_inventory: Option<Inventory>,
+}
+
+/// One CFS layer. Exactly one of `git` / `product`.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Layer {
+  /// Layer name.
+  pub name: Option<String>,
+  /// Playbook to run; CFS defaults to `site.yml`.
+  pub playbook: Option<String>,
+  /// Layer sourced from a git repository.
+  pub git: Option<GitSource>,
+  /// Layer sourced from an installed product catalog entry.
+  pub product: Option<ProductSource>,
+}
+
+/// Git source of a layer. Exactly one of `commit` / `branch` / `tag`.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct GitSource {
+  /// Clone URL.
+  pub url: String,
+  /// Commit id.
+  pub commit: Option<String>,
+  /// Branch name.
+  pub branch: Option<String>,
+  /// Tag name.
+  pub tag: Option<String>,
+}
+
+/// Product source of a layer. At most one of `branch` / `commit`;
+/// `version` is required when neither is set.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ProductSource {
+  /// Product name in the product catalog.
+  pub name: String,
+  /// Product version.
+  pub version: Option<String>,
+  /// Branch of the product's configuration repository.
+  pub branch: Option<String>,
+  /// Commit of the product's configuration repository.
+  pub commit: Option<String>,
+}
+
+/// `additional_inventory` of a configuration. Exactly one of
+/// `commit` / `branch`.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Inventory {
+  /// Inventory name.
+  pub name: Option<String>,
+  /// Clone URL.
+  pub url: String,
+  /// Commit id.
+  pub commit: Option<String>,
+  /// Branch name.
+  pub branch: Option<String>,
+}
+
+/// One `images[]` entry. Exactly one of `base` / `ims` (the latter
+/// being the pre-`base` SAT syntax).
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Image {
+  /// Name of the image to build.
+  pub name: String,
+  /// Label other entries reference via `image_ref`; defaults to
+  /// `name`.
+  pub ref_name: Option<String>,
+  /// Free-text description.
+  pub description: Option<String>,
+  /// What the image is built from.
+  pub base: Option<ImageBase>,
+  /// Legacy form of `base.ims`.
+  pub ims: Option<LegacyIms>,
+  /// CFS configuration applied to the image.
+  pub configuration: Option<String>,
+  /// Ansible groups the image is configured as.
+  pub configuration_group_names: Option<Vec<String>>,
+}
+
+/// `images[].base`. Exactly one of `ims` / `product` / `image_ref`.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ImageBase {
+  /// Existing IMS image or recipe.
+  pub ims: Option<BaseIms>,
+  /// Image or recipe provided by an installed product.
+  pub product: Option<BaseProduct>,
+  /// Another image in this file, by `ref_name` (or `name`).
+  pub image_ref: Option<String>,
+}
+
+/// IMS artifact kind a base refers to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ImsArtifactType {
+  /// An IMS image.
+  Image,
+  /// An IMS recipe.
+  Recipe,
+}
+
+/// `images[].base.ims`. Exactly one of `name` / `id`; `type` is
+/// required with `name` (an `id` may instead use the older
+/// `is_recipe` flag).
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct BaseIms {
+  /// IMS image or recipe name.
+  pub name: Option<String>,
+  /// IMS image or recipe id.
+  pub id: Option<String>,
+  /// Whether `name` / `id` is an image or a recipe.
+  pub r#type: Option<ImsArtifactType>,
+  /// Older spelling of `type: recipe`.
+  pub is_recipe: Option<bool>,
+}
+
+/// `images[].base.product`.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct BaseProduct {
+  /// Product name in the product catalog.
+  pub name: String,
+  /// Product version; latest when unset.
+  pub version: Option<String>,
+  /// Whether the product provides an image or a recipe.
+  pub r#type: ImsArtifactType,
+  /// Narrows the product's artifacts down to one.
+  pub filter: Option<ProductFilter>,
+}
+
+/// Image architecture accepted by a product filter.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+pub enum ImageArch {
+  /// 64-bit ARM.
+  #[serde(rename = "aarch64")]
+  Aarch64,
+  /// 64-bit x86.
+  #[serde(rename = "x86_64")]
+  X86_64,
+}
+
+/// `images[].base.product.filter`. Exactly one of `prefix` /
+/// `wildcard` / `arch`.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ProductFilter {
+  /// Artifact name prefix.
+  pub prefix: Option<String>,
+  /// Artifact name glob.
+  pub wildcard: Option<String>,
+  /// Artifact architecture.
+  pub arch: Option<ImageArch>,
+}
+
+/// Legacy `images[].ims`. Exactly one of `name` / `id`.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct LegacyIms {
+  /// IMS image or recipe name.
+  pub name: Option<String>,
+  /// IMS image or recipe id.
+  pub id: Option<String>,
+  /// Whether `name` / `id` is a recipe.
+  pub is_recipe: bool,
+}
+
+/// One `session_templates[]` entry.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct SessionTemplate {
+  /// BOS session template name.
+  pub name: String,
+  /// Boot image.
+  pub image: SessionTemplateImage,
+  /// CFS configuration applied at boot.
+  pub configuration: String,
+  /// BOS boot sets.
+  pub bos_parameters: BosParameters,
+}
+
+/// `session_templates[].image`: either a bare IMS image name or a
+/// mapping with exactly one of `ims` / `image_ref`.
+#[derive(Debug, Clone)]
+pub enum SessionTemplateImage {
+  /// Bare IMS image name.
+  Name(String),
+  /// Structured image reference.
+  Spec(SessionTemplateImageSpec),
+}
+
+/// Mapping form of `session_templates[].image`.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct SessionTemplateImageSpec {
+  /// Existing IMS image.
+  pub ims: Option<ImsImage>,
+  /// Image built earlier in this file.
+  pub image_ref: Option<String>,
+}
+
+/// `session_templates[].image.ims`. Exactly one of `name` / `id`.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ImsImage {
+  /// IMS image name.
+  pub name: Option<String>,
+  /// IMS image id.
+  pub id: Option<String>,
+}
+
+/// `session_templates[].bos_parameters`.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct BosParameters {
+  /// Boot sets keyed by boot-set name.
+  pub boot_sets: BTreeMap<String, BootSet>,
+}
+
+/// Node architecture accepted in a boot set.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+pub enum BootSetArch {
+  /// x86 nodes.
+  X86,
+  /// ARM nodes.
+  ARM,
+  /// Any other architecture.
+  Other,
+  /// Unknown architecture.
+  Unknown,
+}
+
+/// One BOS boot set. At least one of `node_list` / `node_groups` /
+/// `node_roles_group`.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct BootSet {
+  /// Architecture of the targeted nodes.
+  pub arch: Option<BootSetArch>,
+  /// Kernel command line.
+  pub kernel_parameters: Option<String>,
+  /// Boot network.
+  pub network: Option<String>,
+  /// Explicit xnames.
+  pub node_list: Option<Vec<String>>,
+  /// HSM roles.
+  pub node_roles_group: Option<Vec<String>>,
+  /// HSM groups.
+  pub node_groups: Option<Vec<String>>,
+  /// Root filesystem provider.
+  pub rootfs_provider: Option<String>,
+  /// Root filesystem provider arguments.
+  pub rootfs_provider_passthrough: Option<String>,
+}
+
+impl<'de> Deserialize<'de> for SessionTemplateImage {
+  fn deserialize<D: de::Deserializer<'de>>(d: D) -> Result<Self, D::Error> {
+    struct ImageVisitor;
+
+    impl<'de> Visitor<'de> for ImageVisitor {
+      type Value = SessionTemplateImage;
+
+      fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("an IMS image name or a mapping with `ims` or `image_ref`")
+      }
+
+      fn visit_str<E: de::Error>(self, v: &str) -> Result<Self::Value, E> {
+        Ok(SessionTemplateImage::Name(v.to_string()))
+      }
+
+      fn visit_map<A: MapAccess<'de>>(
+        self,
+        map: A,
+      ) -> Result<Self::Value, A::Error> {
+        SessionTemplateImageSpec::deserialize(
+          de::value::MapAccessDeserializer::new(map),
+        )
+        .map(SessionTemplateImage::Spec)
+      }
+    }
+
+    d.deserialize_any(ImageVisitor)
+  }
+}
+
+/// One problem found in a SAT file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SatValidationError {
+  /// What is wrong, prefixed with the offending element's path (e.g.
+  /// `configurations[0].layers[1].git: ...`).
+  pub message: String,
+  /// 1-based line in the rendered YAML, when known.
+  pub line: Option<usize>,
+  /// 1-based column in the rendered YAML, when known.
+  pub column: Option<usize>,
+}
+
+impl fmt::Display for SatValidationError {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    match (self.line, self.column) {
+      (Some(line), Some(column)) => {
+        write!(f, "line {line}, column {column}: {}", self.message)
+      }
+      _ => f.write_str(&self.message),
+    }
+  }
+}
+
+/// Validate a rendered SAT file and return its typed form.
+///
+/// Runs in two passes. The serde pass reports the first structural
+/// error (YAML syntax, unknown field, missing required field, wrong
+/// type, unknown enum value). If it succeeds, the rule pass reports
+/// every "exactly one of" violation in the file. Nothing outside the
+/// document is consulted: cross-references between entries are
+/// checked later by the apply planner.
+///
+/// # Errors
+///
+/// Returns the list of problems found, each located in
+/// `rendered_yaml` where possible. The list is never empty.
+pub fn validate_sat_file(
+  rendered_yaml: &str,
+) -> Result<SatFile, Vec<SatValidationError>> {
+  let sat_file: SatFile = serde_yaml::from_str(rendered_yaml)
+    .map_err(|e| vec![from_serde_error(&e)])?;
+
+  let mut problems = Vec::new();
+  check_rules(&sat_file, &mut problems);
+  if problems.is_empty() {
+    return Ok(sat_file);
+  }
+  Err(
+    problems
+      .into_iter()
+      .map(|(path, message)| {
+        let location = locate(rendered_yaml, &path);
+        SatValidationError {
+          message: format!("{}: {message}", display_path(&path)),
+          line: location.map(|(l, _)| l),
+          column: location.map(|(_, c)| c),
+        }
+      })
+      .collect(),
+  )
+}
+
+/// Split a serde_yaml error into message and location. serde_yaml
+/// appends ` at line L column C` to its own message; the location is
+/// kept separately so every error renders the same way.
+fn from_serde_error(e: &serde_yaml::Error) -> SatValidationError {
+  let text = e.to_string();
+  match e.location() {
+    Some(loc) => {
+      let suffix = format!(" at line {} column {}", loc.line(), loc.column());
+      SatValidationError {
+        message: text.strip_suffix(&suffix).unwrap_or(&text).to_string(),
+        line: Some(loc.line()),
+        column: Some(loc.column()),
+      }
+    }
+    None => SatValidationError {
+      message: text,
+      line: None,
+      column: None,
+    },
+  }
+}
+
+/// One step into the YAML document.
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Seg {
+  Key(String),
+  Index(usize),
+}
+
+type Path = Vec<Seg>;
+
+fn key(path: &[Seg], k: &str) -> Path {
+  let mut p = path.to_vec();
+  p.push(Seg::Key(k.to_string()));
+  p
+}
+
+fn index(path: &[Seg], i: usize) -> Path {
+  let mut p = path.to_vec();
+  p.push(Seg::Index(i));
+  p
+}
+
+/// Render a path the way serde_yaml does: `images[0].base.ims`.
+fn display_path(path: &[Seg]) -> String {
+  let mut out = String::new();
+  for seg in path {
+    match seg {
+      Seg::Key(k) if out.is_empty() => out.push_str(k),
+      Seg::Key(k) => {
+        out.push('.');
+        out.push_str(k);
+      }
+      Seg::Index(i) => out.push_str(&format!("[{i}]")),
+    }
+  }
+  if out.is_empty() { ".".to_string() } else { out }
+}
+
+/// Require exactly one of the named options to be set.
+fn exactly_one(
+  problems: &mut Vec<(Path, String)>,
+  path: &[Seg],
+  fields: &[(&str, bool)],
+) {
+  let set: Vec<&str> = fields
+    .iter()
+    .filter(|(_, present)| *present)
+    .map(|(name, _)| *name)
+    .collect();
+  if set.len() == 1 {
+    return;
+  }
+  let names = fields
+    .iter()
+    .map(|(name, _)| format!("`{name}`"))
+    .collect::<Vec<_>>()
+    .join(", ");
+  let message = if set.is_empty() {
+    format!("expected exactly one of {names}, found none")
+  } else {
+    format!(
+      "expected exactly one of {names}, found {}",
+      set.join(" and ")
+    )
+  };
+  problems.push((path.to_vec(), message));
+}
+
+/// The rule pass: constraints serde cannot express on plain structs.
+fn check_rules(sat: &SatFile, problems: &mut Vec<(Path, String)>) {
+  for (i, hw) in sat.hardware.iter().flatten().enumerate() {
+    let path = index(&key(&[], "hardware"), i);
+    exactly_one(
+      problems,
+      &path,
+      &[
+        ("pattern", hw.pattern.is_some()),
+        ("nodespattern", hw.nodespattern.is_some()),
+      ],
+    );
+  }
+
+  for (i, cfg) in sat.configurations.iter().flatten().enumerate() {
+    let path = index(&key(&[], "configurations"), i);
+    for (j, layer) in cfg.layers.iter().enumerate() {
+      let path = index(&key(&path, "layers"), j);
+      exactly_one(
+        problems,
+        &path,
+        &[
+          ("git", layer.git.is_some()),
+          ("product", layer.product.is_some()),
+        ],
+      );
+      if let Some(git) = &layer.git {
+        exactly_one(
+          problems,
+          &key(&path, "git"),
+          &[
+            ("commit", git.commit.is_some()),
+            ("branch", git.branch.is_some()),
+            ("tag", git.tag.is_some()),
+          ],
+        );
+      }
+      if let Some(product) = &layer.product {
+        let path = key(&path, "product");
+        if product.branch.is_some() && product.commit.is_some() {
+          problems.push((path, "set `branch` or `commit`, not both".into()));
+        } else if product.branch.is_none()
+          && product.commit.is_none()
+          && product.version.is_none()
+        {
+          problems.push((
+            path,
+            "`version` is required when neither `branch` nor `commit` is set"
+              .into(),
+          ));
+        }
+      }
+    }
+    if let Some(inv) = &cfg.additional_inventory {
+      exactly_one(
+        problems,
+        &key(&path, "additional_inventory"),
+        &[
+          ("commit", inv.commit.is_some()),
+          ("branch", inv.branch.is_some()),
+        ],
+      );
+    }
+  }
+
+  for (i, image) in sat.images.iter().flatten().enumerate() {
+    let path = index(&key(&[], "images"), i);
+    exactly_one(
+      problems,
+      &path,
+      &[("base", image.base.is_some()), ("ims", image.ims.is_some())],
+    );
+    if let Some(ims) = &image.ims {
+      exactly_one(
+        problems,
+        &key(&path, "ims"),
+        &[("name", ims.name.is_some()), ("id", ims.id.is_some())],
+      );
+    }
+    let Some(base) = &image.base else {
+      continue;
+    };
+    let path = key(&path, "base");
+    exactly_one(
+      problems,
+      &path,
+      &[
+        ("ims", base.ims.is_some()),
+        ("product", base.product.is_some()),
+        ("image_ref", base.image_ref.is_some()),
+      ],
+    );
+    if let Some(ims) = &base.ims {
+      let path = key(&path, "ims");
+      exactly_one(
+        problems,
+        &path,
+        &[("name", ims.name.is_some()), ("id", ims.id.is_some())],
+      );
+      if ims.name.is_some() && ims.r#type.is_none() {
+        problems.push((path, "`type` (image or recipe) is required".into()));
+      }
+    }
+    if let Some(filter) = base.product.as_ref().and_then(|p| p.filter.as_ref())
+    {
+      exactly_one(
+        problems,
+        &key(&key(&path, "product"), "filter"),
+        &[
+          ("prefix", filter.prefix.is_some()),
+          ("wildcard", filter.wildcard.is_some()),
+          ("arch", filter.arch.is_some()),
+        ],
+      );
+    }
+  }
+
+  for (i, st) in sat.session_templates.iter().flatten().enumerate() {
+    let path = index(&key(&[], "session_templates"), i);
+    if let SessionTemplateImage::Spec(spec) = &st.image {
+      let path = key(&path, "image");
+      exactly_one(
+        problems,
+        &path,
+        &[
+          ("ims", spec.ims.is_some()),
+          ("image_ref", spec.image_ref.is_some()),
+        ],
+      );
+      if let Some(ims) = &spec.ims {
+        exactly_one(
+          problems,
+          &key(&path, "ims"),
+          &[("name", ims.name.is_some()), ("id", ims.id.is_some())],
+        );
+      }
+    }
+    let boot_sets = key(&key(&path, "bos_parameters"), "boot_sets");
+    for (name, boot_set) in &st.bos_parameters.boot_sets {
+      if boot_set.node_list.is_none()
+        && boot_set.node_groups.is_none()
+        && boot_set.node_roles_group.is_none()
+      {
+        problems.push((
+          key(&boot_sets, name),
+          "expected at least one of `node_list`, `node_groups`, \
+           `node_roles_group`"
+            .into(),
+        ));
+      }
+    }
+  }
+}
+
+/// Find the 1-based (line, column) where the node at `path` starts.
+///
+/// serde_yaml does not expose node positions, but it does stamp the
+/// position of the node being deserialized onto any error raised
+/// while visiting it. [`Probe`] walks to the target node and fails
+/// there on purpose; the error's location is the node's position.
+fn locate(yaml: &str, path: &[Seg]) -> Option<(usize, usize)> {
+  let deserializer = serde_yaml::Deserializer::from_str(yaml);
+  match Probe(path).deserialize(deserializer) {
+    Ok(()) => None,
+    Err(e) => e.location().map(|loc| (loc.line(), loc.column())),
+  }
+}
+
+/// Walks `self.0` into the document and errors at the end of it.
+struct Probe<'a>(&'a [Seg]);
+
+impl Probe<'_> {
+  fn found<E: de::Error>(&self) -> Result<(), E> {
+    if self.0.is_empty() {
+      Err(E::custom("located"))
+    } else {
+      Ok(())
+    }
+  }
+}
+
+impl<'de> DeserializeSeed<'de> for Probe<'_> {
+  type Value = ();
+
+  fn deserialize<D: de::Deserializer<'de>>(self, d: D) -> Result<(), D::Error> {
+    d.deserialize_any(self)
+  }
+}
+
+impl<'de> Visitor<'de> for Probe<'_> {
+  type Value = ();
+
+  fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    f.write_str("any YAML node")
+  }
+
+  fn visit_bool<E: de::Error>(self, _: bool) -> Result<(), E> {
+    self.found()
+  }
+
+  fn visit_i64<E: de::Error>(self, _: i64) -> Result<(), E> {
+    self.found()
+  }
+
+  fn visit_u64<E: de::Error>(self, _: u64) -> Result<(), E> {
+    self.found()
+  }
+
+  fn visit_f64<E: de::Error>(self, _: f64) -> Result<(), E> {
+    self.found()
+  }
+
+  fn visit_str<E: de::Error>(self, _: &str) -> Result<(), E> {
+    self.found()
+  }
+
+  fn visit_unit<E: de::Error>(self) -> Result<(), E> {
+    self.found()
+  }
+
+  fn visit_none<E: de::Error>(self) -> Result<(), E> {
+    self.found()
+  }
+
+  fn visit_some<D: de::Deserializer<'de>>(self, d: D) -> Result<(), D::Error> {
+    self.deserialize(d)
+  }
+
+  fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<(), A::Error> {
+    self.found()?;
+    let target = match self.0[0] {
+      Seg::Index(i) => Some(i),
+      Seg::Key(_) => None,
+    };
+    // Every element must be consumed or serde_yaml reports a length
+    // mismatch instead of our marker.
+    let mut i = 0;
+    loop {
+      let more = if Some(i) == target {
+        seq.next_element_seed(Probe(&self.0[1..]))?
+      } else {
+        seq.next_element::<IgnoredAny>()?.map(|_| ())
+      };
+      if more.is_none() {
+        return Ok(());
+      }
+      i += 1;
+    }
+  }
+
+  fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<(), A::Error> {
+    self.found()?;
+    let target = match &self.0[0] {
+      Seg::Key(k) => Some(k.as_str()),
+      Seg::Index(_) => None,
+    };
+    while let Some(k) = map.next_key::<String>()? {
+      if Some(k.as_str()) == target {
+        map.next_value_seed(Probe(&self.0[1..]))?;
+      } else {
+        map.next_value::<IgnoredAny>()?;
+      }
+    }
+    Ok(())
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn errors(yaml: &str) -> Vec<SatValidationError> {
+    validate_sat_file(yaml).expect_err("expected validation errors")
+  }
+
+  const VALID: &str = "\
+configurations:
+- name: cfg
+  layers:
+  - name: base
+    playbook: site.yml
+    git:
+      url: https://git.example/repo.git
+      branch: main
+  - product:
+      name: cos
+      version: 2.5.97
+images:
+- name: compute
+  ref_name: compute-ref
+  base:
+    ims:
+      name: base-image
+      type: image
+  configuration: cfg
+  configuration_group_names: [Compute]
+- name: child
+  base:
+    image_ref: compute-ref
+session_templates:
+- name: st
+  image:
+    image_ref: child
+  configuration: cfg
+  bos_parameters:
+    boot_sets:
+      compute:
+        arch: X86
+        node_groups: [compute]
+        rootfs_provider: sbps
+";
+
+  #[test]
+  fn valid_file_passes() {
+    let sat = validate_sat_file(VALID).expect("valid SAT file");
+    assert_eq!(sat.images.unwrap().len(), 2);
+  }
+
+  #[test]
+  fn session_template_accepts_bare_image_name() {
+    let yaml = "\
+session_templates:
+- name: st
+  image: my-image
+  configuration: cfg
+  bos_parameters:
+    boot_sets:
+      compute:
+        node_list: [x1000c0s0b0n0]
+";
+    let sat = validate_sat_file(yaml).unwrap();
+    assert!(matches!(
+      sat.session_templates.unwrap()[0].image,
+      SessionTemplateImage::Name(ref n) if n == "my-image"
+    ));
+  }
+
+  #[test]
+  fn unknown_field_is_located() {
+    let yaml = VALID.replace("      branch: main", "      brnach: main");
+    let errs = errors(&yaml);
+    assert_eq!(errs.len(), 1);
+    assert!(
+      errs[0].message.contains("unknown field `brnach`"),
+      "{errs:?}"
+    );
+    assert!(
+      errs[0]
+        .message
+        .starts_with("configurations[0].layers[0].git")
+    );
+    assert_eq!(errs[0].line, Some(8));
+  }
+
+  #[test]
+  fn missing_required_field_is_reported() {
+    let errs = errors("configurations:\n- name: cfg\n");
+    assert!(
+      errs[0].message.contains("missing field `layers`"),
+      "{errs:?}"
+    );
+    assert_eq!(errs[0].line, Some(2));
+  }
+
+  #[test]
+  fn unknown_enum_value_is_reported() {
+    let yaml = VALID.replace("arch: X86", "arch: x86");
+    let errs = errors(&yaml);
+    assert!(
+      errs[0].message.contains("unknown variant `x86`"),
+      "{errs:?}"
+    );
+  }
+
+  #[test]
+  fn unknown_top_level_section_is_rejected() {
+    let errs = errors("image:\n- name: oops\n");
+    assert!(
+      errs[0].message.contains("unknown field `image`"),
+      "{errs:?}"
+    );
+    assert_eq!(errs[0].line, Some(1));
+  }
+
+  #[test]
+  fn rule_errors_are_all_reported_with_location() {
+    let yaml = "\
+configurations:
+- name: cfg
+  layers:
+  - git:
+      url: https://x
+      branch: main
+      tag: v1
+images:
+- name: img
+  base:
+    ims:
+      name: base
+";
+    let errs = errors(yaml);
+    assert_eq!(errs.len(), 2, "{errs:?}");
+    assert_eq!(
+      errs[0].message,
+      "configurations[0].layers[0].git: expected exactly one of `commit`, \
+       `branch`, `tag`, found branch and tag"
+    );
+    assert_eq!((errs[0].line, errs[0].column), (Some(5), Some(7)));
+    assert_eq!(
+      errs[1].message,
+      "images[0].base.ims: `type` (image or recipe) is required"
+    );
+    assert_eq!(errs[1].line, Some(12));
+  }
+
+  #[test]
+  fn boot_set_without_targets_is_rejected() {
+    let yaml = VALID.replace("        node_groups: [compute]\n", "");
+    let errs = errors(&yaml);
+    assert_eq!(
+      errs[0].message,
+      "session_templates[0].bos_parameters.boot_sets.compute: expected at \
+       least one of `node_list`, `node_groups`, `node_roles_group`"
+    );
+  }
+
+  #[test]
+  fn display_includes_location() {
+    let err = SatValidationError {
+      message: "images[0]: bad".into(),
+      line: Some(3),
+      column: Some(1),
+    };
+    assert_eq!(err.to_string(), "line 3, column 1: images[0]: bad");
+  }
+}